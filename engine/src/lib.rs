@@ -8,6 +8,10 @@ pub mod view;
 
 use anyhow::Result;
 use async_trait::async_trait;
+use renderer::{FrameStats, Renderer, RendererConfig};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::time::Instant;
 
 /// Trait defining the core engine interface
 #[async_trait]
@@ -18,20 +22,112 @@ pub trait Engine: Send + Sync {
     /// Render a single frame
     async fn render_frame(&mut self) -> Result<()>;
 
+    /// Inform the engine that the render surface changed size
+    ///
+    /// Does nothing by default.
+    async fn resize(&mut self, _width: u32, _height: u32) -> Result<()> {
+        Ok(())
+    }
+
     /// Shutdown the engine gracefully
     async fn shutdown(&mut self) -> Result<()>;
 }
 
+/// Lifecycle state of an engine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineState {
+    /// Created but not initialized yet
+    Uninitialized,
+    /// Ready to render frames
+    Initialized,
+    /// Shutting down
+    ShuttingDown,
+    /// Shut down; may be initialized again
+    Shutdown,
+}
+
 /// Main engine implementation
 pub struct HorizonEngine {
-    initialized: bool,
+    state: EngineState,
+    renderer: Renderer,
+    /// Set from another task to make `run_loop` return
+    stop_requested: Arc<AtomicBool>,
 }
 
 impl HorizonEngine {
     /// Create a new engine instance
     pub fn new() -> Self {
         tracing::info!("Creating new Horizon Engine");
-        Self { initialized: false }
+        Self {
+            state: EngineState::Uninitialized,
+            renderer: Renderer::new(RendererConfig::default()),
+            stop_requested: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Use a renderer configuration instead of the default
+    pub fn with_renderer_config(mut self, config: RendererConfig) -> Self {
+        self.renderer = Renderer::new(config);
+        self
+    }
+
+    /// Get the lifecycle state
+    pub fn state(&self) -> EngineState {
+        self.state
+    }
+
+    /// Get a flag that stops `run_loop` after the current frame once set
+    ///
+    /// The flag is cleared again when the engine is initialized.
+    pub fn stop_signal(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.stop_requested)
+    }
+
+    /// Fail unless the engine is initialized
+    fn ensure_initialized(&self) -> Result<()> {
+        if self.state != EngineState::Initialized {
+            anyhow::bail!("Engine not initialized");
+        }
+        Ok(())
+    }
+
+    /// Render frames until `frames` have been rendered, or until the engine
+    /// is shut down if `frames` is `None`, returning the number rendered
+    ///
+    /// The loop also ends once the flag from `stop_signal` is set or the
+    /// engine is shut down. Each frame is paced to the renderer's `target_fps`
+    /// by sleeping for the rest of its budget; with vsync the loop yields to
+    /// other tasks between frames.
+    pub async fn run_loop(&mut self, frames: Option<u64>) -> Result<u64> {
+        self.ensure_initialized()?;
+        let budget = self.renderer.frame_budget();
+
+        let mut rendered = 0;
+        while self.state == EngineState::Initialized && frames.is_none_or(|n| rendered < n) {
+            if self.stop_requested.load(Ordering::Acquire) {
+                break;
+            }
+
+            let frame_start = Instant::now();
+            self.render_frame().await?;
+            rendered += 1;
+
+            match budget {
+                Some(budget) => tokio::time::sleep_until(frame_start + budget).await,
+                None => tokio::task::yield_now().await,
+            }
+        }
+        Ok(rendered)
+    }
+
+    /// Get timing statistics for recently rendered frames
+    pub fn frame_stats(&self) -> FrameStats {
+        self.renderer.stats()
+    }
+
+    /// Get the render surface size in pixels as `(width, height)`
+    pub fn size(&self) -> (u32, u32) {
+        self.renderer.size()
     }
 }
 
@@ -44,22 +140,47 @@ impl Default for HorizonEngine {
 #[async_trait]
 impl Engine for HorizonEngine {
     async fn initialize(&mut self) -> Result<()> {
+        if matches!(
+            self.state,
+            EngineState::Initialized | EngineState::ShuttingDown
+        ) {
+            anyhow::bail!("Engine already initialized");
+        }
+
         tracing::info!("Initializing Horizon Engine");
-        self.initialized = true;
+        self.renderer.initialize()?;
+        self.stop_requested.store(false, Ordering::Release);
+        self.state = EngineState::Initialized;
         Ok(())
     }
 
     async fn render_frame(&mut self) -> Result<()> {
-        if !self.initialized {
-            anyhow::bail!("Engine not initialized");
-        }
-        // Placeholder: actual rendering logic will be implemented later
+        self.ensure_initialized()?;
+        self.renderer.render()
+    }
+
+    async fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.ensure_initialized()?;
+        self.renderer.resize(width, height);
         Ok(())
     }
 
+    /// Shut down the engine
+    ///
+    /// Shutting down again does nothing, but an engine that was never
+    /// initialized cannot be shut down.
     async fn shutdown(&mut self) -> Result<()> {
+        match self.state {
+            EngineState::Uninitialized => anyhow::bail!("Engine not initialized"),
+            EngineState::ShuttingDown | EngineState::Shutdown => return Ok(()),
+            EngineState::Initialized => {}
+        }
+
+        self.state = EngineState::ShuttingDown;
+        self.stop_requested.store(true, Ordering::Release);
         tracing::info!("Shutting down Horizon Engine");
-        self.initialized = false;
+        // Renderer resources will be released here once it holds any
+        self.state = EngineState::Shutdown;
         Ok(())
     }
 }
@@ -71,7 +192,24 @@ mod tests {
     #[tokio::test]
     async fn test_engine_initialization() {
         let mut engine = HorizonEngine::new();
+        assert_eq!(engine.state(), EngineState::Uninitialized);
         assert!(engine.initialize().await.is_ok());
+        assert_eq!(engine.state(), EngineState::Initialized);
+    }
+
+    #[tokio::test]
+    async fn test_engine_double_initialize() {
+        let mut engine = HorizonEngine::new();
+        engine.initialize().await.unwrap();
+        assert!(engine.initialize().await.is_err());
+        assert_eq!(engine.state(), EngineState::Initialized);
+    }
+
+    #[tokio::test]
+    async fn test_engine_shutdown_before_init() {
+        let mut engine = HorizonEngine::new();
+        assert!(engine.shutdown().await.is_err());
+        assert_eq!(engine.state(), EngineState::Uninitialized);
     }
 
     #[tokio::test]
@@ -85,6 +223,99 @@ mod tests {
         let mut engine = HorizonEngine::new();
         engine.initialize().await.unwrap();
         assert!(engine.render_frame().await.is_ok());
+        // Idle time between frames is not counted as rendering time
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        assert!(engine.render_frame().await.is_ok());
+        assert!(engine.frame_stats().last_frame_ms < 20.0);
+        assert!(engine.shutdown().await.is_ok());
+        assert_eq!(engine.state(), EngineState::Shutdown);
+
+        // Shutting down again is harmless, but the engine no longer renders
         assert!(engine.shutdown().await.is_ok());
+        assert!(engine.render_frame().await.is_err());
+
+        engine.initialize().await.unwrap();
+        assert!(engine.render_frame().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_run_loop_paces_frames() {
+        let mut engine = HorizonEngine::new().with_renderer_config(RendererConfig {
+            target_fps: 100,
+            vsync: false,
+            ..RendererConfig::default()
+        });
+        assert!(engine.run_loop(Some(1)).await.is_err());
+        engine.initialize().await.unwrap();
+
+        let start = std::time::Instant::now();
+        assert_eq!(engine.run_loop(Some(10)).await.unwrap(), 10);
+        let elapsed = start.elapsed();
+        assert!(
+            elapsed >= std::time::Duration::from_millis(90),
+            "10 frames at 100fps took {:?}",
+            elapsed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_loop_vsync_skips_sleep() {
+        let mut engine = HorizonEngine::new();
+        engine.initialize().await.unwrap();
+
+        let start = std::time::Instant::now();
+        assert_eq!(engine.run_loop(Some(10)).await.unwrap(), 10);
+        assert!(start.elapsed() < std::time::Duration::from_millis(90));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_raises_stop_signal() {
+        let mut engine = HorizonEngine::new();
+        engine.initialize().await.unwrap();
+        let stop = engine.stop_signal();
+
+        engine.shutdown().await.unwrap();
+        assert!(stop.load(Ordering::Acquire));
+
+        engine.initialize().await.unwrap();
+        assert!(!stop.load(Ordering::Acquire));
+    }
+
+    #[tokio::test]
+    async fn test_run_loop_stops_on_signal() {
+        let mut engine = HorizonEngine::new();
+        engine.initialize().await.unwrap();
+
+        let stop = engine.stop_signal();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            stop.store(true, Ordering::Release);
+        });
+
+        let rendered =
+            tokio::time::timeout(std::time::Duration::from_secs(5), engine.run_loop(None))
+                .await
+                .expect("run_loop(None) should stop once signalled")
+                .unwrap();
+        assert!(rendered > 0);
+
+        engine.shutdown().await.unwrap();
+        engine.initialize().await.unwrap();
+        assert_eq!(engine.run_loop(Some(3)).await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_engine_resize_before_init() {
+        let mut engine = HorizonEngine::new();
+        assert!(engine.resize(800, 600).await.is_err());
+        assert_eq!(engine.size(), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_engine_resize() {
+        let mut engine = HorizonEngine::new();
+        engine.initialize().await.unwrap();
+        engine.resize(1280, 720).await.unwrap();
+        assert_eq!(engine.size(), (1280, 720));
     }
 }
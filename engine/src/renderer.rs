@@ -1,12 +1,50 @@
 //! Renderer module - handles the actual rendering pipeline
 
 use anyhow::Result;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Number of recent frames averaged into the frame statistics
+pub const FRAME_HISTORY: usize = 120;
+
+/// Graphics backend used to render frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RendererBackend {
+    /// Use the GPU, falling back to software if it cannot be initialized
+    #[default]
+    Auto,
+    /// Use the GPU (wgpu) only
+    Hardware,
+    /// Render on the CPU only
+    Software,
+}
+
+impl RendererBackend {
+    /// Map the hardware acceleration setting onto a backend
+    ///
+    /// Enabled acceleration still falls back to software when the GPU fails.
+    pub fn from_hardware_acceleration(enabled: bool) -> Self {
+        if enabled {
+            Self::Auto
+        } else {
+            Self::Software
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Auto => "Auto",
+            Self::Hardware => "Hardware",
+            Self::Software => "Software",
+        }
+    }
+}
 
 /// Renderer configuration
 #[derive(Debug, Clone)]
 pub struct RendererConfig {
-    /// Enable hardware acceleration
-    pub hardware_acceleration: bool,
+    /// Graphics backend to use
+    pub backend: RendererBackend,
     /// Target frames per second
     pub target_fps: u32,
     /// Enable vsync
@@ -16,35 +54,206 @@ pub struct RendererConfig {
 impl Default for RendererConfig {
     fn default() -> Self {
         Self {
-            hardware_acceleration: true,
+            backend: RendererBackend::Auto,
             target_fps: 60,
             vsync: true,
         }
     }
 }
 
+/// Timing of recently rendered frames
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameStats {
+    /// Duration of the most recent frame, in milliseconds
+    pub last_frame_ms: f64,
+    /// Average duration of the recent frames, in milliseconds
+    pub avg_frame_ms: f64,
+    /// Frames per second at the average duration
+    pub fps: f64,
+}
+
+/// Ring buffer of recent frame durations
+#[derive(Debug, Clone)]
+pub struct FrameTimer {
+    durations: VecDeque<Duration>,
+    capacity: usize,
+}
+
+impl FrameTimer {
+    /// Create a timer keeping the last `capacity` frame durations
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            durations: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Record a frame duration, dropping the oldest one when full
+    pub fn record(&mut self, duration: Duration) {
+        if self.durations.len() == self.capacity {
+            self.durations.pop_front();
+        }
+        self.durations.push_back(duration);
+    }
+
+    /// Compute statistics over the recorded frames
+    ///
+    /// All values are zero until a frame has been recorded.
+    pub fn stats(&self) -> FrameStats {
+        let Some(last) = self.durations.back() else {
+            return FrameStats::default();
+        };
+
+        let total: Duration = self.durations.iter().sum();
+        let avg_frame_ms = total.as_secs_f64() * 1000.0 / self.durations.len() as f64;
+        let fps = if avg_frame_ms > 0.0 {
+            1000.0 / avg_frame_ms
+        } else {
+            0.0
+        };
+
+        FrameStats {
+            last_frame_ms: last.as_secs_f64() * 1000.0,
+            avg_frame_ms,
+            fps,
+        }
+    }
+}
+
+impl Default for FrameTimer {
+    fn default() -> Self {
+        Self::new(FRAME_HISTORY)
+    }
+}
+
 /// Core renderer implementation
 pub struct Renderer {
     config: RendererConfig,
+    /// Backend chosen by `initialize`
+    active_backend: Option<RendererBackend>,
+    /// Durations of recently rendered frames
+    timer: FrameTimer,
+    /// Surface width and height in pixels
+    size: (u32, u32),
 }
 
 impl Renderer {
     /// Create a new renderer with the given configuration
     pub fn new(config: RendererConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            active_backend: None,
+            timer: FrameTimer::default(),
+            size: (0, 0),
+        }
     }
 
     /// Initialize the renderer
     pub fn initialize(&mut self) -> Result<()> {
+        // Placeholder GPU setup that always succeeds
+        self.initialize_with(|| Ok(()))
+    }
+
+    /// Initialize the renderer, setting up the GPU with `init_hardware`
+    ///
+    /// `Auto` falls back to software rendering when `init_hardware` fails,
+    /// `Hardware` returns its error, and `Software` never calls it.
+    pub fn initialize_with(&mut self, init_hardware: impl FnOnce() -> Result<()>) -> Result<()> {
         tracing::debug!("Initializing renderer with config: {:?}", self.config);
+
+        let backend = match self.config.backend {
+            RendererBackend::Software => RendererBackend::Software,
+            RendererBackend::Hardware => {
+                init_hardware()?;
+                RendererBackend::Hardware
+            }
+            RendererBackend::Auto => match init_hardware() {
+                Ok(()) => RendererBackend::Hardware,
+                Err(e) => {
+                    tracing::warn!("GPU initialization failed, using software rendering: {}", e);
+                    RendererBackend::Software
+                }
+            },
+        };
+
+        tracing::info!("Renderer using {} backend", backend.name());
+        self.active_backend = Some(backend);
         Ok(())
     }
 
+    /// Initialize the renderer on a backend another layer set up
+    ///
+    /// Used when the GPU context is created outside the renderer, e.g. by
+    /// eframe, so the backend reported is the one that context ended up on.
+    pub fn initialize_on(&mut self, backend: RendererBackend) {
+        if backend != self.config.backend && self.config.backend != RendererBackend::Auto {
+            tracing::warn!(
+                "Renderer asked for {} backend but got {}",
+                self.config.backend.name(),
+                backend.name()
+            );
+        }
+        tracing::info!("Renderer using {} backend", backend.name());
+        self.active_backend = Some(backend);
+    }
+
+    /// Get the renderer configuration
+    pub fn config(&self) -> &RendererConfig {
+        &self.config
+    }
+
+    /// Get how long each frame should take, or `None` if frames are paced
+    /// by vsync or the frame rate is unlimited
+    pub fn frame_budget(&self) -> Option<Duration> {
+        if self.config.vsync || self.config.target_fps == 0 {
+            return None;
+        }
+        Some(Duration::from_secs(1) / self.config.target_fps)
+    }
+
+    /// Get the backend in use, once the renderer is initialized
+    pub fn active_backend(&self) -> Option<RendererBackend> {
+        self.active_backend
+    }
+
     /// Render a frame
+    ///
+    /// The time spent rendering is recorded as that frame's duration, so
+    /// idle time between frames is not counted.
     pub fn render(&mut self) -> Result<()> {
+        let start = Instant::now();
+
         // Placeholder for actual rendering logic
+
+        self.record_frame(start.elapsed());
         Ok(())
     }
+
+    /// Record how long a frame rendered outside the renderer took
+    ///
+    /// Used when another layer draws the frames, e.g. eframe.
+    pub fn record_frame(&mut self, duration: Duration) {
+        self.timer.record(duration);
+    }
+
+    /// Get timing statistics for recent frames
+    pub fn stats(&self) -> FrameStats {
+        self.timer.stats()
+    }
+
+    /// Set the size of the surface frames are rendered to
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if self.size != (width, height) {
+            tracing::debug!("Resizing render surface to {}x{}", width, height);
+            self.size = (width, height);
+        }
+    }
+
+    /// Get the surface size in pixels as `(width, height)`
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
 }
 
 #[cfg(test)]
@@ -55,5 +264,126 @@ mod tests {
     fn test_renderer_creation() {
         let renderer = Renderer::new(RendererConfig::default());
         assert_eq!(renderer.config.target_fps, 60);
+        assert_eq!(renderer.stats(), FrameStats::default());
+    }
+
+    #[test]
+    fn test_auto_falls_back_to_software() {
+        let mut renderer = Renderer::new(RendererConfig::default());
+        assert_eq!(renderer.active_backend(), None);
+
+        renderer
+            .initialize_with(|| Err(anyhow::anyhow!("no adapter found")))
+            .unwrap();
+        assert_eq!(renderer.active_backend(), Some(RendererBackend::Software));
+
+        let mut renderer = Renderer::new(RendererConfig::default());
+        renderer.initialize().unwrap();
+        assert_eq!(renderer.active_backend(), Some(RendererBackend::Hardware));
+    }
+
+    #[test]
+    fn test_explicit_backends() {
+        let config = |backend| RendererConfig {
+            backend,
+            ..RendererConfig::default()
+        };
+
+        let mut attempted = false;
+        let mut renderer = Renderer::new(config(RendererBackend::Software));
+        renderer
+            .initialize_with(|| {
+                attempted = true;
+                Ok(())
+            })
+            .unwrap();
+        assert!(!attempted);
+        assert_eq!(renderer.active_backend(), Some(RendererBackend::Software));
+
+        let mut renderer = Renderer::new(config(RendererBackend::Hardware));
+        assert!(renderer
+            .initialize_with(|| Err(anyhow::anyhow!("no adapter found")))
+            .is_err());
+        assert_eq!(renderer.active_backend(), None);
+    }
+
+    #[test]
+    fn test_initialize_on_reports_given_backend() {
+        let mut renderer = Renderer::new(RendererConfig::default());
+        renderer.initialize_on(RendererBackend::Software);
+        assert_eq!(renderer.active_backend(), Some(RendererBackend::Software));
+    }
+
+    #[test]
+    fn test_backend_from_hardware_acceleration() {
+        assert_eq!(
+            RendererBackend::from_hardware_acceleration(true),
+            RendererBackend::Auto
+        );
+        assert_eq!(
+            RendererBackend::from_hardware_acceleration(false),
+            RendererBackend::Software
+        );
+    }
+
+    #[test]
+    fn test_frame_timer_stats() {
+        let mut timer = FrameTimer::new(3);
+        assert_eq!(timer.stats().fps, 0.0);
+
+        for ms in [10, 20, 30] {
+            timer.record(Duration::from_millis(ms));
+        }
+        let stats = timer.stats();
+        assert_eq!(stats.last_frame_ms, 30.0);
+        assert!((stats.avg_frame_ms - 20.0).abs() < 1e-9);
+        assert!((stats.fps - 50.0).abs() < 1e-9);
+
+        // The oldest frame falls out of the ring buffer
+        timer.record(Duration::from_millis(40));
+        let stats = timer.stats();
+        assert!((stats.avg_frame_ms - 30.0).abs() < 1e-9);
+        assert!((stats.fps - 1000.0 / 30.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_render_records_frames() {
+        let mut renderer = Renderer::new(RendererConfig::default());
+        renderer.render().unwrap();
+        assert_eq!(renderer.timer.durations.len(), 1);
+
+        // Time between frames is not part of either frame
+        std::thread::sleep(Duration::from_millis(20));
+        renderer.render().unwrap();
+        assert_eq!(renderer.timer.durations.len(), 2);
+        assert!(renderer.stats().last_frame_ms < 20.0);
+
+        renderer.record_frame(Duration::from_millis(8));
+        assert_eq!(renderer.stats().last_frame_ms, 8.0);
+    }
+
+    #[test]
+    fn test_frame_budget() {
+        let config = RendererConfig {
+            target_fps: 100,
+            vsync: false,
+            ..RendererConfig::default()
+        };
+        assert_eq!(
+            Renderer::new(config.clone()).frame_budget(),
+            Some(Duration::from_millis(10))
+        );
+        assert_eq!(
+            Renderer::new(RendererConfig {
+                target_fps: 0,
+                ..config
+            })
+            .frame_budget(),
+            None
+        );
+        assert_eq!(
+            Renderer::new(RendererConfig::default()).frame_budget(),
+            None
+        );
     }
 }
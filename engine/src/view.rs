@@ -8,6 +8,8 @@ pub struct View {
     id: String,
     title: String,
     url: Option<String>,
+    /// Width and height in pixels
+    size: (u32, u32),
 }
 
 impl View {
@@ -17,6 +19,7 @@ impl View {
             id: id.into(),
             title: title.into(),
             url: None,
+            size: (0, 0),
         }
     }
 
@@ -39,6 +42,16 @@ impl View {
     pub fn url(&self) -> Option<&str> {
         self.url.as_deref()
     }
+
+    /// Set the view size in pixels
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.size = (width, height);
+    }
+
+    /// Get the view size in pixels as `(width, height)`
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
 }
 
 /// View manager handles multiple views
@@ -80,6 +93,35 @@ impl ViewManager {
             anyhow::bail!("Invalid view index")
         }
     }
+
+    /// Remove a view by index and return it
+    ///
+    /// Removing the active view activates its right neighbor, or the left one
+    /// if it was last. The active view is cleared when no views remain.
+    pub fn remove_view(&mut self, index: usize) -> Result<View> {
+        if index >= self.views.len() {
+            anyhow::bail!("Invalid view index");
+        }
+
+        let view = self.views.remove(index);
+        self.active_view = match self.active_view {
+            _ if self.views.is_empty() => None,
+            Some(active) if active > index => Some(active - 1),
+            Some(active) if active == index => Some(index.min(self.views.len() - 1)),
+            active => active,
+        };
+        Ok(view)
+    }
+
+    /// Get the index of the active view
+    pub fn active_index(&self) -> Option<usize> {
+        self.active_view
+    }
+
+    /// Get the number of views
+    pub fn view_count(&self) -> usize {
+        self.views.len()
+    }
 }
 
 impl Default for ViewManager {
@@ -106,4 +148,61 @@ mod tests {
         manager.add_view(view).unwrap();
         assert!(manager.active_view().is_some());
     }
+
+    #[test]
+    fn test_view_size() {
+        let mut view = View::new("view-1", "Test View");
+        assert_eq!(view.size(), (0, 0));
+        view.set_size(1280, 720);
+        assert_eq!(view.size(), (1280, 720));
+    }
+
+    fn manager_with_views(count: usize) -> ViewManager {
+        let mut manager = ViewManager::new();
+        for i in 0..count {
+            manager
+                .add_view(View::new(format!("view-{}", i), "Test View"))
+                .unwrap();
+        }
+        manager
+    }
+
+    #[test]
+    fn test_remove_active_view() {
+        let mut manager = manager_with_views(3);
+        manager.set_active_view(1).unwrap();
+
+        assert_eq!(manager.remove_view(1).unwrap().id(), "view-1");
+        assert_eq!(manager.active_index(), Some(1));
+        assert_eq!(manager.active_view().unwrap().id(), "view-2");
+
+        // Removing the active last view activates its left neighbor
+        assert_eq!(manager.remove_view(1).unwrap().id(), "view-2");
+        assert_eq!(manager.active_view().unwrap().id(), "view-0");
+
+        manager.remove_view(0).unwrap();
+        assert_eq!(manager.view_count(), 0);
+        assert_eq!(manager.active_index(), None);
+        assert!(manager.active_view().is_none());
+    }
+
+    #[test]
+    fn test_remove_first_view() {
+        let mut manager = manager_with_views(3);
+        manager.set_active_view(2).unwrap();
+
+        manager.remove_view(0).unwrap();
+        assert_eq!(manager.active_index(), Some(1));
+        assert_eq!(manager.active_view().unwrap().id(), "view-2");
+    }
+
+    #[test]
+    fn test_remove_last_view() {
+        let mut manager = manager_with_views(3);
+
+        manager.remove_view(2).unwrap();
+        assert_eq!(manager.view_count(), 2);
+        assert_eq!(manager.active_view().unwrap().id(), "view-0");
+        assert!(manager.remove_view(2).is_err());
+    }
 }
@@ -0,0 +1,176 @@
+//! Content script injection for extensions
+//!
+//! Resolves the files an extension's content scripts contribute to a page so
+//! the renderer can apply them.
+
+use crate::manifest::{ContentScript, Manifest};
+use crate::patterns::MatchPattern;
+use std::path::{Component, Path, PathBuf};
+
+/// A content script with its match patterns parsed ahead of time
+#[derive(Debug, Clone)]
+pub struct CompiledContentScript {
+    /// ID of the extension the script belongs to
+    pub extension_id: String,
+    /// The script as declared in the manifest
+    pub script: ContentScript,
+    /// Valid match patterns of the script
+    patterns: Vec<MatchPattern>,
+}
+
+impl CompiledContentScript {
+    /// Compile a content script's match patterns
+    ///
+    /// Invalid patterns are skipped with a warning, so they match nothing.
+    pub fn compile(extension_id: impl Into<String>, script: ContentScript) -> Self {
+        let extension_id = extension_id.into();
+        let patterns = script
+            .matches
+            .iter()
+            .filter_map(|pattern| match MatchPattern::parse(pattern) {
+                Ok(pattern) => Some(pattern),
+                Err(e) => {
+                    tracing::warn!("Ignoring content script pattern in {}: {}", extension_id, e);
+                    None
+                }
+            })
+            .collect();
+
+        Self {
+            extension_id,
+            script,
+            patterns,
+        }
+    }
+
+    /// Check whether the script should run on a URL
+    pub fn matches(&self, url: &str) -> bool {
+        self.patterns.iter().any(|pattern| pattern.matches(url))
+    }
+}
+
+/// Content script injector for a single unpacked extension
+pub struct ContentScriptInjector {
+    manifest: Manifest,
+    extension_dir: PathBuf,
+}
+
+impl ContentScriptInjector {
+    /// Create an injector for an extension loaded from a directory
+    pub fn new(manifest: Manifest, extension_dir: PathBuf) -> Self {
+        Self {
+            manifest,
+            extension_dir,
+        }
+    }
+
+    /// Get the contents of every stylesheet to inject into a page
+    ///
+    /// Only content scripts whose match patterns match `url` contribute, in
+    /// manifest order. Files that cannot be read, or that point outside the
+    /// extension directory, are skipped with a warning.
+    pub fn css_for(&self, url: &str) -> Vec<String> {
+        self.manifest
+            .content_scripts
+            .iter()
+            .filter(|script| script.matches_url(url))
+            .flat_map(|script| script.css.iter())
+            .filter_map(|file| self.read_file(file))
+            .collect()
+    }
+
+    /// Read a file relative to the extension directory
+    fn read_file(&self, file: &str) -> Option<String> {
+        let relative = Path::new(file);
+        if !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            tracing::warn!("Ignoring content script path outside extension: {}", file);
+            return None;
+        }
+
+        match std::fs::read_to_string(self.extension_dir.join(relative)) {
+            Ok(contents) => Some(contents),
+            Err(e) => {
+                tracing::warn!("Failed to read content script {}: {}", file, e);
+                None
+            }
+        }
+    }
+
+    /// Get the extension manifest
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn injector(dir: &TempDir) -> ContentScriptInjector {
+        std::fs::write(dir.path().join("dark.css"), "body { color: white; }").unwrap();
+        std::fs::create_dir(dir.path().join("styles")).unwrap();
+        std::fs::write(dir.path().join("styles/docs.css"), "pre { tab-size: 4; }").unwrap();
+
+        let manifest = Manifest::from_json(
+            r#"{
+                "manifest_version": 2,
+                "name": "Styles",
+                "version": "1.0.0",
+                "description": "User styles",
+                "content_scripts": [
+                    { "matches": ["*://*.example.com/*"], "css": ["dark.css"] },
+                    { "matches": ["https://docs.rs/*"], "css": ["styles/docs.css", "missing.css"] },
+                    { "matches": ["<all_urls>"], "css": ["../outside.css"] }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        ContentScriptInjector::new(manifest, dir.path().to_path_buf())
+    }
+
+    #[test]
+    fn test_css_for_matching_url() {
+        let dir = TempDir::new().unwrap();
+        let injector = injector(&dir);
+
+        assert_eq!(
+            injector.css_for("https://www.example.com/page"),
+            vec!["body { color: white; }"]
+        );
+        assert_eq!(
+            injector.css_for("https://docs.rs/serde"),
+            vec!["pre { tab-size: 4; }"]
+        );
+    }
+
+    #[test]
+    fn test_compiled_script_skips_invalid_patterns() {
+        let script = ContentScript {
+            matches: vec![
+                "https://*zilla.org/".to_string(),
+                "*://*.example.com/*".to_string(),
+            ],
+            js: vec!["content.js".to_string()],
+            css: Vec::new(),
+            run_at: None,
+        };
+        let compiled = CompiledContentScript::compile("ext", script);
+
+        assert!(compiled.matches("https://www.example.com/"));
+        assert!(!compiled.matches("https://mozilla.org/"));
+    }
+
+    #[test]
+    fn test_css_for_non_matching_url() {
+        let dir = TempDir::new().unwrap();
+        let injector = injector(&dir);
+
+        assert!(injector.css_for("https://other.org/").is_empty());
+        assert!(injector.css_for("about:blank").is_empty());
+    }
+}
@@ -3,9 +3,14 @@
 //! Extension framework for the Horizon Browser.
 //! Provides a plugin system for extending browser functionality.
 
+pub mod content_scripts;
 pub mod loader;
 pub mod manifest;
+pub mod patterns;
+pub mod permissions;
 pub mod registry;
+pub mod storage;
+pub mod updater;
 
 use anyhow::Result;
 use async_trait::async_trait;
@@ -57,6 +62,13 @@ impl ExtensionManager {
         }
     }
 
+    /// Create an extension manager that remembers permission grants in a file
+    pub fn with_storage_path(grants_path: std::path::PathBuf) -> Result<Self> {
+        Ok(Self {
+            registry: registry::ExtensionRegistry::with_storage_path(grants_path)?,
+        })
+    }
+
     /// Initialize the extension system
     pub async fn initialize(&mut self) -> Result<()> {
         tracing::info!("Initializing Extension Manager");
@@ -72,6 +84,49 @@ impl ExtensionManager {
     pub fn registry_mut(&mut self) -> &mut registry::ExtensionRegistry {
         &mut self.registry
     }
+
+    /// Get the permissions an extension asks the user to approve
+    pub fn permission_request(&self, id: &str) -> Result<permissions::PermissionRequest> {
+        let info = self
+            .registry
+            .get(id)
+            .ok_or_else(|| anyhow::anyhow!("Extension not found"))?;
+
+        Ok(permissions::PermissionRequest::new(
+            id,
+            &info.permissions,
+            &info.optional_permissions,
+        ))
+    }
+
+    /// Grant an extension its requested permissions, letting it run
+    pub fn grant(&mut self, id: &str) -> Result<()> {
+        self.registry.set_granted(id, true)?;
+        tracing::info!("Granted permissions to extension {}", id);
+        Ok(())
+    }
+
+    /// Deny an extension its requested permissions, keeping it inert
+    pub fn deny(&mut self, id: &str) -> Result<()> {
+        self.registry.set_granted(id, false)?;
+        tracing::info!("Denied permissions to extension {}", id);
+        Ok(())
+    }
+
+    /// Open an extension's storage, if it holds the `storage` permission
+    pub fn storage(&self, id: &str) -> Result<storage::ExtensionStorage> {
+        let info = self
+            .registry
+            .get(id)
+            .ok_or_else(|| anyhow::anyhow!("Extension not found"))?;
+
+        storage::ExtensionStorage::open(info)
+    }
+
+    /// Check whether an extension is enabled and granted its permissions
+    pub fn is_runnable(&self, id: &str) -> bool {
+        self.registry.get(id).is_some_and(|info| info.is_runnable())
+    }
 }
 
 impl Default for ExtensionManager {
@@ -89,4 +144,37 @@ mod tests {
         let mut manager = ExtensionManager::new();
         assert!(manager.initialize().await.is_ok());
     }
+
+    #[test]
+    fn test_grant_and_deny_permissions() {
+        let mut manager = ExtensionManager::new();
+        let manifest = manifest::Manifest::new("Blocker", "1.0.0", "Blocks requests")
+            .with_permissions(vec![
+                "webRequest".to_string(),
+                "webRequestBlocking".to_string(),
+            ]);
+        let id = manager
+            .registry_mut()
+            .register_manifest(&manifest, std::path::Path::new("/extensions/blocker"))
+            .unwrap();
+
+        assert!(!manager.is_runnable(&id));
+        let request = manager.permission_request(&id).unwrap();
+        assert_eq!(request.permissions.len(), 2);
+        assert!(request.has_high_risk());
+
+        manager.grant(&id).unwrap();
+        assert!(manager.is_runnable(&id));
+
+        manager.registry_mut().disable(&id).unwrap();
+        assert!(!manager.is_runnable(&id));
+        manager.registry_mut().enable(&id).unwrap();
+
+        manager.deny(&id).unwrap();
+        assert!(!manager.is_runnable(&id));
+
+        assert!(manager.grant("missing").is_err());
+        assert!(manager.permission_request("missing").is_err());
+        assert!(!manager.is_runnable("missing"));
+    }
 }
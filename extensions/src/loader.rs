@@ -1,21 +1,43 @@
 //! Extension loader - loads extensions from disk
 
-use anyhow::Result;
+use crate::content_scripts::CompiledContentScript;
+use crate::manifest::Manifest;
+use crate::registry::ExtensionRegistry;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 /// Extension loader
 pub struct ExtensionLoader {
     extensions_dir: PathBuf,
+    /// Content scripts of every loaded extension, in load order
+    content_scripts: Vec<CompiledContentScript>,
+    /// Manifests of the loaded extensions, by extension ID
+    manifests: HashMap<String, Manifest>,
 }
 
 impl ExtensionLoader {
     /// Create a new extension loader
     pub fn new(extensions_dir: PathBuf) -> Self {
-        Self { extensions_dir }
+        Self {
+            extensions_dir,
+            content_scripts: Vec::new(),
+            manifests: HashMap::new(),
+        }
     }
 
-    /// Load extensions from the extensions directory
-    pub async fn load_extensions(&self) -> Result<Vec<String>> {
+    /// Load extensions from the extensions directory into a registry
+    ///
+    /// Replaces the content scripts and manifests of any previously loaded
+    /// extensions.
+    /// Extensions that fail to load are skipped with a warning.
+    pub async fn load_extensions(
+        &mut self,
+        registry: &mut ExtensionRegistry,
+    ) -> Result<Vec<String>> {
+        self.content_scripts.clear();
+        self.manifests.clear();
+
         if !self.extensions_dir.exists() {
             std::fs::create_dir_all(&self.extensions_dir)?;
             return Ok(Vec::new());
@@ -30,8 +52,9 @@ impl ExtensionLoader {
             if path.is_dir() {
                 let manifest_path = path.join("manifest.json");
                 if manifest_path.exists() {
-                    if let Some(id) = self.load_extension(&path).await? {
-                        extension_ids.push(id);
+                    match self.load_extension(&path, registry).await {
+                        Ok(id) => extension_ids.push(id),
+                        Err(e) => tracing::warn!("Skipping extension {:?}: {}", path, e),
                     }
                 }
             }
@@ -41,22 +64,81 @@ impl ExtensionLoader {
     }
 
     /// Load a single extension
-    async fn load_extension(&self, path: &Path) -> Result<Option<String>> {
+    async fn load_extension(
+        &mut self,
+        path: &Path,
+        registry: &mut ExtensionRegistry,
+    ) -> Result<String> {
         let manifest_path = path.join("manifest.json");
         let manifest_content = std::fs::read_to_string(manifest_path)?;
-        let manifest: super::manifest::Manifest = serde_json::from_str(&manifest_content)?;
+        let manifest: Manifest = serde_json::from_str(&manifest_content)?;
 
-        // Generate an ID if not provided in manifest
-        let ext_id = manifest.id.clone().unwrap_or_else(|| {
-            // Use directory name as fallback ID
-            path.file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown")
-                .to_string()
-        });
+        let ext_id = registry.register_manifest(&manifest, path)?;
+
+        self.content_scripts.extend(
+            manifest
+                .content_scripts
+                .iter()
+                .map(|script| CompiledContentScript::compile(ext_id.clone(), script.clone())),
+        );
 
         tracing::info!("Loaded extension: {} ({})", manifest.name, ext_id);
-        Ok(Some(ext_id))
+        self.manifests.insert(ext_id.clone(), manifest);
+        Ok(ext_id)
+    }
+
+    /// Read an extension file requested by a web page
+    ///
+    /// Only files matching the manifest's `web_accessible_resources` are
+    /// served, and only for extensions that are enabled and granted their
+    /// permissions in `registry`.
+    pub fn web_resource(
+        &self,
+        extension_id: &str,
+        path: &str,
+        registry: &ExtensionRegistry,
+    ) -> Result<Vec<u8>> {
+        let (Some(manifest), Some(info)) =
+            (self.manifests.get(extension_id), registry.get(extension_id))
+        else {
+            return Err(anyhow!("Extension not found: {}", extension_id));
+        };
+        if !info.is_runnable() {
+            return Err(anyhow!("Extension {} is not enabled", extension_id));
+        }
+        if !manifest.is_web_accessible(path) {
+            return Err(anyhow!(
+                "{} is not web accessible in extension {}",
+                path,
+                extension_id
+            ));
+        }
+
+        let dir = info
+            .path
+            .as_ref()
+            .ok_or_else(|| anyhow!("Extension {} has no directory", extension_id))?;
+        Ok(std::fs::read(dir.join(path.trim_start_matches('/')))?)
+    }
+
+    /// Get the content scripts that should run on a URL, in load order
+    ///
+    /// Scripts of extensions that are disabled or not granted their
+    /// permissions in `registry` are left out.
+    pub fn scripts_for(
+        &self,
+        url: &str,
+        registry: &ExtensionRegistry,
+    ) -> Vec<&CompiledContentScript> {
+        self.content_scripts
+            .iter()
+            .filter(|script| {
+                registry
+                    .get(&script.extension_id)
+                    .is_some_and(|info| info.is_runnable())
+            })
+            .filter(|script| script.matches(url))
+            .collect()
     }
 
     /// Get the extensions directory
@@ -73,8 +155,146 @@ mod tests {
     #[tokio::test]
     async fn test_extension_loader() {
         let temp_dir = TempDir::new().unwrap();
-        let loader = ExtensionLoader::new(temp_dir.path().to_path_buf());
-        let extensions = loader.load_extensions().await.unwrap();
+        let mut loader = ExtensionLoader::new(temp_dir.path().to_path_buf());
+        let mut registry = ExtensionRegistry::new();
+        let extensions = loader.load_extensions(&mut registry).await.unwrap();
         assert_eq!(extensions.len(), 0);
     }
+
+    #[tokio::test]
+    async fn test_invalid_extension_is_skipped() {
+        let temp_dir = TempDir::new().unwrap();
+        let valid_dir = temp_dir.path().join("valid");
+        std::fs::create_dir(&valid_dir).unwrap();
+        std::fs::write(
+            valid_dir.join("manifest.json"),
+            r#"{
+                "manifest_version": 2,
+                "name": "Valid",
+                "version": "1.0.0",
+                "description": "Loads fine"
+            }"#,
+        )
+        .unwrap();
+        let invalid_dir = temp_dir.path().join("invalid");
+        std::fs::create_dir(&invalid_dir).unwrap();
+        std::fs::write(
+            invalid_dir.join("manifest.json"),
+            r#"{
+                "manifest_version": 2,
+                "name": "",
+                "version": "1.0.0",
+                "description": "Has no name"
+            }"#,
+        )
+        .unwrap();
+
+        let mut loader = ExtensionLoader::new(temp_dir.path().to_path_buf());
+        let mut registry = ExtensionRegistry::new();
+        assert_eq!(
+            loader.load_extensions(&mut registry).await.unwrap(),
+            vec!["valid"]
+        );
+        assert!(registry.get("invalid").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_scripts_for_url() {
+        let temp_dir = TempDir::new().unwrap();
+        let ext_dir = temp_dir.path().join("example-styles");
+        std::fs::create_dir(&ext_dir).unwrap();
+        std::fs::write(
+            ext_dir.join("manifest.json"),
+            r#"{
+                "manifest_version": 2,
+                "name": "Example Styles",
+                "version": "1.0.0",
+                "description": "Styles example.com",
+                "permissions": ["storage"],
+                "content_scripts": [
+                    { "matches": ["*://*.example.com/*"], "js": ["example.js"] },
+                    { "matches": ["https://docs.rs/*"], "js": ["docs.js"] }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let mut loader = ExtensionLoader::new(temp_dir.path().to_path_buf());
+        let mut registry = ExtensionRegistry::new();
+        assert_eq!(
+            loader.load_extensions(&mut registry).await.unwrap(),
+            vec!["example-styles"]
+        );
+
+        let info = registry.get("example-styles").unwrap();
+        assert_eq!(info.name, "Example Styles");
+        assert_eq!(info.path.as_deref(), Some(ext_dir.as_path()));
+        assert_eq!(info.permissions, vec!["storage"]);
+
+        assert!(loader
+            .scripts_for("https://www.example.com/page", &registry)
+            .is_empty());
+        registry.set_granted("example-styles", true).unwrap();
+
+        let scripts = loader.scripts_for("https://www.example.com/page", &registry);
+        assert_eq!(scripts.len(), 1);
+        assert_eq!(scripts[0].extension_id, "example-styles");
+        assert_eq!(scripts[0].script.js, vec!["example.js"]);
+
+        assert!(loader
+            .scripts_for("https://example.org/", &registry)
+            .is_empty());
+        assert!(loader.scripts_for("about:blank", &registry).is_empty());
+
+        registry.disable("example-styles").unwrap();
+        assert!(loader
+            .scripts_for("https://www.example.com/page", &registry)
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_web_resource_access() {
+        let temp_dir = TempDir::new().unwrap();
+        let ext_dir = temp_dir.path().join("icons-ext");
+        std::fs::create_dir_all(ext_dir.join("icons")).unwrap();
+        std::fs::write(ext_dir.join("icons/icon.png"), b"png").unwrap();
+        std::fs::write(ext_dir.join("background.js"), b"js").unwrap();
+        std::fs::write(
+            ext_dir.join("manifest.json"),
+            r#"{
+                "manifest_version": 2,
+                "name": "Icons",
+                "version": "1.0.0",
+                "description": "Exposes icons",
+                "web_accessible_resources": ["icons/*"]
+            }"#,
+        )
+        .unwrap();
+
+        let mut loader = ExtensionLoader::new(temp_dir.path().to_path_buf());
+        let mut registry = ExtensionRegistry::new();
+        loader.load_extensions(&mut registry).await.unwrap();
+
+        // Nothing is served until the extension is granted
+        assert!(loader
+            .web_resource("icons-ext", "icons/icon.png", &registry)
+            .is_err());
+        registry.set_granted("icons-ext", true).unwrap();
+
+        assert_eq!(
+            loader
+                .web_resource("icons-ext", "/icons/icon.png", &registry)
+                .unwrap(),
+            b"png"
+        );
+        assert!(loader
+            .web_resource("icons-ext", "background.js", &registry)
+            .is_err());
+        assert!(loader
+            .web_resource("icons-ext", "icons/../background.js", &registry)
+            .is_err());
+        assert!(loader
+            .web_resource("missing", "icons/icon.png", &registry)
+            .is_err());
+    }
 }
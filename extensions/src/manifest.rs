@@ -23,6 +23,9 @@ pub struct Manifest {
     /// Homepage URL
     #[serde(skip_serializing_if = "Option::is_none")]
     pub homepage_url: Option<String>,
+    /// URL of the update manifest listing newer versions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_url: Option<String>,
     /// Icons for different sizes
     #[serde(skip_serializing_if = "Option::is_none")]
     pub icons: Option<HashMap<String, String>>,
@@ -61,6 +64,9 @@ pub struct BackgroundScripts {
     /// Background page
     #[serde(skip_serializing_if = "Option::is_none")]
     pub page: Option<String>,
+    /// Background service worker (manifest v3 only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_worker: Option<String>,
     /// Whether the background should be persistent
     #[serde(skip_serializing_if = "Option::is_none")]
     pub persistent: Option<bool>,
@@ -82,6 +88,15 @@ pub struct ContentScript {
     pub run_at: Option<String>,
 }
 
+impl ContentScript {
+    /// Check whether any of this script's match patterns match a URL
+    pub fn matches_url(&self, url: &str) -> bool {
+        self.matches
+            .iter()
+            .any(|pattern| crate::patterns::url_matches(pattern, url))
+    }
+}
+
 /// Browser action configuration (toolbar button)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BrowserAction {
@@ -168,6 +183,31 @@ impl Permission {
             Self::AllUrls => "<all_urls>",
         }
     }
+
+    /// Parse a permission string from a manifest
+    pub fn parse(permission: &str) -> Option<Self> {
+        [
+            Self::Tabs,
+            Self::Bookmarks,
+            Self::History,
+            Self::Storage,
+            Self::Cookies,
+            Self::WebRequest,
+            Self::WebRequestBlocking,
+            Self::Network,
+            Self::Downloads,
+            Self::Notifications,
+            Self::ContextMenus,
+            Self::AllUrls,
+        ]
+        .into_iter()
+        .find(|candidate| candidate.as_str() == permission)
+    }
+
+    /// Check whether the permission gives broad access to browsing
+    pub fn is_high_risk(&self) -> bool {
+        matches!(self, Self::AllUrls | Self::WebRequestBlocking)
+    }
 }
 
 impl Manifest {
@@ -185,6 +225,7 @@ impl Manifest {
             description: description.into(),
             author: None,
             homepage_url: None,
+            update_url: None,
             icons: None,
             permissions: Vec::new(),
             optional_permissions: Vec::new(),
@@ -226,11 +267,24 @@ impl Manifest {
         self.background = Some(BackgroundScripts {
             scripts: Some(scripts),
             page: None,
+            service_worker: None,
             persistent: Some(true),
         });
         self
     }
 
+    /// Set a background service worker, switching to manifest v3
+    pub fn with_service_worker(mut self, service_worker: impl Into<String>) -> Self {
+        self.manifest_version = 3;
+        self.background = Some(BackgroundScripts {
+            scripts: None,
+            page: None,
+            service_worker: Some(service_worker.into()),
+            persistent: None,
+        });
+        self
+    }
+
     /// Add a content script
     pub fn with_content_script(mut self, matches: Vec<String>, js: Vec<String>) -> Self {
         self.content_scripts.push(ContentScript {
@@ -256,6 +310,21 @@ impl Manifest {
         self
     }
 
+    /// Check whether an extension file may be loaded by web pages
+    ///
+    /// Access is denied unless the path matches one of the
+    /// `web_accessible_resources` entries, which may contain `*` wildcards.
+    pub fn is_web_accessible(&self, path: &str) -> bool {
+        let path = path.trim_start_matches('/');
+        if path.is_empty() || path.split('/').any(|segment| segment == "..") {
+            return false;
+        }
+
+        self.web_accessible_resources
+            .iter()
+            .any(|resource| crate::patterns::glob_matches(resource.trim_start_matches('/'), path))
+    }
+
     /// Parse manifest from JSON string
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
         serde_json::from_str(json)
@@ -272,6 +341,18 @@ impl Manifest {
         if self.manifest_version < 2 || self.manifest_version > 3 {
             return Err("Manifest version must be 2 or 3".to_string());
         }
+        if let Some(service_worker) = self
+            .background
+            .as_ref()
+            .and_then(|background| background.service_worker.as_ref())
+        {
+            if self.manifest_version != 3 {
+                return Err("background.service_worker requires manifest version 3".to_string());
+            }
+            if service_worker.is_empty() {
+                return Err("background.service_worker must not be empty".to_string());
+            }
+        }
         Ok(())
     }
 }
@@ -313,6 +394,75 @@ mod tests {
         assert!(invalid.validate().is_err());
     }
 
+    #[test]
+    fn test_mv3_service_worker() {
+        let json = r#"{
+            "manifest_version": 3,
+            "name": "MV3 Extension",
+            "version": "2.0.0",
+            "description": "Uses a service worker",
+            "background": { "service_worker": "sw.js" }
+        }"#;
+
+        let manifest = Manifest::from_json(json).unwrap();
+        let background = manifest.background.as_ref().unwrap();
+        assert_eq!(background.service_worker.as_deref(), Some("sw.js"));
+        assert!(background.scripts.is_none());
+        assert!(manifest.validate().is_ok());
+
+        let v2 = Manifest {
+            manifest_version: 2,
+            ..manifest.clone()
+        };
+        assert!(v2.validate().is_err());
+
+        let built = Manifest::new("Test", "1.0.0", "Description").with_service_worker("sw.js");
+        assert_eq!(built.manifest_version, 3);
+        assert!(built.validate().is_ok());
+    }
+
+    #[test]
+    fn test_mv2_background_scripts_from_json() {
+        let json = r#"{
+            "manifest_version": 2,
+            "name": "MV2 Extension",
+            "version": "1.0.0",
+            "description": "Uses background scripts",
+            "background": { "scripts": ["bg.js"], "persistent": false }
+        }"#;
+
+        let manifest = Manifest::from_json(json).unwrap();
+        let background = manifest.background.as_ref().unwrap();
+        assert_eq!(background.scripts, Some(vec!["bg.js".to_string()]));
+        assert!(background.service_worker.is_none());
+        assert!(manifest.validate().is_ok());
+    }
+
+    #[test]
+    fn test_permission_parse_round_trip() {
+        for name in ["tabs", "webRequestBlocking", "contextMenus", "<all_urls>"] {
+            assert_eq!(Permission::parse(name).unwrap().as_str(), name);
+        }
+        assert_eq!(Permission::parse("Tabs"), None);
+        assert_eq!(Permission::parse("https://example.com/*"), None);
+    }
+
+    #[test]
+    fn test_web_accessible_resources() {
+        let manifest = Manifest {
+            web_accessible_resources: vec!["icons/*".to_string()],
+            ..Manifest::new("Test", "1.0.0", "Description")
+        };
+
+        assert!(manifest.is_web_accessible("icons/icon-48.png"));
+        assert!(manifest.is_web_accessible("/icons/icon-48.png"));
+        assert!(!manifest.is_web_accessible("background.js"));
+        assert!(!manifest.is_web_accessible("icons/../background.js"));
+
+        let empty = Manifest::new("Test", "1.0.0", "Description");
+        assert!(!empty.is_web_accessible("icons/icon-48.png"));
+    }
+
     #[test]
     fn test_firefox_compatibility() {
         let manifest = Manifest::new("Test Extension", "1.0.0", "Firefox compatible")
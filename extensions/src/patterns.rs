@@ -0,0 +1,379 @@
+//! Match pattern and glob helpers for extension manifests
+
+use anyhow::{anyhow, Result};
+use horizon_url::host_matches;
+
+/// Schemes matched by `<all_urls>`
+const ALL_URLS_SCHEMES: &[&str] = &["http", "https", "ws", "wss", "ftp", "file"];
+
+/// Schemes matched by a `*` scheme in a match pattern
+const WILDCARD_SCHEMES: &[&str] = &["http", "https", "ws", "wss"];
+
+/// Schemes a match pattern may name explicitly
+const SUPPORTED_SCHEMES: &[&str] = &["http", "https", "ws", "wss", "ftp", "file", "data"];
+
+/// A parsed WebExtension match pattern
+///
+/// Either `<all_urls>` or `scheme://host/path`, where the scheme may be `*`,
+/// the host may be `*` or start with `*.` to include subdomains, and the path
+/// may contain `*` wildcards. The path is matched against the URL's path and
+/// query string, ignoring any fragment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatchPattern {
+    /// `<all_urls>`
+    AllUrls,
+    /// A `scheme://host/path` pattern
+    Url {
+        /// Lowercase scheme, or `*`
+        scheme: String,
+        /// Lowercase host, `*`, or `*.domain`; empty for `file` URLs
+        host: String,
+        /// Path glob, starting with `/`
+        path: String,
+    },
+}
+
+impl MatchPattern {
+    /// Parse a match pattern
+    pub fn parse(pattern: &str) -> Result<Self> {
+        if pattern == "<all_urls>" {
+            return Ok(Self::AllUrls);
+        }
+
+        let (scheme, rest) = pattern
+            .split_once("://")
+            .ok_or_else(|| anyhow!("Match pattern has no scheme: {}", pattern))?;
+        let scheme = scheme.to_lowercase();
+        if scheme != "*" && !SUPPORTED_SCHEMES.contains(&scheme.as_str()) {
+            return Err(anyhow!("Unsupported scheme in match pattern: {}", pattern));
+        }
+
+        let path_start = rest
+            .find('/')
+            .ok_or_else(|| anyhow!("Match pattern has no path: {}", pattern))?;
+        let (host, path) = rest.split_at(path_start);
+        let host = host.to_lowercase();
+
+        if scheme == "file" {
+            if !host.is_empty() {
+                return Err(anyhow!("File match pattern has a host: {}", pattern));
+            }
+        } else if host.is_empty() {
+            return Err(anyhow!("Match pattern has no host: {}", pattern));
+        }
+
+        // `*` may only be the whole host or a leading `*.`
+        let named_host = if host == "*" {
+            ""
+        } else {
+            host.strip_prefix("*.").unwrap_or(&host)
+        };
+        if named_host.contains('*') {
+            return Err(anyhow!(
+                "Invalid host wildcard in match pattern: {}",
+                pattern
+            ));
+        }
+        if named_host.contains(':') {
+            return Err(anyhow!("Match pattern host has a port: {}", pattern));
+        }
+
+        Ok(Self::Url {
+            scheme,
+            host,
+            path: path.to_string(),
+        })
+    }
+
+    /// Check whether a URL matches the pattern
+    pub fn matches(&self, url: &str) -> bool {
+        let Some((url_scheme, url_rest)) = url.split_once("://") else {
+            return false;
+        };
+        let url_scheme = url_scheme.to_lowercase();
+
+        let (scheme, host, path) = match self {
+            Self::AllUrls => return ALL_URLS_SCHEMES.contains(&url_scheme.as_str()),
+            Self::Url { scheme, host, path } => (scheme, host, path),
+        };
+
+        let scheme_matches = if scheme == "*" {
+            WILDCARD_SCHEMES.contains(&url_scheme.as_str())
+        } else {
+            *scheme == url_scheme
+        };
+        if !scheme_matches {
+            return false;
+        }
+
+        // Split the URL into host and path, dropping any fragment
+        let url_rest = url_rest.split('#').next().unwrap_or_default();
+        let (url_authority, url_path) = match url_rest.find(['/', '?']) {
+            Some(index) => url_rest.split_at(index),
+            None => (url_rest, "/"),
+        };
+        let url_path = if url_path.starts_with('?') {
+            format!("/{}", url_path)
+        } else {
+            url_path.to_string()
+        };
+        let url_host = url_authority
+            .rsplit('@')
+            .next()
+            .unwrap_or_default()
+            .split(':')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+
+        host_matches(host, &url_host) && glob_matches(path, &url_path)
+    }
+}
+
+impl std::str::FromStr for MatchPattern {
+    type Err = anyhow::Error;
+
+    fn from_str(pattern: &str) -> Result<Self> {
+        Self::parse(pattern)
+    }
+}
+
+/// Check whether a URL matches a WebExtension match pattern
+///
+/// See [`MatchPattern`] for the pattern grammar. Invalid patterns match nothing.
+pub fn url_matches(pattern: &str, url: &str) -> bool {
+    MatchPattern::parse(pattern).is_ok_and(|pattern| pattern.matches(url))
+}
+
+/// Check whether text matches a glob where `*` matches any run of characters
+pub fn glob_matches(glob: &str, text: &str) -> bool {
+    let glob: Vec<char> = glob.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut g, mut t) = (0, 0);
+    // Position of the last `*` in the glob and the text index it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if g < glob.len() && glob[g] == '*' {
+            backtrack = Some((g, t));
+            g += 1;
+        } else if g < glob.len() && glob[g] == text[t] {
+            g += 1;
+            t += 1;
+        } else if let Some((star, matched)) = backtrack {
+            // Let the last `*` absorb one more character
+            g = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, t));
+        } else {
+            return false;
+        }
+    }
+
+    glob[g..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_matches() {
+        assert!(url_matches("*://*.example.com/*", "https://example.com/"));
+        assert!(url_matches(
+            "*://*.example.com/*",
+            "http://www.example.com/page"
+        ));
+        assert!(url_matches(
+            "https://example.com/docs/*",
+            "https://example.com/docs/a?b=1"
+        ));
+        assert!(url_matches("<all_urls>", "file:///home/user/index.html"));
+
+        assert!(!url_matches(
+            "*://*.example.com/*",
+            "https://notexample.com/"
+        ));
+        assert!(!url_matches(
+            "https://example.com/docs/*",
+            "http://example.com/docs/"
+        ));
+        assert!(!url_matches("*://example.com/*", "about:blank"));
+        assert!(!url_matches("not a pattern", "https://example.com/"));
+    }
+
+    /// Assert which URLs a pattern matches, following the examples in the
+    /// WebExtensions match pattern documentation
+    fn check(pattern: &str, matching: &[&str], non_matching: &[&str]) {
+        let parsed = MatchPattern::parse(pattern).unwrap();
+        for url in matching {
+            assert!(parsed.matches(url), "{} should match {}", pattern, url);
+        }
+        for url in non_matching {
+            assert!(!parsed.matches(url), "{} should not match {}", pattern, url);
+        }
+    }
+
+    #[test]
+    fn test_all_urls_pattern() {
+        check(
+            "<all_urls>",
+            &[
+                "http://example.org/",
+                "https://a.org/some/path/",
+                "ws://sockets.somewhere.org/",
+                "wss://ws.example.com/stuff/",
+                "ftp://files.somewhere.org/",
+                "file:///a/",
+            ],
+            &[
+                "resource://a/b/c/",
+                "ftps://files.somewhere.org/",
+                "about:blank",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_wildcard_scheme_and_host() {
+        check(
+            "*://*/*",
+            &[
+                "http://example.org/",
+                "https://a.org/some/path/",
+                "ws://sockets.somewhere.org/",
+                "wss://ws.example.com/stuff/",
+            ],
+            &["ftp://ftp.example.org/", "file:///a/"],
+        );
+        check(
+            "*://*.mozilla.org/*",
+            &[
+                "http://mozilla.org/",
+                "https://mozilla.org/",
+                "http://a.mozilla.org/",
+                "http://a.b.mozilla.org/",
+                "https://b.mozilla.org/path/",
+                "https://B.Mozilla.ORG/path/",
+            ],
+            &[
+                "ftp://mozilla.org/",
+                "http://mozilla.com/",
+                "http://firefox.org/",
+                "http://notmozilla.org/",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_exact_host() {
+        check(
+            "*://mozilla.org/",
+            &["http://mozilla.org/", "https://mozilla.org/"],
+            &[
+                "ftp://mozilla.org/",
+                "http://a.mozilla.org/",
+                "http://mozilla.org/a",
+            ],
+        );
+        check(
+            "ftp://mozilla.org/",
+            &["ftp://mozilla.org"],
+            &[
+                "http://mozilla.org/",
+                "ftp://sub.mozilla.org/",
+                "ftp://mozilla.org/path",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_path_globs() {
+        check(
+            "https://*/path",
+            &[
+                "https://mozilla.org/path",
+                "https://a.mozilla.org/path",
+                "https://something.com/path",
+            ],
+            &[
+                "http://mozilla.org/path",
+                "https://mozilla.org/path/",
+                "https://a.mozilla.org/path/",
+                "https://mozilla.org/path?foo=1",
+            ],
+        );
+        check(
+            "https://mozilla.org/*",
+            &[
+                "https://mozilla.org/",
+                "https://mozilla.org/path",
+                "https://mozilla.org/path/to/doc?foo=1",
+            ],
+            &["http://mozilla.org/path", "https://mozilla.com/path"],
+        );
+        check(
+            "https://mozilla.org/a/b/c/",
+            &[
+                "https://mozilla.org/a/b/c/",
+                "https://mozilla.org/a/b/c/#section1",
+            ],
+            &["https://mozilla.org/a/b/c/d/"],
+        );
+        check(
+            "https://mozilla.org/*/b/*/",
+            &[
+                "https://mozilla.org/a/b/c/",
+                "https://mozilla.org/d/b/f/",
+                "https://mozilla.org/a/b/c/d/",
+                "https://mozilla.org/a/b/c/d/#section1",
+                "https://mozilla.org/a/b/c/d/?foo=/",
+                "https://mozilla.org/a?foo=21314&bar=/b/&extra=c/",
+            ],
+            &[
+                "https://mozilla.org/b/*/",
+                "https://mozilla.org/a/b/",
+                "https://mozilla.org/a/b/c/d/?foo=bar",
+            ],
+        );
+        check(
+            "file:///blah/*",
+            &["file:///blah/", "file:///blah/bleh"],
+            &["file:///bleh/"],
+        );
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        for pattern in [
+            "resource://path/",
+            "https://mozilla.org",
+            "https://mozilla.*.org/",
+            "https://*zilla.org/",
+            "http*://mozilla.org/",
+            "https://mozilla.org:80/",
+            "*://*",
+            "file://*",
+            "file://host/path",
+            "https:///path",
+            "not a pattern",
+        ] {
+            assert!(
+                MatchPattern::parse(pattern).is_err(),
+                "{} should be invalid",
+                pattern
+            );
+            assert!(!url_matches(pattern, "https://mozilla.org/"));
+        }
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("icons/*", "icons/icon-48.png"));
+        assert!(glob_matches("*.css", "styles/page.css"));
+        assert!(glob_matches("a*b*c", "aXXbYYc"));
+        assert!(!glob_matches("icons/*", "background.js"));
+        assert!(!glob_matches("a*b", "aXXc"));
+    }
+}
@@ -0,0 +1,134 @@
+//! Permission requests shown to the user before an extension may run
+
+use crate::manifest::{Manifest, Permission};
+use crate::patterns::MatchPattern;
+
+/// A single permission an extension asks for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestedPermission {
+    /// Permission string as written in the manifest
+    pub name: String,
+    /// The standard permission, if the name is one
+    pub permission: Option<Permission>,
+    /// Whether the permission is only requested at runtime
+    pub optional: bool,
+    /// Whether the permission gives broad access to browsing
+    pub high_risk: bool,
+}
+
+impl RequestedPermission {
+    /// Describe a permission string from a manifest
+    ///
+    /// `<all_urls>`, `webRequestBlocking`, and host permissions covering every
+    /// host (like `*://*/*`) are high-risk.
+    pub fn new(name: impl Into<String>, optional: bool) -> Self {
+        let name = name.into();
+        let permission = Permission::parse(&name);
+        let high_risk = match &permission {
+            Some(permission) => permission.is_high_risk(),
+            None => matches!(
+                MatchPattern::parse(&name),
+                Ok(MatchPattern::Url { host, .. }) if host == "*"
+            ),
+        };
+
+        Self {
+            name,
+            permission,
+            optional,
+            high_risk,
+        }
+    }
+}
+
+/// Permissions an extension asks the user to approve
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionRequest {
+    /// ID of the requesting extension
+    pub extension_id: String,
+    /// Requested permissions, required ones first
+    pub permissions: Vec<RequestedPermission>,
+}
+
+impl PermissionRequest {
+    /// Build a request from required and optional permission strings
+    pub fn new(
+        extension_id: impl Into<String>,
+        permissions: &[String],
+        optional_permissions: &[String],
+    ) -> Self {
+        let permissions = permissions
+            .iter()
+            .map(|name| RequestedPermission::new(name.as_str(), false))
+            .chain(
+                optional_permissions
+                    .iter()
+                    .map(|name| RequestedPermission::new(name.as_str(), true)),
+            )
+            .collect();
+
+        Self {
+            extension_id: extension_id.into(),
+            permissions,
+        }
+    }
+
+    /// Build a request from the permissions declared in a manifest
+    pub fn from_manifest(extension_id: impl Into<String>, manifest: &Manifest) -> Self {
+        Self::new(
+            extension_id,
+            &manifest.permissions,
+            &manifest.optional_permissions,
+        )
+    }
+
+    /// Get the high-risk permissions in the request
+    pub fn high_risk(&self) -> Vec<&RequestedPermission> {
+        self.permissions.iter().filter(|p| p.high_risk).collect()
+    }
+
+    /// Check whether any requested permission is high-risk
+    pub fn has_high_risk(&self) -> bool {
+        self.permissions.iter().any(|p| p.high_risk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_from_manifest() {
+        let manifest = Manifest {
+            optional_permissions: vec!["<all_urls>".to_string()],
+            ..Manifest::new("Test", "1.0.0", "Description").with_permissions(vec![
+                "tabs".to_string(),
+                "webRequestBlocking".to_string(),
+                "https://example.com/*".to_string(),
+            ])
+        };
+
+        let request = PermissionRequest::from_manifest("test", &manifest);
+        assert_eq!(request.extension_id, "test");
+        assert_eq!(request.permissions.len(), 4);
+        assert_eq!(request.permissions[0].permission, Some(Permission::Tabs));
+        assert!(!request.permissions[0].high_risk);
+        assert_eq!(request.permissions[2].permission, None);
+        assert!(!request.permissions[2].high_risk);
+        assert!(request.permissions[3].optional);
+
+        let high_risk: Vec<&str> = request
+            .high_risk()
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(high_risk, vec!["webRequestBlocking", "<all_urls>"]);
+    }
+
+    #[test]
+    fn test_all_hosts_pattern_is_high_risk() {
+        assert!(RequestedPermission::new("*://*/*", false).high_risk);
+        assert!(!RequestedPermission::new("*://*.example.com/*", false).high_risk);
+        assert!(!PermissionRequest::new("test", &["storage".to_string()], &[]).has_high_risk());
+    }
+}
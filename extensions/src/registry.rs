@@ -1,11 +1,20 @@
 //! Extension registry - manages loaded extensions
 
-use anyhow::Result;
-use std::collections::HashMap;
+use crate::manifest::Manifest;
+use anyhow::{anyhow, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+/// File name the granted permissions are saved to in the profile folder
+pub const GRANTS_FILE: &str = "extension_grants.json";
 
 /// Extension registry
 pub struct ExtensionRegistry {
     extensions: HashMap<String, ExtensionInfo>,
+    /// Permissions the user granted, by extension ID
+    grants: BTreeMap<String, BTreeSet<String>>,
+    /// File the grants are saved to, if any
+    storage_path: Option<PathBuf>,
 }
 
 /// Information about a loaded extension
@@ -15,6 +24,23 @@ pub struct ExtensionInfo {
     pub name: String,
     pub version: String,
     pub enabled: bool,
+    /// Directory the extension was loaded from, if any
+    pub path: Option<PathBuf>,
+    /// Permissions declared in the manifest
+    pub permissions: Vec<String>,
+    /// Optional permissions declared in the manifest
+    pub optional_permissions: Vec<String>,
+    /// Whether the user has granted every permission the manifest declares
+    pub granted: bool,
+    /// URL of the extension's update manifest, if any
+    pub update_url: Option<String>,
+}
+
+impl ExtensionInfo {
+    /// Check whether the extension may run: enabled and granted its permissions
+    pub fn is_runnable(&self) -> bool {
+        self.enabled && self.granted
+    }
 }
 
 impl ExtensionRegistry {
@@ -22,9 +48,45 @@ impl ExtensionRegistry {
     pub fn new() -> Self {
         Self {
             extensions: HashMap::new(),
+            grants: BTreeMap::new(),
+            storage_path: None,
         }
     }
 
+    /// Create a registry that remembers permission grants in a file
+    ///
+    /// Extensions registered later start out granted if an earlier session
+    /// granted every permission they declare. A file listing only extension
+    /// IDs, as saved by older versions, grants no permissions.
+    pub fn with_storage_path(path: PathBuf) -> Result<Self> {
+        let grants = if path.exists() {
+            let json = std::fs::read_to_string(&path)?;
+            serde_json::from_str(&json).or_else(|_| {
+                serde_json::from_str::<BTreeSet<String>>(&json)
+                    .map(|ids| ids.into_iter().map(|id| (id, BTreeSet::new())).collect())
+            })?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self {
+            extensions: HashMap::new(),
+            grants,
+            storage_path: Some(path),
+        })
+    }
+
+    /// Save the granted permissions, if the registry has a storage path
+    fn save_grants(&self) -> Result<()> {
+        if let Some(path) = &self.storage_path {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, serde_json::to_string_pretty(&self.grants)?)?;
+        }
+        Ok(())
+    }
+
     /// Register an extension
     pub fn register(
         &mut self,
@@ -38,12 +100,49 @@ impl ExtensionRegistry {
             name: name.into(),
             version: version.into(),
             enabled: true,
+            path: None,
+            permissions: Vec::new(),
+            optional_permissions: Vec::new(),
+            granted: self.is_granted(&id, &[]),
+            update_url: None,
         };
 
         self.extensions.insert(id, info);
         Ok(())
     }
 
+    /// Register an extension from its manifest, returning its ID
+    ///
+    /// The ID is the manifest's `id`, falling back to the name of the
+    /// directory the extension was loaded from.
+    pub fn register_manifest(&mut self, manifest: &Manifest, path: &Path) -> Result<String> {
+        manifest.validate().map_err(|e| anyhow!(e))?;
+
+        let id = match &manifest.id {
+            Some(id) => id.clone(),
+            None => path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .ok_or_else(|| anyhow!("Cannot derive an extension ID from {:?}", path))?
+                .to_string(),
+        };
+
+        let info = ExtensionInfo {
+            id: id.clone(),
+            name: manifest.name.clone(),
+            version: manifest.version.clone(),
+            enabled: true,
+            path: Some(path.to_path_buf()),
+            permissions: manifest.permissions.clone(),
+            optional_permissions: manifest.optional_permissions.clone(),
+            granted: self.is_granted(&id, &manifest.permissions),
+            update_url: manifest.update_url.clone(),
+        };
+
+        self.extensions.insert(id.clone(), info);
+        Ok(id)
+    }
+
     /// Unregister an extension
     pub fn unregister(&mut self, id: &str) -> Result<()> {
         if self.extensions.remove(id).is_some() {
@@ -82,6 +181,46 @@ impl ExtensionRegistry {
             anyhow::bail!("Extension not found")
         }
     }
+
+    /// Check whether an extension was granted all of a set of permissions
+    fn is_granted(&self, id: &str, permissions: &[String]) -> bool {
+        self.grants
+            .get(id)
+            .is_some_and(|granted| permissions.iter().all(|p| granted.contains(p)))
+    }
+
+    /// Get the declared permissions the user has not granted an extension
+    ///
+    /// After an update adds permissions, these are the ones to ask about.
+    pub fn ungranted_permissions(&self, id: &str) -> Vec<&str> {
+        let granted = self.grants.get(id);
+        self.extensions
+            .get(id)
+            .map(|info| {
+                info.permissions
+                    .iter()
+                    .filter(|p| !granted.is_some_and(|granted| granted.contains(*p)))
+                    .map(String::as_str)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Record whether the user granted an extension's declared permissions
+    pub fn set_granted(&mut self, id: &str, granted: bool) -> Result<()> {
+        let Some(info) = self.extensions.get_mut(id) else {
+            anyhow::bail!("Extension not found")
+        };
+        info.granted = granted;
+
+        if granted {
+            self.grants
+                .insert(id.to_string(), info.permissions.iter().cloned().collect());
+        } else {
+            self.grants.remove(id);
+        }
+        self.save_grants()
+    }
 }
 
 impl Default for ExtensionRegistry {
@@ -113,4 +252,107 @@ mod tests {
         registry.unregister("test-1").unwrap();
         assert_eq!(registry.list().len(), 0);
     }
+
+    #[test]
+    fn test_register_manifest() {
+        let mut registry = ExtensionRegistry::new();
+        let manifest = Manifest::new("Tab Tools", "2.1.0", "Tab helpers")
+            .with_permissions(vec!["tabs".to_string(), "storage".to_string()]);
+
+        let id = registry
+            .register_manifest(&manifest, Path::new("/extensions/tab-tools"))
+            .unwrap();
+        assert_eq!(id, "tab-tools");
+
+        let info = registry.get("tab-tools").unwrap();
+        assert_eq!(info.name, "Tab Tools");
+        assert_eq!(info.version, "2.1.0");
+        assert_eq!(info.path, Some(PathBuf::from("/extensions/tab-tools")));
+        assert_eq!(info.permissions, vec!["tabs", "storage"]);
+        assert!(info.optional_permissions.is_empty());
+    }
+
+    #[test]
+    fn test_register_manifest_prefers_manifest_id() {
+        let mut registry = ExtensionRegistry::new();
+        let manifest =
+            Manifest::new("Tab Tools", "2.1.0", "Tab helpers").with_id("tabs@example.com");
+
+        let id = registry
+            .register_manifest(&manifest, Path::new("/extensions/tab-tools"))
+            .unwrap();
+        assert_eq!(id, "tabs@example.com");
+        assert!(registry.get("tab-tools").is_none());
+
+        let invalid = Manifest::new("", "1.0.0", "No name");
+        assert!(registry
+            .register_manifest(&invalid, Path::new("/extensions/unnamed"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_grants_survive_restart() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let grants_path = temp_dir.path().join("grants.json");
+        let manifest = Manifest::new("Tab Tools", "2.1.0", "Tab helpers");
+        let ext_path = Path::new("/extensions/tab-tools");
+
+        let mut registry = ExtensionRegistry::with_storage_path(grants_path.clone()).unwrap();
+        let id = registry.register_manifest(&manifest, ext_path).unwrap();
+        assert!(!registry.get(&id).unwrap().granted);
+        registry.set_granted(&id, true).unwrap();
+
+        let mut registry = ExtensionRegistry::with_storage_path(grants_path.clone()).unwrap();
+        registry.register_manifest(&manifest, ext_path).unwrap();
+        assert!(registry.get(&id).unwrap().is_runnable());
+        registry.set_granted(&id, false).unwrap();
+
+        let mut registry = ExtensionRegistry::with_storage_path(grants_path).unwrap();
+        registry.register_manifest(&manifest, ext_path).unwrap();
+        assert!(!registry.get(&id).unwrap().granted);
+    }
+
+    #[test]
+    fn test_new_permissions_need_a_new_grant() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let grants_path = temp_dir.path().join("grants.json");
+        let ext_path = Path::new("/extensions/tab-tools");
+        let manifest = Manifest::new("Tab Tools", "2.1.0", "Tab helpers")
+            .with_permissions(vec!["tabs".to_string()]);
+
+        let mut registry = ExtensionRegistry::with_storage_path(grants_path.clone()).unwrap();
+        let id = registry.register_manifest(&manifest, ext_path).unwrap();
+        registry.set_granted(&id, true).unwrap();
+
+        // An update asking for more permissions is no longer granted
+        let updated = Manifest::new("Tab Tools", "2.2.0", "Tab helpers")
+            .with_permissions(vec!["tabs".to_string(), "history".to_string()]);
+        let mut registry = ExtensionRegistry::with_storage_path(grants_path.clone()).unwrap();
+        registry.register_manifest(&updated, ext_path).unwrap();
+        assert!(!registry.get(&id).unwrap().granted);
+        assert_eq!(registry.ungranted_permissions(&id), vec!["history"]);
+
+        registry.set_granted(&id, true).unwrap();
+        assert!(registry.ungranted_permissions(&id).is_empty());
+
+        // Dropping a permission keeps the grant
+        let mut registry = ExtensionRegistry::with_storage_path(grants_path).unwrap();
+        registry.register_manifest(&manifest, ext_path).unwrap();
+        assert!(registry.get(&id).unwrap().granted);
+    }
+
+    #[test]
+    fn test_id_only_grants_grant_no_permissions() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let grants_path = temp_dir.path().join("grants.json");
+        std::fs::write(&grants_path, r#"["tab-tools"]"#).unwrap();
+
+        let mut registry = ExtensionRegistry::with_storage_path(grants_path).unwrap();
+        let manifest = Manifest::new("Tab Tools", "2.1.0", "Tab helpers")
+            .with_permissions(vec!["tabs".to_string()]);
+        let id = registry
+            .register_manifest(&manifest, Path::new("/extensions/tab-tools"))
+            .unwrap();
+        assert!(!registry.get(&id).unwrap().granted);
+    }
 }
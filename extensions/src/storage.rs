@@ -0,0 +1,222 @@
+//! Extension storage API
+//!
+//! Each extension gets a key-value store of JSON values, saved to
+//! `storage.json` in the extension's folder. Only extensions granted the
+//! `storage` permission may open it.
+
+use crate::manifest::Permission;
+use crate::registry::ExtensionInfo;
+use anyhow::{anyhow, Result};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// File name used for stored items inside an extension's folder
+const STORAGE_FILE: &str = "storage.json";
+
+/// Default number of bytes an extension may store
+pub const DEFAULT_STORAGE_QUOTA: usize = 5 * 1024 * 1024;
+
+/// Persistent key-value storage for one extension
+pub struct ExtensionStorage {
+    /// ID of the owning extension
+    extension_id: String,
+    /// Stored items
+    items: Map<String, Value>,
+    /// Path to the storage file
+    path: PathBuf,
+    /// Maximum bytes in use, counting keys and serialized values
+    quota: usize,
+}
+
+impl ExtensionStorage {
+    /// Open the storage of a registered extension
+    ///
+    /// Fails unless the extension declares the `storage` permission, has been
+    /// granted its permissions, and was loaded from a folder.
+    pub fn open(info: &ExtensionInfo) -> Result<Self> {
+        let storage = Permission::Storage.as_str();
+        if !info.granted || !info.permissions.iter().any(|p| p == storage) {
+            return Err(anyhow!(
+                "Extension {} does not have the storage permission",
+                info.id
+            ));
+        }
+
+        let dir = info
+            .path
+            .as_ref()
+            .ok_or_else(|| anyhow!("Extension {} has no folder for storage", info.id))?;
+
+        Self::with_storage_path(&info.id, dir.join(STORAGE_FILE))
+    }
+
+    /// Open storage for an extension at a specific file, without permission checks
+    fn with_storage_path(extension_id: &str, path: PathBuf) -> Result<Self> {
+        let mut storage = Self {
+            extension_id: extension_id.to_string(),
+            items: Map::new(),
+            path,
+            quota: DEFAULT_STORAGE_QUOTA,
+        };
+
+        // Load existing items if file exists
+        if storage.path.exists() {
+            let json = std::fs::read_to_string(&storage.path)?;
+            storage.items = serde_json::from_str(&json)?;
+        }
+
+        Ok(storage)
+    }
+
+    /// Set the number of bytes the extension may store
+    pub fn with_quota(mut self, quota: usize) -> Self {
+        self.quota = quota;
+        self
+    }
+
+    /// Get a stored value
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.items.get(key)
+    }
+
+    /// Store a value, replacing any previous value for the key
+    ///
+    /// Fails without changing anything if the result would exceed the quota.
+    pub fn set(&mut self, key: impl Into<String>, value: Value) -> Result<()> {
+        let key = key.into();
+        let old_size = self.items.get(&key).map_or(0, |old| item_size(&key, old));
+        let new_size = self.bytes_in_use() - old_size + item_size(&key, &value);
+        if new_size > self.quota {
+            return Err(anyhow!(
+                "Storage quota of {} bytes exceeded for extension {}",
+                self.quota,
+                self.extension_id
+            ));
+        }
+
+        self.items.insert(key, value);
+        self.save()
+    }
+
+    /// Remove a value, returning it if it was stored
+    pub fn remove(&mut self, key: &str) -> Result<Option<Value>> {
+        let removed = self.items.remove(key);
+        if removed.is_some() {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// Remove every stored value
+    pub fn clear(&mut self) -> Result<()> {
+        self.items.clear();
+        self.save()
+    }
+
+    /// Get the number of bytes in use, counting keys and serialized values
+    pub fn bytes_in_use(&self) -> usize {
+        self.items
+            .iter()
+            .map(|(key, value)| item_size(key, value))
+            .sum()
+    }
+
+    /// Get the storage file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Save items to the storage file
+    fn save(&self) -> Result<()> {
+        let json = serde_json::to_string(&self.items)?;
+        std::fs::write(&self.path, json)?;
+        tracing::debug!(
+            "Saved storage for extension {} to {:?}",
+            self.extension_id,
+            self.path
+        );
+        Ok(())
+    }
+}
+
+/// Count the bytes an item takes against the quota
+fn item_size(key: &str, value: &Value) -> usize {
+    key.len() + value.to_string().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn extension(dir: &TempDir, permissions: &[&str], granted: bool) -> ExtensionInfo {
+        ExtensionInfo {
+            id: "notes".to_string(),
+            name: "Notes".to_string(),
+            version: "1.0.0".to_string(),
+            enabled: true,
+            path: Some(dir.path().to_path_buf()),
+            permissions: permissions.iter().map(|p| p.to_string()).collect(),
+            optional_permissions: Vec::new(),
+            granted,
+            update_url: None,
+        }
+    }
+
+    #[test]
+    fn test_set_get_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let info = extension(&dir, &["storage"], true);
+
+        let mut storage = ExtensionStorage::open(&info).unwrap();
+        storage.set("theme", json!("dark")).unwrap();
+        storage.set("counts", json!({ "visits": 3 })).unwrap();
+        storage.set("stale", json!(true)).unwrap();
+        assert_eq!(storage.remove("stale").unwrap(), Some(json!(true)));
+        assert_eq!(storage.remove("stale").unwrap(), None);
+
+        let reopened = ExtensionStorage::open(&info).unwrap();
+        assert_eq!(reopened.get("theme"), Some(&json!("dark")));
+        assert_eq!(reopened.get("counts"), Some(&json!({ "visits": 3 })));
+        assert_eq!(reopened.get("stale"), None);
+        assert!(reopened.path().starts_with(dir.path()));
+
+        let mut storage = reopened;
+        storage.clear().unwrap();
+        assert_eq!(storage.bytes_in_use(), 0);
+        assert!(ExtensionStorage::open(&info)
+            .unwrap()
+            .get("theme")
+            .is_none());
+    }
+
+    #[test]
+    fn test_quota_enforced() {
+        let dir = TempDir::new().unwrap();
+        let info = extension(&dir, &["storage"], true);
+        let mut storage = ExtensionStorage::open(&info).unwrap().with_quota(20);
+
+        // "key" plus "\"0123456789\"" is 15 bytes
+        storage.set("key", json!("0123456789")).unwrap();
+        assert_eq!(storage.bytes_in_use(), 15);
+        assert!(storage.set("other", json!("0123456789")).is_err());
+        assert_eq!(storage.get("other"), None);
+
+        // Replacing a value only counts the difference
+        storage.set("key", json!("012345678901234")).unwrap();
+        assert_eq!(storage.bytes_in_use(), 20);
+    }
+
+    #[test]
+    fn test_permission_required() {
+        let dir = TempDir::new().unwrap();
+
+        assert!(ExtensionStorage::open(&extension(&dir, &["tabs"], true)).is_err());
+        assert!(ExtensionStorage::open(&extension(&dir, &["storage"], false)).is_err());
+
+        let mut no_folder = extension(&dir, &["storage"], true);
+        no_folder.path = None;
+        assert!(ExtensionStorage::open(&no_folder).is_err());
+    }
+}
@@ -0,0 +1,266 @@
+//! Extension update checking
+//!
+//! Extensions with an `update_url` are checked against a Firefox-style update
+//! manifest listing the versions available for each extension ID.
+
+use crate::registry::{ExtensionInfo, ExtensionRegistry};
+use anyhow::{anyhow, Result};
+use horizon_networking::client::HttpClient;
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// A version offered by an update manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateCandidate {
+    /// Offered version
+    pub version: String,
+    /// Where the package for the version can be downloaded
+    pub update_link: Option<String>,
+}
+
+/// A newer version available for an installed extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AvailableUpdate {
+    /// ID of the extension
+    pub extension_id: String,
+    /// Installed version
+    pub current_version: String,
+    /// Newest offered version
+    pub version: String,
+    /// Where the package for the version can be downloaded
+    pub update_link: Option<String>,
+}
+
+/// Update manifest: `{ "addons": { "<id>": { "updates": [...] } } }`
+#[derive(Debug, Deserialize)]
+struct UpdateManifest {
+    #[serde(default)]
+    addons: HashMap<String, AddonUpdates>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AddonUpdates {
+    #[serde(default)]
+    updates: Vec<UpdateEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UpdateEntry {
+    version: String,
+    update_link: Option<String>,
+}
+
+/// Checks installed extensions for newer versions
+pub struct ExtensionUpdater {
+    client: HttpClient,
+}
+
+impl ExtensionUpdater {
+    /// Create an updater fetching update manifests with a client
+    pub fn new(client: HttpClient) -> Self {
+        Self { client }
+    }
+
+    /// Check every registered extension with an update URL
+    ///
+    /// Extensions whose check fails are skipped with a warning.
+    pub async fn check(&self, registry: &ExtensionRegistry) -> Vec<AvailableUpdate> {
+        let mut updates = Vec::new();
+        for info in registry.list() {
+            match self.check_extension(info).await {
+                Ok(Some(update)) => updates.push(update),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Update check failed for extension {}: {}", info.id, e),
+            }
+        }
+        updates
+    }
+
+    /// Check one extension, returning the newest version if it is newer
+    pub async fn check_extension(&self, info: &ExtensionInfo) -> Result<Option<AvailableUpdate>> {
+        let Some(update_url) = &info.update_url else {
+            return Ok(None);
+        };
+
+        let response = self.client.get(update_url).await?;
+        if !response.is_success() {
+            return Err(anyhow!(
+                "Update manifest request failed with status {}",
+                response.status()
+            ));
+        }
+
+        let candidates = parse_update_manifest(&response.body_string()?, &info.id)?;
+        let newest = candidates
+            .into_iter()
+            .max_by(|a, b| compare_versions(&a.version, &b.version));
+
+        Ok(newest
+            .filter(|candidate| {
+                compare_versions(&candidate.version, &info.version) == Ordering::Greater
+            })
+            .map(|candidate| AvailableUpdate {
+                extension_id: info.id.clone(),
+                current_version: info.version.clone(),
+                version: candidate.version,
+                update_link: candidate.update_link,
+            }))
+    }
+}
+
+/// Get the versions an update manifest offers for an extension
+pub fn parse_update_manifest(json: &str, extension_id: &str) -> Result<Vec<UpdateCandidate>> {
+    let mut manifest: UpdateManifest = serde_json::from_str(json)?;
+    let updates = manifest
+        .addons
+        .remove(extension_id)
+        .map(|addon| addon.updates)
+        .unwrap_or_default();
+
+    Ok(updates
+        .into_iter()
+        .map(|entry| UpdateCandidate {
+            version: entry.version,
+            update_link: entry.update_link,
+        })
+        .collect())
+}
+
+/// Compare two extension versions
+///
+/// Versions are dot-separated parts compared numerically, so `1.10.0` is
+/// newer than `1.2.0`, and missing parts count as zero. A part with a
+/// pre-release suffix (`0-beta`) is older than the bare number.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let a: Vec<&str> = a.trim().split('.').collect();
+    let b: Vec<&str> = b.trim().split('.').collect();
+
+    for i in 0..a.len().max(b.len()) {
+        let ordering = compare_parts(a.get(i).copied(), b.get(i).copied());
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Compare one version part, treating a missing part as `0`
+fn compare_parts(a: Option<&str>, b: Option<&str>) -> Ordering {
+    let (a_number, a_suffix) = split_part(a.unwrap_or("0"));
+    let (b_number, b_suffix) = split_part(b.unwrap_or("0"));
+
+    a_number
+        .cmp(&b_number)
+        .then_with(|| match (a_suffix.is_empty(), b_suffix.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => a_suffix.cmp(b_suffix),
+        })
+}
+
+/// Split a version part into its leading number and any suffix
+fn split_part(part: &str) -> (u64, &str) {
+    let digits = part.len() - part.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let (number, suffix) = part.split_at(digits);
+    (
+        number.parse().unwrap_or(0),
+        suffix.trim_start_matches(['-', '+']),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UPDATE_MANIFEST: &str = r#"{
+        "addons": {
+            "notes@example.com": {
+                "updates": [
+                    { "version": "1.2.0", "update_link": "https://example.com/notes-1.2.0.xpi" },
+                    { "version": "1.10.0", "update_link": "https://example.com/notes-1.10.0.xpi" },
+                    { "version": "1.9.5" }
+                ]
+            },
+            "other@example.com": {
+                "updates": [{ "version": "9.0" }]
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.2.0", "1.10.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.10.0", "1.9.9"), Ordering::Greater);
+        assert_eq!(compare_versions("2.0", "2.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.0.1", "1.0"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0.0-beta", "1.0.0"), Ordering::Less);
+        assert_eq!(
+            compare_versions("1.0.0-alpha", "1.0.0-beta"),
+            Ordering::Less
+        );
+        assert_eq!(compare_versions("1.0.0.1", "1.0.0"), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_parse_update_manifest() {
+        let candidates = parse_update_manifest(UPDATE_MANIFEST, "notes@example.com").unwrap();
+        let versions: Vec<&str> = candidates.iter().map(|c| c.version.as_str()).collect();
+        assert_eq!(versions, vec!["1.2.0", "1.10.0", "1.9.5"]);
+        assert_eq!(
+            candidates[1].update_link.as_deref(),
+            Some("https://example.com/notes-1.10.0.xpi")
+        );
+        assert_eq!(candidates[2].update_link, None);
+
+        assert!(
+            parse_update_manifest(UPDATE_MANIFEST, "missing@example.com")
+                .unwrap()
+                .is_empty()
+        );
+        assert!(parse_update_manifest("not json", "notes@example.com").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_check_finds_newest_version() {
+        let (server_url, _server) = horizon_networking::test_server::serve(|_| {
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                UPDATE_MANIFEST.len(),
+                UPDATE_MANIFEST
+            )
+            .into_bytes()
+        })
+        .await;
+        let update_url = format!("{}/updates.json", server_url);
+
+        let mut registry = ExtensionRegistry::new();
+        let manifest = crate::manifest::Manifest {
+            update_url: Some(update_url),
+            ..crate::manifest::Manifest::new("Notes", "1.2.0", "Notes").with_id("notes@example.com")
+        };
+        registry
+            .register_manifest(&manifest, std::path::Path::new("/extensions/notes"))
+            .unwrap();
+        registry
+            .register_manifest(
+                &crate::manifest::Manifest::new("Local", "1.0.0", "No updates"),
+                std::path::Path::new("/extensions/local"),
+            )
+            .unwrap();
+
+        let updater = ExtensionUpdater::new(HttpClient::new().unwrap());
+        let updates = updater.check(&registry).await;
+
+        assert_eq!(
+            updates,
+            vec![AvailableUpdate {
+                extension_id: "notes@example.com".to_string(),
+                current_version: "1.2.0".to_string(),
+                version: "1.10.0".to_string(),
+                update_link: Some("https://example.com/notes-1.10.0.xpi".to_string()),
+            }]
+        );
+    }
+}
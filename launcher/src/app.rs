@@ -1,8 +1,15 @@
 //! Horizon Browser application
 
 use anyhow::Result;
+use horizon_engine::renderer::RendererBackend;
 use horizon_engine::Engine;
-use std::path::PathBuf;
+use horizon_storage::userdata::{DataType, UserDataManager};
+use horizon_url::ParsedUrl;
+use std::path::{Path, PathBuf};
+
+/// Data cleared on exit when `clear_on_exit` is enabled; bookmarks and
+/// passwords are always kept
+const CLEAR_ON_EXIT: [DataType; 3] = [DataType::Cookies, DataType::Cache, DataType::History];
 
 /// Main application state
 pub struct HorizonApp {
@@ -12,6 +19,8 @@ pub struct HorizonApp {
     storage_manager: horizon_storage::StorageManager,
     extension_manager: horizon_extensions::ExtensionManager,
     sandbox_manager: horizon_sandbox::SandboxManager,
+    /// Whether to open a private window
+    private_window: bool,
 }
 
 impl HorizonApp {
@@ -23,31 +32,105 @@ impl HorizonApp {
         let data_dir = Self::get_data_directory()?;
         tracing::info!("Using data directory: {:?}", data_dir);
 
+        let storage_manager = horizon_storage::StorageManager::for_active_profile(&data_dir)?;
+        let grants_path = storage_manager
+            .base_path()
+            .join(horizon_extensions::registry::GRANTS_FILE);
+
         Ok(Self {
             engine: horizon_engine::HorizonEngine::new(),
             ui_manager: horizon_ui::UIManager::new(),
             network_manager: horizon_networking::NetworkManager::new()?,
-            storage_manager: horizon_storage::StorageManager::new(data_dir)?,
-            extension_manager: horizon_extensions::ExtensionManager::new(),
+            storage_manager,
+            extension_manager: horizon_extensions::ExtensionManager::with_storage_path(
+                grants_path,
+            )?,
             sandbox_manager: horizon_sandbox::SandboxManager::new(),
+            private_window: false,
         })
     }
 
+    /// Open a private window, which leaves no history, cookies, or saved
+    /// passwords behind
+    pub fn with_private_window(mut self, private_window: bool) -> Self {
+        self.private_window = private_window;
+        self
+    }
+
     /// Initialize all subsystems
     async fn initialize(&mut self) -> Result<()> {
         tracing::info!("Initializing subsystems");
 
         self.sandbox_manager.initialize()?;
         self.storage_manager.initialize()?;
+        self.prune_cache();
         self.ui_manager.initialize()?;
+        let themes_dir = self
+            .storage_manager
+            .base_path()
+            .join(horizon_ui::theme::THEMES_DIR);
+        if let Err(e) = self.ui_manager.load_themes(&themes_dir) {
+            tracing::warn!("Failed to load custom themes: {}", e);
+        }
         self.network_manager.initialize().await?;
+        let privacy = &self.storage_manager.settings().privacy;
+        self.network_manager.set_https_only(privacy.https_only);
+        if privacy.tracking_protection {
+            self.network_manager
+                .set_blocklist(Some(horizon_networking::tracking::Blocklist::bundled()));
+        }
+        let settings =
+            horizon_ui::settings::SettingsUI::from_storage(self.storage_manager.settings());
+        if let Err(e) = self
+            .network_manager
+            .apply_vpn_config(settings.network.vpn_config().as_ref())
+        {
+            tracing::warn!("Failed to apply VPN settings: {}", e);
+        }
         self.extension_manager.initialize().await?;
         self.engine.initialize().await?;
 
+        let advanced = &self.storage_manager.settings().advanced;
+        if advanced.preconnect_on_startup {
+            self.warmup_connections().await;
+        }
+
         tracing::info!("All subsystems initialized successfully");
         Ok(())
     }
 
+    /// Shrink the cache directory to the configured maximum size
+    fn prune_cache(&self) {
+        let max_bytes = self.storage_manager.settings().advanced.max_cache_bytes;
+        let result = horizon_storage::userdata::UserDataManager::new(
+            self.storage_manager.base_path().clone(),
+        )
+        .and_then(|user_data| user_data.prune_cache(max_bytes));
+        if let Err(e) = result {
+            tracing::warn!("Failed to prune cache: {}", e);
+        }
+    }
+
+    /// Preconnect to the homepage and search engine so the first navigation is fast
+    async fn warmup_connections(&self) {
+        let settings =
+            horizon_ui::settings::SettingsUI::from_storage(self.storage_manager.settings());
+        let urls = [
+            settings.general.homepage.clone(),
+            settings.general.search_engine.search_url(""),
+        ];
+
+        let hosts: Vec<String> = urls
+            .iter()
+            .filter_map(|url| ParsedUrl::parse(url).ok())
+            .filter(ParsedUrl::is_web)
+            .filter_map(|url| url.host().map(str::to_string))
+            .collect();
+        let hosts: Vec<&str> = hosts.iter().map(String::as_str).collect();
+
+        self.network_manager.warmup(&hosts).await;
+    }
+
     /// Run the application
     pub async fn run(mut self) -> Result<()> {
         // Initialize all subsystems
@@ -56,7 +139,16 @@ impl HorizonApp {
         tracing::info!("Launching browser window");
 
         // Create and run the main window
-        let window_config = horizon_ui::window::WindowConfig::default();
+        let advanced = &self.storage_manager.settings().advanced;
+        let window_config = horizon_ui::window::WindowConfig {
+            security_policy: self.sandbox_manager.policy().clone(),
+            private: self.private_window,
+            renderer_backend: RendererBackend::from_hardware_acceleration(
+                advanced.hardware_acceleration,
+            ),
+            themes: self.ui_manager.themes().clone(),
+            ..Default::default()
+        };
         let window = horizon_ui::window::BrowserWindow::new(window_config);
 
         // Run the window (this blocks until the window is closed)
@@ -74,6 +166,17 @@ impl HorizonApp {
 
         self.engine.shutdown().await?;
 
+        // The window saves settings itself, so pick up changes made while it was open
+        if let Err(e) = self.storage_manager.reload_settings() {
+            tracing::warn!("Failed to reload settings: {}", e);
+        }
+
+        if self.storage_manager.settings().privacy.clear_on_exit {
+            if let Err(e) = clear_browsing_data(self.storage_manager.base_path()) {
+                tracing::error!("Failed to clear browsing data: {}", e);
+            }
+        }
+
         // Save settings before exiting
         if let Err(e) = self.storage_manager.save_settings() {
             tracing::error!("Failed to save settings: {}", e);
@@ -84,7 +187,7 @@ impl HorizonApp {
     }
 
     /// Get the platform-specific data directory
-    fn get_data_directory() -> Result<PathBuf> {
+    pub(crate) fn get_data_directory() -> Result<PathBuf> {
         let data_dir = if cfg!(target_os = "windows") {
             // Windows: %APPDATA%/Horizon
             dirs::data_dir()
@@ -105,3 +208,45 @@ impl HorizonApp {
         Ok(data_dir)
     }
 }
+
+/// Clear the browsing data listed in `CLEAR_ON_EXIT` from a data directory
+fn clear_browsing_data(data_dir: &Path) -> Result<()> {
+    let user_data = UserDataManager::new(data_dir.to_path_buf())?;
+    user_data.clear_types(&CLEAR_ON_EXIT)?;
+    tracing::info!("Cleared browsing data on exit: {:?}", CLEAR_ON_EXIT);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_clear_browsing_data_keeps_bookmarks() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_data = UserDataManager::new(temp_dir.path().to_path_buf()).unwrap();
+        for data_type in [
+            DataType::Cookies,
+            DataType::Cache,
+            DataType::History,
+            DataType::Bookmarks,
+        ] {
+            let dir = user_data.path_for(data_type);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("data.json"), b"{}").unwrap();
+        }
+        std::fs::write(temp_dir.path().join("passwords.dat"), b"secret").unwrap();
+
+        clear_browsing_data(temp_dir.path()).unwrap();
+
+        assert!(!user_data.path_for(DataType::Cookies).exists());
+        assert!(!user_data.path_for(DataType::Cache).exists());
+        assert!(!user_data.path_for(DataType::History).exists());
+        assert!(user_data
+            .path_for(DataType::Bookmarks)
+            .join("data.json")
+            .exists());
+        assert!(temp_dir.path().join("passwords.dat").exists());
+    }
+}
@@ -0,0 +1,205 @@
+//! Crash logging
+//!
+//! The panic hook writes a report for each panic into the data directory's
+//! `crashes/` folder so headless crashes leave something to attach to a bug.
+
+use anyhow::Result;
+use std::io::Write;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Folder for crash files inside the data directory
+pub const CRASH_DIR: &str = "crashes";
+
+/// Default number of crash files to keep
+pub const DEFAULT_MAX_CRASH_FILES: usize = 10;
+
+/// Writes crash files and prunes old ones
+#[derive(Debug, Clone)]
+pub struct CrashLog {
+    /// Directory crash files are written to
+    dir: PathBuf,
+    /// Number of most recent crash files to keep
+    max_files: usize,
+}
+
+impl CrashLog {
+    /// Create a crash log writing to the `crashes/` folder of a data directory
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            dir: data_dir.join(CRASH_DIR),
+            max_files: DEFAULT_MAX_CRASH_FILES,
+        }
+    }
+
+    /// Set how many crash files to keep (at least one)
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files.max(1);
+        self
+    }
+
+    /// Get the crash directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Install a panic hook that logs the panic and writes a crash file
+    ///
+    /// The hook never panics itself: every write error is ignored.
+    pub fn install(self) {
+        std::panic::set_hook(Box::new(move |panic_info| {
+            let message = panic_message(panic_info);
+            let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+
+            // eprintln! panics if stderr is gone, so write to it directly
+            let _ = writeln!(std::io::stderr(), "Horizon Browser panicked: {}", message);
+            tracing::error!("Panic occurred: {}", message);
+
+            match self.write(&message, &backtrace) {
+                Ok(path) => {
+                    let _ = writeln!(std::io::stderr(), "Crash report written to {:?}", path);
+                }
+                Err(e) => {
+                    let _ = writeln!(std::io::stderr(), "Failed to write crash report: {}", e);
+                }
+            }
+        }));
+    }
+
+    /// Write a crash file now, returning its path
+    pub fn write(&self, message: &str, backtrace: &str) -> Result<PathBuf> {
+        self.write_at(message, backtrace, SystemTime::now())
+    }
+
+    /// Write a crash file for a panic at a specific time, returning its path
+    ///
+    /// Older crash files beyond the configured cap are removed afterwards.
+    pub fn write_at(&self, message: &str, backtrace: &str, time: SystemTime) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        let millis = time.duration_since(UNIX_EPOCH)?.as_millis();
+        let path = self.dir.join(format!("crash-{:013}.log", millis));
+
+        let report = format!(
+            "Horizon Browser crash report\n\
+             Version: {}\n\
+             OS: {} ({})\n\
+             Time: {} ms since Unix epoch\n\
+             \n\
+             Panic: {}\n\
+             \n\
+             Backtrace:\n{}\n",
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            millis,
+            message,
+            backtrace,
+        );
+        std::fs::write(&path, report)?;
+
+        self.prune()?;
+        Ok(path)
+    }
+
+    /// List crash files, oldest first
+    pub fn files(&self) -> Result<Vec<PathBuf>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut files: Vec<PathBuf> = std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("crash-") && name.ends_with(".log"))
+            })
+            .collect();
+
+        // File names carry a zero-padded timestamp, so they sort chronologically
+        files.sort();
+        Ok(files)
+    }
+
+    /// Remove the oldest crash files beyond the cap
+    fn prune(&self) -> Result<()> {
+        let files = self.files()?;
+        let excess = files.len().saturating_sub(self.max_files);
+        for path in &files[..excess] {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Describe a panic as its payload message and source location
+fn panic_message(panic_info: &PanicHookInfo<'_>) -> String {
+    let payload = panic_info.payload();
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("<non-string panic payload>");
+
+    match panic_info.location() {
+        Some(location) => format!("{} at {}", message, location),
+        None => message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_crash_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let crash_log = CrashLog::new(temp_dir.path());
+
+        let path = crash_log
+            .write("index out of bounds", "0: horizon::main")
+            .unwrap();
+
+        assert!(path.starts_with(temp_dir.path().join(CRASH_DIR)));
+        let report = std::fs::read_to_string(&path).unwrap();
+        assert!(report.contains("Panic: index out of bounds"));
+        assert!(report.contains("0: horizon::main"));
+        assert!(report.contains(env!("CARGO_PKG_VERSION")));
+        assert!(report.contains(std::env::consts::OS));
+    }
+
+    #[test]
+    fn test_retention_cap_keeps_newest() {
+        let temp_dir = TempDir::new().unwrap();
+        let crash_log = CrashLog::new(temp_dir.path()).with_max_files(3);
+        let base = UNIX_EPOCH + Duration::from_secs(1_000);
+
+        let paths: Vec<PathBuf> = (0..5)
+            .map(|i| {
+                crash_log
+                    .write_at("boom", "", base + Duration::from_secs(i))
+                    .unwrap()
+            })
+            .collect();
+
+        assert_eq!(crash_log.files().unwrap(), paths[2..].to_vec());
+    }
+
+    #[test]
+    fn test_files_ignores_other_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let crash_log = CrashLog::new(temp_dir.path());
+        assert!(crash_log.files().unwrap().is_empty());
+
+        std::fs::create_dir_all(crash_log.dir()).unwrap();
+        std::fs::write(crash_log.dir().join("notes.txt"), "keep me").unwrap();
+        crash_log.write("boom", "").unwrap();
+
+        assert_eq!(crash_log.files().unwrap().len(), 1);
+        assert!(crash_log.dir().join("notes.txt").exists());
+    }
+}
@@ -3,17 +3,30 @@
 //! Main entry point for the Horizon Browser application.
 
 mod app;
+mod crash;
 
 use anyhow::Result;
+use horizon_sandbox::isolation::{IsolationLevel, ProcessIsolator};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize panic handler
-    std::panic::set_hook(Box::new(|panic_info| {
-        eprintln!("Horizon Browser panicked: {:?}", panic_info);
-        tracing::error!("Panic occurred: {:?}", panic_info);
-    }));
+/// Isolation applied when `HORIZON_ISOLATION` does not choose a level
+///
+/// Isolation is opt-in: the syscall allowlists are not known to cover
+/// everything the windowing and GPU stacks need.
+const DEFAULT_ISOLATION: IsolationLevel = IsolationLevel::None;
+
+fn main() -> Result<()> {
+    // Initialize panic handler, writing crash files to the data directory
+    let data_dir = app::HorizonApp::get_data_directory()?;
+    let mut crash_log = crash::CrashLog::new(&data_dir);
+    if let Some(max_files) = std::env::var("HORIZON_MAX_CRASH_FILES")
+        .ok()
+        .and_then(|value| value.parse().ok())
+    {
+        crash_log = crash_log.with_max_files(max_files);
+    }
+    let crash_dir = crash_log.dir().to_path_buf();
+    crash_log.install();
 
     // Initialize logging
     tracing_subscriber::registry()
@@ -25,8 +38,39 @@ async fn main() -> Result<()> {
         .init();
 
     tracing::info!("Starting Horizon Browser v{}", env!("CARGO_PKG_VERSION"));
+    tracing::debug!("Writing crash reports to {:?}", crash_dir);
+
+    // Isolate the process before the runtime starts its threads, so they
+    // all inherit the filesystem restrictions
+    let level = match std::env::var("HORIZON_ISOLATION") {
+        Ok(name) if !name.trim().is_empty() => IsolationLevel::parse(&name).unwrap_or_else(|| {
+            tracing::warn!("Unknown isolation level {:?}, running unisolated", name);
+            DEFAULT_ISOLATION
+        }),
+        _ => DEFAULT_ISOLATION,
+    };
+    if level != IsolationLevel::None {
+        apply_isolation(level, data_dir);
+    }
 
     // Create and run the application
-    let app = app::HorizonApp::new()?;
-    app.run().await
+    let private_window = std::env::args().skip(1).any(|arg| arg == "--private");
+    tokio::runtime::Runtime::new()?.block_on(async {
+        let app = app::HorizonApp::new()?.with_private_window(private_window);
+        app.run().await
+    })
+}
+
+/// Apply process isolation, warning instead of failing if it can't be applied
+fn apply_isolation(level: IsolationLevel, data_dir: std::path::PathBuf) {
+    // Landlock can only allow writing under directories that exist
+    if let Err(e) = std::fs::create_dir_all(&data_dir) {
+        tracing::warn!("Failed to create data directory {:?}: {}", data_dir, e);
+    }
+    if let Err(e) = ProcessIsolator::new(level)
+        .with_profile_dir(data_dir)
+        .apply()
+    {
+        tracing::warn!("Failed to apply {:?} process isolation: {}", level, e);
+    }
 }
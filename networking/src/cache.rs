@@ -0,0 +1,270 @@
+//! In-memory HTTP cache layer
+
+use super::client::{Client, HttpMethod};
+use super::request::{CachePolicy, Request};
+use super::response::Response;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A cached response
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    status: u16,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+    stored_at: Instant,
+    max_age: Duration,
+}
+
+impl CacheEntry {
+    /// Check whether the entry can be used without revalidation
+    fn is_fresh(&self) -> bool {
+        self.stored_at.elapsed() < self.max_age
+    }
+
+    /// Build a response from the cached data
+    fn to_response(&self) -> Response {
+        self.headers.iter().fold(
+            Response::new(self.status, self.body.clone()),
+            |r, (k, v)| r.with_header(k, v),
+        )
+    }
+}
+
+/// HTTP client wrapper that caches GET responses
+///
+/// Each request's [`CachePolicy`] decides whether the cache or the network is
+/// used. Freshness comes from the `max-age` directive of the `Cache-Control`
+/// response header; responses without one are stored but are immediately stale.
+pub struct HttpCache<C: Client> {
+    inner: C,
+    entries: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl<C: Client> HttpCache<C> {
+    /// Create a cache in front of a client
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Number of cached responses
+    pub fn len(&self) -> usize {
+        self.lock_entries().len()
+    }
+
+    /// Check whether the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all cached responses
+    pub fn clear(&self) {
+        self.lock_entries().clear();
+    }
+
+    /// Get the wrapped client
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    fn lock_entries(&self) -> std::sync::MutexGuard<'_, HashMap<String, CacheEntry>> {
+        // A poisoned cache is still usable; entries are replaced wholesale
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Look up a cached response, optionally accepting stale entries
+    fn lookup(&self, url: &str, allow_stale: bool) -> Option<Response> {
+        self.lock_entries()
+            .get(url)
+            .filter(|entry| allow_stale || entry.is_fresh())
+            .map(CacheEntry::to_response)
+    }
+
+    /// Store a response if it is cacheable
+    fn store(&self, url: &str, response: &Response) {
+        if !response.is_success() {
+            return;
+        }
+
+        let cache_control = response
+            .headers()
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("cache-control"))
+            .map(|(_, value)| value.to_lowercase())
+            .unwrap_or_default();
+        if cache_control.contains("no-store") {
+            return;
+        }
+
+        let max_age = cache_control
+            .split(',')
+            .filter_map(|directive| directive.trim().strip_prefix("max-age="))
+            .find_map(|secs| secs.trim().parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or_default();
+
+        self.lock_entries().insert(
+            url.to_string(),
+            CacheEntry {
+                status: response.status(),
+                headers: response.headers().clone(),
+                body: response.body().to_vec(),
+                stored_at: Instant::now(),
+                max_age,
+            },
+        );
+    }
+}
+
+#[async_trait]
+impl<C: Client> Client for HttpCache<C> {
+    async fn send(&self, request: Request) -> Result<Response> {
+        // Only GET responses are cached
+        if request.method() != HttpMethod::Get {
+            return self.inner.send(request).await;
+        }
+
+        let url = request.url().to_string();
+        let cached = match request.cache_policy() {
+            CachePolicy::Normal => self.lookup(&url, false),
+            CachePolicy::NoCache => None,
+            CachePolicy::ForceCache => self.lookup(&url, true),
+            CachePolicy::OnlyIfCached => {
+                return self
+                    .lookup(&url, true)
+                    .ok_or_else(|| anyhow!("No cached response for {}", url));
+            }
+        };
+
+        if let Some(response) = cached {
+            tracing::debug!("Serving {} from cache", url);
+            return Ok(response);
+        }
+
+        let response = self.inner.send(request).await?;
+        self.store(&url, &response);
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Client that counts requests and answers with a fixed Cache-Control
+    struct MockClient {
+        requests: AtomicUsize,
+        cache_control: &'static str,
+    }
+
+    impl MockClient {
+        fn new(cache_control: &'static str) -> Self {
+            Self {
+                requests: AtomicUsize::new(0),
+                cache_control,
+            }
+        }
+
+        fn requests(&self) -> usize {
+            self.requests.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl Client for MockClient {
+        async fn send(&self, _request: Request) -> Result<Response> {
+            let count = self.requests.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(
+                Response::new(200, format!("response {}", count).into_bytes())
+                    .with_header("cache-control", self.cache_control),
+            )
+        }
+    }
+
+    async fn fetch(cache: &HttpCache<MockClient>, policy: CachePolicy) -> Result<String> {
+        let request = Request::get("https://example.com").with_cache_policy(policy);
+        cache.send(request).await?.body_string()
+    }
+
+    #[tokio::test]
+    async fn test_normal_uses_fresh_cache_then_network() {
+        let cache = HttpCache::new(MockClient::new("max-age=3600"));
+
+        assert_eq!(
+            fetch(&cache, CachePolicy::Normal).await.unwrap(),
+            "response 1"
+        );
+        assert_eq!(
+            fetch(&cache, CachePolicy::Normal).await.unwrap(),
+            "response 1"
+        );
+        assert_eq!(cache.inner().requests(), 1);
+
+        // Stale entries go back to the network
+        let stale = HttpCache::new(MockClient::new("max-age=0"));
+        fetch(&stale, CachePolicy::Normal).await.unwrap();
+        assert_eq!(
+            fetch(&stale, CachePolicy::Normal).await.unwrap(),
+            "response 2"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_cache_always_uses_network() {
+        let cache = HttpCache::new(MockClient::new("max-age=3600"));
+
+        fetch(&cache, CachePolicy::Normal).await.unwrap();
+        assert_eq!(
+            fetch(&cache, CachePolicy::NoCache).await.unwrap(),
+            "response 2"
+        );
+        assert_eq!(cache.inner().requests(), 2);
+
+        // The reload refreshed the cached entry
+        assert_eq!(
+            fetch(&cache, CachePolicy::Normal).await.unwrap(),
+            "response 2"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_force_cache_serves_stale() {
+        let cache = HttpCache::new(MockClient::new("max-age=0"));
+
+        fetch(&cache, CachePolicy::Normal).await.unwrap();
+        assert_eq!(
+            fetch(&cache, CachePolicy::ForceCache).await.unwrap(),
+            "response 1"
+        );
+        assert_eq!(cache.inner().requests(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_only_if_cached_errors_on_miss() {
+        let cache = HttpCache::new(MockClient::new("max-age=0"));
+
+        assert!(fetch(&cache, CachePolicy::OnlyIfCached).await.is_err());
+        assert_eq!(cache.inner().requests(), 0);
+
+        fetch(&cache, CachePolicy::Normal).await.unwrap();
+        assert_eq!(
+            fetch(&cache, CachePolicy::OnlyIfCached).await.unwrap(),
+            "response 1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_store_is_not_cached() {
+        let cache = HttpCache::new(MockClient::new("no-store"));
+
+        fetch(&cache, CachePolicy::Normal).await.unwrap();
+        assert!(cache.is_empty());
+    }
+}
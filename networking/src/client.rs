@@ -1,7 +1,23 @@
 //! HTTP client abstraction
 
-use anyhow::Result;
+use super::request::Request;
+use super::tracking::Blocklist;
+use super::traffic::TrafficCounter;
+use super::vpn::{ProxyProtocol, VpnConfig};
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Timeout for requests that don't set their own
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Longest wait for a connection, or for the next read from one
+///
+/// Unlike `DEFAULT_TIMEOUT`, this doesn't limit a whole transfer, so long
+/// downloads can finish as long as data keeps arriving.
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// HTTP method
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -15,6 +31,47 @@ pub enum HttpMethod {
     Patch,
 }
 
+impl HttpMethod {
+    /// Get the matching reqwest method
+    fn to_reqwest(self) -> reqwest::Method {
+        match self {
+            Self::Get => reqwest::Method::GET,
+            Self::Post => reqwest::Method::POST,
+            Self::Put => reqwest::Method::PUT,
+            Self::Delete => reqwest::Method::DELETE,
+            Self::Head => reqwest::Method::HEAD,
+            Self::Options => reqwest::Method::OPTIONS,
+            Self::Patch => reqwest::Method::PATCH,
+        }
+    }
+}
+
+/// How the client handles redirect responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectPolicy {
+    /// Follow up to this many redirects, failing beyond that
+    Follow(usize),
+    /// Return redirect responses as they are
+    None,
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        Self::Follow(10)
+    }
+}
+
+/// Headers not carried over when a redirect leaves the original origin
+const CREDENTIAL_HEADERS: &[&str] = &["authorization", "cookie", "proxy-authorization"];
+
+/// A request sent and its redirects followed
+enum Opened {
+    /// The final response, not yet read, and the URLs redirected from
+    Response(reqwest::Response, Vec<String>),
+    /// A URL in the chain is on the blocklist, with the URLs redirected from
+    Blocked(Vec<String>),
+}
+
 /// HTTP client trait
 #[async_trait]
 pub trait Client: Send + Sync {
@@ -23,43 +80,418 @@ pub trait Client: Send + Sync {
 }
 
 /// HTTP client implementation
+#[derive(Clone)]
 pub struct HttpClient {
     inner: reqwest::Client,
+    /// Proxy URL all traffic goes through, without credentials
+    proxy_url: Option<String>,
+    /// Counter of body bytes sent and received
+    traffic: Arc<TrafficCounter>,
+    /// How redirects are handled
+    redirect_policy: RedirectPolicy,
+    /// Upgrade HTTP requests to HTTPS and refuse redirects back to HTTP
+    https_only: bool,
+    /// Tracker hosts whose requests are not sent
+    blocklist: Option<Arc<Blocklist>>,
 }
 
 impl HttpClient {
     /// Create a new HTTP client
     pub fn new() -> Result<Self> {
-        let inner = reqwest::Client::builder()
-            .user_agent("Horizon/0.1.0")
-            .timeout(std::time::Duration::from_secs(30))
+        let inner = Self::builder().build()?;
+
+        Ok(Self {
+            inner,
+            proxy_url: None,
+            traffic: Arc::default(),
+            redirect_policy: RedirectPolicy::default(),
+            https_only: false,
+            blocklist: None,
+        })
+    }
+
+    /// Create an HTTP client that sends all traffic through a proxy
+    ///
+    /// Supports HTTP/HTTPS proxies and SOCKS4/SOCKS5 proxies, with optional
+    /// authentication (SOCKS5 only for SOCKS). OpenVPN configurations are not
+    /// proxies and are rejected, as are empty hosts and unknown SOCKS versions.
+    pub fn with_proxy(config: &VpnConfig) -> Result<Self> {
+        let (scheme, host, port, username, password) = match config {
+            VpnConfig::Proxy {
+                protocol,
+                host,
+                port,
+                username,
+                password,
+            } => {
+                let scheme = match protocol {
+                    ProxyProtocol::Http => "http",
+                    ProxyProtocol::Https => "https",
+                };
+                (scheme, host, *port, username, password)
+            }
+            VpnConfig::Socks {
+                version,
+                host,
+                port,
+                username,
+                password,
+            } => {
+                // The `a`/`h` schemes have the proxy resolve hostnames, so
+                // lookups don't leak outside it
+                let scheme = match version {
+                    4 => "socks4a",
+                    5 => "socks5h",
+                    _ => return Err(anyhow!("SOCKS version must be 4 or 5, got {}", version)),
+                };
+                (scheme, host, *port, username, password)
+            }
+            VpnConfig::OpenVpn { .. } => {
+                return Err(anyhow!("OpenVPN configurations cannot be used as a proxy"))
+            }
+        };
+
+        if host.trim().is_empty() {
+            return Err(anyhow!("Proxy host must not be empty"));
+        }
+
+        let proxy_url = format!("{}://{}:{}", scheme, host.trim(), port);
+        let mut proxy_with_auth = url::Url::parse(&proxy_url)
+            .map_err(|e| anyhow!("Invalid proxy address {}: {}", proxy_url, e))?;
+        if let Some(username) = username.as_deref().filter(|_| scheme != "socks4a") {
+            proxy_with_auth
+                .set_username(username)
+                .and_then(|_| proxy_with_auth.set_password(password.as_deref()))
+                .map_err(|_| anyhow!("Invalid proxy credentials"))?;
+        }
+
+        let inner = Self::builder()
+            .proxy(reqwest::Proxy::all(proxy_with_auth.as_str())?)
             .build()?;
 
-        Ok(Self { inner })
+        tracing::info!("HTTP client using proxy {}", proxy_url);
+        Ok(Self {
+            inner,
+            proxy_url: Some(proxy_url),
+            traffic: Arc::default(),
+            redirect_policy: RedirectPolicy::default(),
+            https_only: false,
+            blocklist: None,
+        })
+    }
+
+    /// Count traffic in a shared counter instead of the client's own
+    pub fn with_traffic_counter(mut self, traffic: Arc<TrafficCounter>) -> Self {
+        self.traffic = traffic;
+        self
+    }
+
+    /// Set how redirects are handled
+    pub fn with_redirect_policy(mut self, policy: RedirectPolicy) -> Self {
+        self.redirect_policy = policy;
+        self
+    }
+
+    /// Upgrade plain HTTP requests to HTTPS and refuse redirects back to HTTP
+    ///
+    /// Local addresses are exempt; see [`super::https_only`].
+    pub fn with_https_only(mut self, https_only: bool) -> Self {
+        self.https_only = https_only;
+        self
+    }
+
+    /// Block requests to hosts on a tracking blocklist
+    pub fn with_blocklist(mut self, blocklist: Option<Arc<Blocklist>>) -> Self {
+        self.blocklist = blocklist;
+        self
+    }
+
+    /// Get the counter of body bytes sent and received
+    pub fn traffic(&self) -> &Arc<TrafficCounter> {
+        &self.traffic
+    }
+
+    /// Client builder with the shared defaults
+    fn builder() -> reqwest::ClientBuilder {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            reqwest::header::ACCEPT_ENCODING,
+            reqwest::header::HeaderValue::from_static("gzip, deflate, br"),
+        );
+
+        // Redirects are followed by `request` so the chain can be recorded and checked
+        reqwest::Client::builder()
+            .user_agent("Horizon/0.1.0")
+            .default_headers(headers)
+            .redirect(reqwest::redirect::Policy::none())
+            .connect_timeout(IDLE_TIMEOUT)
+            .read_timeout(IDLE_TIMEOUT)
+    }
+
+    /// Open a pooled keep-alive connection to a host ahead of navigation
+    pub async fn preconnect(&self, host: &str) -> Result<()> {
+        tracing::debug!("Preconnecting to {}", host);
+        self.inner
+            .head(format!("https://{}/", host))
+            .timeout(DEFAULT_TIMEOUT)
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    /// Get the proxy URL traffic is sent through, if any
+    pub fn proxy_url(&self) -> Option<&str> {
+        self.proxy_url.as_deref()
     }
 
     /// Perform a GET request
     pub async fn get(&self, url: &str) -> Result<super::response::Response> {
-        tracing::debug!("GET request to {}", url);
-        let response = self.inner.get(url).send().await?;
-        super::response::Response::from_reqwest(response).await
+        self.request(Request::get(url)).await
     }
 
     /// Perform a POST request
     pub async fn post(&self, url: &str, body: Vec<u8>) -> Result<super::response::Response> {
-        tracing::debug!("POST request to {}", url);
-        let response = self.inner.post(url).body(body).send().await?;
-        super::response::Response::from_reqwest(response).await
+        self.request(Request::post(url, body)).await
+    }
+
+    /// Perform a request with its method, headers, and body
+    ///
+    /// Every other request helper goes through here. Redirects are handled
+    /// according to the redirect policy, and the URLs redirected from are
+    /// recorded on the response.
+    pub async fn request(&self, request: Request) -> Result<super::response::Response> {
+        let timeout = request.timeout().unwrap_or(DEFAULT_TIMEOUT);
+        match self.open(&request, Some(timeout)).await? {
+            Opened::Response(response, chain) => {
+                let response = super::response::Response::from_reqwest(response).await?;
+                self.traffic.record_download(response.body().len() as u64);
+                Ok(response.with_redirect_chain(chain))
+            }
+            Opened::Blocked(chain) => {
+                Ok(super::response::Response::new(204, Vec::new()).with_redirect_chain(chain))
+            }
+        }
+    }
+
+    /// Send a request and follow its redirects, returning the final response
+    /// unread
+    ///
+    /// A `timeout` covers each response from connecting until its body has
+    /// been read; without one, only connecting and each read are limited.
+    async fn open(&self, request: &Request, timeout: Option<Duration>) -> Result<Opened> {
+        if self.is_blocked(request, request.url()) {
+            return Ok(Opened::Blocked(Vec::new()));
+        }
+
+        let mut url = request.url().to_string();
+        let upgraded = self.https_only && self.upgrade_to_https(&mut url);
+        let mut method = request.method();
+        let mut headers: Vec<(String, String)> = request.headers().to_vec();
+        let mut body = request.body().map(<[u8]>::to_vec);
+        let mut chain = Vec::new();
+
+        loop {
+            let response = self
+                .send_once(method, &url, &headers, body.as_deref(), timeout)
+                .await
+                .map_err(|e| {
+                    // Never fall back to HTTP; report the missing HTTPS support instead
+                    if upgraded && chain.is_empty() {
+                        anyhow!("This site does not support HTTPS ({}): {}", url, e)
+                    } else {
+                        e
+                    }
+                })?;
+
+            let status = response.status().as_u16();
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|location| location.to_str().ok());
+            let location = match (self.redirect_policy, location) {
+                (RedirectPolicy::Follow(max), Some(location))
+                    if matches!(status, 301 | 302 | 303 | 307 | 308) =>
+                {
+                    if chain.len() >= max {
+                        return Err(anyhow!("Too many redirects (limit {})", max));
+                    }
+                    location.to_string()
+                }
+                _ => return Ok(Opened::Response(response, chain)),
+            };
+
+            let next = self.redirect_target(&url, &location)?;
+            if self.is_blocked(request, next.as_str()) {
+                return Ok(Opened::Blocked(chain));
+            }
+            tracing::debug!("Following {} redirect from {} to {}", status, url, next);
+
+            // 303, and 301/302 after a POST, switch to a GET without a body
+            if status == 303 || (matches!(status, 301 | 302) && method == HttpMethod::Post) {
+                method = HttpMethod::Get;
+                body = None;
+            }
+            if url::Url::parse(&url)?.origin() != next.origin() {
+                headers.retain(|(name, _)| {
+                    !CREDENTIAL_HEADERS.contains(&name.to_lowercase().as_str())
+                });
+            }
+
+            chain.push(std::mem::replace(&mut url, next.to_string()));
+        }
+    }
+
+    /// Check a URL against the blocklist, counting a block for the request's tab
+    fn is_blocked(&self, request: &Request, url: &str) -> bool {
+        let Some(blocklist) = &self.blocklist else {
+            return false;
+        };
+        if !blocklist.should_block(url) {
+            return false;
+        }
+
+        tracing::debug!("Blocked tracker request to {}", url);
+        if let Some(tab_id) = request.tab_id() {
+            blocklist.record_blocked(tab_id);
+        }
+        true
+    }
+
+    /// Stream a GET response body to a file, returning the number of bytes written
+    ///
+    /// Redirects, HTTPS-only mode, and the blocklist apply as for `request`.
+    /// The body is requested without compression so the file holds exactly
+    /// what the server stores, and a slow download only fails if the
+    /// connection stalls. The destination's directory is created if missing.
+    /// `progress` is called after each chunk with the bytes written so far
+    /// and the total size from `Content-Length`, if known. A failed download
+    /// leaves a partial file.
+    pub async fn download_to(
+        &self,
+        url: &str,
+        dest: &Path,
+        mut progress: impl FnMut(u64, Option<u64>),
+    ) -> Result<u64> {
+        use tokio::io::AsyncWriteExt;
+
+        let mut response = self.open_download(url).await?;
+        let total = response.content_length();
+
+        if let Some(dir) = dest.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        let mut file = tokio::fs::File::create(dest).await?;
+
+        let mut written = 0;
+        while let Some(chunk) = response.chunk().await? {
+            file.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+            self.traffic.record_download(chunk.len() as u64);
+            progress(written, total);
+        }
+        file.flush().await?;
+
+        tracing::info!("Downloaded {} ({} bytes) to {:?}", url, written, dest);
+        Ok(written)
+    }
+
+    /// Stream a GET response body without keeping it, returning its size
+    ///
+    /// The request is made as for `download_to`, so the size is what was
+    /// sent over the wire.
+    pub async fn drain(&self, url: &str) -> Result<u64> {
+        let mut response = self.open_download(url).await?;
+        let mut read = 0;
+        while let Some(chunk) = response.chunk().await? {
+            read += chunk.len() as u64;
+            self.traffic.record_download(chunk.len() as u64);
+        }
+        Ok(read)
+    }
+
+    /// Open an uncompressed GET for streaming, failing on a blocked URL or
+    /// an unsuccessful status
+    async fn open_download(&self, url: &str) -> Result<reqwest::Response> {
+        let request = Request::get(url).header("Accept-Encoding", "identity");
+        let response = match self.open(&request, None).await? {
+            Opened::Response(response, _) => response,
+            Opened::Blocked(_) => {
+                return Err(anyhow!("Download from {} blocked as a tracker", url))
+            }
+        };
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Download of {} failed with status {}",
+                url,
+                response.status()
+            ));
+        }
+        Ok(response)
+    }
+
+    /// Rewrite a plain HTTP URL to HTTPS, returning whether it changed
+    fn upgrade_to_https(&self, url: &mut String) -> bool {
+        match super::https_only::upgrade(url) {
+            Some(https) => {
+                tracing::debug!("Upgrading {} to {}", url, https);
+                *url = https;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Send a single request without following redirects, returning the
+    /// response unread
+    async fn send_once(
+        &self,
+        method: HttpMethod,
+        url: &str,
+        headers: &[(String, String)],
+        body: Option<&[u8]>,
+        timeout: Option<Duration>,
+    ) -> Result<reqwest::Response> {
+        tracing::debug!("{:?} request to {}", method, url);
+        let mut builder = self.inner.request(method.to_reqwest(), url);
+
+        if let Some(timeout) = timeout {
+            builder = builder.timeout(timeout);
+        }
+        for (name, value) in headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = body {
+            builder = builder.body(body.to_vec());
+            self.traffic.record_upload(body.len() as u64);
+        }
+
+        Ok(builder.send().await?)
+    }
+
+    /// Resolve a redirect's `Location` against the current URL
+    ///
+    /// Fails on a redirect from HTTPS to plain HTTP in HTTPS-only mode.
+    fn redirect_target(&self, from: &str, location: &str) -> Result<url::Url> {
+        let from = url::Url::parse(from).map_err(|e| anyhow!("Invalid URL {}: {}", from, e))?;
+        let next = from
+            .join(location)
+            .map_err(|e| anyhow!("Invalid redirect location {}: {}", location, e))?;
+
+        if self.https_only && from.scheme() == "https" && next.scheme() == "http" {
+            return Err(anyhow!(
+                "Refusing redirect from {} to insecure {} in HTTPS-only mode",
+                from,
+                next
+            ));
+        }
+        Ok(next)
     }
 }
 
 #[async_trait]
 impl Client for HttpClient {
-    async fn send(&self, request: super::request::Request) -> Result<super::response::Response> {
-        match request.method() {
-            HttpMethod::Get => self.get(request.url()).await,
-            _ => anyhow::bail!("Method not implemented"),
-        }
+    async fn send(&self, request: Request) -> Result<super::response::Response> {
+        self.request(request).await
     }
 }
 
@@ -67,12 +499,370 @@ impl Client for HttpClient {
 mod tests {
     use super::*;
 
+    /// Serve one request on a local port, returning its URL and the raw request bytes
+    async fn capture_one_request() -> (String, tokio::task::JoinHandle<String>) {
+        serve_one_request("HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nOK")
+            .await
+    }
+
+    /// Answer one request on a local port with a raw response
+    async fn serve_one_request(
+        response: impl Into<String>,
+    ) -> (String, tokio::task::JoinHandle<String>) {
+        serve_one_request_after(response, Duration::ZERO).await
+    }
+
+    /// Answer one request on a local port with a raw response after a delay
+    async fn serve_one_request_after(
+        response: impl Into<String>,
+        delay: Duration,
+    ) -> (String, tokio::task::JoinHandle<String>) {
+        let (url, server) = crate::test_server::serve_one(response, delay).await;
+        (format!("{}/echo", url), server)
+    }
+
     #[test]
     fn test_http_client_creation() {
         let client = HttpClient::new();
         assert!(client.is_ok());
     }
 
+    #[test]
+    fn test_http_client_with_proxy() {
+        let config = VpnConfig::Proxy {
+            protocol: ProxyProtocol::Http,
+            host: "proxy.example.com".to_string(),
+            port: 8080,
+            username: Some("user".to_string()),
+            password: Some("p@ss".to_string()),
+        };
+        let client = HttpClient::with_proxy(&config).unwrap();
+        assert_eq!(client.proxy_url(), Some("http://proxy.example.com:8080"));
+
+        let config = VpnConfig::Socks {
+            version: 5,
+            host: "127.0.0.1".to_string(),
+            port: 1080,
+            username: None,
+            password: None,
+        };
+        let client = HttpClient::with_proxy(&config).unwrap();
+        assert_eq!(client.proxy_url(), Some("socks5h://127.0.0.1:1080"));
+
+        let config = VpnConfig::Socks {
+            version: 4,
+            host: "127.0.0.1".to_string(),
+            port: 1080,
+            username: Some("ignored".to_string()),
+            password: None,
+        };
+        let client = HttpClient::with_proxy(&config).unwrap();
+        assert_eq!(client.proxy_url(), Some("socks4a://127.0.0.1:1080"));
+
+        assert_eq!(HttpClient::new().unwrap().proxy_url(), None);
+    }
+
+    #[test]
+    fn test_http_client_with_invalid_proxy() {
+        let bad_version = VpnConfig::Socks {
+            version: 6,
+            host: "127.0.0.1".to_string(),
+            port: 1080,
+            username: None,
+            password: None,
+        };
+        assert!(HttpClient::with_proxy(&bad_version).is_err());
+
+        let empty_host = VpnConfig::Proxy {
+            protocol: ProxyProtocol::Https,
+            host: "  ".to_string(),
+            port: 443,
+            username: None,
+            password: None,
+        };
+        assert!(HttpClient::with_proxy(&empty_host).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_send_post_body_and_headers() {
+        let (url, server) = capture_one_request().await;
+        let client = HttpClient::new().unwrap();
+
+        let request = Request::post(url, b"{\"name\":\"horizon\"}".to_vec())
+            .header("X-Horizon-Test", "yes")
+            .header("Content-Type", "application/json");
+        let response = client.send(request).await.unwrap();
+        assert_eq!(response.status(), 200);
+
+        assert_eq!(client.traffic().total_uploaded(), 18);
+        assert_eq!(client.traffic().total_downloaded(), 2);
+
+        let raw = server.await.unwrap();
+        assert!(raw.starts_with("POST /echo HTTP/1.1"));
+        assert!(raw.to_lowercase().contains("x-horizon-test: yes"));
+        assert!(raw.ends_with("{\"name\":\"horizon\"}"));
+    }
+
+    #[tokio::test]
+    async fn test_get_sends_request_headers() {
+        let (url, server) = capture_one_request().await;
+        let client = HttpClient::new().unwrap();
+
+        let response = client
+            .request(Request::get(url).header("X-Test", "1"))
+            .await
+            .unwrap();
+        assert!(response.is_success());
+
+        let raw = server.await.unwrap();
+        assert!(raw.starts_with("GET /echo HTTP/1.1"));
+        assert!(raw.to_lowercase().contains("x-test: 1\r\n"));
+        assert!(raw
+            .to_lowercase()
+            .contains("accept-encoding: gzip, deflate, br\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_redirect_policy_none_returns_redirect() {
+        let (url, _server) = serve_one_request(
+            "HTTP/1.1 302 Found\r\nLocation: /elsewhere\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        )
+        .await;
+        let client = HttpClient::new()
+            .unwrap()
+            .with_redirect_policy(RedirectPolicy::None);
+
+        let response = client.get(&url).await.unwrap();
+        assert_eq!(response.status(), 302);
+        assert_eq!(response.header("location"), Some("/elsewhere"));
+        assert!(response.redirect_chain().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_redirects_are_followed_and_recorded() {
+        let (final_url, final_server) = capture_one_request().await;
+        let redirect = format!(
+            "HTTP/1.1 303 See Other\r\nLocation: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            final_url
+        );
+        let (url, _server) = serve_one_request(redirect).await;
+        let client = HttpClient::new().unwrap();
+
+        let response = client
+            .request(Request::post(url.clone(), b"form".to_vec()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.redirect_chain(), [url]);
+        assert!(final_server.await.unwrap().starts_with("GET /echo"));
+    }
+
+    #[tokio::test]
+    async fn test_redirect_limit() {
+        let (url, _server) = serve_one_request(
+            "HTTP/1.1 302 Found\r\nLocation: /loop\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        )
+        .await;
+        let client = HttpClient::new()
+            .unwrap()
+            .with_redirect_policy(RedirectPolicy::Follow(0));
+
+        let err = client.get(&url).await.unwrap_err();
+        assert!(err.to_string().contains("Too many redirects"));
+    }
+
+    #[test]
+    fn test_https_only_rejects_downgrade_redirect() {
+        let client = HttpClient::new().unwrap().with_https_only(true);
+
+        assert!(client
+            .redirect_target("https://example.com/login", "http://example.com/")
+            .is_err());
+        assert_eq!(
+            client
+                .redirect_target("https://example.com/login", "/home")
+                .unwrap()
+                .as_str(),
+            "https://example.com/home"
+        );
+        assert!(client
+            .redirect_target("http://example.com/", "http://example.org/")
+            .is_ok());
+
+        let client = client.with_https_only(false);
+        assert!(client
+            .redirect_target("https://example.com/login", "http://example.com/")
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_https_only_exempts_localhost() {
+        let (url, _server) = capture_one_request().await;
+        let client = HttpClient::new().unwrap().with_https_only(true);
+
+        let response = client.get(&url).await.unwrap();
+        assert!(response.is_success());
+        assert!(response.redirect_chain().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_https_only_reports_missing_https() {
+        let client = HttpClient::new().unwrap().with_https_only(true);
+
+        let err = client.get("http://horizon.invalid/").await.unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("This site does not support HTTPS (https://horizon.invalid/)"));
+    }
+
+    #[tokio::test]
+    async fn test_blocked_requests_short_circuit() {
+        let mut blocklist = Blocklist::new();
+        blocklist.add_host("tracker.invalid");
+        let blocklist = Arc::new(blocklist);
+        let client = HttpClient::new()
+            .unwrap()
+            .with_blocklist(Some(Arc::clone(&blocklist)));
+
+        let response = client
+            .request(Request::get("https://cdn.tracker.invalid/pixel.gif").with_tab_id("tab-1"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 204);
+        assert!(response.body().is_empty());
+        assert_eq!(blocklist.blocked_count("tab-1"), 1);
+        assert_eq!(client.traffic().total_downloaded(), 0);
+
+        let (url, _server) = capture_one_request().await;
+        let response = client
+            .request(Request::get(url).with_tab_id("tab-1"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(blocklist.blocked_count("tab-1"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_override() {
+        let (url, _server) = serve_one_request_after(
+            "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            Duration::from_millis(500),
+        )
+        .await;
+        let client = HttpClient::new().unwrap();
+
+        let err = client
+            .request(Request::get(url).with_timeout(Duration::from_millis(1)))
+            .await
+            .unwrap_err();
+        let err = err.downcast_ref::<reqwest::Error>().unwrap();
+        assert!(err.is_timeout());
+    }
+
+    #[tokio::test]
+    async fn test_request_without_timeout_uses_default() {
+        let (url, _server) = serve_one_request_after(
+            "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            Duration::from_millis(100),
+        )
+        .await;
+        let client = HttpClient::new().unwrap();
+
+        let request = Request::get(url);
+        assert_eq!(request.timeout(), None);
+        assert!(client.request(request).await.unwrap().is_success());
+    }
+
+    #[tokio::test]
+    async fn test_head_with_content_encoding() {
+        let (url, _server) = serve_one_request(
+            "HTTP/1.1 200 OK\r\nContent-Encoding: br\r\nContent-Length: 100\r\nConnection: close\r\n\r\n",
+        )
+        .await;
+        let client = HttpClient::new().unwrap();
+
+        let response = client
+            .request(Request::new(HttpMethod::Head, url))
+            .await
+            .unwrap();
+        assert!(response.is_success());
+        assert!(response.body().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_download_to_file() {
+        let (url, server) = serve_one_request(
+            "HTTP/1.1 200 OK\r\nContent-Length: 11\r\nConnection: close\r\n\r\nhello world",
+        )
+        .await;
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dest = temp_dir.path().join("nested").join("hello.txt");
+        let client = HttpClient::new().unwrap();
+
+        let mut reports = Vec::new();
+        let written = client
+            .download_to(&url, &dest, |done, total| reports.push((done, total)))
+            .await
+            .unwrap();
+
+        assert_eq!(written, 11);
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), "hello world");
+        assert_eq!(reports.last(), Some(&(11, Some(11))));
+        assert!(reports.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+        assert_eq!(client.traffic().total_downloaded(), 11);
+
+        // Files are saved as stored, so compression is not accepted
+        let raw = server.await.unwrap().to_lowercase();
+        assert!(raw.contains("accept-encoding: identity\r\n"));
+        assert!(!raw.contains("gzip"));
+    }
+
+    #[tokio::test]
+    async fn test_drain_counts_body() {
+        let (url, _server) = serve_one_request(
+            "HTTP/1.1 200 OK\r\nContent-Length: 11\r\nConnection: close\r\n\r\nhello world",
+        )
+        .await;
+        let client = HttpClient::new().unwrap();
+
+        assert_eq!(client.drain(&url).await.unwrap(), 11);
+        assert_eq!(client.traffic().total_downloaded(), 11);
+    }
+
+    #[tokio::test]
+    async fn test_download_error_status() {
+        let (url, _server) = serve_one_request(
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        )
+        .await;
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dest = temp_dir.path().join("missing.txt");
+
+        let client = HttpClient::new().unwrap();
+        assert!(client.download_to(&url, &dest, |_, _| {}).await.is_err());
+        assert!(!dest.exists());
+    }
+
+    #[tokio::test]
+    async fn test_send_other_methods() {
+        for (method, name) in [
+            (HttpMethod::Put, "PUT"),
+            (HttpMethod::Delete, "DELETE"),
+            (HttpMethod::Patch, "PATCH"),
+            (HttpMethod::Options, "OPTIONS"),
+        ] {
+            let (url, server) = capture_one_request().await;
+            let client = HttpClient::new().unwrap();
+
+            let response = client.send(Request::new(method, url)).await.unwrap();
+            assert!(response.is_success());
+            assert!(server
+                .await
+                .unwrap()
+                .starts_with(&format!("{} /echo", name)));
+        }
+    }
+
     #[test]
     fn test_http_method() {
         assert_eq!(HttpMethod::Get, HttpMethod::Get);
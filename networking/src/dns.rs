@@ -1,8 +1,25 @@
 //! DNS resolution module with configurable DNS providers
 
-use anyhow::Result;
+use crate::client::HttpClient;
+use crate::request::Request;
+use anyhow::{anyhow, Result};
+use hickory_resolver::config::{NameServerConfigGroup, ResolverConfig, ResolverOpts};
+use hickory_resolver::TokioAsyncResolver;
+use horizon_url::ParsedUrl;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::net::IpAddr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// How long resolved addresses stay in the DNS cache when no TTL is known
+const DNS_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Default number of hostnames kept in the DNS cache
+pub const DEFAULT_DNS_CACHE_CAPACITY: usize = 256;
+
+/// Port queries are sent to on upstream DNS servers
+const DNS_PORT: u16 = 53;
 
 /// DNS provider options
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -46,6 +63,16 @@ impl DnsProvider {
         }
     }
 
+    /// Get the DNS-over-HTTPS JSON endpoint for this provider, if it has one
+    pub fn doh_endpoint(&self) -> Option<&'static str> {
+        match self {
+            Self::Google => Some("https://dns.google/resolve"),
+            Self::Cloudflare => Some("https://cloudflare-dns.com/dns-query"),
+            Self::Quad9 => Some("https://dns.quad9.net:5053/dns-query"),
+            Self::System | Self::Custom => None,
+        }
+    }
+
     /// Get all available DNS providers
     pub fn all() -> &'static [Self] {
         &[
@@ -58,6 +85,16 @@ impl DnsProvider {
     }
 }
 
+/// How queries reach the DNS provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum DnsTransport {
+    /// Plaintext DNS on port 53 (default)
+    #[default]
+    Plain,
+    /// Encrypted DNS-over-HTTPS using the provider's JSON API
+    Https,
+}
+
 /// DNS resolver configuration
 #[derive(Debug, Clone, Default)]
 pub struct DnsConfig {
@@ -65,24 +102,164 @@ pub struct DnsConfig {
     pub provider: DnsProvider,
     /// Custom DNS servers (used when provider is Custom)
     pub custom_servers: Vec<IpAddr>,
+    /// Transport for queries (ignored by the System provider)
+    pub transport: DnsTransport,
+}
+
+/// JSON answer of a DNS-over-HTTPS query
+#[derive(Debug, Deserialize)]
+struct DohResponse {
+    /// DNS response code (0 is success)
+    #[serde(rename = "Status")]
+    status: u32,
+    /// Answer records, absent when there are none
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DohRecord>,
+}
+
+/// A record in a DNS-over-HTTPS answer
+#[derive(Debug, Deserialize)]
+struct DohRecord {
+    /// Record type (1 for A, 28 for AAAA)
+    #[serde(rename = "type")]
+    record_type: u16,
+    /// Time to live in seconds
+    #[serde(rename = "TTL")]
+    ttl: u64,
+    /// Record data, an address for A/AAAA records
+    data: String,
+}
+
+/// A cached resolution
+struct CacheEntry {
+    /// Resolved addresses
+    addrs: Vec<IpAddr>,
+    /// Time the entry stops being valid
+    expires_at: Instant,
+    /// Use counter value when the entry was last read or written
+    last_used: u64,
+}
+
+/// Bounded cache of resolved addresses, evicting the least recently used hostname
+struct DnsCache {
+    /// Maximum number of hostnames kept
+    capacity: usize,
+    /// Entries by hostname
+    entries: HashMap<String, CacheEntry>,
+    /// Counter bumped on every use, for recency ordering
+    uses: u64,
+}
+
+impl DnsCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: HashMap::new(),
+            uses: 0,
+        }
+    }
+
+    /// Get unexpired addresses for a hostname, marking it as recently used
+    fn get(&mut self, hostname: &str, now: Instant) -> Option<Vec<IpAddr>> {
+        self.uses += 1;
+        let entry = self.entries.get_mut(hostname)?;
+        if now >= entry.expires_at {
+            self.entries.remove(hostname);
+            return None;
+        }
+
+        entry.last_used = self.uses;
+        Some(entry.addrs.clone())
+    }
+
+    /// Insert addresses for a hostname, evicting the least recently used if full
+    fn insert(&mut self, hostname: &str, addrs: Vec<IpAddr>, expires_at: Instant) {
+        self.uses += 1;
+        self.entries.insert(
+            hostname.to_string(),
+            CacheEntry {
+                addrs,
+                expires_at,
+                last_used: self.uses,
+            },
+        );
+        self.evict_to_capacity();
+    }
+
+    /// Change the capacity, evicting entries that no longer fit
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        self.evict_to_capacity();
+    }
+
+    fn evict_to_capacity(&mut self) {
+        while self.entries.len() > self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(hostname, _)| hostname.clone());
+            match oldest {
+                Some(hostname) => self.entries.remove(&hostname),
+                None => break,
+            };
+        }
+    }
 }
 
+/// Source of the current time, replaceable in tests
+type Clock = Box<dyn Fn() -> Instant + Send + Sync>;
+
 /// DNS resolver with configurable providers
 pub struct DnsResolver {
     config: DnsConfig,
+    /// Recently resolved addresses by hostname
+    cache: Mutex<DnsCache>,
+    /// Clock used for cache expiry
+    clock: Clock,
+    /// Client DNS-over-HTTPS queries are sent with, so they use the proxy
+    client: Option<HttpClient>,
+    /// Resolver querying the active servers, built on first plain lookup
+    upstream: OnceLock<TokioAsyncResolver>,
 }
 
 impl DnsResolver {
     /// Create a new DNS resolver with default configuration
     pub fn new() -> Self {
-        Self {
-            config: DnsConfig::default(),
-        }
+        Self::with_config(DnsConfig::default())
     }
 
     /// Create a DNS resolver with custom configuration
     pub fn with_config(config: DnsConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            cache: Mutex::new(DnsCache::new(DEFAULT_DNS_CACHE_CAPACITY)),
+            clock: Box::new(Instant::now),
+            client: None,
+            upstream: OnceLock::new(),
+        }
+    }
+
+    /// Use a custom clock for cache expiry
+    pub fn with_clock(mut self, clock: impl Fn() -> Instant + Send + Sync + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Set how many hostnames the cache keeps
+    pub fn with_cache_capacity(self, capacity: usize) -> Self {
+        self.set_cache_capacity(capacity);
+        self
+    }
+
+    /// Set the HTTP client DNS-over-HTTPS queries are sent with
+    pub fn set_client(&mut self, client: HttpClient) {
+        self.client = Some(client);
+    }
+
+    /// Get the HTTP client DNS-over-HTTPS queries are sent with, if set
+    pub fn client(&self) -> Option<&HttpClient> {
+        self.client.as_ref()
     }
 
     /// Get the current DNS configuration
@@ -94,18 +271,68 @@ impl DnsResolver {
     pub fn set_config(&mut self, config: DnsConfig) {
         tracing::info!("DNS configuration updated to: {}", config.provider.name());
         self.config = config;
+        self.config_changed();
     }
 
     /// Set DNS provider
     pub fn set_provider(&mut self, provider: DnsProvider) {
         self.config.provider = provider;
+        self.config_changed();
         tracing::info!("DNS provider set to: {}", provider.name());
     }
 
+    /// Set the query transport
+    pub fn set_transport(&mut self, transport: DnsTransport) {
+        self.config.transport = transport;
+        self.config_changed();
+        tracing::info!("DNS transport set to: {:?}", transport);
+    }
+
     /// Set custom DNS servers
     pub fn set_custom_servers(&mut self, servers: Vec<IpAddr>) {
         tracing::info!("Custom DNS servers set: {:?}", servers);
         self.config.custom_servers = servers;
+        self.config_changed();
+    }
+
+    /// Drop cached addresses and the upstream resolver after a config change
+    fn config_changed(&mut self) {
+        self.clear_cache();
+        self.upstream = OnceLock::new();
+    }
+
+    /// Get cached addresses for a hostname, if resolved recently
+    pub fn cached(&self, hostname: &str) -> Option<Vec<IpAddr>> {
+        self.lock_cache().get(hostname, (self.clock)())
+    }
+
+    /// Cache addresses for a hostname for a time to live
+    fn insert_cached(&self, hostname: &str, addrs: Vec<IpAddr>, ttl: Duration) {
+        self.lock_cache()
+            .insert(hostname, addrs, (self.clock)() + ttl);
+    }
+
+    /// Remove all cached addresses
+    pub fn clear_cache(&self) {
+        self.lock_cache().entries.clear();
+        if let Some(upstream) = self.upstream.get() {
+            upstream.clear_cache();
+        }
+    }
+
+    /// Get how many hostnames the cache keeps
+    pub fn cache_capacity(&self) -> usize {
+        self.lock_cache().capacity
+    }
+
+    /// Set how many hostnames the cache keeps (at least one)
+    pub fn set_cache_capacity(&self, capacity: usize) {
+        self.lock_cache().set_capacity(capacity);
+    }
+
+    fn lock_cache(&self) -> std::sync::MutexGuard<'_, DnsCache> {
+        // A poisoned cache is still usable; entries are replaced wholesale
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
     }
 
     /// Get the active DNS servers
@@ -116,25 +343,129 @@ impl DnsResolver {
         }
     }
 
-    /// Resolve a hostname to IP addresses
+    /// Resolve a hostname to IP addresses, using the cache when possible
     pub async fn resolve(&self, hostname: &str) -> Result<Vec<IpAddr>> {
+        if let Some(addrs) = self.cached(hostname) {
+            return Ok(addrs);
+        }
+
         tracing::debug!(
             "Resolving DNS for {} using {}",
             hostname,
             self.config.provider.name()
         );
 
-        // Note: In a full implementation, this would use the configured DNS servers
-        // For now, we use the system resolver regardless of configuration
-        // A complete implementation would use libraries like trust-dns-resolver
-        let addrs: Vec<IpAddr> = tokio::net::lookup_host(format!("{}:80", hostname))
-            .await?
-            .map(|addr| addr.ip())
-            .collect();
+        let (addrs, ttl) = match (self.config.provider, self.config.transport) {
+            (DnsProvider::System, _) => (
+                tokio::net::lookup_host(format!("{}:80", hostname))
+                    .await?
+                    .map(|addr| addr.ip())
+                    .collect(),
+                DNS_CACHE_TTL,
+            ),
+            (_, DnsTransport::Https) => self.resolve_doh(hostname).await?,
+            (_, DnsTransport::Plain) => (
+                self.upstream_resolver()?
+                    .lookup_ip(hostname)
+                    .await
+                    .map_err(|e| anyhow!("DNS lookup for {} failed: {}", hostname, e))?
+                    .iter()
+                    .collect(),
+                DNS_CACHE_TTL,
+            ),
+        };
 
         tracing::debug!("Resolved {} to {:?}", hostname, addrs);
+        self.insert_cached(hostname, addrs.clone(), ttl);
         Ok(addrs)
     }
+
+    /// Get the resolver that queries the active servers directly
+    fn upstream_resolver(&self) -> Result<&TokioAsyncResolver> {
+        let servers = self.active_servers();
+        if servers.is_empty() {
+            return Err(anyhow!(
+                "{} has no DNS servers configured",
+                self.config.provider.name()
+            ));
+        }
+
+        Ok(self.upstream.get_or_init(|| {
+            let name_servers = NameServerConfigGroup::from_ips_clear(&servers, DNS_PORT, true);
+            let config = ResolverConfig::from_parts(None, Vec::new(), name_servers);
+            TokioAsyncResolver::tokio(config, ResolverOpts::default())
+        }))
+    }
+
+    /// Resolve A and AAAA records over DNS-over-HTTPS, returning the addresses and shortest TTL
+    async fn resolve_doh(&self, hostname: &str) -> Result<(Vec<IpAddr>, Duration)> {
+        let endpoint = self.config.provider.doh_endpoint().ok_or_else(|| {
+            anyhow!(
+                "{} does not support DNS-over-HTTPS",
+                self.config.provider.name()
+            )
+        })?;
+        let client = match &self.client {
+            Some(client) => client.clone(),
+            None => HttpClient::new()?,
+        };
+
+        let mut addrs = Vec::new();
+        let mut ttl: Option<Duration> = None;
+        for record_type in ["A", "AAAA"] {
+            let mut url = ParsedUrl::parse(endpoint)?;
+            url.set_query_param("name", hostname);
+            url.set_query_param("type", record_type);
+
+            let request = Request::get(url.as_str()).header("Accept", "application/dns-json");
+            let response = client.request(request).await?;
+            if !response.is_success() {
+                return Err(anyhow!(
+                    "DNS-over-HTTPS query failed with status {}",
+                    response.status()
+                ));
+            }
+
+            let (found, record_ttl) = parse_doh_response(response.body())?;
+            addrs.extend(found);
+            if let Some(record_ttl) = record_ttl {
+                ttl = Some(ttl.map_or(record_ttl, |ttl| ttl.min(record_ttl)));
+            }
+        }
+
+        if addrs.is_empty() {
+            return Err(anyhow!("No addresses found for {}", hostname));
+        }
+        Ok((addrs, ttl.unwrap_or(DNS_CACHE_TTL)))
+    }
+}
+
+/// Parse a DNS-over-HTTPS JSON answer into addresses and the shortest record TTL
+fn parse_doh_response(body: &[u8]) -> Result<(Vec<IpAddr>, Option<Duration>)> {
+    let response: DohResponse = serde_json::from_slice(body)
+        .map_err(|e| anyhow!("Invalid DNS-over-HTTPS response: {}", e))?;
+    if response.status != 0 {
+        return Err(anyhow!(
+            "DNS-over-HTTPS query failed with response code {}",
+            response.status
+        ));
+    }
+
+    let records: Vec<&DohRecord> = response
+        .answer
+        .iter()
+        .filter(|record| matches!(record.record_type, 1 | 28))
+        .collect();
+    let addrs = records
+        .iter()
+        .filter_map(|record| record.data.parse().ok())
+        .collect();
+    let ttl = records
+        .iter()
+        .map(|record| Duration::from_secs(record.ttl))
+        .min();
+
+    Ok((addrs, ttl))
 }
 
 impl Default for DnsResolver {
@@ -146,6 +477,7 @@ impl Default for DnsResolver {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Arc;
 
     #[test]
     fn test_dns_resolver_creation() {
@@ -159,4 +491,160 @@ mod tests {
         let result = resolver.resolve("localhost").await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_custom_without_servers_errors() {
+        let resolver = DnsResolver::with_config(DnsConfig {
+            provider: DnsProvider::Custom,
+            ..DnsConfig::default()
+        });
+
+        let err = resolver.resolve("example.com").await.unwrap_err();
+        assert!(err.to_string().contains("no DNS servers configured"));
+    }
+
+    #[test]
+    fn test_parse_doh_response() {
+        // Recorded from https://cloudflare-dns.com/dns-query?name=www.example.com&type=A
+        let body = br#"{"Status":0,"TC":false,"RD":true,"RA":true,"AD":false,"CD":false,
+            "Question":[{"name":"www.example.com","type":1}],
+            "Answer":[
+                {"name":"www.example.com","type":5,"TTL":3600,"data":"www.example.com-v4.edgesuite.net."},
+                {"name":"www.example.com-v4.edgesuite.net.","type":1,"TTL":300,"data":"23.215.0.136"},
+                {"name":"www.example.com-v4.edgesuite.net.","type":1,"TTL":120,"data":"23.215.0.138"}
+            ]}"#;
+
+        let (addrs, ttl) = parse_doh_response(body).unwrap();
+        assert_eq!(
+            addrs,
+            vec![
+                "23.215.0.136".parse::<IpAddr>().unwrap(),
+                "23.215.0.138".parse::<IpAddr>().unwrap(),
+            ]
+        );
+        assert_eq!(ttl, Some(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn test_parse_doh_response_ipv6_and_errors() {
+        let body = br#"{"Status":0,"Answer":[{"name":"example.com","type":28,"TTL":60,"data":"2606:2800:21f:cb07:6820:80da:af6b:8b2c"}]}"#;
+        let (addrs, _) = parse_doh_response(body).unwrap();
+        assert!(addrs[0].is_ipv6());
+
+        let no_answer = br#"{"Status":0}"#;
+        assert_eq!(parse_doh_response(no_answer).unwrap(), (Vec::new(), None));
+
+        let nxdomain = br#"{"Status":3,"Question":[{"name":"nope.invalid","type":1}]}"#;
+        assert!(parse_doh_response(nxdomain).is_err());
+        assert!(parse_doh_response(b"<html>").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_doh_requires_endpoint() {
+        let resolver = DnsResolver::with_config(DnsConfig {
+            provider: DnsProvider::Custom,
+            custom_servers: vec!["1.1.1.1".parse().unwrap()],
+            transport: DnsTransport::Https,
+        });
+
+        let err = resolver.resolve("example.com").await.unwrap_err();
+        assert!(err.to_string().contains("does not support DNS-over-HTTPS"));
+    }
+
+    #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore = "requires network access")]
+    async fn test_cloudflare_resolution() {
+        let mut resolver = DnsResolver::new();
+        resolver.set_provider(DnsProvider::Cloudflare);
+
+        let addrs = resolver.resolve("one.one.one.one").await.unwrap();
+        assert!(addrs.contains(&"1.1.1.1".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_upstream_resolver_is_reused_until_config_changes() {
+        let mut resolver = DnsResolver::with_config(DnsConfig {
+            provider: DnsProvider::Cloudflare,
+            transport: DnsTransport::Plain,
+            ..DnsConfig::default()
+        });
+        assert!(resolver.upstream.get().is_none());
+
+        resolver.upstream_resolver().unwrap();
+        assert!(resolver.upstream.get().is_some());
+
+        resolver.set_custom_servers(vec!["9.9.9.9".parse().unwrap()]);
+        assert!(resolver.upstream.get().is_none());
+    }
+
+    /// A resolver whose clock only moves when the returned handle is advanced
+    fn resolver_with_manual_clock(config: DnsConfig) -> (DnsResolver, Arc<Mutex<Instant>>) {
+        let now = Arc::new(Mutex::new(Instant::now()));
+        let clock = Arc::clone(&now);
+        let resolver = DnsResolver::with_config(config).with_clock(move || *clock.lock().unwrap());
+        (resolver, now)
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit_skips_lookup_until_expiry() {
+        // Lookups with this configuration always fail, so a success must come from the cache
+        let (resolver, now) = resolver_with_manual_clock(DnsConfig {
+            provider: DnsProvider::Custom,
+            ..DnsConfig::default()
+        });
+        let addrs = vec!["192.0.2.1".parse().unwrap()];
+        resolver.insert_cached("cached.test", addrs.clone(), DNS_CACHE_TTL);
+
+        *now.lock().unwrap() += DNS_CACHE_TTL - Duration::from_secs(1);
+        assert_eq!(resolver.resolve("cached.test").await.unwrap(), addrs);
+
+        *now.lock().unwrap() += Duration::from_secs(1);
+        assert!(resolver.resolve("cached.test").await.is_err());
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let resolver = DnsResolver::new().with_cache_capacity(2);
+        assert_eq!(resolver.cache_capacity(), 2);
+        let addrs = vec!["192.0.2.1".parse::<IpAddr>().unwrap()];
+
+        resolver.insert_cached("a.test", addrs.clone(), DNS_CACHE_TTL);
+        resolver.insert_cached("b.test", addrs.clone(), DNS_CACHE_TTL);
+        assert!(resolver.cached("a.test").is_some());
+        resolver.insert_cached("c.test", addrs.clone(), DNS_CACHE_TTL);
+
+        assert!(resolver.cached("a.test").is_some());
+        assert!(resolver.cached("b.test").is_none());
+        assert!(resolver.cached("c.test").is_some());
+
+        resolver.set_cache_capacity(1);
+        assert!(resolver.cached("a.test").is_none());
+        assert!(resolver.cached("c.test").is_some());
+    }
+
+    #[test]
+    fn test_config_changes_flush_cache() {
+        let mut resolver = DnsResolver::new();
+        let addrs = vec!["192.0.2.1".parse::<IpAddr>().unwrap()];
+
+        resolver.insert_cached("a.test", addrs.clone(), DNS_CACHE_TTL);
+        resolver.set_provider(DnsProvider::Quad9);
+        assert!(resolver.cached("a.test").is_none());
+
+        resolver.insert_cached("a.test", addrs.clone(), DNS_CACHE_TTL);
+        resolver.set_config(DnsConfig::default());
+        assert!(resolver.cached("a.test").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_dns_cache() {
+        let mut resolver = DnsResolver::new();
+        assert!(resolver.cached("localhost").is_none());
+
+        let addrs = resolver.resolve("localhost").await.unwrap();
+        assert_eq!(resolver.cached("localhost"), Some(addrs));
+
+        resolver.set_provider(DnsProvider::Cloudflare);
+        assert!(resolver.cached("localhost").is_none());
+    }
 }
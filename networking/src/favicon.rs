@@ -0,0 +1,196 @@
+//! Favicon fetching
+//!
+//! Favicons are fetched once per host, from a page's `<link rel="icon">` URL
+//! or `/favicon.ico`. Concurrent requests for the same host share one fetch,
+//! and failed fetches are remembered so they are not retried.
+
+use crate::client::HttpClient;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+use url::Url;
+
+/// A fetched favicon
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Favicon {
+    /// URL the icon was fetched from
+    pub url: String,
+    /// Image file contents
+    pub bytes: Vec<u8>,
+}
+
+/// Result of a fetch, shared by every request for the host
+type FaviconSlot = Arc<OnceCell<Option<Arc<Favicon>>>>;
+
+/// Per-host cache of favicons
+pub struct FaviconCache {
+    client: HttpClient,
+    icons: Mutex<HashMap<String, FaviconSlot>>,
+}
+
+impl FaviconCache {
+    /// Create a cache fetching favicons with a client
+    pub fn new(client: HttpClient) -> Self {
+        Self {
+            client,
+            icons: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the cache key for a page: its host and any non-default port
+    pub fn key(page_url: &str) -> Option<String> {
+        let url = Url::parse(page_url).ok()?;
+        let host = url.host_str()?.to_ascii_lowercase();
+        Some(match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host,
+        })
+    }
+
+    /// Get the favicon URL for a page, resolving a `<link rel="icon">` href
+    /// if the page has one
+    pub fn favicon_url(page_url: &str, link_href: Option<&str>) -> Option<String> {
+        let page = Url::parse(page_url).ok()?;
+        if !matches!(page.scheme(), "http" | "https") {
+            return None;
+        }
+
+        let url = match link_href {
+            Some(href) => page.join(href).ok()?,
+            None => page.join("/favicon.ico").ok()?,
+        };
+        Some(url.to_string())
+    }
+
+    /// Get the favicon for a page's host, fetching it on first use
+    ///
+    /// Returns `None` if the page has no host or the icon could not be
+    /// fetched.
+    pub async fn get(&self, page_url: &str, link_href: Option<&str>) -> Option<Arc<Favicon>> {
+        let key = Self::key(page_url)?;
+        let icon_url = Self::favicon_url(page_url, link_href)?;
+        let slot = self
+            .icons
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(key)
+            .or_default()
+            .clone();
+
+        slot.get_or_init(|| self.fetch(icon_url)).await.clone()
+    }
+
+    /// Get a favicon already in the cache without fetching
+    pub fn cached(&self, page_url: &str) -> Option<Arc<Favicon>> {
+        let key = Self::key(page_url)?;
+        let icons = self.icons.lock().unwrap_or_else(|e| e.into_inner());
+        icons.get(&key)?.get()?.clone()
+    }
+
+    /// Fetch a favicon, logging failures
+    async fn fetch(&self, icon_url: String) -> Option<Arc<Favicon>> {
+        match self.client.get(&icon_url).await {
+            Ok(response) if response.is_success() && !response.body().is_empty() => {
+                tracing::debug!("Fetched favicon {}", icon_url);
+                Some(Arc::new(Favicon {
+                    url: icon_url,
+                    bytes: response.body().to_vec(),
+                }))
+            }
+            Ok(response) => {
+                tracing::debug!("No favicon at {} (status {})", icon_url, response.status());
+                None
+            }
+            Err(e) => {
+                tracing::debug!("Failed to fetch favicon {}: {}", icon_url, e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const ICON: &[u8] = b"\x00\x00\x01\x00icon";
+
+    #[test]
+    fn test_key() {
+        assert_eq!(
+            FaviconCache::key("https://Example.com/a?b"),
+            Some("example.com".to_string())
+        );
+        assert_eq!(
+            FaviconCache::key("https://example.com/a"),
+            FaviconCache::key("http://example.com/other")
+        );
+        assert_eq!(
+            FaviconCache::key("http://localhost:8080/"),
+            Some("localhost:8080".to_string())
+        );
+        assert_ne!(
+            FaviconCache::key("https://example.com/"),
+            FaviconCache::key("https://docs.example.com/")
+        );
+        assert_eq!(FaviconCache::key("about:blank"), None);
+    }
+
+    #[test]
+    fn test_favicon_url() {
+        assert_eq!(
+            FaviconCache::favicon_url("https://example.com/docs/page.html", None).as_deref(),
+            Some("https://example.com/favicon.ico")
+        );
+        assert_eq!(
+            FaviconCache::favicon_url("https://example.com/docs/page.html", Some("icons/a.png"))
+                .as_deref(),
+            Some("https://example.com/docs/icons/a.png")
+        );
+        assert_eq!(
+            FaviconCache::favicon_url(
+                "https://example.com/",
+                Some("https://cdn.example.net/icon.png")
+            )
+            .as_deref(),
+            Some("https://cdn.example.net/icon.png")
+        );
+        assert_eq!(
+            FaviconCache::favicon_url("file:///home/me/a.html", None),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_fetches_are_shared() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let server_requests = requests.clone();
+        let (server_url, _server) = crate::test_server::serve(move |_| {
+            server_requests.fetch_add(1, Ordering::SeqCst);
+            let mut response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: image/x-icon\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                ICON.len()
+            )
+            .into_bytes();
+            response.extend_from_slice(ICON);
+            response
+        })
+        .await;
+
+        let cache = FaviconCache::new(HttpClient::new().unwrap());
+        let first_page = format!("{}/one", server_url);
+        let second_page = format!("{}/two", server_url);
+        let (first, second) =
+            tokio::join!(cache.get(&first_page, None), cache.get(&second_page, None));
+
+        let first = first.unwrap();
+        assert_eq!(first.bytes, ICON);
+        assert_eq!(first.url, format!("{}/favicon.ico", server_url));
+        assert!(Arc::ptr_eq(&first, &second.unwrap()));
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        assert!(cache.cached(&first_page).is_some());
+        assert!(cache.cached("https://example.com/").is_none());
+    }
+}
@@ -0,0 +1,89 @@
+//! HTTPS-only mode
+//!
+//! Upgrades plain HTTP URLs to HTTPS. Local addresses are exempt, since they
+//! rarely have certificates, and non-web schemes such as `about:` and `file:`
+//! are left alone.
+
+use horizon_url::ParsedUrl;
+use std::net::IpAddr;
+
+/// Check whether a URL is exempt from the HTTPS upgrade
+pub fn is_exempt(url: &str) -> bool {
+    let Ok(parsed) = ParsedUrl::parse(url) else {
+        return true;
+    };
+    if !parsed.is_web() {
+        return true;
+    }
+
+    match parsed.host() {
+        Some(host) => is_local_host(host),
+        None => true,
+    }
+}
+
+/// Get the HTTPS version of a plain HTTP URL, if it should be upgraded
+pub fn upgrade(url: &str) -> Option<String> {
+    if is_exempt(url) {
+        return None;
+    }
+
+    let mut parsed = ParsedUrl::parse(url).ok()?;
+    if parsed.scheme() != "http" {
+        return None;
+    }
+    parsed.set_scheme("https").ok()?;
+    Some(parsed.to_string())
+}
+
+/// Check whether a host is the local machine
+fn is_local_host(host: &str) -> bool {
+    if host == "localhost" || host.ends_with(".localhost") {
+        return true;
+    }
+
+    host.trim_matches(['[', ']'])
+        .parse::<IpAddr>()
+        .is_ok_and(|ip| ip.is_loopback())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upgrade_rewrites_http() {
+        assert_eq!(
+            upgrade("http://example.com/path?q=1"),
+            Some("https://example.com/path?q=1".into())
+        );
+        assert_eq!(
+            upgrade("http://example.com:8080/"),
+            Some("https://example.com:8080/".into())
+        );
+        assert_eq!(
+            upgrade("HTTP://Example.com:80"),
+            Some("https://example.com/".into())
+        );
+        assert_eq!(upgrade("https://example.com/"), None);
+    }
+
+    #[test]
+    fn test_exemptions() {
+        for url in [
+            "about:home",
+            "file:///home/user/page.html",
+            "http://localhost:3000/",
+            "http://app.localhost/",
+            "http://127.0.0.1/",
+            "http://[::1]:8080/",
+            "not a url",
+        ] {
+            assert!(is_exempt(url), "{} should be exempt", url);
+            assert_eq!(upgrade(url), None);
+        }
+
+        assert!(!is_exempt("http://192.168.1.1/"));
+        assert!(!is_exempt("http://localhost.example.com/"));
+    }
+}
@@ -1,28 +1,63 @@
 //! # Horizon Networking
 //!
 //! Networking layer for the Horizon Browser.
-//! Provides HTTP client, DNS resolution, VPN management, speed testing,
-//! and request/response handling.
+//! Provides HTTP client, HTTP caching, DNS resolution, favicon fetching,
+//! HTTPS-only mode, VPN management, tracking protection, speed testing,
+//! traffic accounting, and request/response handling.
 
+pub mod cache;
 pub mod client;
 pub mod dns;
+pub mod favicon;
+pub mod https_only;
 pub mod request;
 pub mod response;
 pub mod speedtest;
+#[cfg(any(test, feature = "test-server"))]
+pub mod test_server;
+pub mod tracking;
+pub mod traffic;
 pub mod vpn;
 
 use anyhow::Result;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Time allowed for each connection during warm-up
+const WARMUP_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Results of warming up connections
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WarmupStats {
+    /// Hosts with a keep-alive connection ready for reuse
+    pub connections_opened: usize,
+}
 
 /// Network manager coordinates all networking operations
 pub struct NetworkManager {
     client: client::HttpClient,
+    dns: dns::DnsResolver,
+    /// Traffic counter shared by every client the manager creates
+    traffic: Arc<traffic::TrafficCounter>,
+    /// Whether clients upgrade requests to HTTPS and refuse downgrade redirects
+    https_only: bool,
+    /// Tracker blocklist, when tracking protection is on
+    blocklist: Option<Arc<tracking::Blocklist>>,
 }
 
 impl NetworkManager {
     /// Create a new network manager
     pub fn new() -> Result<Self> {
+        let traffic = Arc::new(traffic::TrafficCounter::new());
+        let client = client::HttpClient::new()?.with_traffic_counter(Arc::clone(&traffic));
+        let mut dns = dns::DnsResolver::new();
+        dns.set_client(client.clone());
         Ok(Self {
-            client: client::HttpClient::new()?,
+            client,
+            dns,
+            traffic,
+            https_only: false,
+            blocklist: None,
         })
     }
 
@@ -36,6 +71,111 @@ impl NetworkManager {
     pub fn client(&self) -> &client::HttpClient {
         &self.client
     }
+
+    /// Get a reference to the DNS resolver
+    pub fn dns(&self) -> &dns::DnsResolver {
+        &self.dns
+    }
+
+    /// Replace the HTTP client, sending DNS-over-HTTPS queries through it too
+    fn set_client(&mut self, client: client::HttpClient) {
+        self.dns.set_client(client.clone());
+        self.client = client;
+    }
+
+    /// Turn tracking protection on with a blocklist, or off with None
+    pub fn set_blocklist(&mut self, blocklist: Option<tracking::Blocklist>) {
+        self.blocklist = blocklist.map(Arc::new);
+        self.set_client(self.client.clone().with_blocklist(self.blocklist.clone()));
+    }
+
+    /// Get how many tracker requests have been blocked for a tab
+    pub fn blocked_count(&self, tab_id: &str) -> u64 {
+        self.blocklist
+            .as_ref()
+            .map_or(0, |blocklist| blocklist.blocked_count(tab_id))
+    }
+
+    /// Get the traffic counter shared by the manager's clients
+    pub fn traffic(&self) -> &Arc<traffic::TrafficCounter> {
+        &self.traffic
+    }
+
+    /// Create a speed test manager that tests with the manager's client
+    pub fn speed_test(&self) -> speedtest::SpeedTestManager {
+        speedtest::SpeedTestManager::new().with_client(self.client.clone())
+    }
+
+    /// Create a VPN manager reporting the manager's traffic
+    pub fn vpn(&self) -> vpn::VpnManager {
+        vpn::VpnManager::new().with_traffic_counter(Arc::clone(&self.traffic))
+    }
+
+    /// Get byte totals and the current transfer rate across all requests
+    pub fn traffic_stats(&self) -> speedtest::NetworkStats {
+        self.traffic.stats()
+    }
+
+    /// Open keep-alive connections to hosts ahead of use
+    ///
+    /// The client resolves each host itself while connecting. Failures are
+    /// logged and skipped; each connection is bounded by a short timeout so
+    /// warm-up never holds up startup for long.
+    pub async fn warmup(&self, hosts: &[&str]) -> WarmupStats {
+        let mut stats = WarmupStats::default();
+
+        for host in hosts {
+            match tokio::time::timeout(WARMUP_TIMEOUT, self.client.preconnect(host)).await {
+                Ok(Ok(())) => stats.connections_opened += 1,
+                Ok(Err(e)) => tracing::debug!("Warm-up connection to {} failed: {}", host, e),
+                Err(_) => tracing::debug!("Warm-up connection to {} timed out", host),
+            }
+        }
+
+        tracing::info!(
+            "Warmed up connections to {} of {} hosts",
+            stats.connections_opened,
+            hosts.len()
+        );
+        stats
+    }
+
+    /// Rebuild the HTTP client after the VPN configuration changes
+    ///
+    /// Proxy and SOCKS configurations route traffic through the proxy; no
+    /// configuration or an OpenVPN configuration uses a direct client. On
+    /// error the current client is kept.
+    pub fn apply_vpn_config(&mut self, config: Option<&vpn::VpnConfig>) -> Result<()> {
+        let client = match config {
+            Some(config @ (vpn::VpnConfig::Proxy { .. } | vpn::VpnConfig::Socks { .. })) => {
+                client::HttpClient::with_proxy(config)?
+            }
+            Some(vpn::VpnConfig::OpenVpn { .. }) | None => client::HttpClient::new()?,
+        };
+        self.set_client(
+            client
+                .with_traffic_counter(Arc::clone(&self.traffic))
+                .with_https_only(self.https_only)
+                .with_blocklist(self.blocklist.clone()),
+        );
+        Ok(())
+    }
+
+    /// Upgrade requests to HTTPS and refuse downgrade redirects, for the HTTPS-only privacy setting
+    pub fn set_https_only(&mut self, https_only: bool) {
+        self.https_only = https_only;
+        self.set_client(self.client.clone().with_https_only(https_only));
+    }
+
+    /// Check whether clients upgrade requests to HTTPS
+    pub fn is_https_only(&self) -> bool {
+        self.https_only
+    }
+
+    /// Check whether tracking protection has a blocklist
+    pub fn has_blocklist(&self) -> bool {
+        self.blocklist.is_some()
+    }
 }
 
 impl Default for NetworkManager {
@@ -54,6 +194,81 @@ mod tests {
         assert!(manager.is_ok());
     }
 
+    #[test]
+    fn test_speed_test_reads_shared_traffic() {
+        let manager = NetworkManager::new().unwrap();
+        let speed_test = manager.speed_test();
+        manager.client().traffic().record_download(2048);
+
+        let stats = speed_test.get_current_stats();
+        assert_eq!(stats.total_downloaded, 2048);
+        assert!(stats.download_kbps > 0.0);
+    }
+
+    #[test]
+    fn test_privacy_settings_are_reported() {
+        let mut manager = NetworkManager::new().unwrap();
+        assert!(!manager.is_https_only());
+        assert!(!manager.has_blocklist());
+
+        manager.set_https_only(true);
+        manager.set_blocklist(Some(tracking::Blocklist::bundled()));
+        assert!(manager.is_https_only());
+        assert!(manager.has_blocklist());
+
+        manager.set_blocklist(None);
+        assert!(!manager.has_blocklist());
+    }
+
+    #[test]
+    fn test_apply_vpn_config() {
+        let mut manager = NetworkManager::new().unwrap();
+        let config = vpn::VpnConfig::Socks {
+            version: 5,
+            host: "127.0.0.1".to_string(),
+            port: 9050,
+            username: None,
+            password: None,
+        };
+
+        manager.apply_vpn_config(Some(&config)).unwrap();
+        assert_eq!(
+            manager.client().proxy_url(),
+            Some("socks5h://127.0.0.1:9050")
+        );
+        // DNS-over-HTTPS queries go through the proxy as well
+        assert_eq!(
+            manager.dns().client().and_then(|client| client.proxy_url()),
+            Some("socks5h://127.0.0.1:9050")
+        );
+
+        manager.apply_vpn_config(None).unwrap();
+        assert_eq!(manager.client().proxy_url(), None);
+    }
+
+    #[test]
+    fn test_traffic_stats_survive_client_rebuild() {
+        let mut manager = NetworkManager::new().unwrap();
+        manager.client().traffic().record_download(4096);
+
+        manager.apply_vpn_config(None).unwrap();
+        manager.client().traffic().record_upload(1024);
+
+        let stats = manager.traffic_stats();
+        assert_eq!(stats.total_downloaded, 4096);
+        assert_eq!(stats.total_uploaded, 1024);
+        assert!(stats.download_kbps >= 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_warmup_skips_unreachable_hosts() {
+        let manager = NetworkManager::new().unwrap();
+
+        let stats = manager.warmup(&["127.0.0.1:1"]).await;
+
+        assert_eq!(stats.connections_opened, 0);
+    }
+
     #[tokio::test]
     async fn test_network_manager_initialization() {
         let mut manager = NetworkManager::new().unwrap();
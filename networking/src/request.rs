@@ -1,6 +1,21 @@
 //! HTTP request module
 
 use super::client::HttpMethod;
+use std::time::Duration;
+
+/// How a request should use the HTTP cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CachePolicy {
+    /// Use a fresh cached response, otherwise go to the network
+    #[default]
+    Normal,
+    /// Always go to the network and update the cache (reload)
+    NoCache,
+    /// Use any cached response, even a stale one, otherwise go to the network
+    ForceCache,
+    /// Use any cached response and fail if there is none
+    OnlyIfCached,
+}
 
 /// HTTP request
 #[derive(Debug, Clone)]
@@ -9,27 +24,41 @@ pub struct Request {
     url: String,
     headers: Vec<(String, String)>,
     body: Option<Vec<u8>>,
+    cache_policy: CachePolicy,
+    /// Tab the request was made for, if any
+    tab_id: Option<String>,
+    /// Timeout overriding the client default
+    timeout: Option<Duration>,
 }
 
 impl Request {
-    /// Create a new GET request
-    pub fn get(url: impl Into<String>) -> Self {
+    /// Create a new request with any method
+    pub fn new(method: HttpMethod, url: impl Into<String>) -> Self {
         Self {
-            method: HttpMethod::Get,
+            method,
             url: url.into(),
             headers: Vec::new(),
             body: None,
+            cache_policy: CachePolicy::default(),
+            tab_id: None,
+            timeout: None,
         }
     }
 
+    /// Create a new GET request
+    pub fn get(url: impl Into<String>) -> Self {
+        Self::new(HttpMethod::Get, url)
+    }
+
     /// Create a new POST request
     pub fn post(url: impl Into<String>, body: Vec<u8>) -> Self {
-        Self {
-            method: HttpMethod::Post,
-            url: url.into(),
-            headers: Vec::new(),
-            body: Some(body),
-        }
+        Self::new(HttpMethod::Post, url).with_body(body)
+    }
+
+    /// Set the body
+    pub fn with_body(mut self, body: Vec<u8>) -> Self {
+        self.body = Some(body);
+        self
     }
 
     /// Get the HTTP method
@@ -57,6 +86,39 @@ impl Request {
     pub fn body(&self) -> Option<&[u8]> {
         self.body.as_deref()
     }
+
+    /// Set the cache policy
+    pub fn with_cache_policy(mut self, policy: CachePolicy) -> Self {
+        self.cache_policy = policy;
+        self
+    }
+
+    /// Get the cache policy
+    pub fn cache_policy(&self) -> CachePolicy {
+        self.cache_policy
+    }
+
+    /// Set a timeout for this request, overriding the client default
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Get the timeout override, if any
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Set the tab the request is made for
+    pub fn with_tab_id(mut self, tab_id: impl Into<String>) -> Self {
+        self.tab_id = Some(tab_id.into());
+        self
+    }
+
+    /// Get the tab the request is made for
+    pub fn tab_id(&self) -> Option<&str> {
+        self.tab_id.as_deref()
+    }
 }
 
 #[cfg(test)]
@@ -68,6 +130,7 @@ mod tests {
         let req = Request::get("https://example.com");
         assert_eq!(req.method(), HttpMethod::Get);
         assert_eq!(req.url(), "https://example.com");
+        assert_eq!(req.cache_policy(), CachePolicy::Normal);
     }
 
     #[test]
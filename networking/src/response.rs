@@ -1,7 +1,12 @@
 //! HTTP response module
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use std::collections::HashMap;
+use std::io::Read;
+
+/// Largest body a response may decode to, so a small compressed body can't
+/// expand to fill memory
+const MAX_DECODED_BODY_SIZE: usize = 64 * 1024 * 1024;
 
 /// HTTP response
 #[derive(Debug)]
@@ -9,6 +14,8 @@ pub struct Response {
     status: u16,
     headers: HashMap<String, String>,
     body: Vec<u8>,
+    /// URLs redirected from before reaching this response, in order
+    redirect_chain: Vec<String>,
 }
 
 impl Response {
@@ -18,9 +25,35 @@ impl Response {
             status,
             headers: HashMap::new(),
             body,
+            redirect_chain: Vec::new(),
         }
     }
 
+    /// Create from a status, headers, and a body as sent on the wire
+    ///
+    /// The body is decoded according to the `content-encoding` header; the
+    /// headers themselves are kept as received. Responses without a body,
+    /// like those to HEAD requests and 204 and 304 responses, describe the
+    /// encoding of a body that wasn't sent, so nothing is decoded.
+    pub fn from_parts(
+        status: u16,
+        headers: HashMap<String, String>,
+        raw_body: Vec<u8>,
+    ) -> Result<Self> {
+        let has_body = !raw_body.is_empty() && !matches!(status, 204 | 304);
+        let body = match headers.get("content-encoding") {
+            Some(encoding) if has_body => decode_body(encoding, raw_body, MAX_DECODED_BODY_SIZE)?,
+            _ => raw_body,
+        };
+
+        Ok(Self {
+            status,
+            headers,
+            body,
+            redirect_chain: Vec::new(),
+        })
+    }
+
     /// Create from reqwest response
     pub async fn from_reqwest(response: reqwest::Response) -> Result<Self> {
         let status = response.status().as_u16();
@@ -35,12 +68,29 @@ impl Response {
         }
 
         let body = response.bytes().await?.to_vec();
+        Self::from_parts(status, headers, body)
+    }
 
-        Ok(Self {
-            status,
-            headers,
-            body,
-        })
+    /// Add a header
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    /// Record the URLs redirected from before this response
+    pub fn with_redirect_chain(mut self, chain: Vec<String>) -> Self {
+        self.redirect_chain = chain;
+        self
+    }
+
+    /// Get the URLs redirected from before this response, starting with the requested URL
+    pub fn redirect_chain(&self) -> &[String] {
+        &self.redirect_chain
+    }
+
+    /// Get all headers
+    pub fn headers(&self) -> &HashMap<String, String> {
+        &self.headers
     }
 
     /// Get the status code
@@ -69,9 +119,81 @@ impl Response {
     }
 }
 
+/// Undo a `content-encoding`, applying the listed codings in reverse order
+///
+/// Fails if any step decodes to more than `limit` bytes.
+fn decode_body(encoding: &str, body: Vec<u8>, limit: usize) -> Result<Vec<u8>> {
+    // Read one byte past the limit to tell a body at the limit from one over it
+    let read_capped = |reader: &mut dyn Read, decoded: &mut Vec<u8>| {
+        reader.take(limit as u64 + 1).read_to_end(decoded)
+    };
+
+    let mut body = body;
+    for coding in encoding.rsplit(',').map(|c| c.trim().to_lowercase()) {
+        let mut decoded = Vec::new();
+        match coding.as_str() {
+            "" | "identity" => continue,
+            "gzip" | "x-gzip" => {
+                read_capped(
+                    &mut flate2::read::MultiGzDecoder::new(body.as_slice()),
+                    &mut decoded,
+                )?;
+            }
+            "deflate" => {
+                // Should be zlib-wrapped, but some servers send raw deflate
+                if read_capped(
+                    &mut flate2::read::ZlibDecoder::new(body.as_slice()),
+                    &mut decoded,
+                )
+                .is_err()
+                {
+                    decoded.clear();
+                    read_capped(
+                        &mut flate2::read::DeflateDecoder::new(body.as_slice()),
+                        &mut decoded,
+                    )?;
+                }
+            }
+            "br" => {
+                read_capped(
+                    &mut brotli_decompressor::Decompressor::new(body.as_slice(), 4096),
+                    &mut decoded,
+                )?;
+            }
+            other => return Err(anyhow!("Unsupported content encoding: {}", other)),
+        }
+        if decoded.len() > limit {
+            return Err(anyhow!(
+                "Decoded response body is larger than {} bytes",
+                limit
+            ));
+        }
+        body = decoded;
+    }
+    Ok(body)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use flate2::write::{DeflateEncoder, GzEncoder, ZlibEncoder};
+    use flate2::Compression;
+    use std::io::Write;
+
+    const TEXT: &str = "Hello, compressed world!";
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn encoded_headers(encoding: &str, length: usize) -> HashMap<String, String> {
+        HashMap::from([
+            ("content-encoding".to_string(), encoding.to_string()),
+            ("content-length".to_string(), length.to_string()),
+        ])
+    }
 
     #[test]
     fn test_response_creation() {
@@ -86,6 +208,95 @@ mod tests {
         assert_eq!(response.body_string().unwrap(), "Hello, World!");
     }
 
+    #[test]
+    fn test_gzip_body_is_decoded() {
+        let compressed = gzip(TEXT.as_bytes());
+        let length = compressed.len();
+        let response =
+            Response::from_parts(200, encoded_headers("gzip", length), compressed).unwrap();
+
+        assert_eq!(response.body_string().unwrap(), TEXT);
+        assert_eq!(response.header("content-encoding"), Some("gzip"));
+        assert_eq!(
+            response.header("content-length"),
+            Some(length.to_string().as_str())
+        );
+    }
+
+    #[test]
+    fn test_deflate_body_is_decoded() {
+        let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+        zlib.write_all(TEXT.as_bytes()).unwrap();
+        let zlib = zlib.finish().unwrap();
+        let response = Response::from_parts(200, encoded_headers("deflate", 0), zlib).unwrap();
+        assert_eq!(response.body_string().unwrap(), TEXT);
+
+        let mut raw = DeflateEncoder::new(Vec::new(), Compression::default());
+        raw.write_all(TEXT.as_bytes()).unwrap();
+        let raw = raw.finish().unwrap();
+        let response = Response::from_parts(200, encoded_headers("deflate", 0), raw).unwrap();
+        assert_eq!(response.body_string().unwrap(), TEXT);
+    }
+
+    #[test]
+    fn test_brotli_body_is_decoded() {
+        // "The quick brown fox jumps over the lazy dog" repeated 4096 times
+        let compressed = vec![
+            0x5B, 0xFF, 0xAF, 0x02, 0xC0, 0x22, 0x79, 0x5C, 0xFB, 0x5A, 0x8C, 0x42, 0x3B, 0xF4,
+            0x25, 0x55, 0x19, 0x5A, 0x92, 0x99, 0xB1, 0x35, 0xC8, 0x19, 0x9E, 0x9E, 0x0A, 0x7B,
+            0x4B, 0x90, 0xB9, 0x3C, 0x98, 0xC8, 0x09, 0x40, 0xF3, 0xE6, 0xD9, 0x4D, 0xE4, 0x6D,
+            0x65, 0x1B, 0x27, 0x87, 0x13, 0x5F, 0xA6, 0xE9, 0x30, 0x96, 0x7B, 0x3C, 0x15, 0xD8,
+            0x53, 0x1C,
+        ];
+        let response = Response::from_parts(200, encoded_headers("br", 58), compressed).unwrap();
+
+        let fox = "The quick brown fox jumps over the lazy dog";
+        assert_eq!(response.body_string().unwrap(), fox.repeat(4096));
+    }
+
+    #[test]
+    fn test_bodiless_responses_are_not_decoded() {
+        for encoding in ["gzip", "deflate", "br"] {
+            for status in [200, 204, 304] {
+                let response =
+                    Response::from_parts(status, encoded_headers(encoding, 0), Vec::new()).unwrap();
+                assert!(response.body().is_empty());
+            }
+        }
+
+        // 304s describe the cached body, so stray bytes are left alone
+        let response =
+            Response::from_parts(304, encoded_headers("gzip", 4), b"junk".to_vec()).unwrap();
+        assert_eq!(response.body(), b"junk");
+    }
+
+    #[test]
+    fn test_decoded_size_is_capped() {
+        let zeros = gzip(&[0; 4096]);
+        assert_eq!(
+            decode_body("gzip", zeros.clone(), 4096).unwrap().len(),
+            4096
+        );
+
+        let err = decode_body("gzip", zeros, 4095).unwrap_err();
+        assert!(err.to_string().contains("larger than 4095 bytes"));
+        assert!(decode_body("gzip, gzip", gzip(&gzip(&[0; 4096])), 1024).is_err());
+    }
+
+    #[test]
+    fn test_stacked_and_unknown_encodings() {
+        let twice = gzip(&gzip(TEXT.as_bytes()));
+        let response =
+            Response::from_parts(200, encoded_headers("gzip, identity, GZIP", 0), twice).unwrap();
+        assert_eq!(response.body_string().unwrap(), TEXT);
+
+        let plain = Response::from_parts(200, HashMap::new(), TEXT.into()).unwrap();
+        assert_eq!(plain.body_string().unwrap(), TEXT);
+
+        assert!(Response::from_parts(200, encoded_headers("zstd", 0), TEXT.into()).is_err());
+        assert!(Response::from_parts(200, encoded_headers("gzip", 0), TEXT.into()).is_err());
+    }
+
     #[test]
     fn test_response_success() {
         let success = Response::new(200, vec![]);
@@ -1,8 +1,16 @@
 //! Network speed test module
 
+use crate::client::{HttpClient, HttpMethod};
+use crate::request::Request;
+use crate::response::Response;
+use crate::traffic::TrafficCounter;
 use anyhow::Result;
+use horizon_url::ParsedUrl;
 use serde::{Deserialize, Serialize};
-use std::time::Instant;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Speed test results
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -86,11 +94,28 @@ impl Default for SpeedTestConfig {
     }
 }
 
+/// Number of requests the download and upload phases are split into
+const TRANSFER_STEPS: usize = 10;
+
+/// Maximum number of results kept in the speed test history
+pub const MAX_HISTORY: usize = 50;
+
 /// Speed test manager
+///
+/// Measures against a server implementing Cloudflare's speed test endpoints:
+/// `GET /__down?bytes=N` returns N bytes and `POST /__up` accepts a body.
 pub struct SpeedTestManager {
     config: SpeedTestConfig,
     last_result: Option<SpeedTestResult>,
+    /// Completed results, oldest first
+    history: Vec<SpeedTestResult>,
     is_running: bool,
+    /// Set to stop the running test at the next check
+    cancel_flag: Arc<AtomicBool>,
+    /// Client tests run with, or None for a default client
+    client: Option<HttpClient>,
+    /// Counter current network statistics are read from
+    traffic: Arc<TrafficCounter>,
 }
 
 impl SpeedTestManager {
@@ -99,7 +124,11 @@ impl SpeedTestManager {
         Self {
             config: SpeedTestConfig::default(),
             last_result: None,
+            history: Vec::new(),
             is_running: false,
+            cancel_flag: Arc::new(AtomicBool::new(false)),
+            client: None,
+            traffic: Arc::new(TrafficCounter::new()),
         }
     }
 
@@ -108,10 +137,22 @@ impl SpeedTestManager {
         Self {
             config,
             last_result: None,
+            history: Vec::new(),
             is_running: false,
+            cancel_flag: Arc::new(AtomicBool::new(false)),
+            client: None,
+            traffic: Arc::new(TrafficCounter::new()),
         }
     }
 
+    /// Run tests with a client, reading current network statistics from its
+    /// traffic counter
+    pub fn with_client(mut self, client: HttpClient) -> Self {
+        self.traffic = Arc::clone(client.traffic());
+        self.client = Some(client);
+        self
+    }
+
     /// Check if a speed test is currently running
     pub fn is_running(&self) -> bool {
         self.is_running
@@ -122,7 +163,75 @@ impl SpeedTestManager {
         self.last_result.as_ref()
     }
 
+    /// Get completed results, oldest first
+    pub fn history(&self) -> &[SpeedTestResult] {
+        &self.history
+    }
+
+    /// Add a result to the history, dropping the oldest beyond the cap
+    fn record_result(&mut self, result: SpeedTestResult) {
+        self.history.push(result.clone());
+        if self.history.len() > MAX_HISTORY {
+            let excess = self.history.len() - MAX_HISTORY;
+            self.history.drain(..excess);
+        }
+        self.last_result = Some(result);
+    }
+
+    /// Get the average download speed across the history in Mbps
+    pub fn average_download(&self) -> Option<f64> {
+        if self.history.is_empty() {
+            return None;
+        }
+        let speeds: Vec<f64> = self.history.iter().map(|r| r.download_mbps).collect();
+        Some(mean(&speeds))
+    }
+
+    /// Get the lowest ping in the history in milliseconds
+    pub fn best_ping(&self) -> Option<f64> {
+        self.history.iter().map(|r| r.ping_ms).reduce(f64::min)
+    }
+
+    /// Save the history to a file
+    pub fn save_history(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.history)?;
+        std::fs::write(path, json)?;
+        tracing::debug!("Saved speed test history to {:?}", path);
+        Ok(())
+    }
+
+    /// Load the history from a file, replacing the current one
+    pub fn load_history(&mut self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let json = std::fs::read_to_string(path)?;
+        let mut history: Vec<SpeedTestResult> = serde_json::from_str(&json)?;
+        if history.len() > MAX_HISTORY {
+            history.drain(..history.len() - MAX_HISTORY);
+        }
+        self.last_result = history.last().cloned();
+        self.history = history;
+        tracing::debug!("Loaded speed test history from {:?}", path);
+        Ok(())
+    }
+
+    /// Cancel the running speed test
+    ///
+    /// The test stops between phases and between requests within a phase.
+    pub fn cancel(&self) {
+        self.cancel_flag.store(true, Ordering::SeqCst);
+    }
+
+    /// Get a handle that cancels the running test when set, for use from other tasks
+    pub fn cancel_handle(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.cancel_flag)
+    }
+
     /// Run a speed test
+    ///
+    /// Returns an error if the test is cancelled before it completes.
     pub async fn run_test<F>(&mut self, mut progress_callback: F) -> Result<SpeedTestResult>
     where
         F: FnMut(SpeedTestPhase),
@@ -132,133 +241,204 @@ impl SpeedTestManager {
         }
 
         self.is_running = true;
-        tracing::info!("Starting speed test...");
+        self.cancel_flag.store(false, Ordering::SeqCst);
+        tracing::info!("Starting speed test against {}", self.config.server_url);
+
+        let result = self.measure(&mut progress_callback).await;
+        self.is_running = false;
+        let result = result?;
+
+        self.record_result(result.clone());
+
+        tracing::info!(
+            "Speed test complete: Down={:.2} Mbps, Up={:.2} Mbps, Ping={:.2} ms",
+            result.download_mbps,
+            result.upload_mbps,
+            result.ping_ms
+        );
+
+        Ok(result)
+    }
+
+    /// Run all test phases
+    async fn measure<F>(&self, progress_callback: &mut F) -> Result<SpeedTestResult>
+    where
+        F: FnMut(SpeedTestPhase),
+    {
+        let client = match &self.client {
+            Some(client) => client.clone(),
+            None => HttpClient::new()?,
+        };
 
         // Phase 1: Ping test
         progress_callback(SpeedTestPhase::Ping);
-        let ping_ms = self.test_ping().await?;
-        let jitter_ms = self.calculate_jitter().await?;
+        let ping_samples = self.test_ping(&client).await?;
 
         // Phase 2: Download test
-        let mut download_mbps: f64 = 0.0;
-        for i in 0..10 {
-            progress_callback(SpeedTestPhase::Download((i * 10) as u8));
-            let speed = self.test_download().await?;
-            download_mbps = download_mbps.max(speed);
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-        }
+        self.check_cancelled()?;
+        let download_mbps = self.test_download(&client, progress_callback).await?;
 
         // Phase 3: Upload test
-        let mut upload_mbps: f64 = 0.0;
-        for i in 0..10 {
-            progress_callback(SpeedTestPhase::Upload((i * 10) as u8));
-            let speed = self.test_upload().await?;
-            upload_mbps = upload_mbps.max(speed);
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-        }
+        self.check_cancelled()?;
+        let upload_mbps = self.test_upload(&client, progress_callback).await?;
 
         progress_callback(SpeedTestPhase::Complete);
 
-        let result = SpeedTestResult {
+        Ok(SpeedTestResult {
             download_mbps,
             upload_mbps,
-            ping_ms,
-            jitter_ms,
-            server_location: self.get_server_location().await?,
+            ping_ms: mean(&ping_samples),
+            jitter_ms: jitter(&ping_samples),
+            server_location: self.server_location(),
             timestamp: std::time::SystemTime::now(),
-        };
+        })
+    }
 
-        self.last_result = Some(result.clone());
-        self.is_running = false;
+    /// Time HEAD requests to the server, returning each round trip in milliseconds
+    async fn test_ping(&self, client: &HttpClient) -> Result<Vec<f64>> {
+        tracing::debug!("Testing ping...");
 
-        tracing::info!(
-            "Speed test complete: Down={:.2} Mbps, Up={:.2} Mbps, Ping={:.2} ms",
-            result.download_mbps,
-            result.upload_mbps,
-            result.ping_ms
-        );
+        let mut samples = Vec::with_capacity(self.config.ping_count);
+        for _ in 0..self.config.ping_count {
+            self.check_cancelled()?;
+            let request = Request::new(HttpMethod::Head, self.download_url(0));
+            let start = Instant::now();
+            ensure_success(client.request(request).await?)?;
+            samples.push(start.elapsed().as_secs_f64() * 1000.0);
+        }
 
-        Ok(result)
+        Ok(samples)
     }
 
-    /// Test ping/latency
-    async fn test_ping(&self) -> Result<f64> {
-        tracing::debug!("Testing ping...");
+    /// Download `download_size` bytes in steps, returning the speed in Mbps
+    async fn test_download<F>(&self, client: &HttpClient, progress_callback: &mut F) -> Result<f64>
+    where
+        F: FnMut(SpeedTestPhase),
+    {
+        let step_size = (self.config.download_size / TRANSFER_STEPS).max(1);
+        let time_limit = Duration::from_secs(self.config.download_duration_secs);
+        let mut bytes = 0;
+        let mut elapsed = Duration::ZERO;
 
-        // Note: In a full implementation, this would:
-        // 1. Make multiple HTTP HEAD requests to a test server
-        // 2. Measure round-trip time for each request
-        // 3. Calculate average latency
+        for step in 0..TRANSFER_STEPS {
+            self.check_cancelled()?;
+            progress_callback(SpeedTestPhase::Download(
+                (step * 100 / TRANSFER_STEPS) as u8,
+            ));
 
-        // Simulate ping test
-        let mut total_ms = 0.0;
-        for _ in 0..self.config.ping_count {
             let start = Instant::now();
-            // Simulate network request
-            tokio::time::sleep(tokio::time::Duration::from_millis(20)).await;
-            let elapsed = start.elapsed().as_secs_f64() * 1000.0;
-            total_ms += elapsed;
+            bytes += client.drain(&self.download_url(step_size)).await? as usize;
+            elapsed += start.elapsed();
+
+            if elapsed >= time_limit {
+                break;
+            }
         }
 
-        let avg_ms = total_ms / self.config.ping_count as f64;
-        Ok(avg_ms)
+        Ok(mbps(bytes, elapsed))
     }
 
-    /// Calculate jitter
-    async fn calculate_jitter(&self) -> Result<f64> {
-        // Note: In a full implementation, this would calculate
-        // the variance in ping times
-        Ok(5.0) // Simulated jitter
-    }
+    /// Upload `upload_size` bytes in steps, returning the speed in Mbps
+    async fn test_upload<F>(&self, client: &HttpClient, progress_callback: &mut F) -> Result<f64>
+    where
+        F: FnMut(SpeedTestPhase),
+    {
+        let step_size = (self.config.upload_size / TRANSFER_STEPS).max(1);
+        let time_limit = Duration::from_secs(self.config.upload_duration_secs);
+        let url = format!("{}/__up", self.server_base());
+        let mut bytes = 0;
+        let mut elapsed = Duration::ZERO;
 
-    /// Test download speed
-    async fn test_download(&self) -> Result<f64> {
-        // Note: In a full implementation, this would:
-        // 1. Download a file of known size from test server
-        // 2. Measure time taken
-        // 3. Calculate speed in Mbps
+        for step in 0..TRANSFER_STEPS {
+            self.check_cancelled()?;
+            progress_callback(SpeedTestPhase::Upload((step * 100 / TRANSFER_STEPS) as u8));
+
+            let start = Instant::now();
+            ensure_success(client.post(&url, vec![0u8; step_size]).await?)?;
+            elapsed += start.elapsed();
+            bytes += step_size;
 
-        // Simulate download speed test
-        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+            if elapsed >= time_limit {
+                break;
+            }
+        }
 
-        // Simulate varying download speeds (50-150 Mbps)
-        let speed = 50.0 + (rand::random::<f64>() * 100.0);
-        Ok(speed)
+        Ok(mbps(bytes, elapsed))
     }
 
-    /// Test upload speed
-    async fn test_upload(&self) -> Result<f64> {
-        // Note: In a full implementation, this would:
-        // 1. Upload data to test server
-        // 2. Measure time taken
-        // 3. Calculate speed in Mbps
+    /// Fail if the test has been cancelled
+    fn check_cancelled(&self) -> Result<()> {
+        if self.cancel_flag.load(Ordering::SeqCst) {
+            tracing::info!("Speed test cancelled");
+            return Err(anyhow::anyhow!("Speed test cancelled"));
+        }
+        Ok(())
+    }
 
-        // Simulate upload speed test
-        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    /// Get the server URL without a trailing slash
+    fn server_base(&self) -> &str {
+        self.config.server_url.trim_end_matches('/')
+    }
 
-        // Simulate varying upload speeds (20-80 Mbps)
-        let speed = 20.0 + (rand::random::<f64>() * 60.0);
-        Ok(speed)
+    /// Get the URL that returns `bytes` bytes
+    fn download_url(&self, bytes: usize) -> String {
+        format!("{}/__down?bytes={}", self.server_base(), bytes)
     }
 
-    /// Get the test server location
-    async fn get_server_location(&self) -> Result<String> {
-        // Note: In a full implementation, this would query the server
-        // for its geographic location
-        Ok("Cloudflare Network".to_string())
+    /// Get the test server location, shown as its host
+    fn server_location(&self) -> String {
+        ParsedUrl::parse(&self.config.server_url)
+            .ok()
+            .and_then(|url| url.host().map(str::to_string))
+            .unwrap_or_else(|| self.config.server_url.clone())
     }
 
     /// Get current network statistics (real-time)
     pub fn get_current_stats(&self) -> NetworkStats {
-        // Note: In a full implementation, this would monitor actual
-        // network traffic and calculate real-time speeds
-        NetworkStats {
-            download_kbps: 0.0,
-            upload_kbps: 0.0,
-            total_downloaded: 0,
-            total_uploaded: 0,
-        }
+        self.traffic.stats()
+    }
+}
+
+/// Fail on a non-success response
+fn ensure_success(response: Response) -> Result<Response> {
+    if !response.is_success() {
+        return Err(anyhow::anyhow!(
+            "Speed test server returned status {}",
+            response.status()
+        ));
+    }
+    Ok(response)
+}
+
+/// Convert bytes transferred over a duration to megabits per second
+fn mbps(bytes: usize, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs <= 0.0 {
+        return 0.0;
+    }
+    bytes as f64 * 8.0 / secs / 1_000_000.0
+}
+
+/// Average of samples (0 if there are none)
+fn mean(samples: &[f64]) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
     }
+    samples.iter().sum::<f64>() / samples.len() as f64
+}
+
+/// Jitter as the standard deviation of ping samples (0 if there are none)
+fn jitter(samples: &[f64]) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let average = mean(samples);
+    let variance = samples
+        .iter()
+        .map(|sample| (sample - average).powi(2))
+        .sum::<f64>()
+        / samples.len() as f64;
+    variance.sqrt()
 }
 
 /// Real-time network statistics
@@ -307,19 +487,209 @@ mod tests {
         assert_eq!(config.upload_duration_secs, 10);
     }
 
+    #[test]
+    fn test_mbps() {
+        assert_eq!(mbps(1_250_000, Duration::from_secs(1)), 10.0);
+        assert_eq!(mbps(1_250_000, Duration::from_millis(500)), 20.0);
+        assert_eq!(mbps(1_000, Duration::ZERO), 0.0);
+    }
+
+    #[test]
+    fn test_jitter_is_stddev_of_pings() {
+        assert_eq!(mean(&[10.0, 20.0, 30.0]), 20.0);
+        assert!((jitter(&[10.0, 20.0, 30.0]) - 8.1650).abs() < 0.001);
+        assert_eq!(jitter(&[5.0, 5.0, 5.0]), 0.0);
+        assert_eq!(jitter(&[]), 0.0);
+        assert_eq!(mean(&[]), 0.0);
+    }
+
+    /// Serve the speed test endpoints on a local port, returning its URL
+    async fn spawn_speed_test_server() -> String {
+        let (url, _server) = crate::test_server::serve(|request| {
+            let request_line = request.lines().next().unwrap_or_default();
+            let size = request_line
+                .split_once("bytes=")
+                .and_then(|(_, rest)| rest.split(' ').next()?.parse().ok())
+                .filter(|_| request_line.starts_with("GET"))
+                .unwrap_or(0);
+            let mut response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                size
+            )
+            .into_bytes();
+            response.resize(response.len() + size, b'0');
+            response
+        })
+        .await;
+
+        url
+    }
+
     #[tokio::test]
     async fn test_speed_test_run() {
+        let server_url = spawn_speed_test_server().await;
+        let mut manager = SpeedTestManager::with_config(SpeedTestConfig {
+            server_url,
+            ping_count: 3,
+            download_size: 100_000,
+            upload_size: 50_000,
+            ..SpeedTestConfig::default()
+        });
+
+        let mut phases = Vec::new();
+        let result = manager.run_test(|phase| phases.push(phase)).await.unwrap();
+
+        assert!(result.download_mbps > 0.0);
+        assert!(result.upload_mbps > 0.0);
+        assert!(result.ping_ms > 0.0);
+        assert!(result.jitter_ms >= 0.0);
+        assert_eq!(result.server_location, "127.0.0.1");
+        assert_eq!(phases.first(), Some(&SpeedTestPhase::Ping));
+        assert_eq!(phases.last(), Some(&SpeedTestPhase::Complete));
+        assert!(!manager.is_running());
+        assert!(manager.last_result().is_some());
+        assert_eq!(manager.history().len(), 1);
+    }
+
+    /// A result with the given download speed and ping
+    fn result(download_mbps: f64, ping_ms: f64) -> SpeedTestResult {
+        SpeedTestResult {
+            download_mbps,
+            upload_mbps: 10.0,
+            ping_ms,
+            jitter_ms: 1.0,
+            server_location: "speed.cloudflare.com".to_string(),
+            timestamp: std::time::SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn test_history_cap_evicts_oldest() {
+        let mut manager = SpeedTestManager::new();
+        for i in 0..MAX_HISTORY + 5 {
+            manager.record_result(result(i as f64, 20.0));
+        }
+
+        assert_eq!(manager.history().len(), MAX_HISTORY);
+        assert_eq!(manager.history()[0].download_mbps, 5.0);
+        assert_eq!(
+            manager.last_result().unwrap().download_mbps,
+            (MAX_HISTORY + 4) as f64
+        );
+    }
+
+    #[test]
+    fn test_history_stats() {
         let mut manager = SpeedTestManager::new();
+        assert_eq!(manager.average_download(), None);
+        assert_eq!(manager.best_ping(), None);
+
+        manager.record_result(result(100.0, 30.0));
+        manager.record_result(result(50.0, 12.5));
+        manager.record_result(result(60.0, 18.0));
+
+        assert_eq!(manager.average_download(), Some(70.0));
+        assert_eq!(manager.best_ping(), Some(12.5));
+    }
+
+    #[test]
+    fn test_history_save_load_roundtrip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("speedtest_history.json");
+
+        let mut manager = SpeedTestManager::new();
+        manager.record_result(result(100.0, 30.0));
+        manager.record_result(result(50.0, 12.5));
+        manager.save_history(&path).unwrap();
+
+        let mut reloaded = SpeedTestManager::new();
+        reloaded.load_history(&path).unwrap();
+        assert_eq!(reloaded.history().len(), 2);
+        assert_eq!(reloaded.history()[1].ping_ms, 12.5);
+        assert_eq!(reloaded.last_result().unwrap().download_mbps, 50.0);
+
+        // A missing file leaves the history untouched
+        reloaded
+            .load_history(&temp_dir.path().join("missing.json"))
+            .unwrap();
+        assert_eq!(reloaded.history().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_stops_before_download() {
+        let server_url = spawn_speed_test_server().await;
+        let mut manager = SpeedTestManager::with_config(SpeedTestConfig {
+            server_url,
+            ping_count: 1,
+            ..SpeedTestConfig::default()
+        });
+        let cancel = manager.cancel_handle();
+
+        let mut phases = Vec::new();
         let result = manager
             .run_test(|phase| {
-                tracing::debug!("Phase: {:?}", phase);
+                phases.push(phase);
+                if phase == SpeedTestPhase::Ping {
+                    cancel.store(true, Ordering::SeqCst);
+                }
             })
             .await;
 
-        assert!(result.is_ok());
-        let result = result.unwrap();
+        assert!(result.unwrap_err().to_string().contains("cancelled"));
+        assert_eq!(phases, vec![SpeedTestPhase::Ping]);
+        assert!(!manager.is_running());
+        assert!(manager.last_result().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_speed_test_uses_given_client() {
+        let server_url = spawn_speed_test_server().await;
+        let traffic = Arc::new(TrafficCounter::new());
+        let client = HttpClient::new()
+            .unwrap()
+            .with_traffic_counter(Arc::clone(&traffic));
+        let mut manager = SpeedTestManager::with_config(SpeedTestConfig {
+            server_url,
+            ping_count: 1,
+            download_size: 100_000,
+            upload_size: 10_000,
+            ..SpeedTestConfig::default()
+        })
+        .with_client(client);
+
+        manager.run_test(|_| {}).await.unwrap();
+        assert!(traffic.total_downloaded() >= 100_000);
+        assert!(traffic.total_uploaded() >= 10_000);
+        assert_eq!(
+            manager.get_current_stats().total_downloaded,
+            traffic.total_downloaded()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_speed_test_failure_clears_running() {
+        let mut manager = SpeedTestManager::with_config(SpeedTestConfig {
+            server_url: "http://127.0.0.1:1".to_string(),
+            ..SpeedTestConfig::default()
+        });
+
+        assert!(manager.run_test(|_| {}).await.is_err());
+        assert!(!manager.is_running());
+        assert!(manager.last_result().is_none());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore = "requires network access")]
+    async fn test_speed_test_live() {
+        let mut manager = SpeedTestManager::with_config(SpeedTestConfig {
+            ping_count: 3,
+            download_size: 1024 * 1024,
+            upload_size: 256 * 1024,
+            ..SpeedTestConfig::default()
+        });
+
+        let result = manager.run_test(|_| {}).await.unwrap();
         assert!(result.download_mbps > 0.0);
-        assert!(result.upload_mbps > 0.0);
-        assert!(result.ping_ms > 0.0);
+        assert_eq!(result.server_location, "speed.cloudflare.com");
     }
 }
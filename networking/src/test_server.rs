@@ -0,0 +1,106 @@
+//! Local HTTP server for tests
+//!
+//! Available to this crate's tests, and to other crates' tests through the
+//! `test-server` feature.
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// Bind a listener on a free local port, returning it and its base URL
+pub async fn bind() -> (TcpListener, String) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    (listener, url)
+}
+
+/// Read one request from a connection, returning its head and body as text
+///
+/// Reads until the body announced by `Content-Length` has arrived or the
+/// client closes the connection.
+pub async fn read_request(stream: &mut TcpStream) -> String {
+    let mut raw = Vec::new();
+    let mut buf = [0u8; 8192];
+
+    loop {
+        let n = stream.read(&mut buf).await.unwrap_or(0);
+        raw.extend_from_slice(&buf[..n]);
+        if n == 0 {
+            break;
+        }
+
+        let text = String::from_utf8_lossy(&raw);
+        if let Some(head_end) = text.find("\r\n\r\n") {
+            let content_length = text[..head_end]
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().ok())?
+                })
+                .unwrap_or(0);
+            if raw.len() >= head_end + 4 + content_length {
+                break;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&raw).to_string()
+}
+
+/// Answer every request with the raw response `respond` builds from it
+///
+/// Returns the server's base URL and the task running it.
+pub async fn serve(
+    respond: impl Fn(&str) -> Vec<u8> + Send + Sync + 'static,
+) -> (String, JoinHandle<()>) {
+    serve_and_hold(respond, Duration::ZERO).await
+}
+
+/// Answer every request like `serve`, then hold each connection open
+///
+/// Useful for responses that announce more body than they send.
+pub async fn serve_and_hold(
+    respond: impl Fn(&str) -> Vec<u8> + Send + Sync + 'static,
+    hold: Duration,
+) -> (String, JoinHandle<()>) {
+    let (listener, url) = bind().await;
+    let respond = Arc::new(respond);
+
+    let server = tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let respond = Arc::clone(&respond);
+            tokio::spawn(async move {
+                let request = read_request(&mut stream).await;
+                // The client may have given up waiting, so ignore write errors
+                let _ = stream.write_all(&respond(&request)).await;
+                tokio::time::sleep(hold).await;
+            });
+        }
+    });
+
+    (url, server)
+}
+
+/// Answer a single request with a raw response after a delay
+///
+/// Returns the server's base URL and a task yielding the raw request.
+pub async fn serve_one(
+    response: impl Into<String>,
+    delay: Duration,
+) -> (String, JoinHandle<String>) {
+    let response = response.into();
+    let (listener, url) = bind().await;
+
+    let server = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let request = read_request(&mut stream).await;
+        tokio::time::sleep(delay).await;
+        let _ = stream.write_all(response.as_bytes()).await;
+        request
+    });
+
+    (url, server)
+}
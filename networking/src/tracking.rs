@@ -0,0 +1,245 @@
+//! Tracking protection
+//!
+//! A host blocklist matched against request URLs. Blocking a host also
+//! blocks its subdomains.
+
+use anyhow::Result;
+use horizon_url::ParsedUrl;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Tracker hosts blocked by default
+const BUNDLED_HOSTS: &[&str] = &[
+    "doubleclick.net",
+    "google-analytics.com",
+    "googletagmanager.com",
+    "googlesyndication.com",
+    "googleadservices.com",
+    "adservice.google.com",
+    "connect.facebook.net",
+    "pixel.facebook.com",
+    "analytics.twitter.com",
+    "ads.linkedin.com",
+    "bat.bing.com",
+    "scorecardresearch.com",
+    "quantserve.com",
+    "adnxs.com",
+    "criteo.com",
+    "taboola.com",
+    "outbrain.com",
+    "hotjar.com",
+    "mixpanel.com",
+    "segment.io",
+];
+
+/// Host blocklist with per-tab counts of blocked requests
+#[derive(Debug, Default)]
+pub struct Blocklist {
+    /// Blocked hosts, lowercase
+    hosts: HashSet<String>,
+    /// Blocked request counts by tab ID
+    blocked: Mutex<HashMap<String, u64>>,
+}
+
+impl Blocklist {
+    /// Create an empty blocklist
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a blocklist with the bundled tracker hosts
+    pub fn bundled() -> Self {
+        let mut blocklist = Self::new();
+        for host in BUNDLED_HOSTS {
+            blocklist.add_host(host);
+        }
+        blocklist
+    }
+
+    /// Parse host rules, one per line
+    ///
+    /// Accepts hosts-file lines (`0.0.0.0 tracker.example`), bare domains,
+    /// and EasyList domain anchors (`||tracker.example^`). Comments starting
+    /// with `#` or `!` and any other EasyList rules are skipped.
+    pub fn from_rules(rules: &str) -> Self {
+        let mut blocklist = Self::new();
+        for line in rules.lines() {
+            if let Some(host) = parse_rule(line) {
+                blocklist.add_host(host);
+            }
+        }
+        blocklist
+    }
+
+    /// Load host rules from a file
+    pub fn load(path: &Path) -> Result<Self> {
+        let rules = std::fs::read_to_string(path)?;
+        let blocklist = Self::from_rules(&rules);
+        tracing::info!("Loaded {} blocked hosts from {:?}", blocklist.len(), path);
+        Ok(blocklist)
+    }
+
+    /// Block a host and its subdomains
+    pub fn add_host(&mut self, host: &str) {
+        let host = host.trim().trim_end_matches('.').to_lowercase();
+        if !host.is_empty() {
+            self.hosts.insert(host);
+        }
+    }
+
+    /// Count blocked hosts
+    pub fn len(&self) -> usize {
+        self.hosts.len()
+    }
+
+    /// Check if the blocklist is empty
+    pub fn is_empty(&self) -> bool {
+        self.hosts.is_empty()
+    }
+
+    /// Check whether a request to a URL should be blocked
+    pub fn should_block(&self, url: &str) -> bool {
+        let Some(host) = ParsedUrl::parse(url)
+            .ok()
+            .and_then(|url| url.host().map(str::to_string))
+        else {
+            return false;
+        };
+
+        // Check the host and each parent domain
+        let mut domain = host.trim_end_matches('.');
+        loop {
+            if self.hosts.contains(domain) {
+                return true;
+            }
+            match domain.split_once('.') {
+                Some((_, parent)) => domain = parent,
+                None => return false,
+            }
+        }
+    }
+
+    /// Count a blocked request for a tab
+    pub fn record_blocked(&self, tab_id: &str) {
+        *self.lock_blocked().entry(tab_id.to_string()).or_default() += 1;
+    }
+
+    /// Get how many requests have been blocked for a tab
+    pub fn blocked_count(&self, tab_id: &str) -> u64 {
+        self.lock_blocked().get(tab_id).copied().unwrap_or(0)
+    }
+
+    /// Reset a tab's blocked count, e.g. when it navigates to a new page
+    pub fn reset_blocked_count(&self, tab_id: &str) {
+        self.lock_blocked().remove(tab_id);
+    }
+
+    fn lock_blocked(&self) -> std::sync::MutexGuard<'_, HashMap<String, u64>> {
+        // A poisoned counter map is still usable; counts are only incremented
+        self.blocked.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Extract the host from a blocklist rule line
+fn parse_rule(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+        return None;
+    }
+
+    // EasyList domain anchor: ||host^ with no further path or options
+    if let Some(rule) = line.strip_prefix("||") {
+        let host = rule.strip_suffix('^').unwrap_or(rule);
+        return is_host(host).then_some(host);
+    }
+
+    // Hosts file: address followed by host, with an optional trailing comment
+    let mut fields = line.split('#').next()?.split_whitespace();
+    let first = fields.next()?;
+    let host = match fields.next() {
+        Some(host) if first.parse::<std::net::IpAddr>().is_ok() => host,
+        Some(_) => return None,
+        None => first,
+    };
+
+    (is_host(host) && host != "localhost").then_some(host)
+}
+
+/// Check whether text looks like a host name
+fn is_host(text: &str) -> bool {
+    text.contains('.')
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = "\
+# Hosts file entries
+0.0.0.0 tracker.example
+127.0.0.1 ads.example.org # inline comment
+127.0.0.1 localhost
+! EasyList entries
+||metrics.example.net^
+||example.com/ads/*
+@@||allowed.example^
+plain-domain.example
+";
+
+    #[test]
+    fn test_parse_rules() {
+        let blocklist = Blocklist::from_rules(RULES);
+        assert_eq!(blocklist.len(), 4);
+        assert!(!blocklist.should_block("http://localhost/"));
+        assert!(!blocklist.should_block("https://example.com/ads/banner.png"));
+        assert!(!blocklist.should_block("https://allowed.example/"));
+    }
+
+    #[test]
+    fn test_exact_and_subdomain_blocks() {
+        let blocklist = Blocklist::from_rules(RULES);
+
+        assert!(blocklist.should_block("https://tracker.example/pixel.gif"));
+        assert!(blocklist.should_block("https://cdn.tracker.example/script.js"));
+        assert!(blocklist.should_block("https://a.b.metrics.example.net/"));
+        assert!(blocklist.should_block("http://ADS.example.org:8080/"));
+        assert!(blocklist.should_block("https://plain-domain.example/"));
+    }
+
+    #[test]
+    fn test_allowed_hosts() {
+        let blocklist = Blocklist::from_rules(RULES);
+
+        assert!(!blocklist.should_block("https://example.org/"));
+        assert!(!blocklist.should_block("https://nottracker.example/"));
+        assert!(!blocklist.should_block("https://tracker.example.com/"));
+        assert!(!blocklist.should_block("about:blank"));
+        assert!(!blocklist.should_block("not a url"));
+    }
+
+    #[test]
+    fn test_bundled_list() {
+        let blocklist = Blocklist::bundled();
+        assert!(blocklist.should_block("https://www.google-analytics.com/collect"));
+        assert!(!blocklist.should_block("https://www.google.com/"));
+    }
+
+    #[test]
+    fn test_blocked_counts_per_tab() {
+        let blocklist = Blocklist::new();
+        blocklist.record_blocked("tab-1");
+        blocklist.record_blocked("tab-1");
+        blocklist.record_blocked("tab-2");
+
+        assert_eq!(blocklist.blocked_count("tab-1"), 2);
+        assert_eq!(blocklist.blocked_count("tab-2"), 1);
+        assert_eq!(blocklist.blocked_count("tab-3"), 0);
+
+        blocklist.reset_blocked_count("tab-1");
+        assert_eq!(blocklist.blocked_count("tab-1"), 0);
+    }
+}
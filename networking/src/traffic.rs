@@ -0,0 +1,193 @@
+//! Network traffic accounting
+
+use crate::speedtest::NetworkStats;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Window the current transfer rate is averaged over
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+
+/// Traffic within this long of the newest sample is added to it, bounding
+/// the number of samples kept
+const SAMPLE_BUCKET: Duration = Duration::from_millis(100);
+
+/// Bytes transferred in one sample bucket
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    /// When the bucket started
+    at: Instant,
+    downloaded: u64,
+    uploaded: u64,
+}
+
+/// Byte totals shared by every client that sends traffic
+#[derive(Debug, Default)]
+pub struct TrafficCounter {
+    /// Total response body bytes read
+    downloaded: AtomicU64,
+    /// Total request body bytes written
+    uploaded: AtomicU64,
+    /// Traffic within the rate window, oldest first
+    samples: Mutex<VecDeque<Sample>>,
+}
+
+impl TrafficCounter {
+    /// Create a counter with zero totals
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count bytes read from a response body
+    pub fn record_download(&self, bytes: u64) {
+        self.record_at(Instant::now(), bytes, 0);
+    }
+
+    /// Count bytes written in a request body
+    pub fn record_upload(&self, bytes: u64) {
+        self.record_at(Instant::now(), 0, bytes);
+    }
+
+    /// Count traffic at a specific time
+    fn record_at(&self, at: Instant, downloaded: u64, uploaded: u64) {
+        self.downloaded.fetch_add(downloaded, Ordering::Relaxed);
+        self.uploaded.fetch_add(uploaded, Ordering::Relaxed);
+
+        let mut samples = self.lock_samples();
+        match samples.back_mut() {
+            Some(last) if at.saturating_duration_since(last.at) < SAMPLE_BUCKET => {
+                last.downloaded += downloaded;
+                last.uploaded += uploaded;
+            }
+            _ => samples.push_back(Sample {
+                at,
+                downloaded,
+                uploaded,
+            }),
+        }
+        trim_samples(&mut samples, at);
+    }
+
+    /// Lock the samples
+    ///
+    /// A poisoned sample list is still usable; samples are only appended,
+    /// added to, and trimmed.
+    fn lock_samples(&self) -> std::sync::MutexGuard<'_, VecDeque<Sample>> {
+        self.samples.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Get the total bytes downloaded
+    pub fn total_downloaded(&self) -> u64 {
+        self.downloaded.load(Ordering::Relaxed)
+    }
+
+    /// Get the total bytes uploaded
+    pub fn total_uploaded(&self) -> u64 {
+        self.uploaded.load(Ordering::Relaxed)
+    }
+
+    /// Get totals and the transfer rate over the last few seconds
+    pub fn stats(&self) -> NetworkStats {
+        self.stats_at(Instant::now())
+    }
+
+    /// Get totals and the transfer rate as of a specific time
+    ///
+    /// The rate is the traffic recorded within the rate window before `now`,
+    /// averaged over the window, however often it is queried.
+    pub fn stats_at(&self, now: Instant) -> NetworkStats {
+        let mut samples = self.lock_samples();
+        trim_samples(&mut samples, now);
+        let (downloaded, uploaded) = samples
+            .iter()
+            .filter(|sample| sample.at <= now)
+            .fold((0, 0), |(down, up), sample| {
+                (down + sample.downloaded, up + sample.uploaded)
+            });
+        let secs = RATE_WINDOW.as_secs_f64();
+
+        NetworkStats {
+            download_kbps: downloaded as f64 / 1024.0 / secs,
+            upload_kbps: uploaded as f64 / 1024.0 / secs,
+            total_downloaded: self.total_downloaded(),
+            total_uploaded: self.total_uploaded(),
+        }
+    }
+}
+
+/// Drop samples that ended before the rate window leading up to `now`
+fn trim_samples(samples: &mut VecDeque<Sample>, now: Instant) {
+    while samples
+        .front()
+        .is_some_and(|sample| now.saturating_duration_since(sample.at) > RATE_WINDOW)
+    {
+        samples.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_totals() {
+        let counter = TrafficCounter::new();
+        counter.record_download(1_000);
+        counter.record_download(500);
+        counter.record_upload(200);
+
+        let stats = counter.stats();
+        assert_eq!(stats.total_downloaded, 1_500);
+        assert_eq!(stats.total_uploaded, 200);
+    }
+
+    #[test]
+    fn test_rolling_rate() {
+        let counter = TrafficCounter::new();
+        let start = Instant::now();
+        assert_eq!(counter.stats_at(start).download_kbps, 0.0);
+
+        counter.record_at(start, 20 * 1024, 0);
+        counter.record_at(start + Duration::from_millis(50), 5 * 1024, 10 * 1024);
+        counter.record_at(start + Duration::from_secs(2), 0, 5 * 1024);
+        let stats = counter.stats_at(start + Duration::from_secs(3));
+        assert_eq!(stats.download_kbps, 5.0);
+        assert_eq!(stats.upload_kbps, 3.0);
+
+        // Once the window has passed with no traffic the rate drops back to zero
+        let idle = counter.stats_at(start + Duration::from_secs(20));
+        assert!(idle.download_kbps >= 0.0);
+        assert_eq!(idle.download_kbps, 0.0);
+        assert_eq!(idle.total_downloaded, 25 * 1024);
+    }
+
+    #[test]
+    fn test_rate_does_not_depend_on_polling() {
+        let counter = TrafficCounter::new();
+        let start = Instant::now();
+
+        // Traffic a while after the last query still shows up in the rate
+        counter.stats_at(start);
+        counter.record_at(start + Duration::from_secs(30), 10 * 1024, 0);
+        let stats = counter.stats_at(start + Duration::from_secs(31));
+        assert_eq!(stats.download_kbps, 2.0);
+    }
+
+    #[test]
+    fn test_samples_are_bucketed() {
+        let counter = TrafficCounter::new();
+        let start = Instant::now();
+        for ms in 0..1_000 {
+            counter.record_at(start + Duration::from_millis(ms), 1, 0);
+        }
+
+        assert_eq!(counter.lock_samples().len(), 10);
+        assert_eq!(
+            counter
+                .stats_at(start + Duration::from_secs(1))
+                .total_downloaded,
+            1_000
+        );
+    }
+}
@@ -1,9 +1,20 @@
 //! VPN management module for per-browser VPN support
 
+use crate::client::HttpClient;
+use crate::traffic::TrafficCounter;
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::net::IpAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Endpoint that echoes the caller's public IP address as plain text
+const IP_ECHO_URL: &str = "https://api.ipify.org";
+
+/// How long to wait for the IP echo endpoint
+const PUBLIC_IP_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// VPN connection status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -30,6 +41,28 @@ impl VpnStatus {
     }
 }
 
+/// Outcome of testing whether browser traffic goes through the VPN
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionTest {
+    /// Requests leave from a different public IP than direct ones
+    Routed,
+    /// Requests leave from the same public IP, or the VPN is not connected
+    NotRouted,
+    /// The connection cannot be tested from the browser
+    NotSupported,
+}
+
+impl ConnectionTest {
+    /// Get the display name for this outcome
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Routed => "Working",
+            Self::NotRouted => "Not working",
+            Self::NotSupported => "Not supported",
+        }
+    }
+}
+
 /// VPN configuration type
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum VpnConfig {
@@ -70,6 +103,35 @@ pub enum VpnConfig {
     },
 }
 
+impl VpnConfig {
+    /// Get the password, if any
+    pub fn password(&self) -> Option<&str> {
+        match self {
+            Self::OpenVpn { password, .. }
+            | Self::Proxy { password, .. }
+            | Self::Socks { password, .. } => password.as_deref(),
+        }
+    }
+
+    /// Replace the password
+    pub fn set_password(&mut self, new_password: Option<String>) {
+        match self {
+            Self::OpenVpn { password, .. }
+            | Self::Proxy { password, .. }
+            | Self::Socks { password, .. } => *password = new_password,
+        }
+    }
+}
+
+/// Named VPN configurations as written to disk
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SavedVpnConfigs {
+    /// Configurations by name, with passwords removed
+    configs: BTreeMap<String, VpnConfig>,
+    /// Name of the active configuration
+    active: Option<String>,
+}
+
 /// Proxy protocol
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ProxyProtocol {
@@ -106,14 +168,27 @@ pub struct VpnStats {
     pub public_ip: Option<IpAddr>,
 }
 
+/// Callback invoked with the new VPN status
+pub type StatusListener = Box<dyn Fn(VpnStatus) + Send + Sync>;
+
 /// VPN manager for handling VPN connections
 pub struct VpnManager {
     /// Current VPN configuration
     config: Option<VpnConfig>,
+    /// Saved configurations by name
+    named_configs: BTreeMap<String, VpnConfig>,
+    /// Name of the active saved configuration
+    active_name: Option<String>,
     /// Current VPN status
     status: VpnStatus,
+    /// Callbacks notified when the status changes
+    status_listeners: Vec<StatusListener>,
     /// Connection statistics
     stats: VpnStats,
+    /// Counter the connection's traffic is read from
+    traffic: Option<Arc<TrafficCounter>>,
+    /// When the connection came up, with the byte totals at that time
+    connected_at: Option<(Instant, u64, u64)>,
     /// Whether VPN is enabled globally
     enabled: bool,
 }
@@ -123,12 +198,23 @@ impl VpnManager {
     pub fn new() -> Self {
         Self {
             config: None,
+            named_configs: BTreeMap::new(),
+            active_name: None,
             status: VpnStatus::Disconnected,
+            status_listeners: Vec::new(),
             stats: VpnStats::default(),
+            traffic: None,
+            connected_at: None,
             enabled: false,
         }
     }
 
+    /// Read connection traffic from a shared counter
+    pub fn with_traffic_counter(mut self, traffic: Arc<TrafficCounter>) -> Self {
+        self.traffic = Some(traffic);
+        self
+    }
+
     /// Check if VPN is enabled
     pub fn is_enabled(&self) -> bool {
         self.enabled
@@ -154,22 +240,150 @@ impl VpnManager {
         self.status
     }
 
+    /// Register a callback invoked synchronously whenever the status changes
+    pub fn on_status_change(&mut self, listener: StatusListener) {
+        self.status_listeners.push(listener);
+    }
+
+    /// Update the status, notifying listeners if it changed
+    fn set_status(&mut self, status: VpnStatus) {
+        if self.status == status {
+            return;
+        }
+
+        self.status = status;
+        for listener in &self.status_listeners {
+            listener(status);
+        }
+    }
+
     /// Get the current VPN configuration
     pub fn config(&self) -> Option<&VpnConfig> {
         self.config.as_ref()
     }
 
     /// Get VPN statistics
-    pub fn stats(&self) -> &VpnStats {
-        &self.stats
+    ///
+    /// With a traffic counter, transfer figures cover the traffic since the
+    /// connection came up.
+    pub fn stats(&self) -> VpnStats {
+        let mut stats = self.stats.clone();
+        if let (Some(traffic), Some((since, downloaded, uploaded))) =
+            (&self.traffic, self.connected_at)
+        {
+            let current = traffic.stats();
+            stats.download_speed = (current.download_kbps * 1024.0) as u64;
+            stats.upload_speed = (current.upload_kbps * 1024.0) as u64;
+            stats.bytes_downloaded = current.total_downloaded.saturating_sub(downloaded);
+            stats.bytes_uploaded = current.total_uploaded.saturating_sub(uploaded);
+            stats.duration = since.elapsed().as_secs();
+        }
+        stats
     }
 
     /// Set VPN configuration
     pub fn set_config(&mut self, config: VpnConfig) {
+        self.active_name = None;
         self.config = Some(config);
         tracing::info!("VPN configuration updated");
     }
 
+    /// Save a named configuration, replacing any with the same name
+    pub fn add_config(&mut self, name: &str, config: VpnConfig) {
+        if self.active_name.as_deref() == Some(name) {
+            self.config = Some(config.clone());
+        }
+        self.named_configs.insert(name.to_string(), config);
+        tracing::info!("Saved VPN configuration {:?}", name);
+    }
+
+    /// Remove a named configuration, returning it if it existed
+    ///
+    /// Removing the active configuration also clears the current configuration.
+    pub fn remove_config(&mut self, name: &str) -> Option<VpnConfig> {
+        let removed = self.named_configs.remove(name)?;
+        if self.active_name.as_deref() == Some(name) {
+            self.active_name = None;
+            self.config = None;
+        }
+        tracing::info!("Removed VPN configuration {:?}", name);
+        Some(removed)
+    }
+
+    /// List the names of saved configurations in alphabetical order
+    pub fn list_configs(&self) -> Vec<&str> {
+        self.named_configs.keys().map(String::as_str).collect()
+    }
+
+    /// Make a named configuration the one `connect` uses
+    pub fn activate(&mut self, name: &str) -> Result<()> {
+        let config = self
+            .named_configs
+            .get(name)
+            .ok_or_else(|| anyhow!("No VPN configuration named {:?}", name))?;
+
+        self.config = Some(config.clone());
+        self.active_name = Some(name.to_string());
+        tracing::info!("Activated VPN configuration {:?}", name);
+        Ok(())
+    }
+
+    /// Get the name of the active saved configuration
+    pub fn active_config_name(&self) -> Option<&str> {
+        self.active_name.as_deref()
+    }
+
+    /// Set the password of a named configuration
+    ///
+    /// Saved files never contain passwords, so callers keep them in a secure
+    /// store and restore them with this after loading.
+    pub fn set_config_password(&mut self, name: &str, password: Option<String>) -> Result<()> {
+        let config = self
+            .named_configs
+            .get_mut(name)
+            .ok_or_else(|| anyhow!("No VPN configuration named {:?}", name))?;
+
+        config.set_password(password);
+        if self.active_name.as_deref() == Some(name) {
+            self.config = Some(config.clone());
+        }
+        Ok(())
+    }
+
+    /// Save named configurations to a file, without passwords
+    pub fn save_configs(&self, path: &Path) -> Result<()> {
+        let mut configs = self.named_configs.clone();
+        for config in configs.values_mut() {
+            config.set_password(None);
+        }
+
+        let saved = SavedVpnConfigs {
+            configs,
+            active: self.active_name.clone(),
+        };
+        let json = serde_json::to_string_pretty(&saved)?;
+        std::fs::write(path, json)?;
+        tracing::debug!("Saved VPN configurations to {:?}", path);
+        Ok(())
+    }
+
+    /// Load named configurations from a file, activating the saved active one
+    pub fn load_configs(&mut self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let json = std::fs::read_to_string(path)?;
+        let saved: SavedVpnConfigs = serde_json::from_str(&json)?;
+        self.named_configs = saved.configs;
+        self.active_name = None;
+        if let Some(name) = saved.active {
+            self.activate(&name)?;
+        }
+        tracing::debug!("Loaded VPN configurations from {:?}", path);
+        Ok(())
+    }
+
     /// Load OpenVPN configuration from .ovpn file
     pub fn load_ovpn_config(&mut self, path: PathBuf) -> Result<()> {
         // Validate file exists and has .ovpn extension
@@ -181,6 +395,7 @@ impl VpnManager {
             return Err(anyhow!("File must have .ovpn extension"));
         }
 
+        self.active_name = None;
         self.config = Some(VpnConfig::OpenVpn {
             config_path: path.clone(),
             username: None,
@@ -200,6 +415,7 @@ impl VpnManager {
         username: Option<String>,
         password: Option<String>,
     ) {
+        self.active_name = None;
         self.config = Some(VpnConfig::Proxy {
             protocol,
             host: host.clone(),
@@ -223,6 +439,7 @@ impl VpnManager {
             return Err(anyhow!("SOCKS version must be 4 or 5"));
         }
 
+        self.active_name = None;
         self.config = Some(VpnConfig::Socks {
             version,
             host: host.clone(),
@@ -244,7 +461,7 @@ impl VpnManager {
             return Err(anyhow!("VPN already connected"));
         }
 
-        self.status = VpnStatus::Connecting;
+        self.set_status(VpnStatus::Connecting);
         tracing::info!("Connecting to VPN...");
 
         // Note: In a full implementation, this would:
@@ -255,8 +472,15 @@ impl VpnManager {
 
         tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
 
-        self.status = VpnStatus::Connected;
+        self.set_status(VpnStatus::Connected);
         self.stats = VpnStats::default();
+        self.connected_at = self.traffic.as_ref().map(|traffic| {
+            (
+                Instant::now(),
+                traffic.total_downloaded(),
+                traffic.total_uploaded(),
+            )
+        });
         tracing::info!("VPN connected successfully");
 
         Ok(())
@@ -269,8 +493,9 @@ impl VpnManager {
             return;
         }
 
-        self.status = VpnStatus::Disconnected;
+        self.set_status(VpnStatus::Disconnected);
         self.stats = VpnStats::default();
+        self.connected_at = None;
         tracing::info!("VPN disconnected");
     }
 
@@ -279,25 +504,83 @@ impl VpnManager {
         self.stats = stats;
     }
 
-    /// Get the current public IP address
+    /// Get the current public IP address, through the VPN when connected
     pub async fn get_public_ip(&self) -> Result<IpAddr> {
-        // Note: In a full implementation, this would query an IP detection service
-        // For now, return a placeholder
-        Err(anyhow!("Public IP detection not yet implemented"))
+        fetch_public_ip(&self.client()?).await
     }
 
     /// Test VPN connection
-    pub async fn test_connection(&self) -> Result<bool> {
+    ///
+    /// Fetches the public IP with and without the VPN and reports whether
+    /// they differ. OpenVPN tunnels the whole system, so the direct request
+    /// goes through it too and the connection cannot be tested this way.
+    pub async fn test_connection(&self) -> Result<ConnectionTest> {
         if self.status != VpnStatus::Connected {
-            return Ok(false);
+            return Ok(ConnectionTest::NotRouted);
+        }
+        if matches!(self.config, Some(VpnConfig::OpenVpn { .. })) {
+            return Ok(ConnectionTest::NotSupported);
         }
 
-        // Note: In a full implementation, this would:
-        // 1. Make a test request through the VPN
-        // 2. Verify the request succeeds
-        // 3. Check that IP has changed
-        Ok(true)
+        let direct_ip = fetch_public_ip(&HttpClient::new()?).await?;
+        let vpn_ip = self.get_public_ip().await?;
+        tracing::info!("Public IP without VPN: {}, with VPN: {}", direct_ip, vpn_ip);
+
+        Ok(if direct_ip != vpn_ip {
+            ConnectionTest::Routed
+        } else {
+            ConnectionTest::NotRouted
+        })
+    }
+
+    /// Get an HTTP client that routes through the VPN when connected
+    ///
+    /// OpenVPN tunnels the whole system, so it needs no client-side proxy.
+    fn client(&self) -> Result<HttpClient> {
+        let client = match &self.config {
+            Some(config @ (VpnConfig::Proxy { .. } | VpnConfig::Socks { .. }))
+                if self.status == VpnStatus::Connected =>
+            {
+                HttpClient::with_proxy(config)?
+            }
+            _ => HttpClient::new()?,
+        };
+        Ok(match &self.traffic {
+            Some(traffic) => client.with_traffic_counter(Arc::clone(traffic)),
+            None => client,
+        })
+    }
+}
+
+/// Query the IP echo endpoint with a client
+async fn fetch_public_ip(client: &HttpClient) -> Result<IpAddr> {
+    let response = tokio::time::timeout(PUBLIC_IP_TIMEOUT, client.get(IP_ECHO_URL))
+        .await
+        .map_err(|_| anyhow!("Timed out fetching public IP from {}", IP_ECHO_URL))??;
+    if !response.is_success() {
+        return Err(anyhow!(
+            "Public IP lookup failed with status {}",
+            response.status()
+        ));
     }
+
+    parse_public_ip(&response.body_string()?)
+}
+
+/// Parse an IP echo response body
+///
+/// Accepts a bare address, as returned by ipify, or Cloudflare's
+/// `/cdn-cgi/trace` format with an `ip=` line.
+fn parse_public_ip(body: &str) -> Result<IpAddr> {
+    let address = body
+        .lines()
+        .find_map(|line| line.strip_prefix("ip="))
+        .unwrap_or(body)
+        .trim();
+
+    address
+        .parse()
+        .map_err(|_| anyhow!("Malformed public IP response: {:?}", address))
 }
 
 impl Default for VpnManager {
@@ -309,6 +592,7 @@ impl Default for VpnManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn test_vpn_manager_creation() {
@@ -360,6 +644,170 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_public_ip() {
+        assert_eq!(
+            parse_public_ip("203.0.113.7\n").unwrap(),
+            "203.0.113.7".parse::<IpAddr>().unwrap()
+        );
+        assert!(parse_public_ip("2001:db8::1").unwrap().is_ipv6());
+
+        let trace = "fl=29f1\nh=www.cloudflare.com\nip=198.51.100.4\nts=1700000000.123\n";
+        assert_eq!(
+            parse_public_ip(trace).unwrap(),
+            "198.51.100.4".parse::<IpAddr>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_public_ip_malformed() {
+        assert!(parse_public_ip("").is_err());
+        assert!(parse_public_ip("<html>Too Many Requests</html>").is_err());
+        assert!(parse_public_ip("ip=not-an-address").is_err());
+        assert!(parse_public_ip("256.1.1.1").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_connection_requires_connected_vpn() {
+        let manager = VpnManager::new();
+        assert_eq!(
+            manager.test_connection().await.unwrap(),
+            ConnectionTest::NotRouted
+        );
+    }
+
+    #[tokio::test]
+    async fn test_openvpn_connection_is_not_tested() {
+        let mut manager = VpnManager::new();
+        manager.set_config(VpnConfig::OpenVpn {
+            config_path: PathBuf::from("/etc/openvpn/home.ovpn"),
+            username: None,
+            password: None,
+        });
+        manager.connect().await.unwrap();
+
+        assert_eq!(
+            manager.test_connection().await.unwrap(),
+            ConnectionTest::NotSupported
+        );
+    }
+
+    /// A proxy configuration with a password
+    fn work_proxy() -> VpnConfig {
+        VpnConfig::Proxy {
+            protocol: ProxyProtocol::Http,
+            host: "proxy.work.example".to_string(),
+            port: 3128,
+            username: Some("alice".to_string()),
+            password: Some("secret".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_named_configs() {
+        let mut manager = VpnManager::new();
+        manager.add_config("work", work_proxy());
+        manager.add_config(
+            "home",
+            VpnConfig::OpenVpn {
+                config_path: PathBuf::from("/etc/openvpn/home.ovpn"),
+                username: None,
+                password: None,
+            },
+        );
+        assert_eq!(manager.list_configs(), vec!["home", "work"]);
+        assert!(manager.config().is_none());
+
+        manager.activate("work").unwrap();
+        assert_eq!(manager.active_config_name(), Some("work"));
+        assert!(matches!(manager.config(), Some(VpnConfig::Proxy { .. })));
+
+        assert!(manager.remove_config("home").is_some());
+        assert_eq!(manager.active_config_name(), Some("work"));
+        assert!(manager.remove_config("work").is_some());
+        assert_eq!(manager.active_config_name(), None);
+        assert!(manager.config().is_none());
+        assert!(manager.remove_config("work").is_none());
+    }
+
+    #[test]
+    fn test_activate_missing_config_errors() {
+        let mut manager = VpnManager::new();
+        manager.add_config("work", work_proxy());
+
+        assert!(manager.activate("personal").is_err());
+        assert_eq!(manager.active_config_name(), None);
+        assert!(manager.config().is_none());
+    }
+
+    #[test]
+    fn test_named_configs_persist_without_passwords() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("vpn.json");
+
+        let mut manager = VpnManager::new();
+        manager.add_config("work", work_proxy());
+        manager.activate("work").unwrap();
+        manager.save_configs(&path).unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("secret"));
+
+        let mut reloaded = VpnManager::new();
+        reloaded.load_configs(&path).unwrap();
+        assert_eq!(reloaded.list_configs(), vec!["work"]);
+        assert_eq!(reloaded.active_config_name(), Some("work"));
+        assert_eq!(reloaded.config().unwrap().password(), None);
+
+        reloaded
+            .set_config_password("work", Some("secret".to_string()))
+            .unwrap();
+        assert_eq!(reloaded.config().unwrap().password(), Some("secret"));
+    }
+
+    #[tokio::test]
+    async fn test_status_listeners_observe_transitions() {
+        let observed = Arc::new(Mutex::new(Vec::new()));
+        let mut manager = VpnManager::new();
+        let listener_observed = Arc::clone(&observed);
+        manager.on_status_change(Box::new(move |status| {
+            listener_observed.lock().unwrap().push(status);
+        }));
+
+        manager.add_config("work", work_proxy());
+        manager.activate("work").unwrap();
+        manager.connect().await.unwrap();
+        manager.disable();
+
+        assert_eq!(
+            *observed.lock().unwrap(),
+            vec![
+                VpnStatus::Connecting,
+                VpnStatus::Connected,
+                VpnStatus::Disconnected,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stats_count_traffic_since_connect() {
+        let traffic = Arc::new(TrafficCounter::new());
+        let mut manager = VpnManager::new().with_traffic_counter(Arc::clone(&traffic));
+        manager.add_config("work", work_proxy());
+        manager.activate("work").unwrap();
+
+        traffic.record_download(1000);
+        manager.connect().await.unwrap();
+        traffic.record_download(4096);
+        traffic.record_upload(512);
+
+        let stats = manager.stats();
+        assert_eq!(stats.bytes_downloaded, 4096);
+        assert_eq!(stats.bytes_uploaded, 512);
+        assert!(stats.download_speed > 0);
+
+        manager.disconnect();
+        assert_eq!(manager.stats().bytes_downloaded, 0);
+    }
+
     #[tokio::test]
     async fn test_vpn_connect_without_config() {
         let mut manager = VpnManager::new();
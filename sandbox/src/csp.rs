@@ -0,0 +1,325 @@
+//! Content Security Policy parsing and enforcement
+//!
+//! Supports the `default-src`, `script-src`, `img-src`, and `connect-src`
+//! directives with `'self'`, `'none'`, `*`, scheme sources (`https:`), and
+//! host sources (`https://*.example.com:443/path`). Other directives and
+//! keywords are ignored.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use url::Url;
+
+/// Kind of resource a page loads, deciding which directive applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceType {
+    /// Scripts, governed by `script-src`
+    Script,
+    /// Images, governed by `img-src`
+    Image,
+    /// Fetch, XHR, and WebSocket connections, governed by `connect-src`
+    Connect,
+    /// Anything else, governed by `default-src`
+    Other,
+}
+
+impl ResourceType {
+    /// Get the directive governing this resource type
+    pub fn directive(&self) -> &'static str {
+        match self {
+            ResourceType::Script => "script-src",
+            ResourceType::Image => "img-src",
+            ResourceType::Connect => "connect-src",
+            ResourceType::Other => "default-src",
+        }
+    }
+}
+
+/// A single source expression in a directive
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Source {
+    /// `'none'`
+    None,
+    /// `'self'`
+    SelfOrigin,
+    /// `*`
+    Wildcard,
+    /// A scheme such as `https:`
+    Scheme(String),
+    /// A host with optional scheme, port, and path
+    Host {
+        scheme: Option<String>,
+        host: String,
+        port: Option<String>,
+        path: Option<String>,
+    },
+    /// A keyword or hash that does not match URLs, like `'unsafe-inline'`
+    Other,
+}
+
+impl Source {
+    /// Parse a source expression
+    fn parse(expression: &str) -> Self {
+        let lower = expression.to_ascii_lowercase();
+        match lower.as_str() {
+            "'none'" => return Source::None,
+            "'self'" => return Source::SelfOrigin,
+            "*" => return Source::Wildcard,
+            _ if lower.starts_with('\'') => return Source::Other,
+            _ => {}
+        }
+
+        if let Some(scheme) = lower.strip_suffix(':') {
+            if is_scheme(scheme) {
+                return Source::Scheme(scheme.to_string());
+            }
+        }
+
+        let (scheme, rest) = match lower.split_once("://") {
+            Some((scheme, rest)) if is_scheme(scheme) => (Some(scheme.to_string()), rest),
+            Some(_) => return Source::Other,
+            None => (None, lower.as_str()),
+        };
+        let (host_port, path) = match rest.find('/') {
+            Some(index) => (&rest[..index], Some(rest[index..].to_string())),
+            None => (rest, None),
+        };
+        let (host, port) = match host_port.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port.to_string())),
+            None => (host_port, None),
+        };
+        if host.is_empty() {
+            return Source::Other;
+        }
+
+        Source::Host {
+            scheme,
+            host: host.to_string(),
+            port,
+            path,
+        }
+    }
+
+    /// Check whether a URL matches this source on a page with `origin`
+    fn matches(&self, url: &Url, origin: &Url) -> bool {
+        match self {
+            Source::None | Source::Other => false,
+            Source::SelfOrigin => url.origin() == origin.origin(),
+            Source::Wildcard => !matches!(url.scheme(), "data" | "blob" | "filesystem"),
+            Source::Scheme(scheme) => scheme_matches(scheme, url.scheme()),
+            Source::Host {
+                scheme,
+                host,
+                port,
+                path,
+            } => {
+                let scheme_ok = match scheme {
+                    Some(scheme) => scheme_matches(scheme, url.scheme()),
+                    None => scheme_matches(origin.scheme(), url.scheme()),
+                };
+                scheme_ok
+                    && url.host_str().is_some_and(|h| host_matches(host, h))
+                    && port_matches(port.as_deref(), url)
+                    && path.as_deref().is_none_or(|p| path_matches(p, url.path()))
+            }
+        }
+    }
+}
+
+/// A parsed Content-Security-Policy for one page
+#[derive(Debug, Clone)]
+pub struct Csp {
+    /// Origin of the protected page, used for `'self'`
+    origin: Url,
+    /// Source lists by directive name
+    directives: HashMap<String, Vec<Source>>,
+}
+
+impl Csp {
+    /// Parse a Content-Security-Policy header sent with the page at `page_url`
+    ///
+    /// Parsing is lenient like browsers: unknown directives are ignored and
+    /// only the first occurrence of a repeated directive counts.
+    pub fn parse(header: &str, page_url: &str) -> Result<Self> {
+        let origin = Url::parse(page_url)?;
+        let mut directives = HashMap::new();
+
+        for directive in header.split(';') {
+            let mut tokens = directive.split_ascii_whitespace();
+            let Some(name) = tokens.next() else {
+                continue;
+            };
+            let name = name.to_ascii_lowercase();
+            if directives.contains_key(&name) {
+                tracing::debug!("Ignoring repeated CSP directive {}", name);
+                continue;
+            }
+            directives.insert(name, tokens.map(Source::parse).collect());
+        }
+
+        Ok(Self { origin, directives })
+    }
+
+    /// Check whether the policy allows loading a resource
+    ///
+    /// Falls back to `default-src` when the resource type's directive is
+    /// missing, and allows everything when neither is present.
+    pub fn allows(&self, resource_url: &str, resource_type: ResourceType) -> bool {
+        let sources = self
+            .directives
+            .get(resource_type.directive())
+            .or_else(|| self.directives.get("default-src"));
+        let Some(sources) = sources else {
+            return true;
+        };
+
+        let Ok(url) = Url::parse(resource_url) else {
+            return false;
+        };
+        sources
+            .iter()
+            .any(|source| source.matches(&url, &self.origin))
+    }
+}
+
+/// Check whether a string is a valid URL scheme
+fn is_scheme(scheme: &str) -> bool {
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Check a URL scheme against a source scheme, allowing secure upgrades
+fn scheme_matches(source: &str, scheme: &str) -> bool {
+    source == scheme
+        || matches!(
+            (source, scheme),
+            ("http", "https") | ("ws", "wss" | "http" | "https") | ("wss", "https")
+        )
+}
+
+/// Check a URL host against a source host, which may start with `*.`
+///
+/// Unlike other host patterns, a CSP wildcard does not match the bare domain.
+fn host_matches(source: &str, host: &str) -> bool {
+    horizon_url::host_matches(source, host) && source.strip_prefix("*.") != Some(host)
+}
+
+/// Check a URL port against a source port, `*`, or the scheme default
+fn port_matches(source: Option<&str>, url: &Url) -> bool {
+    match source {
+        Some("*") => true,
+        Some(port) => port.parse().ok() == url.port_or_known_default(),
+        None => url.port().is_none(),
+    }
+}
+
+/// Check a URL path against a source path, which matches a prefix when it
+/// ends in `/`
+fn path_matches(source: &str, path: &str) -> bool {
+    if source.ends_with('/') {
+        path.starts_with(source)
+    } else {
+        path == source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "https://example.com/app/index.html";
+
+    #[test]
+    fn test_none_blocks_everything() {
+        let csp = Csp::parse(
+            "default-src 'self'; object-src 'none'; img-src 'none'",
+            PAGE,
+        )
+        .unwrap();
+        assert!(!csp.allows("https://example.com/logo.png", ResourceType::Image));
+        assert!(!csp.allows("data:image/png;base64,AAAA", ResourceType::Image));
+        assert!(csp.allows("https://example.com/app.js", ResourceType::Script));
+    }
+
+    #[test]
+    fn test_self_and_default_fallback() {
+        // GitHub-style policy
+        let csp = Csp::parse(
+            "default-src 'none'; script-src 'self' github.githubassets.com; \
+             connect-src 'self' api.github.com wss://alive.github.com",
+            PAGE,
+        )
+        .unwrap();
+
+        assert!(csp.allows("https://example.com/main.js", ResourceType::Script));
+        assert!(csp.allows(
+            "https://github.githubassets.com/app.js",
+            ResourceType::Script
+        ));
+        assert!(!csp.allows("https://evil.com/x.js", ResourceType::Script));
+        assert!(!csp.allows("http://example.com/main.js", ResourceType::Script));
+
+        assert!(csp.allows("https://api.github.com/user", ResourceType::Connect));
+        assert!(csp.allows("wss://alive.github.com/socket", ResourceType::Connect));
+        assert!(!csp.allows("ws://alive.github.com/socket", ResourceType::Connect));
+
+        // No img-src, so default-src 'none' applies
+        assert!(!csp.allows("https://example.com/logo.png", ResourceType::Image));
+    }
+
+    #[test]
+    fn test_scheme_and_wildcard_sources() {
+        let csp = Csp::parse(
+            "img-src * data:; script-src https: 'unsafe-inline'; connect-src *.example.org:*",
+            PAGE,
+        )
+        .unwrap();
+
+        assert!(csp.allows("https://cdn.example.net/a.png", ResourceType::Image));
+        assert!(csp.allows("data:image/png;base64,AAAA", ResourceType::Image));
+        assert!(!csp.allows("blob:https://example.com/123", ResourceType::Image));
+
+        assert!(csp.allows("https://anywhere.com/lib.js", ResourceType::Script));
+        assert!(!csp.allows("http://anywhere.com/lib.js", ResourceType::Script));
+
+        assert!(csp.allows("https://api.example.org:8443/", ResourceType::Connect));
+        assert!(!csp.allows("https://example.org/", ResourceType::Connect));
+        assert!(!csp.allows("http://api.example.org/", ResourceType::Connect));
+
+        // No default-src, so other resources are unrestricted
+        assert!(csp.allows("https://fonts.example.com/a.woff", ResourceType::Other));
+    }
+
+    #[test]
+    fn test_host_source_ports_and_paths() {
+        let csp = Csp::parse(
+            "script-src https://cdn.example.com/js/ https://static.example.com:8443/app.js",
+            PAGE,
+        )
+        .unwrap();
+
+        assert!(csp.allows("https://cdn.example.com/js/lib.js", ResourceType::Script));
+        assert!(!csp.allows("https://cdn.example.com/css/a.js", ResourceType::Script));
+        assert!(!csp.allows(
+            "https://cdn.example.com:444/js/lib.js",
+            ResourceType::Script
+        ));
+        assert!(csp.allows(
+            "https://static.example.com:8443/app.js",
+            ResourceType::Script
+        ));
+        assert!(!csp.allows("https://static.example.com/app.js", ResourceType::Script));
+    }
+
+    #[test]
+    fn test_parse_is_lenient() {
+        let csp = Csp::parse(
+            " ; SCRIPT-SRC 'SELF' ; script-src *; bogus-directive x",
+            PAGE,
+        )
+        .unwrap();
+        assert!(csp.allows("https://example.com/a.js", ResourceType::Script));
+        assert!(!csp.allows("https://other.com/a.js", ResourceType::Script));
+        assert!(Csp::parse("default-src 'self'", "not a url").is_err());
+    }
+}
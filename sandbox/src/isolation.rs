@@ -1,6 +1,14 @@
 //! Process isolation module
+//!
+//! On Linux, `Basic` and `Full` isolation install a seccomp-bpf syscall
+//! allowlist, and `Full` also restricts filesystem access with Landlock.
+//! Other platforms are not isolated yet.
+
+#[cfg(target_os = "linux")]
+mod linux;
 
 use anyhow::Result;
+use std::path::PathBuf;
 
 /// Process isolation level
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -13,15 +21,191 @@ pub enum IsolationLevel {
     Full,
 }
 
+impl IsolationLevel {
+    /// Parse a level name: `none`, `basic`, or `full`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "none" => Some(Self::None),
+            "basic" => Some(Self::Basic),
+            "full" => Some(Self::Full),
+            _ => None,
+        }
+    }
+}
+
+/// Syscalls allowed under `Full` isolation: memory, threads, time, I/O and
+/// changes to files Landlock permits, and the outgoing connections name
+/// resolution needs
+const FULL_SYSCALLS: &[&str] = &[
+    "read",
+    "write",
+    "readv",
+    "writev",
+    "pread64",
+    "pwrite64",
+    "close",
+    "lseek",
+    "fstat",
+    "newfstatat",
+    "statx",
+    "openat",
+    "faccessat",
+    "readlinkat",
+    "getdents64",
+    "getcwd",
+    "fcntl",
+    "ioctl",
+    "dup",
+    "dup3",
+    "pipe2",
+    "eventfd2",
+    "epoll_create1",
+    "epoll_ctl",
+    "epoll_pwait",
+    "ppoll",
+    "recvmsg",
+    "sendmsg",
+    "shutdown",
+    "socket",
+    "socketpair",
+    "connect",
+    "sendto",
+    "recvfrom",
+    "sendmmsg",
+    "getsockname",
+    "getpeername",
+    "setsockopt",
+    "getsockopt",
+    "mkdirat",
+    "unlinkat",
+    "renameat",
+    "renameat2",
+    "ftruncate",
+    "fsync",
+    "fdatasync",
+    "flock",
+    "mmap",
+    "munmap",
+    "mprotect",
+    "mremap",
+    "madvise",
+    "brk",
+    "membarrier",
+    "rt_sigaction",
+    "rt_sigprocmask",
+    "rt_sigreturn",
+    "sigaltstack",
+    "tgkill",
+    "futex",
+    "set_robust_list",
+    "rseq",
+    // Only for new threads under `Full`; `clone3` always fails with `ENOSYS`
+    // so libc falls back to `clone`, whose flags can be checked
+    "clone",
+    "sched_yield",
+    "sched_getaffinity",
+    "clock_gettime",
+    "clock_nanosleep",
+    "nanosleep",
+    "gettimeofday",
+    "getrandom",
+    "getpid",
+    "gettid",
+    "getuid",
+    "geteuid",
+    "getgid",
+    "getegid",
+    "uname",
+    "prlimit64",
+    "prctl",
+    "restart_syscall",
+    "exit",
+    "exit_group",
+    // Legacy variants used by x86_64 libc
+    "open",
+    "stat",
+    "lstat",
+    "access",
+    "readlink",
+    "poll",
+    "epoll_wait",
+    "arch_prctl",
+    "mkdir",
+    "unlink",
+    "rename",
+];
+
+/// Syscalls additionally allowed under `Basic` isolation: listening
+/// sockets, file permissions, and spawning processes
+const BASIC_SYSCALLS: &[&str] = &[
+    "bind",
+    "listen",
+    "accept4",
+    "fchmod",
+    "fchmodat",
+    "statfs",
+    "fstatfs",
+    "sysinfo",
+    "memfd_create",
+    "inotify_init1",
+    "inotify_add_watch",
+    "inotify_rm_watch",
+    "execve",
+    "wait4",
+    "kill",
+    // Legacy variants used by x86_64 libc
+    "chmod",
+    "fork",
+    "vfork",
+];
+
+/// System directories `Full` isolation leaves readable, for libraries,
+/// fonts, configuration, and the sockets of the display server and resolver
+const READ_ONLY_DIRS: &[&str] = &[
+    "/usr",
+    "/lib",
+    "/lib64",
+    "/etc",
+    "/dev",
+    "/proc",
+    "/sys",
+    "/run",
+    "/tmp",
+    "/var/cache/fontconfig",
+];
+
+/// Font directories under the home directory `Full` isolation leaves readable
+const USER_FONT_DIRS: &[&str] = &[".local/share/fonts", ".fonts", ".cache/fontconfig"];
+
+/// Filesystem access allowed under isolation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilesystemRules {
+    /// Directories that may be read and written
+    pub read_write: Vec<PathBuf>,
+    /// Directories that may only be read
+    pub read_only: Vec<PathBuf>,
+}
+
 /// Process isolator
 pub struct ProcessIsolator {
     level: IsolationLevel,
+    /// Profile directory left writable under full isolation
+    profile_dir: Option<PathBuf>,
 }
 
 impl ProcessIsolator {
     /// Create a new process isolator
     pub fn new(level: IsolationLevel) -> Self {
-        Self { level }
+        Self {
+            level,
+            profile_dir: None,
+        }
+    }
+
+    /// Set the profile directory the process may write under full isolation
+    pub fn with_profile_dir(mut self, profile_dir: PathBuf) -> Self {
+        self.profile_dir = Some(profile_dir);
+        self
     }
 
     /// Get the isolation level
@@ -29,28 +213,75 @@ impl ProcessIsolator {
         self.level
     }
 
+    /// Get the names of the syscalls the isolation level allows, or `None`
+    /// if syscalls are not filtered
+    ///
+    /// Names without a syscall on the current architecture are skipped when
+    /// the filter is built.
+    pub fn allowed_syscalls(&self) -> Option<Vec<&'static str>> {
+        match self.level {
+            IsolationLevel::None => None,
+            IsolationLevel::Basic => Some([FULL_SYSCALLS, BASIC_SYSCALLS].concat()),
+            IsolationLevel::Full => Some(FULL_SYSCALLS.to_vec()),
+        }
+    }
+
+    /// Get the filesystem access the isolation level allows, or `None` if
+    /// filesystem access is not restricted
+    pub fn filesystem_rules(&self) -> Option<FilesystemRules> {
+        if self.level != IsolationLevel::Full {
+            return None;
+        }
+
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let user_fonts = home
+            .iter()
+            .flat_map(|home| USER_FONT_DIRS.iter().map(move |dir| home.join(dir)));
+
+        Some(FilesystemRules {
+            read_write: self.profile_dir.iter().cloned().collect(),
+            read_only: READ_ONLY_DIRS
+                .iter()
+                .map(PathBuf::from)
+                .chain(user_fonts)
+                .collect(),
+        })
+    }
+
     /// Apply process isolation
+    ///
+    /// This cannot be undone and also applies to every thread of the process.
     pub fn apply(&self) -> Result<()> {
         match self.level {
             IsolationLevel::None => {
                 tracing::debug!("No process isolation applied");
             }
-            IsolationLevel::Basic => {
-                tracing::debug!("Applying basic process isolation");
-                // Placeholder: implement basic isolation
-            }
-            IsolationLevel::Full => {
-                tracing::debug!("Applying full process isolation");
-                // Placeholder: implement full isolation
-                // This would typically involve:
-                // - Process sandboxing
-                // - Filesystem restrictions
-                // - Network restrictions
-                // - System call filtering
+            IsolationLevel::Basic | IsolationLevel::Full => {
+                tracing::debug!("Applying {:?} process isolation", self.level);
+                self.apply_platform()?;
             }
         }
         Ok(())
     }
+
+    #[cfg(target_os = "linux")]
+    fn apply_platform(&self) -> Result<()> {
+        // Landlock needs its own syscalls, so restrict files before filtering them
+        if let Some(rules) = self.filesystem_rules() {
+            linux::restrict_filesystem(&rules)?;
+        }
+        if let Some(syscalls) = self.allowed_syscalls() {
+            let threads_only = self.level == IsolationLevel::Full;
+            linux::install_syscall_filter(&syscalls, threads_only)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn apply_platform(&self) -> Result<()> {
+        tracing::warn!("Process isolation is not supported on this platform");
+        Ok(())
+    }
 }
 
 impl Default for ProcessIsolator {
@@ -67,6 +298,64 @@ mod tests {
     fn test_process_isolator() {
         let isolator = ProcessIsolator::new(IsolationLevel::Full);
         assert_eq!(isolator.level(), IsolationLevel::Full);
-        assert!(isolator.apply().is_ok());
+
+        // Applying real isolation would sandbox the test process
+        let none = ProcessIsolator::new(IsolationLevel::None);
+        assert!(none.allowed_syscalls().is_none());
+        assert!(none.filesystem_rules().is_none());
+        assert!(none.apply().is_ok());
+    }
+
+    #[test]
+    fn test_parse_isolation_level() {
+        assert_eq!(IsolationLevel::parse("none"), Some(IsolationLevel::None));
+        assert_eq!(IsolationLevel::parse("Basic"), Some(IsolationLevel::Basic));
+        assert_eq!(IsolationLevel::parse(" full "), Some(IsolationLevel::Full));
+        assert_eq!(IsolationLevel::parse("strict"), None);
+    }
+
+    #[test]
+    fn test_syscall_allowlists() {
+        let full = ProcessIsolator::new(IsolationLevel::Full)
+            .allowed_syscalls()
+            .unwrap();
+        let basic = ProcessIsolator::new(IsolationLevel::Basic)
+            .allowed_syscalls()
+            .unwrap();
+
+        assert!(full.contains(&"read"));
+        assert!(full.contains(&"futex"));
+        assert!(full.contains(&"socket"));
+        assert!(full.contains(&"connect"));
+        assert!(!full.contains(&"bind"));
+        assert!(!full.contains(&"execve"));
+        assert!(!full.contains(&"clone3"));
+        assert!(basic.contains(&"bind"));
+        assert!(basic.contains(&"execve"));
+        assert!(full.iter().all(|syscall| basic.contains(syscall)));
+        assert!(!basic.contains(&"ptrace"));
+        assert!(!basic.contains(&"mount"));
+    }
+
+    #[test]
+    fn test_filesystem_rules() {
+        let profile_dir = PathBuf::from("/home/user/.local/share/Horizon/profiles/default");
+        let rules = ProcessIsolator::new(IsolationLevel::Full)
+            .with_profile_dir(profile_dir.clone())
+            .filesystem_rules()
+            .unwrap();
+        assert_eq!(rules.read_write, vec![profile_dir.clone()]);
+        assert!(rules.read_only.contains(&PathBuf::from("/usr")));
+        assert!(rules.read_only.contains(&PathBuf::from("/run")));
+        assert!(rules.read_only.contains(&PathBuf::from("/tmp")));
+        assert!(rules
+            .read_only
+            .contains(&PathBuf::from("/var/cache/fontconfig")));
+        assert!(!rules.read_only.contains(&PathBuf::from("/home")));
+
+        assert!(ProcessIsolator::new(IsolationLevel::Basic)
+            .with_profile_dir(profile_dir)
+            .filesystem_rules()
+            .is_none());
     }
 }
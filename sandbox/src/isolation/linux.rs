@@ -0,0 +1,373 @@
+//! Linux isolation with seccomp-bpf and Landlock
+
+use super::FilesystemRules;
+use anyhow::{anyhow, Result};
+use landlock::{
+    path_beneath_rules, Access, AccessFs, Ruleset, RulesetAttr, RulesetCreatedAttr, RulesetStatus,
+    ABI,
+};
+use seccompiler::{
+    BpfProgram, SeccompAction, SeccompCmpArgLen, SeccompCmpOp, SeccompCondition, SeccompFilter,
+    SeccompRule, TargetArch,
+};
+use std::collections::BTreeMap;
+
+/// Newest Landlock ABI the ruleset asks for; older kernels enforce what
+/// they support
+const LANDLOCK_ABI: ABI = ABI::V2;
+
+/// Syscall numbers by name for the current architecture
+const SYSCALLS: &[(&str, libc::c_long)] = &[
+    ("read", libc::SYS_read),
+    ("write", libc::SYS_write),
+    ("readv", libc::SYS_readv),
+    ("writev", libc::SYS_writev),
+    ("pread64", libc::SYS_pread64),
+    ("pwrite64", libc::SYS_pwrite64),
+    ("close", libc::SYS_close),
+    ("lseek", libc::SYS_lseek),
+    ("fstat", libc::SYS_fstat),
+    ("newfstatat", libc::SYS_newfstatat),
+    ("statx", libc::SYS_statx),
+    ("openat", libc::SYS_openat),
+    ("faccessat", libc::SYS_faccessat),
+    ("readlinkat", libc::SYS_readlinkat),
+    ("getdents64", libc::SYS_getdents64),
+    ("getcwd", libc::SYS_getcwd),
+    ("fcntl", libc::SYS_fcntl),
+    ("ioctl", libc::SYS_ioctl),
+    ("dup", libc::SYS_dup),
+    ("dup3", libc::SYS_dup3),
+    ("pipe2", libc::SYS_pipe2),
+    ("eventfd2", libc::SYS_eventfd2),
+    ("epoll_create1", libc::SYS_epoll_create1),
+    ("epoll_ctl", libc::SYS_epoll_ctl),
+    ("epoll_pwait", libc::SYS_epoll_pwait),
+    ("ppoll", libc::SYS_ppoll),
+    ("recvmsg", libc::SYS_recvmsg),
+    ("sendmsg", libc::SYS_sendmsg),
+    ("shutdown", libc::SYS_shutdown),
+    ("mmap", libc::SYS_mmap),
+    ("munmap", libc::SYS_munmap),
+    ("mprotect", libc::SYS_mprotect),
+    ("mremap", libc::SYS_mremap),
+    ("madvise", libc::SYS_madvise),
+    ("brk", libc::SYS_brk),
+    ("membarrier", libc::SYS_membarrier),
+    ("rt_sigaction", libc::SYS_rt_sigaction),
+    ("rt_sigprocmask", libc::SYS_rt_sigprocmask),
+    ("rt_sigreturn", libc::SYS_rt_sigreturn),
+    ("sigaltstack", libc::SYS_sigaltstack),
+    ("tgkill", libc::SYS_tgkill),
+    ("futex", libc::SYS_futex),
+    ("set_robust_list", libc::SYS_set_robust_list),
+    ("rseq", libc::SYS_rseq),
+    ("clone", libc::SYS_clone),
+    ("clone3", libc::SYS_clone3),
+    ("sched_yield", libc::SYS_sched_yield),
+    ("sched_getaffinity", libc::SYS_sched_getaffinity),
+    ("clock_gettime", libc::SYS_clock_gettime),
+    ("clock_nanosleep", libc::SYS_clock_nanosleep),
+    ("nanosleep", libc::SYS_nanosleep),
+    ("gettimeofday", libc::SYS_gettimeofday),
+    ("getrandom", libc::SYS_getrandom),
+    ("getpid", libc::SYS_getpid),
+    ("gettid", libc::SYS_gettid),
+    ("getuid", libc::SYS_getuid),
+    ("geteuid", libc::SYS_geteuid),
+    ("getgid", libc::SYS_getgid),
+    ("getegid", libc::SYS_getegid),
+    ("uname", libc::SYS_uname),
+    ("prlimit64", libc::SYS_prlimit64),
+    ("prctl", libc::SYS_prctl),
+    ("restart_syscall", libc::SYS_restart_syscall),
+    ("exit", libc::SYS_exit),
+    ("exit_group", libc::SYS_exit_group),
+    ("socket", libc::SYS_socket),
+    ("socketpair", libc::SYS_socketpair),
+    ("connect", libc::SYS_connect),
+    ("bind", libc::SYS_bind),
+    ("listen", libc::SYS_listen),
+    ("accept4", libc::SYS_accept4),
+    ("getsockname", libc::SYS_getsockname),
+    ("getpeername", libc::SYS_getpeername),
+    ("setsockopt", libc::SYS_setsockopt),
+    ("getsockopt", libc::SYS_getsockopt),
+    ("sendto", libc::SYS_sendto),
+    ("recvfrom", libc::SYS_recvfrom),
+    ("sendmmsg", libc::SYS_sendmmsg),
+    ("mkdirat", libc::SYS_mkdirat),
+    ("unlinkat", libc::SYS_unlinkat),
+    ("renameat2", libc::SYS_renameat2),
+    ("fchmod", libc::SYS_fchmod),
+    ("fchmodat", libc::SYS_fchmodat),
+    ("ftruncate", libc::SYS_ftruncate),
+    ("fsync", libc::SYS_fsync),
+    ("fdatasync", libc::SYS_fdatasync),
+    ("flock", libc::SYS_flock),
+    ("statfs", libc::SYS_statfs),
+    ("fstatfs", libc::SYS_fstatfs),
+    ("sysinfo", libc::SYS_sysinfo),
+    ("memfd_create", libc::SYS_memfd_create),
+    ("inotify_init1", libc::SYS_inotify_init1),
+    ("inotify_add_watch", libc::SYS_inotify_add_watch),
+    ("inotify_rm_watch", libc::SYS_inotify_rm_watch),
+    ("execve", libc::SYS_execve),
+    ("wait4", libc::SYS_wait4),
+    ("kill", libc::SYS_kill),
+    #[cfg(target_arch = "x86_64")]
+    ("open", libc::SYS_open),
+    #[cfg(target_arch = "x86_64")]
+    ("stat", libc::SYS_stat),
+    #[cfg(target_arch = "x86_64")]
+    ("lstat", libc::SYS_lstat),
+    #[cfg(target_arch = "x86_64")]
+    ("access", libc::SYS_access),
+    #[cfg(target_arch = "x86_64")]
+    ("readlink", libc::SYS_readlink),
+    #[cfg(target_arch = "x86_64")]
+    ("poll", libc::SYS_poll),
+    #[cfg(target_arch = "x86_64")]
+    ("epoll_wait", libc::SYS_epoll_wait),
+    #[cfg(target_arch = "x86_64")]
+    ("arch_prctl", libc::SYS_arch_prctl),
+    #[cfg(target_arch = "x86_64")]
+    ("renameat", libc::SYS_renameat),
+    #[cfg(target_arch = "x86_64")]
+    ("mkdir", libc::SYS_mkdir),
+    #[cfg(target_arch = "x86_64")]
+    ("unlink", libc::SYS_unlink),
+    #[cfg(target_arch = "x86_64")]
+    ("rename", libc::SYS_rename),
+    #[cfg(target_arch = "x86_64")]
+    ("chmod", libc::SYS_chmod),
+    #[cfg(target_arch = "x86_64")]
+    ("fork", libc::SYS_fork),
+    #[cfg(target_arch = "x86_64")]
+    ("vfork", libc::SYS_vfork),
+];
+
+/// Get the number of a syscall on the current architecture
+// `c_long` is only `i64` on 64-bit targets
+#[allow(clippy::unnecessary_cast)]
+fn syscall_number(name: &str) -> Option<i64> {
+    SYSCALLS
+        .iter()
+        .find(|(syscall, _)| *syscall == name)
+        .map(|(_, number)| *number as i64)
+}
+
+/// Get the number of `clone3`, which is filtered on its own
+fn clone3_number() -> Result<i64> {
+    syscall_number("clone3").ok_or_else(|| anyhow!("clone3 is not a known syscall"))
+}
+
+/// Get the seccomp architecture of the current target
+fn target_arch() -> Result<TargetArch> {
+    TargetArch::try_from(std::env::consts::ARCH)
+        .map_err(|e| anyhow!("Seccomp is not supported on this architecture: {}", e))
+}
+
+/// Build a filter allowing only the named syscalls, failing others with `EPERM`
+///
+/// With `threads_only`, `clone` is only allowed with `CLONE_THREAD`, so no
+/// new processes can be created. `clone3` is left to `clone3_filter`.
+pub(super) fn syscall_filter(syscalls: &[&str], threads_only: bool) -> Result<SeccompFilter> {
+    let mut rules = BTreeMap::from([(clone3_number()?, Vec::new())]);
+    for name in syscalls {
+        let Some(number) = syscall_number(name) else {
+            tracing::debug!("Syscall {} does not exist on this architecture", name);
+            continue;
+        };
+        let conditions = if threads_only && *name == "clone" {
+            let thread = libc::CLONE_THREAD as u64;
+            vec![SeccompRule::new(vec![SeccompCondition::new(
+                0,
+                SeccompCmpArgLen::Qword,
+                SeccompCmpOp::MaskedEq(thread),
+                thread,
+            )?])?]
+        } else {
+            Vec::new()
+        };
+        rules.insert(number, conditions);
+    }
+
+    Ok(SeccompFilter::new(
+        rules,
+        SeccompAction::Errno(libc::EPERM as u32),
+        SeccompAction::Allow,
+        target_arch()?,
+    )?)
+}
+
+/// Build a filter failing `clone3` with `ENOSYS`
+///
+/// Seccomp cannot read the flags `clone3` takes in memory, so it is refused
+/// in a way that makes libc fall back to `clone`.
+pub(super) fn clone3_filter() -> Result<SeccompFilter> {
+    let rules = BTreeMap::from([(clone3_number()?, Vec::new())]);
+    Ok(SeccompFilter::new(
+        rules,
+        SeccompAction::Allow,
+        SeccompAction::Errno(libc::ENOSYS as u32),
+        target_arch()?,
+    )?)
+}
+
+/// Install a syscall allowlist on every thread of the process
+///
+/// The `clone3` filter goes first, since the allowlist blocks installing
+/// more filters. Seccomp prefers an error over allowing a syscall, so
+/// `clone3` still fails with `ENOSYS`.
+pub(super) fn install_syscall_filter(syscalls: &[&str], threads_only: bool) -> Result<()> {
+    let clone3: BpfProgram = clone3_filter()?.try_into()?;
+    let program: BpfProgram = syscall_filter(syscalls, threads_only)?.try_into()?;
+    seccompiler::apply_filter_all_threads(&clone3)?;
+    seccompiler::apply_filter_all_threads(&program)?;
+    tracing::info!(
+        "Installed seccomp filter allowing {} syscalls",
+        syscalls.len()
+    );
+    Ok(())
+}
+
+/// Restrict filesystem access of the calling thread with Landlock
+///
+/// Threads started afterwards inherit the restriction, so this should run
+/// before other threads are spawned. Kernels without Landlock are left
+/// unrestricted with a warning.
+pub(super) fn restrict_filesystem(rules: &FilesystemRules) -> Result<()> {
+    let status = Ruleset::default()
+        .handle_access(AccessFs::from_all(LANDLOCK_ABI))?
+        .create()?
+        .add_rules(path_beneath_rules(
+            &rules.read_only,
+            AccessFs::from_read(LANDLOCK_ABI),
+        ))?
+        .add_rules(path_beneath_rules(
+            &rules.read_write,
+            AccessFs::from_all(LANDLOCK_ABI),
+        ))?
+        .restrict_self()?;
+
+    match status.ruleset {
+        RulesetStatus::FullyEnforced => tracing::info!("Landlock filesystem rules enforced"),
+        RulesetStatus::PartiallyEnforced => {
+            tracing::warn!("Landlock filesystem rules only partially enforced")
+        }
+        RulesetStatus::NotEnforced => {
+            tracing::warn!("Landlock is not supported by this kernel; filesystem is unrestricted")
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::isolation::{IsolationLevel, ProcessIsolator};
+
+    #[test]
+    fn test_syscall_numbers() {
+        assert!(syscall_number("read").is_some());
+        #[cfg(target_arch = "x86_64")]
+        assert_eq!(syscall_number("openat"), Some(257));
+        assert_eq!(syscall_number("ptrace"), None);
+
+        // Every allowed name is known, at least on x86_64
+        let basic = ProcessIsolator::new(IsolationLevel::Basic)
+            .allowed_syscalls()
+            .unwrap();
+        #[cfg(target_arch = "x86_64")]
+        assert!(basic.iter().all(|name| syscall_number(name).is_some()));
+        assert!(basic.iter().any(|name| syscall_number(name).is_some()));
+    }
+
+    #[test]
+    fn test_syscall_filter_compiles() {
+        let full = ProcessIsolator::new(IsolationLevel::Full)
+            .allowed_syscalls()
+            .unwrap();
+        let basic = ProcessIsolator::new(IsolationLevel::Basic)
+            .allowed_syscalls()
+            .unwrap();
+
+        let full: BpfProgram = syscall_filter(&full, true).unwrap().try_into().unwrap();
+        let basic: BpfProgram = syscall_filter(&basic, false).unwrap().try_into().unwrap();
+        let empty: BpfProgram = syscall_filter(&[], false).unwrap().try_into().unwrap();
+
+        // Each allowed syscall adds instructions to the program
+        assert!(full.len() > empty.len());
+        assert!(basic.len() > full.len());
+
+        // Checking the flags of `clone` takes more instructions
+        let clone: BpfProgram = syscall_filter(&["clone"], false)
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let threads: BpfProgram = syscall_filter(&["clone"], true)
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert!(threads.len() > clone.len());
+
+        let clone3: BpfProgram = clone3_filter().unwrap().try_into().unwrap();
+        assert!(!clone3.is_empty());
+    }
+
+    /// Environment variable giving the sandboxed child the port to connect to
+    const CHILD_PORT_VAR: &str = "HORIZON_SANDBOX_TEST_PORT";
+
+    #[test]
+    fn test_sandboxed_child_can_resolve_and_connect() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // Isolation cannot be undone, so it is applied in a child process
+        // running only `sandboxed_child`
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "isolation::linux::tests::sandboxed_child"])
+            .args(["--nocapture", "--test-threads=1"])
+            .env(CHILD_PORT_VAR, port.to_string())
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            output.status.success(),
+            "{}{}",
+            stdout,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(stdout.contains("1 passed"), "{}", stdout);
+
+        listener.set_nonblocking(true).unwrap();
+        assert!(listener.accept().is_ok());
+    }
+
+    /// Apply full isolation, then resolve `localhost` and connect to the
+    /// parent test; does nothing unless run by it
+    #[test]
+    fn sandboxed_child() {
+        use std::net::ToSocketAddrs;
+
+        let Ok(port) = std::env::var(CHILD_PORT_VAR) else {
+            return;
+        };
+        ProcessIsolator::new(IsolationLevel::Full).apply().unwrap();
+
+        let addrs: Vec<_> = ("localhost", port.parse::<u16>().unwrap())
+            .to_socket_addrs()
+            .unwrap()
+            .collect();
+        assert!(!addrs.is_empty());
+        std::net::TcpStream::connect(&addrs[..]).unwrap();
+
+        // Listening is only allowed under basic isolation
+        assert!(std::net::TcpListener::bind("127.0.0.1:0").is_err());
+
+        // Threads can still be started, but not processes
+        assert_eq!(std::thread::spawn(|| 1).join().unwrap(), 1);
+        assert!(std::process::Command::new("true").status().is_err());
+    }
+}
@@ -3,6 +3,7 @@
 //! Sandboxing and process isolation for the Horizon Browser.
 //! Provides security policies and runtime guards.
 
+pub mod csp;
 pub mod isolation;
 pub mod policy;
 
@@ -10,14 +11,14 @@ use anyhow::Result;
 
 /// Sandbox manager
 pub struct SandboxManager {
-    policy: policy::SecurityPolicy,
+    policies: policy::PolicyStore,
 }
 
 impl SandboxManager {
     /// Create a new sandbox manager
     pub fn new() -> Self {
         Self {
-            policy: policy::SecurityPolicy::default(),
+            policies: policy::PolicyStore::default(),
         }
     }
 
@@ -27,14 +28,24 @@ impl SandboxManager {
         Ok(())
     }
 
-    /// Get the security policy
+    /// Get the default security policy
     pub fn policy(&self) -> &policy::SecurityPolicy {
-        &self.policy
+        self.policies.default_policy()
     }
 
-    /// Update the security policy
+    /// Update the default security policy
     pub fn set_policy(&mut self, policy: policy::SecurityPolicy) {
-        self.policy = policy;
+        self.policies.set_default_policy(policy);
+    }
+
+    /// Get the per-site policy overrides
+    pub fn policies_mut(&mut self) -> &mut policy::PolicyStore {
+        &mut self.policies
+    }
+
+    /// Get the security policy for a URL, including any site override
+    pub fn effective_policy(&self, url: &str) -> policy::SecurityPolicy {
+        self.policies.resolve(url)
     }
 }
 
@@ -53,4 +64,25 @@ mod tests {
         let mut manager = SandboxManager::new();
         assert!(manager.initialize().is_ok());
     }
+
+    #[test]
+    fn test_effective_policy() {
+        let mut manager = SandboxManager::new();
+        manager.policies_mut().set_override(
+            "example.com",
+            policy::PolicyOverride::default().with_javascript(false),
+        );
+
+        assert!(
+            !manager
+                .effective_policy("https://example.com/")
+                .enable_javascript
+        );
+        assert!(
+            manager
+                .effective_policy("https://example.org/")
+                .enable_javascript
+        );
+        assert!(manager.policy().enable_javascript);
+    }
 }
@@ -1,4 +1,9 @@
 //! Security policy definitions
+//!
+//! A global [`SecurityPolicy`] can be overridden per site with a
+//! [`PolicyStore`] mapping host patterns to [`PolicyOverride`]s.
+
+use horizon_url::host_matches;
 
 /// Security policy configuration
 #[derive(Debug, Clone)]
@@ -43,11 +48,36 @@ impl SecurityPolicy {
     }
 
     /// Check if a resource should be allowed
-    pub fn allow_resource(&self, url: &str) -> bool {
-        if url.starts_with("file://") && !self.allow_file_urls {
-            return false;
+    ///
+    /// `page_is_secure` says whether the page loading the resource was served
+    /// over HTTPS, or is `None` for top-level navigations. `about:`, `data:`,
+    /// and `blob:` URLs are always allowed, `file:` URLs only if
+    /// `allow_file_urls` is set, and insecure `http:`/`ws:` resources on a
+    /// secure page are blocked if `block_mixed_content` is set.
+    pub fn allow_resource(&self, url: &str, page_is_secure: Option<bool>) -> bool {
+        let scheme = url
+            .split_once(':')
+            .map(|(scheme, _)| scheme.to_ascii_lowercase())
+            .unwrap_or_default();
+
+        match scheme.as_str() {
+            "about" | "data" | "blob" => true,
+            "file" => self.allow_file_urls,
+            "http" | "ws" => !(self.block_mixed_content && page_is_secure == Some(true)),
+            _ => true,
         }
-        true
+    }
+
+    /// Check a page's Content Security Policy, if it sent one, for a resource
+    ///
+    /// Always allows the resource when `csp_enabled` is off.
+    pub fn csp_allows(
+        &self,
+        csp: Option<&crate::csp::Csp>,
+        url: &str,
+        resource_type: crate::csp::ResourceType,
+    ) -> bool {
+        !self.csp_enabled || csp.is_none_or(|csp| csp.allows(url, resource_type))
     }
 }
 
@@ -57,6 +87,147 @@ impl Default for SecurityPolicy {
     }
 }
 
+/// Settings that replace the default policy on matching sites
+///
+/// Unset fields keep the default policy's value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PolicyOverride {
+    /// Enable JavaScript
+    pub enable_javascript: Option<bool>,
+    /// Enable WebAssembly
+    pub enable_wasm: Option<bool>,
+    /// Allow file:// URLs
+    pub allow_file_urls: Option<bool>,
+    /// Block mixed content
+    pub block_mixed_content: Option<bool>,
+}
+
+impl PolicyOverride {
+    /// Set whether JavaScript is enabled
+    pub fn with_javascript(mut self, enabled: bool) -> Self {
+        self.enable_javascript = Some(enabled);
+        self
+    }
+
+    /// Set whether WebAssembly is enabled
+    pub fn with_wasm(mut self, enabled: bool) -> Self {
+        self.enable_wasm = Some(enabled);
+        self
+    }
+
+    /// Set whether file:// URLs are allowed
+    pub fn with_file_urls(mut self, allowed: bool) -> Self {
+        self.allow_file_urls = Some(allowed);
+        self
+    }
+
+    /// Set whether mixed content is blocked
+    pub fn with_mixed_content_blocked(mut self, blocked: bool) -> Self {
+        self.block_mixed_content = Some(blocked);
+        self
+    }
+
+    /// Apply the set fields to a policy
+    pub fn apply(&self, policy: &mut SecurityPolicy) {
+        if let Some(enabled) = self.enable_javascript {
+            policy.enable_javascript = enabled;
+        }
+        if let Some(enabled) = self.enable_wasm {
+            policy.enable_wasm = enabled;
+        }
+        if let Some(allowed) = self.allow_file_urls {
+            policy.allow_file_urls = allowed;
+        }
+        if let Some(blocked) = self.block_mixed_content {
+            policy.block_mixed_content = blocked;
+        }
+    }
+}
+
+/// A default security policy with per-site overrides
+///
+/// Host patterns are either an exact host (`example.com`) or a wildcard
+/// (`*.example.com`) matching the domain and all its subdomains.
+#[derive(Debug, Clone, Default)]
+pub struct PolicyStore {
+    default: SecurityPolicy,
+    overrides: Vec<(String, PolicyOverride)>,
+}
+
+impl PolicyStore {
+    /// Create a store with a default policy and no overrides
+    pub fn new(default: SecurityPolicy) -> Self {
+        Self {
+            default,
+            overrides: Vec::new(),
+        }
+    }
+
+    /// Get the default policy
+    pub fn default_policy(&self) -> &SecurityPolicy {
+        &self.default
+    }
+
+    /// Replace the default policy
+    pub fn set_default_policy(&mut self, policy: SecurityPolicy) {
+        self.default = policy;
+    }
+
+    /// Set the override for a host pattern, replacing any existing one
+    pub fn set_override(&mut self, pattern: impl Into<String>, policy_override: PolicyOverride) {
+        let pattern = pattern.into().to_ascii_lowercase();
+        self.overrides.retain(|(p, _)| *p != pattern);
+        self.overrides.push((pattern, policy_override));
+    }
+
+    /// Remove the override for a host pattern, returning whether one was removed
+    pub fn remove_override(&mut self, pattern: &str) -> bool {
+        let pattern = pattern.to_ascii_lowercase();
+        let initial_len = self.overrides.len();
+        self.overrides.retain(|(p, _)| *p != pattern);
+        self.overrides.len() < initial_len
+    }
+
+    /// Get the host patterns and their overrides
+    pub fn overrides(&self) -> &[(String, PolicyOverride)] {
+        &self.overrides
+    }
+
+    /// Get the policy for a URL
+    ///
+    /// Every override matching the URL's host is layered on the default,
+    /// from the least to the most specific pattern, so an exact host wins
+    /// over a wildcard.
+    pub fn resolve(&self, url: &str) -> SecurityPolicy {
+        let mut policy = self.default.clone();
+        let Some(host) = url::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+        else {
+            return policy;
+        };
+
+        let mut matching: Vec<&(String, PolicyOverride)> = self
+            .overrides
+            .iter()
+            .filter(|(pattern, _)| host_matches(pattern, &host))
+            .collect();
+        matching.sort_by_key(|(pattern, _)| specificity(pattern));
+        for (_, policy_override) in matching {
+            policy_override.apply(&mut policy);
+        }
+        policy
+    }
+}
+
+/// Rank a host pattern so exact hosts outrank wildcards of the same domain
+fn specificity(pattern: &str) -> (usize, bool) {
+    match pattern.strip_prefix("*.") {
+        Some(domain) => (domain.len(), false),
+        None => (pattern.len(), true),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -79,10 +250,100 @@ mod tests {
     #[test]
     fn test_allow_resource() {
         let policy = SecurityPolicy::default();
-        assert!(policy.allow_resource("https://example.com"));
-        assert!(!policy.allow_resource("file:///etc/passwd"));
+        assert!(policy.allow_resource("https://example.com", None));
+        assert!(!policy.allow_resource("file:///etc/passwd", None));
+        assert!(!policy.allow_resource("FILE:///etc/passwd", Some(false)));
+
+        let permissive = SecurityPolicy::permissive();
+        assert!(permissive.allow_resource("file:///home/user/file.txt", None));
+    }
+
+    #[test]
+    fn test_internal_schemes_allowed() {
+        let policy = SecurityPolicy::default();
+        for url in [
+            "about:blank",
+            "about:settings",
+            "data:text/plain;base64,SGVsbG8=",
+            "blob:https://example.com/550e8400-e29b-41d4-a716-446655440000",
+        ] {
+            assert!(policy.allow_resource(url, None), "{}", url);
+            assert!(policy.allow_resource(url, Some(true)), "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_mixed_content_blocked() {
+        let policy = SecurityPolicy::default();
+        assert!(!policy.allow_resource("http://cdn.example.com/app.js", Some(true)));
+        assert!(!policy.allow_resource("ws://example.com/socket", Some(true)));
+        assert!(policy.allow_resource("https://cdn.example.com/app.js", Some(true)));
+        assert!(policy.allow_resource("wss://example.com/socket", Some(true)));
+
+        // Insecure pages and top-level navigations are not mixed content
+        assert!(policy.allow_resource("http://cdn.example.com/app.js", Some(false)));
+        assert!(policy.allow_resource("http://example.com/", None));
 
         let permissive = SecurityPolicy::permissive();
-        assert!(permissive.allow_resource("file:///home/user/file.txt"));
+        assert!(permissive.allow_resource("http://cdn.example.com/app.js", Some(true)));
+    }
+
+    #[test]
+    fn test_csp_allows() {
+        use crate::csp::{Csp, ResourceType};
+
+        let csp = Csp::parse("script-src 'self'", "https://example.com/").unwrap();
+        let url = "https://evil.com/x.js";
+        assert!(!SecurityPolicy::default().csp_allows(Some(&csp), url, ResourceType::Script));
+        assert!(SecurityPolicy::default().csp_allows(None, url, ResourceType::Script));
+        assert!(SecurityPolicy::permissive().csp_allows(Some(&csp), url, ResourceType::Script));
+    }
+
+    #[test]
+    fn test_override_disables_javascript_for_one_site() {
+        let mut store = PolicyStore::default();
+        store.set_override(
+            "ads.example.com",
+            PolicyOverride::default().with_javascript(false),
+        );
+
+        let policy = store.resolve("https://ads.example.com/banner");
+        assert!(!policy.enable_javascript);
+        assert!(policy.enable_wasm);
+        assert!(policy.block_mixed_content);
+
+        assert!(store.resolve("https://example.com/").enable_javascript);
+        assert!(store.resolve("https://news.example.org/").enable_javascript);
+        assert!(store.resolve("not a url").enable_javascript);
+    }
+
+    #[test]
+    fn test_wildcard_override_and_specificity() {
+        let mut store = PolicyStore::new(SecurityPolicy {
+            enable_javascript: false,
+            ..SecurityPolicy::default()
+        });
+        store.set_override(
+            "*.trusted.com",
+            PolicyOverride::default()
+                .with_javascript(true)
+                .with_wasm(false),
+        );
+        store.set_override("app.trusted.com", PolicyOverride::default().with_wasm(true));
+
+        let root = store.resolve("https://trusted.com/");
+        assert!(root.enable_javascript);
+        assert!(!root.enable_wasm);
+
+        // The exact host is layered over the wildcard
+        let app = store.resolve("https://APP.trusted.com/");
+        assert!(app.enable_javascript);
+        assert!(app.enable_wasm);
+
+        assert!(!store.resolve("https://untrusted.com/").enable_javascript);
+
+        assert!(store.remove_override("*.trusted.com"));
+        assert!(!store.remove_override("*.trusted.com"));
+        assert!(!store.resolve("https://trusted.com/").enable_javascript);
     }
 }
@@ -0,0 +1,267 @@
+//! Bookmark storage backed by the user data directory
+
+use crate::userdata::{DataType, UserDataManager};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// File name used for bookmarks inside the bookmarks data directory
+const BOOKMARKS_FILE: &str = "bookmarks.json";
+
+/// A saved bookmark
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    /// Bookmarked URL
+    pub url: String,
+    /// Page title at the time of bookmarking
+    pub title: String,
+    /// Date added
+    pub added_at: std::time::SystemTime,
+    /// Folder the bookmark belongs to (None for the top level)
+    pub folder: Option<String>,
+}
+
+impl Bookmark {
+    /// Create a new bookmark at the top level
+    pub fn new(url: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            title: title.into(),
+            added_at: std::time::SystemTime::now(),
+            folder: None,
+        }
+    }
+
+    /// Place the bookmark in a folder
+    pub fn in_folder(mut self, folder: impl Into<String>) -> Self {
+        self.folder = Some(folder.into());
+        self
+    }
+}
+
+/// Bookmark manager for storing and retrieving bookmarks
+pub struct BookmarkManager {
+    /// Bookmarks in insertion order
+    bookmarks: Vec<Bookmark>,
+    /// Path to the bookmarks file
+    storage_path: Option<PathBuf>,
+}
+
+impl BookmarkManager {
+    /// Create a new, in-memory bookmark manager
+    pub fn new() -> Self {
+        Self {
+            bookmarks: Vec::new(),
+            storage_path: None,
+        }
+    }
+
+    /// Create a bookmark manager with a storage path
+    pub fn with_storage_path(path: PathBuf) -> Result<Self> {
+        let mut manager = Self::new();
+        manager.storage_path = Some(path.clone());
+
+        // Load existing bookmarks if file exists
+        if path.exists() {
+            manager.load(&path)?;
+        }
+
+        Ok(manager)
+    }
+
+    /// Create a bookmark manager stored in the user data bookmarks directory
+    pub fn from_user_data(user_data: &UserDataManager) -> Result<Self> {
+        let dir = user_data.path_for(DataType::Bookmarks);
+        if !dir.exists() {
+            std::fs::create_dir_all(&dir)?;
+        }
+
+        Self::with_storage_path(dir.join(BOOKMARKS_FILE))
+    }
+
+    /// Add a bookmark, replacing any existing bookmark for the same URL
+    pub fn add(&mut self, bookmark: Bookmark) {
+        self.bookmarks.retain(|b| b.url != bookmark.url);
+        tracing::info!("Added bookmark for {}", bookmark.url);
+        self.bookmarks.push(bookmark);
+    }
+
+    /// Remove the bookmark for a URL, returning whether one was removed
+    pub fn remove_by_url(&mut self, url: &str) -> bool {
+        let initial_len = self.bookmarks.len();
+        self.bookmarks.retain(|b| b.url != url);
+
+        let removed = self.bookmarks.len() < initial_len;
+        if removed {
+            tracing::info!("Removed bookmark for {}", url);
+        }
+        removed
+    }
+
+    /// Check if a URL is bookmarked
+    pub fn contains(&self, url: &str) -> bool {
+        self.bookmarks.iter().any(|b| b.url == url)
+    }
+
+    /// Get all bookmarks
+    pub fn list(&self) -> &[Bookmark] {
+        &self.bookmarks
+    }
+
+    /// Get all bookmarks in a folder (None for the top level)
+    pub fn list_in_folder(&self, folder: Option<&str>) -> Vec<&Bookmark> {
+        self.bookmarks
+            .iter()
+            .filter(|b| b.folder.as_deref() == folder)
+            .collect()
+    }
+
+    /// List folder names in alphabetical order
+    pub fn folders(&self) -> Vec<&str> {
+        let mut folders: Vec<&str> = self
+            .bookmarks
+            .iter()
+            .filter_map(|b| b.folder.as_deref())
+            .collect();
+        folders.sort_unstable();
+        folders.dedup();
+        folders
+    }
+
+    /// Group bookmarks by folder, top level first and then folders alphabetically
+    ///
+    /// Bookmarks keep the order they were added in within each group, and
+    /// empty groups are left out.
+    pub fn grouped_by_folder(&self) -> Vec<(Option<&str>, Vec<&Bookmark>)> {
+        std::iter::once(None)
+            .chain(self.folders().into_iter().map(Some))
+            .map(|folder| (folder, self.list_in_folder(folder)))
+            .filter(|(_, bookmarks)| !bookmarks.is_empty())
+            .collect()
+    }
+
+    /// Count bookmarks
+    pub fn count(&self) -> usize {
+        self.bookmarks.len()
+    }
+
+    /// Save bookmarks to file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.bookmarks)?;
+        std::fs::write(path, json)?;
+        tracing::debug!("Saved bookmarks to {:?}", path);
+        Ok(())
+    }
+
+    /// Save to the configured storage path
+    pub fn save_to_storage(&self) -> Result<()> {
+        if let Some(path) = &self.storage_path {
+            self.save(path)
+        } else {
+            Err(anyhow!("No storage path configured"))
+        }
+    }
+
+    /// Load bookmarks from file
+    pub fn load(&mut self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let json = std::fs::read_to_string(path)?;
+        self.bookmarks = serde_json::from_str(&json)?;
+        tracing::debug!("Loaded bookmarks from {:?}", path);
+        Ok(())
+    }
+}
+
+impl Default for BookmarkManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_add_and_contains() {
+        let mut manager = BookmarkManager::new();
+        manager.add(Bookmark::new("https://example.com", "Example"));
+
+        assert!(manager.contains("https://example.com"));
+        assert!(!manager.contains("https://other.com"));
+        assert_eq!(manager.count(), 1);
+    }
+
+    #[test]
+    fn test_add_replaces_same_url() {
+        let mut manager = BookmarkManager::new();
+        manager.add(Bookmark::new("https://example.com", "Old"));
+        manager.add(Bookmark::new("https://example.com", "New"));
+
+        assert_eq!(manager.count(), 1);
+        assert_eq!(manager.list()[0].title, "New");
+    }
+
+    #[test]
+    fn test_remove_by_url() {
+        let mut manager = BookmarkManager::new();
+        manager.add(Bookmark::new("https://example.com", "Example"));
+
+        assert!(manager.remove_by_url("https://example.com"));
+        assert!(!manager.contains("https://example.com"));
+        assert!(!manager.remove_by_url("https://example.com"));
+    }
+
+    #[test]
+    fn test_list_in_folder() {
+        let mut manager = BookmarkManager::new();
+        manager.add(Bookmark::new("https://example.com", "Example"));
+        manager.add(Bookmark::new("https://rust-lang.org", "Rust").in_folder("Dev"));
+
+        assert_eq!(manager.list_in_folder(None).len(), 1);
+        let dev = manager.list_in_folder(Some("Dev"));
+        assert_eq!(dev.len(), 1);
+        assert_eq!(dev[0].url, "https://rust-lang.org");
+    }
+
+    #[test]
+    fn test_grouped_by_folder() {
+        let mut manager = BookmarkManager::new();
+        assert!(manager.grouped_by_folder().is_empty());
+
+        manager.add(Bookmark::new("https://rust-lang.org", "Rust").in_folder("Dev"));
+        manager.add(Bookmark::new("https://news.example.com", "News").in_folder("Reading"));
+        manager.add(Bookmark::new("https://docs.rs", "Docs").in_folder("Dev"));
+        assert_eq!(manager.folders(), vec!["Dev", "Reading"]);
+
+        let groups = manager.grouped_by_folder();
+        let folders: Vec<Option<&str>> = groups.iter().map(|(folder, _)| *folder).collect();
+        assert_eq!(folders, vec![Some("Dev"), Some("Reading")]);
+        let dev: Vec<&str> = groups[0].1.iter().map(|b| b.title.as_str()).collect();
+        assert_eq!(dev, vec!["Rust", "Docs"]);
+
+        manager.add(Bookmark::new("https://example.com", "Example"));
+        let groups = manager.grouped_by_folder();
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].0, None);
+        assert_eq!(groups[0].1[0].url, "https://example.com");
+    }
+
+    #[test]
+    fn test_persistence_across_reload() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_data = UserDataManager::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let mut manager = BookmarkManager::from_user_data(&user_data).unwrap();
+        manager.add(Bookmark::new("https://example.com", "Example").in_folder("Work"));
+        manager.save_to_storage().unwrap();
+
+        let reloaded = BookmarkManager::from_user_data(&user_data).unwrap();
+        assert!(reloaded.contains("https://example.com"));
+        assert_eq!(reloaded.list_in_folder(Some("Work")).len(), 1);
+    }
+}
@@ -0,0 +1,417 @@
+//! Cookie jar backed by the user data directory
+//!
+//! Cookies are set from `Set-Cookie` headers and sent back to matching URLs.
+//! Only cookies with a `Max-Age` are saved; session cookies live in memory
+//! until the browser closes. `Expires` dates are not parsed, so cookies
+//! using only `Expires` are treated as session cookies.
+
+use crate::userdata::{DataType, UserDataManager};
+use anyhow::{anyhow, Result};
+use horizon_url::{is_public_suffix, ParsedUrl};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// File name used for cookies inside the user data cookies directory
+const COOKIES_FILE: &str = "cookies.json";
+
+/// Longest a cookie is kept, however large its `Max-Age`
+const MAX_COOKIE_AGE: Duration = Duration::from_secs(400 * 24 * 60 * 60);
+
+/// A single cookie
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cookie {
+    /// Cookie name
+    pub name: String,
+    /// Cookie value
+    pub value: String,
+    /// Host the cookie belongs to
+    pub domain: String,
+    /// Whether subdomains of `domain` also receive the cookie
+    pub include_subdomains: bool,
+    /// Path prefix the cookie is sent for
+    pub path: String,
+    /// Whether the cookie is only sent over HTTPS
+    pub secure: bool,
+    /// When the cookie expires, or `None` for a session cookie
+    pub expires: Option<SystemTime>,
+}
+
+impl Cookie {
+    /// Check whether the cookie has expired
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    /// Check whether the cookie is sent to a URL
+    fn matches(&self, url: &ParsedUrl) -> bool {
+        let Some(host) = url.host() else {
+            return false;
+        };
+        let domain_ok = host == self.domain
+            || (self.include_subdomains && host.ends_with(&format!(".{}", self.domain)));
+        let path_ok = url.path() == self.path
+            || (url.path().starts_with(&self.path)
+                && (self.path.ends_with('/') || url.path()[self.path.len()..].starts_with('/')));
+
+        domain_ok && path_ok && (!self.secure || url.scheme() == "https")
+    }
+}
+
+/// Cookie jar
+pub struct CookieStore {
+    /// Stored cookies, including session cookies
+    cookies: Vec<Cookie>,
+    /// Path to the cookies file, or `None` for a jar kept only in memory
+    storage_path: Option<PathBuf>,
+}
+
+impl CookieStore {
+    /// Create a new, in-memory cookie jar
+    pub fn new() -> Self {
+        Self {
+            cookies: Vec::new(),
+            storage_path: None,
+        }
+    }
+
+    /// Create a cookie jar with a storage path
+    pub fn with_storage_path(path: PathBuf) -> Result<Self> {
+        let mut store = Self::new();
+        store.storage_path = Some(path.clone());
+
+        // Load existing cookies if file exists
+        if path.exists() {
+            store.load(&path)?;
+        }
+
+        Ok(store)
+    }
+
+    /// Create a cookie jar stored in the user data cookies directory
+    pub fn from_user_data(user_data: &UserDataManager) -> Result<Self> {
+        let dir = user_data.path_for(DataType::Cookies);
+        if !dir.exists() {
+            std::fs::create_dir_all(&dir)?;
+        }
+
+        Self::with_storage_path(dir.join(COOKIES_FILE))
+    }
+
+    /// Check whether the jar is saved to disk
+    pub fn is_persistent(&self) -> bool {
+        self.storage_path.is_some()
+    }
+
+    /// Store a cookie from a `Set-Cookie` header received from a URL
+    ///
+    /// A cookie replaces any with the same name, domain, and path, and a
+    /// `Max-Age` of zero or less deletes it. Cookies expire after at most
+    /// 400 days, and an unparsable `Max-Age` is ignored.
+    pub fn set_from_header(&mut self, url: &str, header: &str) -> Result<()> {
+        let url = ParsedUrl::parse(url)?;
+        let host = url
+            .host()
+            .ok_or_else(|| anyhow!("Cannot set cookies for {}", url.as_str()))?
+            .to_lowercase();
+
+        let mut parts = header.split(';');
+        let (name, value) = parts
+            .next()
+            .and_then(|pair| pair.split_once('='))
+            .ok_or_else(|| anyhow!("Invalid Set-Cookie header: {}", header))?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(anyhow!("Invalid Set-Cookie header: {}", header));
+        }
+
+        let mut cookie = Cookie {
+            name: name.to_string(),
+            value: value.trim().to_string(),
+            domain: host.clone(),
+            include_subdomains: false,
+            path: default_path(url.path()),
+            secure: false,
+            expires: None,
+        };
+        for attribute in parts {
+            let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "domain" => {
+                    let domain = value.trim_start_matches('.').to_lowercase();
+                    if host != domain && !host.ends_with(&format!(".{}", domain)) {
+                        return Err(anyhow!("{} cannot set cookies for {}", host, domain));
+                    }
+                    // A public suffix may only name the host itself, and then
+                    // the cookie stays host-only instead of covering every site
+                    if is_public_suffix(&domain) {
+                        if host != domain {
+                            return Err(anyhow!(
+                                "{} cannot set cookies for public suffix {}",
+                                host,
+                                domain
+                            ));
+                        }
+                        continue;
+                    }
+                    cookie.domain = domain;
+                    cookie.include_subdomains = true;
+                }
+                "path" if value.starts_with('/') => cookie.path = value.to_string(),
+                "secure" => cookie.secure = true,
+                "max-age" => match value.parse::<i64>() {
+                    Ok(seconds) => {
+                        let age = Duration::from_secs(seconds.max(0).unsigned_abs());
+                        cookie.expires = SystemTime::now().checked_add(age.min(MAX_COOKIE_AGE));
+                    }
+                    Err(_) => tracing::debug!("Ignoring invalid Max-Age {:?} from {}", value, host),
+                },
+                _ => {}
+            }
+        }
+
+        self.cookies.retain(|c| {
+            (c.name.as_str(), c.domain.as_str(), c.path.as_str())
+                != (
+                    cookie.name.as_str(),
+                    cookie.domain.as_str(),
+                    cookie.path.as_str(),
+                )
+        });
+        if !cookie.is_expired(SystemTime::now()) {
+            self.cookies.push(cookie);
+        }
+        Ok(())
+    }
+
+    /// Get the `Cookie` header value to send to a URL, if any cookies match
+    pub fn cookie_header(&self, url: &str) -> Option<String> {
+        let url = ParsedUrl::parse(url).ok()?;
+        let now = SystemTime::now();
+        let header = self
+            .cookies
+            .iter()
+            .filter(|cookie| !cookie.is_expired(now) && cookie.matches(&url))
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        (!header.is_empty()).then_some(header)
+    }
+
+    /// Get all cookies
+    pub fn cookies(&self) -> &[Cookie] {
+        &self.cookies
+    }
+
+    /// Remove all cookies
+    pub fn clear(&mut self) {
+        self.cookies.clear();
+    }
+
+    /// Save unexpired cookies with an expiry to file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let now = SystemTime::now();
+        let saved: Vec<&Cookie> = self
+            .cookies
+            .iter()
+            .filter(|cookie| cookie.expires.is_some() && !cookie.is_expired(now))
+            .collect();
+        let json = serde_json::to_string_pretty(&saved)?;
+        std::fs::write(path, json)?;
+        tracing::debug!("Saved {} cookies to {:?}", saved.len(), path);
+        Ok(())
+    }
+
+    /// Save to the configured storage path
+    pub fn save_to_storage(&self) -> Result<()> {
+        if let Some(path) = &self.storage_path {
+            self.save(path)
+        } else {
+            Err(anyhow!("No storage path configured"))
+        }
+    }
+
+    /// Load cookies from file, dropping expired ones
+    pub fn load(&mut self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let json = std::fs::read_to_string(path)?;
+        let cookies: Vec<Cookie> = serde_json::from_str(&json)?;
+        let now = SystemTime::now();
+        self.cookies = cookies
+            .into_iter()
+            .filter(|cookie| !cookie.is_expired(now))
+            .collect();
+        tracing::debug!("Loaded cookies from {:?}", path);
+        Ok(())
+    }
+}
+
+impl Default for CookieStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Get the default cookie path for a request path: its directory
+fn default_path(path: &str) -> String {
+    match path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(index) => path[..index].to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_cookie_matching() {
+        let mut store = CookieStore::new();
+        store
+            .set_from_header("https://example.com/account/login", "sid=abc; Secure")
+            .unwrap();
+        store
+            .set_from_header(
+                "https://www.example.com/",
+                "theme=dark; Domain=example.com; Path=/",
+            )
+            .unwrap();
+
+        assert_eq!(
+            store
+                .cookie_header("https://example.com/account/settings")
+                .as_deref(),
+            Some("sid=abc; theme=dark")
+        );
+        // Secure cookies are not sent over HTTP, and paths must match
+        assert_eq!(
+            store
+                .cookie_header("http://example.com/account/")
+                .as_deref(),
+            Some("theme=dark")
+        );
+        assert_eq!(
+            store
+                .cookie_header("https://example.com/accounts")
+                .as_deref(),
+            Some("theme=dark")
+        );
+        // Host-only cookies skip subdomains
+        assert_eq!(
+            store
+                .cookie_header("https://docs.example.com/account/")
+                .as_deref(),
+            Some("theme=dark")
+        );
+        assert_eq!(store.cookie_header("https://other.com/"), None);
+
+        assert!(store
+            .set_from_header("https://example.com/", "x=1; Domain=other.com")
+            .is_err());
+    }
+
+    #[test]
+    fn test_public_suffix_domains_are_rejected() {
+        let mut store = CookieStore::new();
+        assert!(store
+            .set_from_header("https://example.com/", "x=1; Domain=com")
+            .is_err());
+        assert!(store
+            .set_from_header("https://foo.co.uk/", "x=1; Domain=co.uk")
+            .is_err());
+        assert!(store
+            .set_from_header("https://app.herokuapp.com/", "x=1; Domain=herokuapp.com")
+            .is_err());
+        assert!(store.cookies().is_empty());
+
+        // A registrable domain under a multi-label suffix is still allowed
+        store
+            .set_from_header("https://www.foo.co.uk/", "x=1; Domain=foo.co.uk")
+            .unwrap();
+        assert_eq!(
+            store.cookie_header("https://shop.foo.co.uk/").as_deref(),
+            Some("x=1")
+        );
+        assert_eq!(store.cookie_header("https://bar.co.uk/"), None);
+    }
+
+    #[test]
+    fn test_cookie_replacement_and_deletion() {
+        let mut store = CookieStore::new();
+        store
+            .set_from_header("https://example.com/", "a=1")
+            .unwrap();
+        store
+            .set_from_header("https://example.com/", "a=2")
+            .unwrap();
+        assert_eq!(
+            store.cookie_header("https://example.com/").as_deref(),
+            Some("a=2")
+        );
+
+        store
+            .set_from_header("https://example.com/", "a=; Max-Age=0")
+            .unwrap();
+        assert!(store.cookies().is_empty());
+    }
+
+    #[test]
+    fn test_max_age_is_clamped() {
+        let mut store = CookieStore::new();
+        store
+            .set_from_header("https://example.com/", "a=1; Max-Age=9223372036854775807")
+            .unwrap();
+        let expires = store.cookies()[0].expires.unwrap();
+        assert!(expires <= SystemTime::now() + MAX_COOKIE_AGE);
+
+        // A bad Max-Age leaves a session cookie rather than rejecting it
+        store
+            .set_from_header("https://example.com/", "b=2; Max-Age=soon; Secure")
+            .unwrap();
+        assert_eq!(store.cookies()[1].expires, None);
+        assert!(store.cookies()[1].secure);
+
+        store
+            .set_from_header("https://example.com/", "a=; Max-Age=-5")
+            .unwrap();
+        assert_eq!(store.cookies().len(), 1);
+    }
+
+    #[test]
+    fn test_only_persistent_cookies_are_saved() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_data = UserDataManager::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let mut store = CookieStore::from_user_data(&user_data).unwrap();
+        assert!(store.is_persistent());
+        store
+            .set_from_header("https://example.com/", "session=1")
+            .unwrap();
+        store
+            .set_from_header("https://example.com/", "remember=1; Max-Age=3600")
+            .unwrap();
+        store.save_to_storage().unwrap();
+
+        let reloaded = CookieStore::from_user_data(&user_data).unwrap();
+        assert_eq!(
+            reloaded.cookie_header("https://example.com/").as_deref(),
+            Some("remember=1")
+        );
+    }
+
+    #[test]
+    fn test_in_memory_store_is_not_saved() {
+        let mut store = CookieStore::new();
+        assert!(!store.is_persistent());
+        store
+            .set_from_header("https://example.com/", "remember=1; Max-Age=3600")
+            .unwrap();
+        assert!(store.save_to_storage().is_err());
+    }
+}
@@ -0,0 +1,673 @@
+//! Download tracking
+//!
+//! Each download streams to a file in the download directory on a background
+//! task. The list of downloads can be saved so interrupted downloads are still
+//! shown after a restart.
+
+use crate::userdata::{DataType, UserDataManager};
+use anyhow::{anyhow, Result};
+use horizon_networking::client::HttpClient;
+use horizon_url::ParsedUrl;
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::task::JoinHandle;
+
+/// File name used for the download list inside the user data history directory
+const DOWNLOADS_FILE: &str = "downloads.json";
+
+/// State of a download
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DownloadState {
+    /// Waiting for its transfer to start
+    Queued,
+    /// Transferring
+    Active,
+    /// Stopped by the user, with a partial file left on disk
+    Paused,
+    /// Finished successfully
+    Completed,
+    /// Stopped by an error
+    Failed,
+}
+
+impl DownloadState {
+    /// Check whether the download is queued or transferring
+    pub fn is_active(self) -> bool {
+        matches!(self, DownloadState::Queued | DownloadState::Active)
+    }
+}
+
+/// A tracked download
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Download {
+    /// Unique download ID
+    pub id: String,
+    /// URL being downloaded
+    pub url: String,
+    /// File name in the download directory
+    pub filename: String,
+    /// Full path of the destination file
+    pub path: PathBuf,
+    /// Bytes written so far
+    pub bytes_done: u64,
+    /// Total size, if the server reported it
+    pub total: Option<u64>,
+    /// Current state
+    pub state: DownloadState,
+    /// Error message of a failed download
+    pub error: Option<String>,
+}
+
+/// Download manager running and tracking downloads
+pub struct DownloadManager {
+    /// Client downloads are fetched with
+    client: HttpClient,
+    /// Directory new downloads are saved to
+    download_dir: PathBuf,
+    /// Downloads in start order, shared with the transfer tasks
+    downloads: Arc<Mutex<Vec<Download>>>,
+    /// Running transfer tasks by download ID
+    tasks: HashMap<String, JoinHandle<()>>,
+    /// Path to the downloads file
+    storage_path: Option<PathBuf>,
+}
+
+impl DownloadManager {
+    /// Create a new, in-memory download manager saving to a directory
+    pub fn new(client: HttpClient, download_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            client,
+            download_dir: download_dir.into(),
+            downloads: Arc::new(Mutex::new(Vec::new())),
+            tasks: HashMap::new(),
+            storage_path: None,
+        }
+    }
+
+    /// Create a download manager with a storage path
+    ///
+    /// Downloads that were queued or active when the list was saved are
+    /// loaded as paused, since their transfers did not survive the restart.
+    pub fn with_storage_path(
+        client: HttpClient,
+        download_dir: impl Into<PathBuf>,
+        path: PathBuf,
+    ) -> Result<Self> {
+        let mut manager = Self::new(client, download_dir);
+        manager.storage_path = Some(path.clone());
+
+        // Load existing downloads if file exists
+        if path.exists() {
+            manager.load(&path)?;
+        }
+
+        Ok(manager)
+    }
+
+    /// Create a download manager whose list is stored in the user data
+    /// history directory, so clearing history also clears it
+    pub fn from_user_data(
+        client: HttpClient,
+        download_dir: impl Into<PathBuf>,
+        user_data: &UserDataManager,
+    ) -> Result<Self> {
+        let dir = user_data.path_for(DataType::History);
+        if !dir.exists() {
+            std::fs::create_dir_all(&dir)?;
+        }
+
+        Self::with_storage_path(client, download_dir, dir.join(DOWNLOADS_FILE))
+    }
+
+    /// Fetch downloads started or resumed from now on with another client
+    pub fn set_client(&mut self, client: HttpClient) {
+        self.client = client;
+    }
+
+    /// Start downloading a URL into the download directory, returning its ID
+    ///
+    /// Must be called within a Tokio runtime; the transfer runs on a spawned task.
+    pub fn start(&mut self, url: &str) -> Result<String> {
+        ParsedUrl::parse(url)?;
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let path = self.unique_destination(&download_destination(&self.download_dir, url));
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        self.lock_downloads().push(Download {
+            id: id.clone(),
+            url: url.to_string(),
+            filename,
+            path,
+            bytes_done: 0,
+            total: None,
+            state: DownloadState::Queued,
+            error: None,
+        });
+        self.spawn(&id);
+
+        tracing::info!("Started download {} of {}", id, url);
+        Ok(id)
+    }
+
+    /// Pause a queued or active download, keeping its partial file
+    pub async fn pause(&mut self, id: &str) -> Result<()> {
+        let state = self.state(id)?;
+        if !matches!(state, DownloadState::Queued | DownloadState::Active) {
+            return Err(anyhow!("Cannot pause a {:?} download", state));
+        }
+
+        self.stop_task(id).await;
+        self.update(id, |download| download.state = DownloadState::Paused);
+        tracing::info!("Paused download {}", id);
+        Ok(())
+    }
+
+    /// Resume a paused or failed download
+    ///
+    /// The transfer restarts from the beginning, overwriting the partial file.
+    pub fn resume(&mut self, id: &str) -> Result<()> {
+        let state = self.state(id)?;
+        if !matches!(state, DownloadState::Paused | DownloadState::Failed) {
+            return Err(anyhow!("Cannot resume a {:?} download", state));
+        }
+
+        self.update(id, |download| {
+            download.state = DownloadState::Queued;
+            download.bytes_done = 0;
+            download.error = None;
+        });
+        self.spawn(id);
+        tracing::info!("Resumed download {}", id);
+        Ok(())
+    }
+
+    /// Cancel a download and remove it from the list
+    ///
+    /// The partial file of an unfinished download is deleted; a completed
+    /// download's file is kept.
+    pub async fn cancel(&mut self, id: &str) -> Result<()> {
+        let state = self.state(id)?;
+        self.stop_task(id).await;
+
+        let download = {
+            let mut downloads = self.lock_downloads();
+            let index = downloads
+                .iter()
+                .position(|download| download.id == id)
+                .ok_or_else(|| anyhow!("Download not found: {}", id))?;
+            downloads.remove(index)
+        };
+
+        if state != DownloadState::Completed {
+            match tokio::fs::remove_file(&download.path).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        tracing::info!("Cancelled download {}", id);
+        Ok(())
+    }
+
+    /// Remove completed downloads from the list, keeping their files
+    ///
+    /// Returns how many downloads were removed.
+    pub fn clear_completed(&mut self) -> usize {
+        let mut downloads = self.lock_downloads();
+        let before = downloads.len();
+        downloads.retain(|download| download.state != DownloadState::Completed);
+        before - downloads.len()
+    }
+
+    /// List downloads in start order
+    pub fn list(&self) -> Vec<Download> {
+        self.lock_downloads().clone()
+    }
+
+    /// Get a download by ID
+    pub fn get(&self, id: &str) -> Option<Download> {
+        self.lock_downloads()
+            .iter()
+            .find(|download| download.id == id)
+            .cloned()
+    }
+
+    /// Count the queued and transferring downloads
+    pub fn active_count(&self) -> usize {
+        self.lock_downloads()
+            .iter()
+            .filter(|download| download.state.is_active())
+            .count()
+    }
+
+    /// Get the combined progress of the queued and transferring downloads,
+    /// from 0.0 to 1.0, or `None` if none are
+    pub fn aggregate_progress(&self) -> Option<f32> {
+        aggregate_progress(&self.lock_downloads())
+    }
+
+    /// Save the download list to file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(&*self.lock_downloads())?;
+        std::fs::write(path, json)?;
+        tracing::debug!("Saved downloads to {:?}", path);
+        Ok(())
+    }
+
+    /// Save to the configured storage path
+    pub fn save_to_storage(&self) -> Result<()> {
+        if let Some(path) = &self.storage_path {
+            self.save(path)
+        } else {
+            Err(anyhow!("No storage path configured"))
+        }
+    }
+
+    /// Load the download list from file
+    pub fn load(&mut self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let json = std::fs::read_to_string(path)?;
+        let mut downloads: Vec<Download> = serde_json::from_str(&json)?;
+        for download in &mut downloads {
+            if matches!(
+                download.state,
+                DownloadState::Queued | DownloadState::Active
+            ) {
+                download.state = DownloadState::Paused;
+            }
+        }
+
+        *self.lock_downloads() = downloads;
+        tracing::debug!("Loaded downloads from {:?}", path);
+        Ok(())
+    }
+
+    /// Spawn the transfer task for a download
+    fn spawn(&mut self, id: &str) {
+        let Some(download) = self.get(id) else {
+            return;
+        };
+        let client = self.client.clone();
+        let downloads = Arc::clone(&self.downloads);
+        let id = id.to_string();
+
+        let task_id = id.clone();
+        let handle = tokio::spawn(async move {
+            let update = |change: &dyn Fn(&mut Download)| {
+                let mut downloads = downloads.lock().unwrap_or_else(|e| e.into_inner());
+                if let Some(download) = downloads.iter_mut().find(|d| d.id == task_id) {
+                    change(download);
+                }
+            };
+
+            update(&|download| download.state = DownloadState::Active);
+            let result = client
+                .download_to(&download.url, &download.path, |done, total| {
+                    update(&|download| {
+                        download.bytes_done = done;
+                        download.total = total;
+                    })
+                })
+                .await;
+
+            match result {
+                Ok(written) => update(&|download| {
+                    download.bytes_done = written;
+                    download.state = DownloadState::Completed;
+                }),
+                Err(e) => {
+                    tracing::warn!("Download {} failed: {}", task_id, e);
+                    let error = e.to_string();
+                    update(&|download| {
+                        download.state = DownloadState::Failed;
+                        download.error = Some(error.clone());
+                    });
+                }
+            }
+        });
+
+        self.tasks.insert(id, handle);
+    }
+
+    /// Abort a download's transfer task and wait for it to stop
+    async fn stop_task(&mut self, id: &str) {
+        if let Some(handle) = self.tasks.remove(id) {
+            handle.abort();
+            // The task has stopped once its handle resolves, cancelled or not
+            let _ = handle.await;
+        }
+    }
+
+    /// Get the state of a download
+    fn state(&self, id: &str) -> Result<DownloadState> {
+        self.get(id)
+            .map(|download| download.state)
+            .ok_or_else(|| anyhow!("Download not found: {}", id))
+    }
+
+    /// Apply a change to a download
+    fn update(&self, id: &str, change: impl FnOnce(&mut Download)) {
+        if let Some(download) = self
+            .lock_downloads()
+            .iter_mut()
+            .find(|download| download.id == id)
+        {
+            change(download);
+        }
+    }
+
+    /// Pick a destination path not used by a file or another download
+    ///
+    /// Clashing names get a numeric suffix, e.g. `report (1).pdf`.
+    fn unique_destination(&self, destination: &Path) -> PathBuf {
+        let stem = destination
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let extension = destination
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy()))
+            .unwrap_or_default();
+        let dir = destination.parent().unwrap_or(&self.download_dir);
+
+        let downloads = self.lock_downloads();
+        let is_taken =
+            |path: &Path| path.exists() || downloads.iter().any(|download| download.path == path);
+
+        let mut path = destination.to_path_buf();
+        let mut suffix = 1;
+        while is_taken(&path) {
+            path = dir.join(format!("{} ({}){}", stem, suffix, extension));
+            suffix += 1;
+        }
+        path
+    }
+
+    fn lock_downloads(&self) -> MutexGuard<'_, Vec<Download>> {
+        // A poisoned list is still usable; every update leaves a whole entry
+        self.downloads.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Get the combined progress of the active downloads in a list
+///
+/// Progress is bytes done over total bytes, so large downloads weigh more.
+/// Downloads of unknown size are left out of the ratio.
+fn aggregate_progress(downloads: &[Download]) -> Option<f32> {
+    let mut active = downloads
+        .iter()
+        .filter(|download| download.state.is_active())
+        .peekable();
+    active.peek()?;
+
+    let (done, total) = active
+        .filter_map(|download| Some((download.bytes_done, download.total?)))
+        .fold((0u64, 0u64), |(done, total), (d, t)| {
+            (done + d.min(t), total + t)
+        });
+    if total == 0 {
+        return Some(0.0);
+    }
+    Some(done as f32 / total as f32)
+}
+
+/// Get the file name a download of a URL is saved as
+///
+/// Uses the percent-decoded last path segment, falling back to the host and
+/// then to `download`. Path separators in the decoded name are replaced so
+/// the file always lands in the download directory.
+pub fn file_name_for(url: &str) -> String {
+    let parsed = ParsedUrl::parse(url).ok();
+    let name = parsed
+        .as_ref()
+        .and_then(|url| url.file_name().or_else(|| url.host()))
+        .map(|name| {
+            percent_decode_str(name)
+                .decode_utf8_lossy()
+                .replace(['/', '\\'], "_")
+        })
+        .unwrap_or_default();
+
+    match name.trim() {
+        "" | "." | ".." => "download".to_string(),
+        _ => name,
+    }
+}
+
+/// Get where a download of a URL is saved in a download directory
+pub fn download_destination(download_dir: &Path, url: &str) -> PathBuf {
+    download_dir.join(file_name_for(url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    /// Serve every request with a response, optionally holding the
+    /// connection open afterwards so the download never finishes
+    async fn serve(response: &'static str, stall: bool) -> (String, JoinHandle<()>) {
+        let hold = if stall {
+            Duration::from_secs(60)
+        } else {
+            Duration::ZERO
+        };
+        let (url, server) = horizon_networking::test_server::serve_and_hold(
+            move |_| response.as_bytes().to_vec(),
+            hold,
+        )
+        .await;
+
+        (format!("{}/files/report.txt", url), server)
+    }
+
+    /// Wait until a download satisfies a condition
+    async fn wait_for(manager: &DownloadManager, id: &str, done: impl Fn(&Download) -> bool) {
+        for _ in 0..500 {
+            if manager.get(id).is_some_and(|download| done(&download)) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("Timed out waiting for download: {:?}", manager.get(id));
+    }
+
+    #[tokio::test]
+    async fn test_download_completes() {
+        let (url, _server) = serve(
+            "HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello",
+            false,
+        )
+        .await;
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = DownloadManager::new(HttpClient::new().unwrap(), temp_dir.path());
+
+        let id = manager.start(&url).unwrap();
+        wait_for(&manager, &id, |d| d.state == DownloadState::Completed).await;
+
+        let download = manager.get(&id).unwrap();
+        assert_eq!(download.filename, "report.txt");
+        assert_eq!(download.bytes_done, 5);
+        assert_eq!(download.total, Some(5));
+        assert_eq!(std::fs::read_to_string(&download.path).unwrap(), "hello");
+        assert!(manager.pause(&id).await.is_err());
+
+        assert_eq!(manager.clear_completed(), 1);
+        assert!(manager.list().is_empty());
+        assert!(download.path.exists());
+        assert_eq!(manager.clear_completed(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_download_fails_on_error_status() {
+        let (url, _server) = serve(
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            false,
+        )
+        .await;
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = DownloadManager::new(HttpClient::new().unwrap(), temp_dir.path());
+
+        let id = manager.start(&url).unwrap();
+        wait_for(&manager, &id, |d| d.state == DownloadState::Failed).await;
+        assert!(manager.get(&id).unwrap().error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_pause_resume_and_cancel() {
+        let (url, _server) = serve(
+            "HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\npartial",
+            true,
+        )
+        .await;
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = DownloadManager::new(HttpClient::new().unwrap(), temp_dir.path());
+
+        let id = manager.start(&url).unwrap();
+        wait_for(&manager, &id, |d| {
+            d.state == DownloadState::Active && d.bytes_done == 7
+        })
+        .await;
+        assert_eq!(manager.get(&id).unwrap().total, Some(100));
+        assert_eq!(manager.active_count(), 1);
+        assert_eq!(manager.aggregate_progress(), Some(0.07));
+
+        manager.pause(&id).await.unwrap();
+        assert_eq!(manager.active_count(), 0);
+        assert_eq!(manager.aggregate_progress(), None);
+        let paused = manager.get(&id).unwrap();
+        assert_eq!(paused.state, DownloadState::Paused);
+        assert!(paused.path.exists());
+        assert!(manager.pause(&id).await.is_err());
+
+        manager.resume(&id).unwrap();
+        assert!(manager.resume(&id).is_err());
+        wait_for(&manager, &id, |d| {
+            d.state == DownloadState::Active && d.bytes_done == 7
+        })
+        .await;
+
+        manager.cancel(&id).await.unwrap();
+        assert!(manager.list().is_empty());
+        assert!(!paused.path.exists());
+        assert!(manager.cancel(&id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_clashing_names_get_suffix() {
+        let (url, _server) = serve("HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n", true).await;
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("report.txt"), "existing").unwrap();
+        let mut manager = DownloadManager::new(HttpClient::new().unwrap(), temp_dir.path());
+
+        let first = manager.start(&url).unwrap();
+        let second = manager.start(&url).unwrap();
+
+        assert_eq!(manager.get(&first).unwrap().filename, "report (1).txt");
+        assert_eq!(manager.get(&second).unwrap().filename, "report (2).txt");
+        assert!(manager.start("not a url").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_downloads_reload_paused() {
+        let (url, _server) = serve("HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\n", true).await;
+        let temp_dir = TempDir::new().unwrap();
+        let storage_path = temp_dir.path().join("downloads.json");
+
+        let mut manager = DownloadManager::with_storage_path(
+            HttpClient::new().unwrap(),
+            temp_dir.path(),
+            storage_path.clone(),
+        )
+        .unwrap();
+        let id = manager.start(&url).unwrap();
+        manager.save_to_storage().unwrap();
+
+        let reloaded = DownloadManager::with_storage_path(
+            HttpClient::new().unwrap(),
+            temp_dir.path(),
+            storage_path,
+        )
+        .unwrap();
+        let download = reloaded.get(&id).unwrap();
+        assert_eq!(download.url, url);
+        assert_eq!(download.state, DownloadState::Paused);
+    }
+
+    #[test]
+    fn test_aggregate_progress() {
+        let download = |state, bytes_done, total| Download {
+            id: String::new(),
+            url: String::new(),
+            filename: String::new(),
+            path: PathBuf::new(),
+            bytes_done,
+            total,
+            state,
+            error: None,
+        };
+
+        assert_eq!(aggregate_progress(&[]), None);
+        let idle = [
+            download(DownloadState::Completed, 100, Some(100)),
+            download(DownloadState::Paused, 10, Some(100)),
+            download(DownloadState::Failed, 0, None),
+        ];
+        assert_eq!(aggregate_progress(&idle), None);
+
+        // Only the active downloads count: (25 + 0) / (100 + 300)
+        let mixed = [
+            download(DownloadState::Completed, 500, Some(500)),
+            download(DownloadState::Active, 25, Some(100)),
+            download(DownloadState::Queued, 0, Some(300)),
+            download(DownloadState::Paused, 50, Some(100)),
+            download(DownloadState::Active, 40, None),
+        ];
+        assert_eq!(aggregate_progress(&mixed), Some(0.0625));
+
+        // Active downloads of unknown size give no progress to report
+        let unknown = [download(DownloadState::Active, 40, None)];
+        assert_eq!(aggregate_progress(&unknown), Some(0.0));
+    }
+
+    #[test]
+    fn test_file_name_for() {
+        assert_eq!(
+            file_name_for("https://example.com/a/report.pdf?x=1"),
+            "report.pdf"
+        );
+        assert_eq!(file_name_for("https://example.com/dir/"), "dir");
+        assert_eq!(file_name_for("https://example.com/"), "example.com");
+        assert_eq!(file_name_for("not a url"), "download");
+    }
+
+    #[test]
+    fn test_file_name_for_decodes() {
+        assert_eq!(
+            file_name_for("https://example.com/my%20file.pdf"),
+            "my file.pdf"
+        );
+        assert_eq!(
+            file_name_for("https://example.com/caf%C3%A9.txt"),
+            "café.txt"
+        );
+        // Encoded separators cannot escape the download directory
+        assert_eq!(
+            file_name_for("https://example.com/..%2F..%2Fetc%2Fpasswd"),
+            ".._.._etc_passwd"
+        );
+        assert_eq!(file_name_for("https://example.com/%20"), "download");
+    }
+}
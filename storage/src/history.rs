@@ -0,0 +1,576 @@
+//! Browsing history storage backed by the user data directory
+
+use crate::userdata::{DataType, UserDataManager};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// File name used for history inside the history data directory
+const HISTORY_FILE: &str = "history.json";
+
+/// A visited page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Visited URL
+    pub url: String,
+    /// Page title (may be empty if unknown)
+    pub title: String,
+    /// Time of the most recent visit
+    pub visited_at: SystemTime,
+    /// Number of times the URL has been visited
+    pub visit_count: u32,
+}
+
+/// History entries visited on the same day
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryDay {
+    /// Day number, counted in local calendar days since the Unix epoch
+    pub day: u64,
+    /// Entries visited that day, most recent first
+    pub entries: Vec<HistoryEntry>,
+}
+
+/// Common time windows for clearing history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClearWindow {
+    LastHour,
+    Today,
+    All,
+}
+
+impl ClearWindow {
+    /// Get the label for this window
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::LastHour => "Last hour",
+            Self::Today => "Today",
+            Self::All => "All history",
+        }
+    }
+
+    /// Get all clear windows
+    pub fn all() -> &'static [Self] {
+        &[Self::LastHour, Self::Today, Self::All]
+    }
+
+    /// Get the `[from, to]` range the window covers at a point in time
+    pub fn range(&self, now: SystemTime) -> (SystemTime, SystemTime) {
+        let from = match self {
+            Self::LastHour => now
+                .checked_sub(Duration::from_secs(60 * 60))
+                .unwrap_or(SystemTime::UNIX_EPOCH),
+            Self::Today => start_of_day(now),
+            Self::All => SystemTime::UNIX_EPOCH,
+        };
+        (from, now)
+    }
+}
+
+/// History store, keeping one entry per URL
+pub struct HistoryStore {
+    /// History entries
+    entries: Vec<HistoryEntry>,
+    /// Path to the history file
+    storage_path: Option<PathBuf>,
+    /// Bumped whenever the entries change
+    revision: u64,
+}
+
+impl HistoryStore {
+    /// Create a new, in-memory history store
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            storage_path: None,
+            revision: 0,
+        }
+    }
+
+    /// Create a history store with a storage path
+    pub fn with_storage_path(path: PathBuf) -> Result<Self> {
+        let mut store = Self::new();
+        store.storage_path = Some(path.clone());
+
+        // Load existing history if file exists
+        if path.exists() {
+            store.load(&path)?;
+        }
+
+        Ok(store)
+    }
+
+    /// Create a history store in the user data history directory
+    pub fn from_user_data(user_data: &UserDataManager) -> Result<Self> {
+        let dir = user_data.path_for(DataType::History);
+        if !dir.exists() {
+            std::fs::create_dir_all(&dir)?;
+        }
+
+        Self::with_storage_path(dir.join(HISTORY_FILE))
+    }
+
+    /// Record a visit to a URL now
+    pub fn record_visit(&mut self, url: &str, title: &str) {
+        self.record_visit_at(url, title, SystemTime::now());
+    }
+
+    /// Record a visit to a URL at a specific time
+    ///
+    /// Repeat visits bump the visit count of the existing entry instead of
+    /// adding a new one. An empty title keeps the previously known title.
+    pub fn record_visit_at(&mut self, url: &str, title: &str, visited_at: SystemTime) {
+        self.revision += 1;
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.url == url) {
+            entry.visit_count += 1;
+            entry.visited_at = visited_at;
+            if !title.is_empty() {
+                entry.title = title.to_string();
+            }
+        } else {
+            self.entries.push(HistoryEntry {
+                url: url.to_string(),
+                title: title.to_string(),
+                visited_at,
+                visit_count: 1,
+            });
+        }
+    }
+
+    /// Search history by URL or title (case-insensitive), most recent first
+    pub fn search(&self, query: &str) -> Vec<HistoryEntry> {
+        let query_lower = query.to_lowercase();
+        let mut results: Vec<HistoryEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| {
+                entry.url.to_lowercase().contains(&query_lower)
+                    || entry.title.to_lowercase().contains(&query_lower)
+            })
+            .cloned()
+            .collect();
+
+        results.sort_by_key(|e| std::cmp::Reverse(e.visited_at));
+        results
+    }
+
+    /// Get the most recently visited entries
+    pub fn recent(&self, limit: usize) -> Vec<HistoryEntry> {
+        let mut results = self.entries.clone();
+        results.sort_by_key(|e| std::cmp::Reverse(e.visited_at));
+        results.truncate(limit);
+        results
+    }
+
+    /// Suggest URLs for an address bar prefix
+    ///
+    /// Matches the prefix (case-insensitive) against the full URL and against
+    /// the URL with its scheme and `www.` stripped, so `exa` finds
+    /// `https://www.example.com`. Results are ranked by visit count, then by
+    /// most recent visit. An empty prefix yields no suggestions.
+    pub fn suggest(&self, prefix: &str, limit: usize) -> Vec<String> {
+        let prefix = prefix.trim().to_lowercase();
+        if prefix.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches: Vec<&HistoryEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| {
+                let url = entry.url.to_lowercase();
+                url.starts_with(&prefix) || strip_url(&url).starts_with(&prefix)
+            })
+            .collect();
+
+        matches.sort_by(|a, b| {
+            b.visit_count
+                .cmp(&a.visit_count)
+                .then(b.visited_at.cmp(&a.visited_at))
+        });
+
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|entry| entry.url.clone())
+            .collect()
+    }
+
+    /// Remove entries last visited within `[from, to]`, returning how many were removed
+    pub fn clear_range(&mut self, from: SystemTime, to: SystemTime) -> usize {
+        let initial_len = self.entries.len();
+        self.entries
+            .retain(|e| e.visited_at < from || e.visited_at > to);
+
+        let removed = initial_len - self.entries.len();
+        self.revision += 1;
+        tracing::info!("Cleared {} history entries", removed);
+        removed
+    }
+
+    /// Remove the entry for a URL, returning whether one was removed
+    pub fn remove(&mut self, url: &str) -> bool {
+        let initial_len = self.entries.len();
+        self.entries.retain(|e| e.url != url);
+        self.revision += 1;
+        self.entries.len() < initial_len
+    }
+
+    /// Remove the entries visited within a clear window, returning how many were removed
+    pub fn clear_window(&mut self, window: ClearWindow, now: SystemTime) -> usize {
+        let (from, to) = window.range(now);
+        self.clear_range(from, to)
+    }
+
+    /// Clear all history
+    pub fn clear_all(&mut self) {
+        self.entries.clear();
+        self.revision += 1;
+        tracing::info!("Cleared all history");
+    }
+
+    /// Get a number that changes whenever the entries do, so views built from
+    /// them know when to rebuild
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Count history entries
+    pub fn count(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Save history to file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.entries)?;
+        std::fs::write(path, json)?;
+        tracing::debug!("Saved history to {:?}", path);
+        Ok(())
+    }
+
+    /// Save to the configured storage path
+    pub fn save_to_storage(&self) -> Result<()> {
+        if let Some(path) = &self.storage_path {
+            self.save(path)
+        } else {
+            Err(anyhow!("No storage path configured"))
+        }
+    }
+
+    /// Load history from file
+    pub fn load(&mut self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let json = std::fs::read_to_string(path)?;
+        self.entries = serde_json::from_str(&json)?;
+        self.revision += 1;
+        tracing::debug!("Loaded history from {:?}", path);
+        Ok(())
+    }
+}
+
+/// Group entries by the local day they were visited, most recent first
+pub fn group_by_day(mut entries: Vec<HistoryEntry>) -> Vec<HistoryDay> {
+    entries.sort_by_key(|e| std::cmp::Reverse(e.visited_at));
+
+    let mut days: Vec<HistoryDay> = Vec::new();
+    for entry in entries {
+        let day = day_of(entry.visited_at);
+        match days.last_mut() {
+            Some(last) if last.day == day => last.entries.push(entry),
+            _ => days.push(HistoryDay {
+                day,
+                entries: vec![entry],
+            }),
+        }
+    }
+    days
+}
+
+/// Get the local calendar day number of a time, counted from the Unix epoch
+pub fn day_of(time: SystemTime) -> u64 {
+    let date = DateTime::<Local>::from(time).date_naive();
+    u64::try_from((date - NaiveDate::default()).num_days()).unwrap_or(0)
+}
+
+/// Get the local midnight starting the day a time falls on
+///
+/// If a clock change skips midnight, the day starts when the clocks do.
+fn start_of_day(time: SystemTime) -> SystemTime {
+    let local = DateTime::<Local>::from(time);
+    local
+        .with_time(NaiveTime::MIN)
+        .earliest()
+        .map(SystemTime::from)
+        .unwrap_or_else(|| {
+            let since_midnight = local.time() - NaiveTime::MIN;
+            time - since_midnight.to_std().unwrap_or_default()
+        })
+}
+
+/// Format a day number as a `YYYY-MM-DD` date
+pub fn format_day(day: u64) -> String {
+    // Civil-from-days conversion over 400-year eras
+    let z = day + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + u64::from(m <= 2);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+/// Strip the scheme and `www.` from a URL, leaving the host and path
+fn strip_url(url: &str) -> &str {
+    let without_scheme = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(url);
+    without_scheme
+        .strip_prefix("www.")
+        .unwrap_or(without_scheme)
+}
+
+impl Default for HistoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_visit_count_dedup_by_url() {
+        let mut store = HistoryStore::new();
+        store.record_visit("https://example.com", "Example");
+        store.record_visit("https://example.com", "");
+        store.record_visit("https://other.com", "Other");
+
+        assert_eq!(store.count(), 2);
+        let results = store.search("example.com");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].visit_count, 2);
+        assert_eq!(results[0].title, "Example");
+    }
+
+    #[test]
+    fn test_search_case_insensitive() {
+        let mut store = HistoryStore::new();
+        store.record_visit("https://rust-lang.org", "The Rust Programming Language");
+        store.record_visit("https://example.com", "Example Domain");
+
+        assert_eq!(store.search("RUST").len(), 1);
+        assert_eq!(store.search("domain").len(), 1);
+        assert_eq!(store.search("https").len(), 2);
+    }
+
+    #[test]
+    fn test_recent_orders_by_visit_time() {
+        let base = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let mut store = HistoryStore::new();
+        store.record_visit_at("https://a.com", "A", base);
+        store.record_visit_at("https://b.com", "B", base + Duration::from_secs(10));
+        store.record_visit_at("https://c.com", "C", base + Duration::from_secs(20));
+
+        let recent = store.recent(2);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].url, "https://c.com");
+        assert_eq!(recent[1].url, "https://b.com");
+    }
+
+    #[test]
+    fn test_clear_range() {
+        let base = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let mut store = HistoryStore::new();
+        store.record_visit_at("https://a.com", "A", base);
+        store.record_visit_at("https://b.com", "B", base + Duration::from_secs(10));
+        store.record_visit_at("https://c.com", "C", base + Duration::from_secs(20));
+
+        let removed = store.clear_range(
+            base + Duration::from_secs(5),
+            base + Duration::from_secs(20),
+        );
+
+        assert_eq!(removed, 2);
+        assert_eq!(store.count(), 1);
+        assert_eq!(store.recent(10)[0].url, "https://a.com");
+    }
+
+    #[test]
+    fn test_clear_window() {
+        let now = local_time(2024, 1, 2, 10, 30);
+        let mut store = HistoryStore::new();
+        store.record_visit_at("https://recent.com", "", now - Duration::from_secs(600));
+        store.record_visit_at(
+            "https://morning.com",
+            "",
+            now - Duration::from_secs(3 * 3_600),
+        );
+        store.record_visit_at(
+            "https://yesterday.com",
+            "",
+            now - Duration::from_secs(86_400),
+        );
+
+        assert_eq!(store.clear_window(ClearWindow::LastHour, now), 1);
+        assert_eq!(store.clear_window(ClearWindow::Today, now), 1);
+        assert_eq!(store.recent(10)[0].url, "https://yesterday.com");
+        assert_eq!(store.clear_window(ClearWindow::All, now), 1);
+        assert_eq!(store.count(), 0);
+    }
+
+    #[test]
+    fn test_today_starts_at_local_midnight() {
+        let now = local_time(2024, 1, 2, 0, 20);
+        assert_eq!(
+            ClearWindow::Today.range(now),
+            (local_time(2024, 1, 2, 0, 0), now)
+        );
+
+        let mut store = HistoryStore::new();
+        store.record_visit_at("https://late.com", "", local_time(2024, 1, 1, 23, 50));
+        store.record_visit_at("https://early.com", "", local_time(2024, 1, 2, 0, 10));
+        assert_eq!(store.clear_window(ClearWindow::Today, now), 1);
+        assert_eq!(store.recent(10)[0].url, "https://late.com");
+    }
+
+    #[test]
+    fn test_revision_changes_with_entries() {
+        let mut store = HistoryStore::new();
+        let mut last = store.revision();
+        let mut assert_changed = |store: &HistoryStore| {
+            assert_ne!(store.revision(), last);
+            last = store.revision();
+        };
+
+        store.record_visit("https://example.com", "Example");
+        assert_changed(&store);
+        store.remove("https://example.com");
+        assert_changed(&store);
+        store.record_visit("https://example.com", "Example");
+        store.clear_all();
+        assert_changed(&store);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut store = HistoryStore::new();
+        store.record_visit("https://example.com", "Example");
+        store.record_visit("https://other.com", "Other");
+
+        assert!(store.remove("https://example.com"));
+        assert!(!store.remove("https://example.com"));
+        assert_eq!(store.count(), 1);
+    }
+
+    #[test]
+    fn test_group_by_day() {
+        let mut store = HistoryStore::new();
+        store.record_visit_at("https://a.com", "A", local_time(2024, 1, 1, 0, 1));
+        store.record_visit_at("https://b.com", "B", local_time(2024, 1, 2, 0, 2));
+        store.record_visit_at("https://c.com", "C", local_time(2024, 1, 1, 23, 59));
+        store.record_visit_at("https://d.com", "D", local_time(2024, 1, 2, 0, 1));
+
+        let days = group_by_day(store.recent(usize::MAX));
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].day, 19_724);
+        let urls: Vec<&str> = days[0].entries.iter().map(|e| e.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://b.com", "https://d.com"]);
+        let urls: Vec<&str> = days[1].entries.iter().map(|e| e.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://c.com", "https://a.com"]);
+
+        assert!(group_by_day(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_format_day() {
+        assert_eq!(format_day(0), "1970-01-01");
+        assert_eq!(format_day(19_723), "2024-01-01");
+        assert_eq!(format_day(19_782), "2024-02-29");
+        assert_eq!(format_day(11_016), "2000-02-29");
+    }
+
+    #[test]
+    fn test_suggest_ranks_by_visit_count_then_recency() {
+        let base = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let mut store = HistoryStore::new();
+        store.record_visit_at("https://example.com/old", "", base);
+        store.record_visit_at(
+            "https://example.com/new",
+            "",
+            base + Duration::from_secs(10),
+        );
+        store.record_visit_at("https://example.com/popular", "", base);
+        store.record_visit_at("https://example.com/popular", "", base);
+
+        let suggestions = store.suggest("https://example.com", 10);
+        assert_eq!(
+            suggestions,
+            vec![
+                "https://example.com/popular",
+                "https://example.com/new",
+                "https://example.com/old",
+            ]
+        );
+        assert_eq!(store.suggest("https://example.com", 1).len(), 1);
+    }
+
+    #[test]
+    fn test_suggest_matches_stripped_host() {
+        let mut store = HistoryStore::new();
+        store.record_visit("https://www.example.com/page", "");
+        store.record_visit("http://rust-lang.org", "");
+
+        assert_eq!(
+            store.suggest("exa", 6),
+            vec!["https://www.example.com/page"]
+        );
+        assert_eq!(store.suggest("RUST", 6), vec!["http://rust-lang.org"]);
+        assert_eq!(store.suggest("https://www.ex", 6).len(), 1);
+        assert!(store.suggest("page", 6).is_empty());
+    }
+
+    #[test]
+    fn test_suggest_edge_cases() {
+        let mut store = HistoryStore::new();
+        store.record_visit("about:settings", "");
+        store.record_visit("https://example.com", "");
+
+        assert!(store.suggest("", 6).is_empty());
+        assert!(store.suggest("   ", 6).is_empty());
+        assert_eq!(store.suggest("about:", 6), vec!["about:settings"]);
+    }
+
+    #[test]
+    fn test_persistence_across_reload() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_data = UserDataManager::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let mut store = HistoryStore::from_user_data(&user_data).unwrap();
+        store.record_visit("https://example.com", "Example");
+        store.save_to_storage().unwrap();
+
+        let reloaded = HistoryStore::from_user_data(&user_data).unwrap();
+        assert_eq!(reloaded.count(), 1);
+        assert_eq!(reloaded.recent(1)[0].url, "https://example.com");
+    }
+
+    /// Get a time on a local calendar day
+    fn local_time(year: i32, month: u32, day: u32, hour: u32, min: u32) -> SystemTime {
+        use chrono::TimeZone;
+        Local
+            .with_ymd_and_hms(year, month, day, hour, min, 0)
+            .unwrap()
+            .into()
+    }
+}
@@ -1,16 +1,23 @@
 //! # Horizon Storage
 //!
 //! Storage layer for the Horizon Browser.
-//! Provides user data storage, settings, profiles, secure storage, and password management.
+//! Provides user data storage, settings, profiles, secure storage, bookmarks,
+//! browsing history, cookies, downloads, per-site zoom, and password
+//! management.
 
+pub mod bookmarks;
+pub mod cookies;
+pub mod downloads;
+pub mod history;
 pub mod passwords;
 pub mod profile;
 pub mod secure;
 pub mod settings;
 pub mod userdata;
+pub mod zoom;
 
 use anyhow::Result;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Storage manager coordinates all storage operations
 pub struct StorageManager {
@@ -34,6 +41,16 @@ impl StorageManager {
         })
     }
 
+    /// Create a storage manager keeping its data in a profile's directory
+    pub fn for_profile(profile: &profile::Profile) -> Result<Self> {
+        Self::new(profile.path().to_path_buf())
+    }
+
+    /// Create a storage manager for the active profile in a data directory
+    pub fn for_active_profile(data_dir: &Path) -> Result<Self> {
+        Self::new(profile::active_profile_dir(data_dir)?)
+    }
+
     /// Initialize the storage system
     pub fn initialize(&mut self) -> Result<()> {
         tracing::info!("Initializing Storage Manager at {:?}", self.base_path);
@@ -45,6 +62,26 @@ impl StorageManager {
         &self.base_path
     }
 
+    /// Get the user data manager for this storage's directory
+    pub fn user_data(&self) -> Result<userdata::UserDataManager> {
+        userdata::UserDataManager::new(self.base_path.clone())
+    }
+
+    /// Open the bookmarks stored in this storage's directory
+    pub fn bookmarks(&self) -> Result<bookmarks::BookmarkManager> {
+        bookmarks::BookmarkManager::from_user_data(&self.user_data()?)
+    }
+
+    /// Open the browsing history stored in this storage's directory
+    pub fn history(&self) -> Result<history::HistoryStore> {
+        history::HistoryStore::from_user_data(&self.user_data()?)
+    }
+
+    /// Open the password store in this storage's directory, locked
+    pub fn passwords(&self) -> Result<passwords::PasswordManager> {
+        passwords::PasswordManager::from_user_data(&self.user_data()?)
+    }
+
     /// Get the settings
     pub fn settings(&self) -> &settings::Settings {
         &self.settings
@@ -59,6 +96,12 @@ impl StorageManager {
     pub fn save_settings(&self) -> Result<()> {
         self.settings.save(&self.base_path.join("settings.toml"))
     }
+
+    /// Reload settings from disk, picking up changes saved elsewhere
+    pub fn reload_settings(&mut self) -> Result<()> {
+        self.settings = settings::Settings::load(&self.base_path.join("settings.toml"))?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -79,4 +122,54 @@ mod tests {
         let mut manager = StorageManager::new(temp_dir.path().to_path_buf()).unwrap();
         assert!(manager.initialize().is_ok());
     }
+
+    #[test]
+    fn test_reload_settings() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = StorageManager::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let mut saved = settings::Settings::new();
+        saved.privacy.clear_on_exit = true;
+        saved.save(&temp_dir.path().join("settings.toml")).unwrap();
+
+        assert!(!manager.settings().privacy.clear_on_exit);
+        manager.reload_settings().unwrap();
+        assert!(manager.settings().privacy.clear_on_exit);
+    }
+
+    #[test]
+    fn test_profiles_are_isolated() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut profiles = profile::ProfileManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let work = profiles.create_profile("Work").unwrap();
+        let home = profiles.create_profile("Home").unwrap();
+
+        let mut work_storage = StorageManager::for_profile(&work).unwrap();
+        work_storage.settings_mut().general.homepage = "https://work.example.com".to_string();
+        work_storage.save_settings().unwrap();
+        let mut bookmarks = work_storage.bookmarks().unwrap();
+        bookmarks.add(bookmarks::Bookmark::new(
+            "https://intranet.example.com",
+            "Intranet",
+        ));
+        bookmarks.save_to_storage().unwrap();
+
+        let home_storage = StorageManager::for_profile(&home).unwrap();
+        assert_ne!(
+            home_storage.settings().general.homepage,
+            "https://work.example.com"
+        );
+        assert!(home_storage.bookmarks().unwrap().list().is_empty());
+        assert!(!home.path().join("settings.toml").exists());
+
+        let work_storage = StorageManager::for_profile(&work).unwrap();
+        assert_eq!(
+            work_storage.settings().general.homepage,
+            "https://work.example.com"
+        );
+        assert!(work_storage
+            .bookmarks()
+            .unwrap()
+            .contains("https://intranet.example.com"));
+    }
 }
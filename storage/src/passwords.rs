@@ -1,10 +1,33 @@
 //! Password management module for secure credential storage
+//!
+//! Passwords are stored encrypted with AES-256-GCM. The key is derived from a
+//! master password with Argon2, and the file starts with a header holding the
+//! salt and nonce.
 
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
 use anyhow::{anyhow, Result};
+use argon2::Argon2;
+use horizon_url::{is_public_suffix, ParsedUrl};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
 
+/// File name for encrypted passwords inside the data directory
+const PASSWORDS_FILE: &str = "passwords.dat";
+/// Magic bytes identifying an encrypted password file
+const FILE_MAGIC: &[u8; 4] = b"HZPW";
+/// Encrypted password file format version
+const FILE_VERSION: u8 = 1;
+/// Length of the key derivation salt in bytes
+const SALT_LEN: usize = 16;
+/// Length of the AES-GCM nonce in bytes
+const NONCE_LEN: usize = 12;
+/// Length of the derived AES-256 key in bytes
+const KEY_LEN: usize = 32;
+/// Length of the file header (magic, version, salt, nonce)
+const HEADER_LEN: usize = FILE_MAGIC.len() + 1 + SALT_LEN + NONCE_LEN;
+
 /// A stored password entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PasswordEntry {
@@ -40,13 +63,28 @@ impl PasswordEntry {
     }
 
     /// Normalize URL for consistent matching
+    ///
+    /// Keeps the host (without `www.`), any non-default port, and the path
+    /// without trailing slashes. The scheme, userinfo, query, and fragment are
+    /// dropped. Input without a scheme is treated as an `https` URL.
     fn normalize_url(url: &str) -> String {
-        // Remove protocol and trailing slashes
-        url.trim_start_matches("https://")
-            .trim_start_matches("http://")
-            .trim_start_matches("www.")
-            .trim_end_matches('/')
-            .to_lowercase()
+        let url = url.trim();
+        let parsed = ParsedUrl::parse(url)
+            .ok()
+            .filter(ParsedUrl::is_web)
+            .or_else(|| ParsedUrl::parse(&format!("https://{}", url)).ok())
+            .filter(|parsed| parsed.host().is_some());
+
+        let Some(parsed) = parsed else {
+            return url.trim_end_matches('/').to_lowercase();
+        };
+
+        let mut normalized = parsed.host_without_www().unwrap_or_default().to_string();
+        if let Some(port) = parsed.port() {
+            normalized.push_str(&format!(":{}", port));
+        }
+        normalized.push_str(parsed.path().trim_end_matches('/'));
+        normalized.to_lowercase()
     }
 
     /// Get the stored password (requires authentication in full implementation)
@@ -69,6 +107,59 @@ impl PasswordEntry {
     pub fn domain(&self) -> &str {
         &self.url
     }
+
+    /// Get the host part of a normalized URL
+    fn host_of(normalized_url: &str) -> &str {
+        normalized_url
+            .split(['/', '?', '#'])
+            .next()
+            .unwrap_or_default()
+            .split(':')
+            .next()
+            .unwrap_or_default()
+    }
+}
+
+/// How saved entries are matched against the URL being filled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchStrategy {
+    /// Only entries saved for the same normalized URL
+    #[default]
+    Exact,
+    /// Also entries saved for a parent domain of the requested host within the
+    /// same registrable domain, so a password saved for `google.com` is
+    /// offered on `mail.google.com`
+    IncludeSubdomains,
+}
+
+impl MatchStrategy {
+    /// Check whether an entry saved for `stored_url` matches `requested_url`
+    ///
+    /// Both URLs must already be normalized. Entries saved for a public
+    /// suffix such as `com` or `co.uk` are never widened to other hosts.
+    fn matches(self, stored_url: &str, requested_url: &str) -> bool {
+        if stored_url == requested_url {
+            return true;
+        }
+
+        match self {
+            Self::Exact => false,
+            Self::IncludeSubdomains => {
+                let stored_host = PasswordEntry::host_of(stored_url);
+                let requested_host = PasswordEntry::host_of(requested_url);
+                let registrable = |host: &str| {
+                    ParsedUrl::parse(&format!("https://{}", host))
+                        .ok()
+                        .and_then(|url| url.registrable_domain())
+                };
+                !is_public_suffix(stored_host)
+                    && requested_host
+                        .strip_suffix(stored_host)
+                        .is_some_and(|prefix| prefix.ends_with('.'))
+                    && registrable(stored_host) == registrable(requested_host)
+            }
+        }
+    }
 }
 
 /// Password manager for storing and retrieving credentials
@@ -79,6 +170,12 @@ pub struct PasswordManager {
     storage_path: Option<std::path::PathBuf>,
     /// Whether the manager has been modified
     modified: bool,
+    /// Key derived from the master password (None while locked)
+    key: Option<[u8; KEY_LEN]>,
+    /// Salt the key was derived with
+    salt: [u8; SALT_LEN],
+    /// How autofill matches saved entries against URLs
+    match_strategy: MatchStrategy,
 }
 
 impl PasswordManager {
@@ -88,24 +185,72 @@ impl PasswordManager {
             passwords: HashMap::new(),
             storage_path: None,
             modified: false,
+            key: None,
+            salt: rand::random(),
+            match_strategy: MatchStrategy::default(),
         }
     }
 
     /// Create a password manager with a storage path
+    ///
+    /// The manager starts locked; existing passwords are loaded by `unlock`.
     pub fn with_storage_path(path: std::path::PathBuf) -> Result<Self> {
         let mut manager = Self::new();
-        manager.storage_path = Some(path.clone());
+        manager.storage_path = Some(path);
+        Ok(manager)
+    }
+
+    /// Create a password manager stored in the user data directory
+    pub fn from_user_data(user_data: &crate::userdata::UserDataManager) -> Result<Self> {
+        Self::with_storage_path(user_data.data_dir().join(PASSWORDS_FILE))
+    }
 
-        // Load existing passwords if file exists
-        if path.exists() {
-            manager.load(&path)?;
+    /// Unlock the password store with the master password
+    ///
+    /// If the storage file exists, its entries are loaded as by `load`, and a
+    /// wrong master password returns an error leaving the store locked.
+    /// Otherwise a new key is derived for a fresh store.
+    pub fn unlock(&mut self, master_password: &str) -> Result<()> {
+        if let Some(path) = self.storage_path.clone().filter(|p| p.exists()) {
+            self.load(&path, master_password)?;
+            tracing::info!("Unlocked passwords from {:?}", path);
+        } else {
+            self.key = Some(derive_key(master_password, &self.salt)?);
+            tracing::info!("Unlocked new password store");
         }
 
-        Ok(manager)
+        Ok(())
+    }
+
+    /// Lock the password store, discarding the key and decrypted entries
+    ///
+    /// Unsaved changes are lost; call `save_to_storage` first to keep them.
+    pub fn lock(&mut self) {
+        if let Some(key) = self.key.as_mut() {
+            key.fill(0);
+        }
+        self.key = None;
+        self.passwords.clear();
+        self.modified = false;
+        tracing::info!("Locked password store");
+    }
+
+    /// Check if the password store is unlocked
+    pub fn is_unlocked(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Get the key, or an error if the store is locked
+    fn unlocked_key(&self) -> Result<&[u8; KEY_LEN]> {
+        self.key
+            .as_ref()
+            .ok_or_else(|| anyhow!("Password store is locked"))
     }
 
-    /// Add a new password entry
+    /// Add a new password entry (requires the store to be unlocked)
     pub fn add_password(&mut self, url: String, username: String, password: String) -> Result<()> {
+        self.unlocked_key()?;
+
         let entry = PasswordEntry::new(url.clone(), username.clone(), password);
         let normalized_url = PasswordEntry::normalize_url(&url);
 
@@ -139,13 +284,32 @@ impl PasswordManager {
             .unwrap_or_default()
     }
 
-    /// Get a specific password entry
-    pub fn get_password(&mut self, url: &str, username: &str) -> Option<&mut PasswordEntry> {
+    /// Get a specific password entry (requires the store to be unlocked)
+    pub fn get_entry(&self, url: &str, username: &str) -> Result<Option<&PasswordEntry>> {
+        self.unlocked_key()?;
+
         let normalized_url = PasswordEntry::normalize_url(url);
-        self.passwords
-            .get_mut(&normalized_url)?
-            .iter_mut()
-            .find(|e| e.username == username)
+        Ok(self
+            .passwords
+            .get(&normalized_url)
+            .and_then(|entries| entries.iter().find(|e| e.username == username)))
+    }
+
+    /// Record that a saved password was used, bumping its use count
+    pub fn record_use(&mut self, url: &str, username: &str) -> Result<()> {
+        self.unlocked_key()?;
+
+        let normalized_url = PasswordEntry::normalize_url(url);
+        let entry = self
+            .passwords
+            .get_mut(&normalized_url)
+            .and_then(|entries| entries.iter_mut().find(|e| e.username == username))
+            .ok_or_else(|| anyhow!("Password not found for {} on {}", username, normalized_url))?;
+
+        entry.increment_use_count();
+        entry.modified_at = std::time::SystemTime::now();
+        self.modified = true;
+        Ok(())
     }
 
     /// Update an existing password
@@ -155,6 +319,8 @@ impl PasswordManager {
         username: &str,
         new_password: String,
     ) -> Result<()> {
+        self.unlocked_key()?;
+
         let normalized_url = PasswordEntry::normalize_url(url);
 
         if let Some(entries) = self.passwords.get_mut(&normalized_url) {
@@ -249,13 +415,25 @@ impl PasswordManager {
             .collect()
     }
 
-    /// Save passwords to file
+    /// Save passwords to file, encrypted with the master key
     pub fn save(&self, path: &Path) -> Result<()> {
-        // Note: In a full implementation, passwords would be encrypted before saving
-        // using a master password or system keychain integration
-
+        let key = self.unlocked_key()?;
         let json = serde_json::to_string_pretty(&self.passwords)?;
-        std::fs::write(path, json)?;
+
+        let nonce: [u8; NONCE_LEN] = rand::random();
+        let cipher = Aes256Gcm::new_from_slice(key)?;
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), json.as_bytes())
+            .map_err(|_| anyhow!("Failed to encrypt passwords"))?;
+
+        let mut data = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+        data.extend_from_slice(FILE_MAGIC);
+        data.push(FILE_VERSION);
+        data.extend_from_slice(&self.salt);
+        data.extend_from_slice(&nonce);
+        data.extend_from_slice(&ciphertext);
+
+        std::fs::write(path, data)?;
         tracing::info!("Saved passwords to {:?}", path);
         Ok(())
     }
@@ -269,38 +447,210 @@ impl PasswordManager {
         }
     }
 
-    /// Load passwords from file
-    pub fn load(&mut self, path: &Path) -> Result<()> {
+    /// Load passwords from a file, unlocking the store with the master password
+    ///
+    /// The key is derived from the salt in the file's header. A plaintext JSON
+    /// file written before passwords were encrypted is loaded as is and saved
+    /// back encrypted under the master password.
+    pub fn load(&mut self, path: &Path, master_password: &str) -> Result<()> {
         if !path.exists() {
             return Ok(());
         }
 
-        let json = std::fs::read_to_string(path)?;
-        self.passwords = serde_json::from_str(&json)?;
+        let data = std::fs::read(path)?;
+        if !data.starts_with(FILE_MAGIC) {
+            let passwords = serde_json::from_slice(&data)
+                .map_err(|e| anyhow!("Not a password file {:?}: {}", path, e))?;
+            let salt: [u8; SALT_LEN] = rand::random();
+            self.key = Some(derive_key(master_password, &salt)?);
+            self.salt = salt;
+            self.passwords = passwords;
+            self.save(path)?;
+            self.modified = false;
+            tracing::info!("Encrypted plaintext passwords in {:?}", path);
+            return Ok(());
+        }
+
+        let (salt, _, _) = parse_header(&data)?;
+        let key = derive_key(master_password, &salt)?;
+        self.passwords = decrypt_entries(&key, &data)?;
+        self.salt = salt;
+        self.key = Some(key);
         self.modified = false;
         tracing::info!("Loaded passwords from {:?}", path);
         Ok(())
     }
 
+    /// Import passwords from a Firefox or Chrome CSV export
+    ///
+    /// Requires the store to be unlocked. The header must name `url` (or `origin`), `username`, and `password`
+    /// columns; other columns are ignored. Rows missing a URL or password are
+    /// skipped, and entries that already exist are counted as duplicates, so
+    /// importing the same file twice adds nothing the second time.
+    pub fn import_csv<R: std::io::Read>(&mut self, reader: R) -> Result<ImportReport> {
+        self.unlocked_key()?;
+
+        let mut csv_reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+
+        let headers = csv_reader.headers()?.clone();
+        let column = |names: &[&str]| {
+            headers.iter().position(|header| {
+                let header = header.trim_start_matches('\u{feff}').trim();
+                names.iter().any(|name| header.eq_ignore_ascii_case(name))
+            })
+        };
+        let (url_col, username_col, password_col) = match (
+            column(&["url", "origin"]),
+            column(&["username"]),
+            column(&["password"]),
+        ) {
+            (Some(url), Some(username), Some(password)) => (url, username, password),
+            _ => {
+                return Err(anyhow!(
+                    "CSV header must contain url, username, and password columns"
+                ))
+            }
+        };
+
+        let mut report = ImportReport::default();
+        for record in csv_reader.records() {
+            let Ok(record) = record else {
+                report.skipped += 1;
+                continue;
+            };
+
+            // Passwords may start or end with spaces, so only trim the others
+            let field = |index: usize| record.get(index).unwrap_or_default();
+            let (url, username, password) = (
+                field(url_col).trim(),
+                field(username_col).trim(),
+                field(password_col),
+            );
+            if url.is_empty() || password.is_empty() {
+                report.skipped += 1;
+                continue;
+            }
+
+            match self.add_password(url.to_string(), username.to_string(), password.to_string()) {
+                Ok(()) => report.imported += 1,
+                Err(_) => report.duplicates += 1,
+            }
+        }
+
+        tracing::info!(
+            "Imported {} passwords ({} skipped, {} duplicates)",
+            report.imported,
+            report.skipped,
+            report.duplicates
+        );
+        Ok(report)
+    }
+
+    /// Export all passwords as a `url,username,password` CSV
+    ///
+    /// Requires the store to be unlocked. Fields containing commas, quotes,
+    /// or newlines are quoted, so the output can be read back by `import_csv`.
+    pub fn export_csv<W: std::io::Write>(&self, writer: W) -> Result<()> {
+        self.unlocked_key()?;
+
+        let mut csv_writer = csv::Writer::from_writer(writer);
+        csv_writer.write_record(["url", "username", "password"])?;
+        for entry in self.get_all_entries() {
+            csv_writer.write_record([&entry.url, &entry.username, &entry.password])?;
+        }
+        csv_writer.flush()?;
+
+        tracing::info!("Exported {} passwords", self.count());
+        Ok(())
+    }
+
+    /// Set how autofill matches saved entries against URLs
+    pub fn set_match_strategy(&mut self, strategy: MatchStrategy) {
+        self.match_strategy = strategy;
+    }
+
+    /// Get the autofill match strategy
+    pub fn match_strategy(&self) -> MatchStrategy {
+        self.match_strategy
+    }
+
     /// Auto-fill suggestions for a URL
+    ///
+    /// Entries saved for the exact URL come first, followed by any entries
+    /// widened in by the match strategy.
     pub fn get_autofill_suggestions(&self, url: &str) -> Vec<AutofillSuggestion> {
         let normalized_url = PasswordEntry::normalize_url(url);
 
-        self.passwords
-            .get(&normalized_url)
-            .map(|entries| {
-                entries
-                    .iter()
-                    .map(|entry| AutofillSuggestion {
-                        username: entry.username.clone(),
-                        display_name: entry.display_name.clone(),
-                        last_used: entry.modified_at,
-                        use_count: entry.use_count,
-                    })
-                    .collect()
+        let exact = self.passwords.get(&normalized_url).into_iter().flatten();
+        let widened = self
+            .passwords
+            .iter()
+            .filter(|(stored_url, _)| {
+                **stored_url != normalized_url
+                    && self.match_strategy.matches(stored_url, &normalized_url)
             })
-            .unwrap_or_default()
+            .flat_map(|(_, entries)| entries);
+
+        exact
+            .chain(widened)
+            .map(|entry| AutofillSuggestion {
+                username: entry.username.clone(),
+                display_name: entry.display_name.clone(),
+                last_used: entry.modified_at,
+                use_count: entry.use_count,
+            })
+            .collect()
+    }
+}
+
+/// Derive the encryption key from a master password and salt
+fn derive_key(master_password: &str, salt: &[u8]) -> Result<[u8; KEY_LEN]> {
+    let mut key = [0u8; KEY_LEN];
+    Argon2::default()
+        .hash_password_into(master_password.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
+/// Split an encrypted password file into salt, nonce, and ciphertext
+fn parse_header(data: &[u8]) -> Result<([u8; SALT_LEN], [u8; NONCE_LEN], &[u8])> {
+    if data.len() < HEADER_LEN || !data.starts_with(FILE_MAGIC) {
+        return Err(anyhow!("Not an encrypted password file"));
     }
+
+    let version = data[FILE_MAGIC.len()];
+    if version != FILE_VERSION {
+        return Err(anyhow!("Unsupported password file version {}", version));
+    }
+
+    let (salt, rest) = data[FILE_MAGIC.len() + 1..].split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    Ok((salt.try_into()?, nonce.try_into()?, ciphertext))
+}
+
+/// Decrypt an encrypted password file into its entries
+fn decrypt_entries(
+    key: &[u8; KEY_LEN],
+    data: &[u8],
+) -> Result<HashMap<String, Vec<PasswordEntry>>> {
+    let (_, nonce, ciphertext) = parse_header(data)?;
+    let cipher = Aes256Gcm::new_from_slice(key)?;
+    let json = cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext)
+        .map_err(|_| anyhow!("Failed to decrypt passwords (wrong master password?)"))?;
+
+    Ok(serde_json::from_slice(&json)?)
+}
+
+/// Result of importing passwords from a CSV export
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Entries added to the password manager
+    pub imported: usize,
+    /// Malformed rows or rows missing a URL or password
+    pub skipped: usize,
+    /// Entries that already existed
+    pub duplicates: usize,
 }
 
 /// Auto-fill suggestion for password fields
@@ -325,7 +675,14 @@ impl Default for PasswordManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tempfile::NamedTempFile;
+    use tempfile::{NamedTempFile, TempDir};
+
+    /// A new, unlocked password manager
+    fn unlocked_manager() -> PasswordManager {
+        let mut manager = PasswordManager::new();
+        manager.unlock("master").unwrap();
+        manager
+    }
 
     #[test]
     fn test_password_manager_creation() {
@@ -335,7 +692,7 @@ mod tests {
 
     #[test]
     fn test_add_password() {
-        let mut manager = PasswordManager::new();
+        let mut manager = unlocked_manager();
         let result = manager.add_password(
             "https://example.com".to_string(),
             "user@example.com".to_string(),
@@ -347,7 +704,7 @@ mod tests {
 
     #[test]
     fn test_get_passwords_for_url() {
-        let mut manager = PasswordManager::new();
+        let mut manager = unlocked_manager();
         manager
             .add_password(
                 "https://example.com".to_string(),
@@ -369,7 +726,7 @@ mod tests {
 
     #[test]
     fn test_url_normalization() {
-        let mut manager = PasswordManager::new();
+        let mut manager = unlocked_manager();
         manager
             .add_password(
                 "https://example.com/".to_string(),
@@ -385,9 +742,26 @@ mod tests {
         assert_eq!(passwords2.len(), 1); // Both normalize to "example.com"
     }
 
+    #[test]
+    fn test_url_normalization_strips_userinfo_query_and_fragment() {
+        assert_eq!(
+            PasswordEntry::normalize_url("https://user:pw@www.Example.com/login/?next=/#form"),
+            "example.com/login"
+        );
+        assert_eq!(
+            PasswordEntry::normalize_url("http://example.com:8080/"),
+            "example.com:8080"
+        );
+        assert_eq!(
+            PasswordEntry::normalize_url("https://example.com:443"),
+            "example.com"
+        );
+        assert_eq!(PasswordEntry::normalize_url("Example.com"), "example.com");
+    }
+
     #[test]
     fn test_duplicate_prevention() {
-        let mut manager = PasswordManager::new();
+        let mut manager = unlocked_manager();
         manager
             .add_password(
                 "https://example.com".to_string(),
@@ -407,6 +781,7 @@ mod tests {
     #[test]
     fn test_update_password() {
         let mut manager = PasswordManager::new();
+        manager.unlock("master").unwrap();
         manager
             .add_password(
                 "https://example.com".to_string(),
@@ -422,13 +797,43 @@ mod tests {
         );
         assert!(result.is_ok());
 
-        let entry = manager.get_password("https://example.com", "user@example.com");
+        let entry = manager
+            .get_entry("https://example.com", "user@example.com")
+            .unwrap();
         assert_eq!(entry.unwrap().get_password(), "newpass");
     }
 
     #[test]
-    fn test_delete_password() {
+    fn test_record_use() {
         let mut manager = PasswordManager::new();
+        manager.unlock("master").unwrap();
+        manager
+            .add_password(
+                "https://example.com".to_string(),
+                "user@example.com".to_string(),
+                "password".to_string(),
+            )
+            .unwrap();
+
+        // Lookups only need a shared borrow
+        let manager_ref = &manager;
+        let entry = manager_ref
+            .get_entry("https://example.com", "user@example.com")
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.use_count, 0);
+
+        manager
+            .record_use("https://example.com", "user@example.com")
+            .unwrap();
+        let suggestions = manager.get_autofill_suggestions("https://example.com");
+        assert_eq!(suggestions[0].use_count, 1);
+        assert!(manager.record_use("https://example.com", "nobody").is_err());
+    }
+
+    #[test]
+    fn test_delete_password() {
+        let mut manager = unlocked_manager();
         manager
             .add_password(
                 "https://example.com".to_string(),
@@ -446,9 +851,61 @@ mod tests {
 
     #[test]
     fn test_save_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("passwords.dat");
+        let mut manager = PasswordManager::with_storage_path(path.clone()).unwrap();
+        manager.unlock("master").unwrap();
+
+        manager
+            .add_password(
+                "https://example.com".to_string(),
+                "user@example.com".to_string(),
+                "password123".to_string(),
+            )
+            .unwrap();
+
+        manager.save_to_storage().unwrap();
+
+        // The file must not contain the plaintext password
+        let raw = std::fs::read(&path).unwrap();
+        assert!(!raw.windows(11).any(|w| w == b"password123"));
+
+        let mut manager2 = PasswordManager::with_storage_path(path).unwrap();
+        manager2.unlock("master").unwrap();
+
+        assert_eq!(manager2.count(), 1);
+        let entry = manager2
+            .get_entry("https://example.com", "user@example.com")
+            .unwrap();
+        assert_eq!(entry.unwrap().get_password(), "password123");
+    }
+
+    #[test]
+    fn test_wrong_master_password() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("passwords.dat");
+        let mut manager = PasswordManager::with_storage_path(path.clone()).unwrap();
+        manager.unlock("master").unwrap();
+        manager
+            .add_password(
+                "https://example.com".to_string(),
+                "user@example.com".to_string(),
+                "password123".to_string(),
+            )
+            .unwrap();
+        manager.save_to_storage().unwrap();
+
+        let mut manager2 = PasswordManager::with_storage_path(path).unwrap();
+        assert!(manager2.unlock("wrong").is_err());
+        assert!(!manager2.is_unlocked());
+        assert_eq!(manager2.count(), 0);
+    }
+
+    #[test]
+    fn test_locked_store_rejects_access() {
         let temp_file = NamedTempFile::new().unwrap();
         let mut manager = PasswordManager::new();
-
+        manager.unlock("master").unwrap();
         manager
             .add_password(
                 "https://example.com".to_string(),
@@ -456,20 +913,291 @@ mod tests {
                 "password123".to_string(),
             )
             .unwrap();
+        manager.save(temp_file.path()).unwrap();
+
+        manager.lock();
+        assert!(!manager.is_unlocked());
+        assert_eq!(manager.count(), 0);
+        assert!(manager
+            .get_entry("https://example.com", "user@example.com")
+            .is_err());
+        let locked = |result: Result<()>| {
+            assert_eq!(result.unwrap_err().to_string(), "Password store is locked");
+        };
+        locked(manager.add_password(
+            "https://example.com".to_string(),
+            "other@example.com".to_string(),
+            "password456".to_string(),
+        ));
+        locked(manager.record_use("https://example.com", "user@example.com"));
+        locked(manager.update_password(
+            "https://example.com",
+            "user@example.com",
+            "password456".to_string(),
+        ));
+        locked(
+            manager
+                .import_csv("url,username,password\nhttps://a.com,u,p\n".as_bytes())
+                .map(|_| ()),
+        );
+        assert_eq!(manager.count(), 0);
+        assert!(manager.save(temp_file.path()).is_err());
+        assert!(manager.load(temp_file.path(), "wrong").is_err());
+        assert!(!manager.is_unlocked());
+
+        manager.load(temp_file.path(), "master").unwrap();
+        assert!(manager.is_unlocked());
+        assert_eq!(manager.count(), 1);
+    }
 
+    #[test]
+    fn test_load_uses_file_salt() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut manager = PasswordManager::new();
+        manager.unlock("master").unwrap();
+        manager
+            .add_password(
+                "https://example.com".to_string(),
+                "user@example.com".to_string(),
+                "password123".to_string(),
+            )
+            .unwrap();
         manager.save(temp_file.path()).unwrap();
 
-        let mut manager2 = PasswordManager::new();
-        manager2.load(temp_file.path()).unwrap();
+        // A store unlocked with a different salt can still read the file
+        let mut other = PasswordManager::new();
+        other.unlock("master").unwrap();
+        other.load(temp_file.path(), "master").unwrap();
+        assert_eq!(other.count(), 1);
+    }
 
-        assert_eq!(manager2.count(), 1);
-        let passwords = manager2.get_passwords_for_url("https://example.com");
-        assert_eq!(passwords.len(), 1);
+    #[test]
+    fn test_plaintext_store_is_migrated() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("passwords.json");
+        let mut legacy = unlocked_manager();
+        legacy
+            .add_password(
+                "https://example.com".to_string(),
+                "user@example.com".to_string(),
+                "password123".to_string(),
+            )
+            .unwrap();
+        std::fs::write(&path, serde_json::to_string(&legacy.passwords).unwrap()).unwrap();
+
+        let mut manager = PasswordManager::with_storage_path(path.clone()).unwrap();
+        manager.unlock("master").unwrap();
+        assert_eq!(manager.count(), 1);
+
+        // The file is rewritten encrypted under the master password
+        let raw = std::fs::read(&path).unwrap();
+        assert!(raw.starts_with(FILE_MAGIC));
+        assert!(!raw.windows(11).any(|w| w == b"password123"));
+
+        let mut reopened = PasswordManager::with_storage_path(path).unwrap();
+        assert!(reopened.unlock("wrong").is_err());
+        reopened.unlock("master").unwrap();
+        let entry = reopened
+            .get_entry("https://example.com", "user@example.com")
+            .unwrap();
+        assert_eq!(entry.unwrap().get_password(), "password123");
     }
 
     #[test]
-    fn test_search() {
+    fn test_import_csv() {
+        let csv = "\u{feff}url,username,password,httpRealm\n\
+                   https://example.com,alice,secret1,\n\
+                   https://test.com,bob,secret2,\n";
+        let mut manager = unlocked_manager();
+
+        let report = manager.import_csv(csv.as_bytes()).unwrap();
+        assert_eq!(
+            report,
+            ImportReport {
+                imported: 2,
+                skipped: 0,
+                duplicates: 0
+            }
+        );
+        assert_eq!(manager.get_passwords_for_url("example.com").len(), 1);
+
+        // Re-importing is idempotent
+        let report = manager.import_csv(csv.as_bytes()).unwrap();
+        assert_eq!(report.imported, 0);
+        assert_eq!(report.duplicates, 2);
+        assert_eq!(manager.count(), 2);
+    }
+
+    #[test]
+    fn test_import_csv_skips_bad_rows() {
+        let csv = "name,origin,username,password\n\
+                   Example,https://example.com,alice,secret1\n\
+                   Example,https://example.com,alice,secret1\n\
+                   Test,https://test.com,bob,\n\
+                   Short,https://short.com\n";
+        let mut manager = unlocked_manager();
+
+        let report = manager.import_csv(csv.as_bytes()).unwrap();
+        assert_eq!(
+            report,
+            ImportReport {
+                imported: 1,
+                skipped: 2,
+                duplicates: 1
+            }
+        );
+        assert!(manager.import_csv("url,user,pass\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_import_csv_keeps_password_spaces() {
+        let csv = "url,username,password\n https://example.com , alice ,\" secret \"\n";
+        let mut manager = PasswordManager::new();
+        manager.unlock("master").unwrap();
+
+        manager.import_csv(csv.as_bytes()).unwrap();
+        let entry = manager
+            .get_entry("https://example.com", "alice")
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.get_password(), " secret ");
+    }
+
+    #[test]
+    fn test_export_csv_round_trip() {
         let mut manager = PasswordManager::new();
+        manager.unlock("master").unwrap();
+        manager
+            .add_password(
+                "https://example.com".to_string(),
+                "alice".to_string(),
+                "pa,ss \"quoted\"".to_string(),
+            )
+            .unwrap();
+        manager
+            .add_password(
+                "https://test.com".to_string(),
+                "bob".to_string(),
+                "secret".to_string(),
+            )
+            .unwrap();
+
+        let mut buffer = Vec::new();
+        manager.export_csv(&mut buffer).unwrap();
+        assert!(buffer.starts_with(b"url,username,password\n"));
+
+        let mut imported = PasswordManager::new();
+        imported.unlock("master").unwrap();
+        let report = imported.import_csv(buffer.as_slice()).unwrap();
+        assert_eq!(report.imported, 2);
+        assert_eq!(imported.count(), manager.count());
+
+        let entry = imported.get_entry("example.com", "alice").unwrap();
+        assert_eq!(entry.unwrap().get_password(), "pa,ss \"quoted\"");
+        let entry = imported.get_entry("test.com", "bob").unwrap();
+        assert_eq!(entry.unwrap().get_password(), "secret");
+    }
+
+    #[test]
+    fn test_export_csv_requires_unlock() {
+        let manager = PasswordManager::new();
+        let mut buffer = Vec::new();
+        assert!(manager.export_csv(&mut buffer).is_err());
+        assert!(buffer.is_empty());
+    }
+
+    fn manager_with_google_entries() -> PasswordManager {
+        let mut manager = unlocked_manager();
+        manager
+            .add_password(
+                "https://google.com".to_string(),
+                "alice".to_string(),
+                "pass1".to_string(),
+            )
+            .unwrap();
+        manager
+            .add_password(
+                "https://mail.google.com".to_string(),
+                "bob".to_string(),
+                "pass2".to_string(),
+            )
+            .unwrap();
+        manager
+    }
+
+    #[test]
+    fn test_autofill_exact_match() {
+        let manager = manager_with_google_entries();
+        assert_eq!(manager.match_strategy(), MatchStrategy::Exact);
+
+        let suggestions = manager.get_autofill_suggestions("https://mail.google.com/");
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].username, "bob");
+        assert!(manager
+            .get_autofill_suggestions("https://docs.google.com")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_autofill_include_subdomains() {
+        let mut manager = manager_with_google_entries();
+        manager.set_match_strategy(MatchStrategy::IncludeSubdomains);
+
+        let suggestions = manager.get_autofill_suggestions("https://mail.google.com");
+        let usernames: Vec<&str> = suggestions.iter().map(|s| s.username.as_str()).collect();
+        assert_eq!(usernames, vec!["bob", "alice"]);
+
+        let suggestions = manager.get_autofill_suggestions("https://docs.google.com/document");
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].username, "alice");
+
+        // A subdomain entry is not offered on its parent domain
+        let suggestions = manager.get_autofill_suggestions("https://google.com");
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].username, "alice");
+    }
+
+    #[test]
+    fn test_autofill_unrelated_domains_never_match() {
+        let mut manager = manager_with_google_entries();
+        manager
+            .add_password(
+                "https://com".to_string(),
+                "carol".to_string(),
+                "pass3".to_string(),
+            )
+            .unwrap();
+        manager.set_match_strategy(MatchStrategy::IncludeSubdomains);
+
+        assert!(manager
+            .get_autofill_suggestions("https://notgoogle.com")
+            .is_empty());
+        assert!(manager
+            .get_autofill_suggestions("https://google.com.evil.org")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_autofill_public_suffixes_never_match() {
+        let mut manager = unlocked_manager();
+        for url in ["https://co.uk", "https://github.io"] {
+            manager
+                .add_password(url.to_string(), "carol".to_string(), "pass".to_string())
+                .unwrap();
+        }
+        manager.set_match_strategy(MatchStrategy::IncludeSubdomains);
+
+        assert!(manager
+            .get_autofill_suggestions("https://bank.co.uk")
+            .is_empty());
+        assert!(manager
+            .get_autofill_suggestions("https://someone.github.io")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_search() {
+        let mut manager = unlocked_manager();
         manager
             .add_password(
                 "https://example.com".to_string(),
@@ -4,6 +4,32 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+/// File name of the metadata stored in each profile directory
+const PROFILE_FILE: &str = "profile.json";
+
+/// File in the profiles directory holding the ID of the active profile
+const ACTIVE_FILE: &str = "active";
+
+/// Directory inside the data directory holding profiles
+pub const PROFILES_DIR: &str = "profiles";
+
+/// Name of the profile created when none exist
+pub const DEFAULT_PROFILE_NAME: &str = "Default";
+
+/// Files and directories kept at the top of the data directory before
+/// profiles existed, moved into the default profile when it is created
+const LEGACY_ENTRIES: &[&str] = &[
+    "settings.toml",
+    "passwords.dat",
+    "bookmarks",
+    "history",
+    "cookies",
+    "cache",
+    "local_storage",
+    "site_settings",
+    "themes",
+];
+
 /// User profile
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Profile {
@@ -50,17 +76,60 @@ pub struct ProfileManager {
 }
 
 impl ProfileManager {
-    /// Create a new profile manager
+    /// Create a new profile manager, loading existing profiles from disk
+    ///
+    /// The last active profile is restored if it still exists, otherwise the
+    /// first profile is active.
     pub fn new(profiles_dir: PathBuf) -> Result<Self> {
         if !profiles_dir.exists() {
             std::fs::create_dir_all(&profiles_dir)?;
         }
 
-        Ok(Self {
+        let mut manager = Self {
             profiles_dir,
             profiles: Vec::new(),
             active_profile: None,
-        })
+        };
+        manager.load_profiles()?;
+
+        let active_path = manager.profiles_dir.join(ACTIVE_FILE);
+        if active_path.exists() {
+            let id = std::fs::read_to_string(&active_path)?;
+            if manager.set_active_profile(id.trim()).is_err() {
+                tracing::warn!("Last active profile {} no longer exists", id.trim());
+            }
+        }
+        Ok(manager)
+    }
+
+    /// Rebuild the profile list from the `profile.json` in each profile directory
+    ///
+    /// Directories without readable metadata are skipped. The active profile
+    /// is kept if it still exists, otherwise the first profile becomes active.
+    pub fn load_profiles(&mut self) -> Result<()> {
+        let mut profiles = Vec::new();
+        for entry in std::fs::read_dir(&self.profiles_dir)? {
+            let path = entry?.path();
+            let metadata_path = path.join(PROFILE_FILE);
+            if !metadata_path.is_file() {
+                continue;
+            }
+
+            match read_profile(&metadata_path) {
+                Ok(mut profile) => {
+                    profile.path = path;
+                    profiles.push(profile);
+                }
+                Err(e) => tracing::warn!("Skipping profile at {:?}: {}", path, e),
+            }
+        }
+        profiles.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+        self.profiles = profiles;
+
+        if self.active_profile().is_none() {
+            self.active_profile = self.profiles.first().map(|p| p.id.clone());
+        }
+        Ok(())
     }
 
     /// Create a new profile
@@ -72,10 +141,12 @@ impl ProfileManager {
         std::fs::create_dir_all(&path)?;
 
         let profile = Profile::new(id.clone(), name, path);
+        let json = serde_json::to_string_pretty(&profile)?;
+        std::fs::write(profile.path.join(PROFILE_FILE), json)?;
         self.profiles.push(profile.clone());
 
         if self.active_profile.is_none() {
-            self.active_profile = Some(id);
+            self.set_active_profile(&id)?;
         }
 
         Ok(profile)
@@ -93,15 +164,86 @@ impl ProfileManager {
             .and_then(|id| self.profiles.iter().find(|p| p.id == *id))
     }
 
-    /// Set the active profile
+    /// Set the active profile, remembering it for the next launch
     pub fn set_active_profile(&mut self, id: &str) -> Result<()> {
         if self.profiles.iter().any(|p| p.id == id) {
+            std::fs::write(self.profiles_dir.join(ACTIVE_FILE), id)?;
             self.active_profile = Some(id.to_string());
             Ok(())
         } else {
             anyhow::bail!("Profile not found")
         }
     }
+
+    /// Delete a profile and its directory
+    ///
+    /// The active profile cannot be deleted; activate another one first.
+    pub fn delete_profile(&mut self, id: &str) -> Result<()> {
+        if self.active_profile.as_deref() == Some(id) {
+            anyhow::bail!("Cannot delete the active profile");
+        }
+
+        let index = self
+            .profiles
+            .iter()
+            .position(|p| p.id == id)
+            .ok_or_else(|| anyhow::anyhow!("Profile not found"))?;
+        let profile = self.profiles.remove(index);
+        if profile.path.exists() {
+            std::fs::remove_dir_all(&profile.path)?;
+        }
+
+        tracing::info!("Deleted profile {} ({})", profile.name, profile.id);
+        Ok(())
+    }
+}
+
+/// Get the directory of the active profile in a data directory, creating
+/// a default profile if there are none
+///
+/// Data stored at the top of the data directory by versions without
+/// profiles is moved into the new default profile.
+pub fn active_profile_dir(data_dir: &Path) -> Result<PathBuf> {
+    let mut manager = ProfileManager::new(data_dir.join(PROFILES_DIR))?;
+    if let Some(profile) = manager.active_profile() {
+        return Ok(profile.path.clone());
+    }
+
+    let profile = manager.create_profile(DEFAULT_PROFILE_NAME)?;
+    migrate_legacy_data(data_dir, &profile.path)?;
+    Ok(profile.path)
+}
+
+/// Move data stored at the top of the data directory, from before profiles
+/// existed, into a profile directory
+///
+/// Entries the profile already has are left alone. Returns how many entries
+/// were moved.
+pub fn migrate_legacy_data(data_dir: &Path, profile_dir: &Path) -> Result<usize> {
+    let mut moved = 0;
+    for name in LEGACY_ENTRIES {
+        let from = data_dir.join(name);
+        let to = profile_dir.join(name);
+        if from.exists() && !to.exists() {
+            std::fs::rename(&from, &to)?;
+            moved += 1;
+        }
+    }
+
+    if moved > 0 {
+        tracing::info!(
+            "Moved {} data entries into profile {:?}",
+            moved,
+            profile_dir
+        );
+    }
+    Ok(moved)
+}
+
+/// Read profile metadata from a `profile.json` file
+fn read_profile(path: &Path) -> Result<Profile> {
+    let json = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&json)?)
 }
 
 #[cfg(test)]
@@ -127,4 +269,104 @@ mod tests {
         assert!(manager.active_profile().is_some());
         assert_eq!(manager.active_profile().unwrap().name(), "Default");
     }
+
+    #[test]
+    fn test_profiles_reload() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let work = manager.create_profile("Work").unwrap();
+        let home = manager.create_profile("Home").unwrap();
+        std::fs::create_dir(temp_dir.path().join("not-a-profile")).unwrap();
+
+        let reloaded = ProfileManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let ids: Vec<&str> = reloaded.profiles().iter().map(Profile::id).collect();
+        assert_eq!(ids, vec![home.id(), work.id()]);
+        assert_eq!(reloaded.profiles()[1].name(), "Work");
+        assert_eq!(reloaded.profiles()[1].path(), work.path());
+    }
+
+    #[test]
+    fn test_delete_profile() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let first = manager.create_profile("First").unwrap();
+        let second = manager.create_profile("Second").unwrap();
+
+        assert!(manager.delete_profile(first.id()).is_err());
+        assert!(first.path().exists());
+
+        manager.set_active_profile(second.id()).unwrap();
+        manager.delete_profile(first.id()).unwrap();
+        assert!(!first.path().exists());
+        assert_eq!(manager.profiles().len(), 1);
+        assert!(manager.delete_profile(first.id()).is_err());
+
+        manager.load_profiles().unwrap();
+        assert_eq!(manager.profiles().len(), 1);
+    }
+
+    #[test]
+    fn test_active_profile_restored() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf()).unwrap();
+        manager.create_profile("Work").unwrap();
+        let home = manager.create_profile("Home").unwrap();
+        manager.set_active_profile(home.id()).unwrap();
+
+        let reloaded = ProfileManager::new(temp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(reloaded.active_profile().unwrap().id(), home.id());
+    }
+
+    #[test]
+    fn test_dangling_active_profile_falls_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manager = ProfileManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let work = manager.create_profile("Work").unwrap();
+        std::fs::write(temp_dir.path().join(ACTIVE_FILE), "missing-id").unwrap();
+
+        let reloaded = ProfileManager::new(temp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(reloaded.active_profile().unwrap().id(), work.id());
+    }
+
+    #[test]
+    fn test_active_profile_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = active_profile_dir(temp_dir.path()).unwrap();
+        assert!(dir.starts_with(temp_dir.path().join(PROFILES_DIR)));
+        assert!(dir.join(PROFILE_FILE).exists());
+
+        // The default profile is reused rather than created again
+        assert_eq!(active_profile_dir(temp_dir.path()).unwrap(), dir);
+    }
+
+    #[test]
+    fn test_legacy_data_moves_into_default_profile() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path();
+        std::fs::write(data_dir.join("settings.toml"), "[general]").unwrap();
+        std::fs::write(data_dir.join("passwords.dat"), "HZPW").unwrap();
+        std::fs::create_dir(data_dir.join("bookmarks")).unwrap();
+        std::fs::write(data_dir.join("bookmarks/bookmarks.json"), "[]").unwrap();
+        std::fs::create_dir(data_dir.join("history")).unwrap();
+        std::fs::create_dir(data_dir.join("crashes")).unwrap();
+
+        let dir = active_profile_dir(data_dir).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.join("settings.toml")).unwrap(),
+            "[general]"
+        );
+        assert!(dir.join("passwords.dat").exists());
+        assert!(dir.join("bookmarks/bookmarks.json").exists());
+        assert!(dir.join("history").is_dir());
+        assert!(!data_dir.join("settings.toml").exists());
+        assert!(!data_dir.join("bookmarks").exists());
+        // Data that is not per profile stays where it was
+        assert!(data_dir.join("crashes").is_dir());
+
+        // Later launches leave new top-level files alone
+        std::fs::write(data_dir.join("settings.toml"), "[other]").unwrap();
+        assert_eq!(active_profile_dir(data_dir).unwrap(), dir);
+        assert!(data_dir.join("settings.toml").exists());
+        assert_eq!(migrate_legacy_data(data_dir, &dir).unwrap(), 0);
+    }
 }
@@ -2,7 +2,8 @@
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 /// Browser settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +17,10 @@ pub struct Settings {
     pub general: GeneralSettings,
     /// Advanced settings
     pub advanced: AdvancedSettings,
+    /// Network settings
+    pub network: NetworkSettings,
+    /// Keyboard shortcuts, as action names mapped to key bindings like `Ctrl+T`
+    pub shortcuts: BTreeMap<String, Vec<String>>,
 }
 
 /// Privacy-related settings
@@ -76,12 +81,18 @@ pub struct GeneralSettings {
     pub homepage: String,
     /// Search engine
     pub search_engine: String,
+    /// URL template of a custom search engine, with a `{query}` placeholder
+    pub custom_search_url: Option<String>,
     /// Download directory
     pub download_directory: String,
     /// Restore tabs on startup
     pub restore_tabs_on_startup: bool,
     /// Ask where to save files
     pub ask_where_to_save: bool,
+    /// Activate the left neighbor instead of the right one when closing a tab
+    pub select_left_on_close: bool,
+    /// Switch to a tab after hovering it for this many milliseconds (None to disable)
+    pub hover_switch_ms: Option<u64>,
 }
 
 impl Default for GeneralSettings {
@@ -100,13 +111,26 @@ impl Default for GeneralSettings {
         Self {
             homepage: "about:home".to_string(),
             search_engine: "DuckDuckGo".to_string(),
+            custom_search_url: None,
             download_directory: download_dir,
             restore_tabs_on_startup: false,
             ask_where_to_save: true,
+            select_left_on_close: false,
+            hover_switch_ms: None,
         }
     }
 }
 
+impl GeneralSettings {
+    /// Get where a download from a URL is saved in the download directory
+    ///
+    /// The file is named after the last path segment, falling back to the
+    /// host and then to `download`.
+    pub fn download_destination(&self, url: &str) -> PathBuf {
+        crate::downloads::download_destination(Path::new(&self.download_directory), url)
+    }
+}
+
 /// Advanced settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -117,6 +141,10 @@ pub struct AdvancedSettings {
     pub hardware_acceleration: bool,
     /// Enable experimental features
     pub experimental_features: bool,
+    /// Resolve and connect to the homepage and search engine hosts on startup
+    pub preconnect_on_startup: bool,
+    /// Maximum size of the cache directory in bytes
+    pub max_cache_bytes: u64,
 }
 
 impl Default for AdvancedSettings {
@@ -125,6 +153,39 @@ impl Default for AdvancedSettings {
             enable_developer_tools: false,
             hardware_acceleration: true,
             experimental_features: false,
+            preconnect_on_startup: false,
+            max_cache_bytes: crate::userdata::DEFAULT_MAX_CACHE_BYTES,
+        }
+    }
+}
+
+/// Network settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkSettings {
+    /// DNS provider name
+    pub dns_provider: String,
+    /// Custom DNS servers (comma-separated)
+    pub custom_dns_servers: String,
+    /// Enable VPN
+    pub vpn_enabled: bool,
+    /// VPN configuration type name
+    pub vpn_type: String,
+    /// Proxy host (for proxy-based VPN)
+    pub proxy_host: String,
+    /// Proxy port
+    pub proxy_port: u16,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            dns_provider: "System".to_string(),
+            custom_dns_servers: String::new(),
+            vpn_enabled: false,
+            vpn_type: "Proxy".to_string(),
+            proxy_host: String::new(),
+            proxy_port: 8080,
         }
     }
 }
@@ -137,6 +198,8 @@ impl Settings {
             appearance: AppearanceSettings::default(),
             general: GeneralSettings::default(),
             advanced: AdvancedSettings::default(),
+            network: NetworkSettings::default(),
+            shortcuts: BTreeMap::new(),
         }
     }
 
@@ -170,6 +233,32 @@ mod tests {
     use super::*;
     use tempfile::NamedTempFile;
 
+    #[test]
+    fn test_download_destination() {
+        let general = GeneralSettings {
+            download_directory: "/home/user/Downloads".to_string(),
+            ..GeneralSettings::default()
+        };
+        let dest = |url: &str| general.download_destination(url);
+
+        assert_eq!(
+            dest("https://example.com/files/report.pdf?x=1"),
+            PathBuf::from("/home/user/Downloads/report.pdf")
+        );
+        assert_eq!(
+            dest("https://example.com/dir/"),
+            PathBuf::from("/home/user/Downloads/dir")
+        );
+        assert_eq!(
+            dest("https://example.com/"),
+            PathBuf::from("/home/user/Downloads/example.com")
+        );
+        assert_eq!(
+            dest("not a url"),
+            PathBuf::from("/home/user/Downloads/download")
+        );
+    }
+
     #[test]
     fn test_settings_default() {
         let settings = Settings::default();
@@ -192,6 +281,46 @@ mod tests {
         assert_eq!(settings.appearance.theme, loaded.appearance.theme);
     }
 
+    #[test]
+    fn test_network_settings_save_load() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut settings = Settings::new();
+        settings.network.vpn_enabled = true;
+        settings.network.vpn_type = "Socks5".to_string();
+        settings.network.proxy_host = "proxy.example.com".to_string();
+        settings.network.proxy_port = 1080;
+        settings.network.dns_provider = "Custom".to_string();
+        settings.network.custom_dns_servers = "1.1.1.1, 9.9.9.9".to_string();
+
+        settings.save(temp_file.path()).unwrap();
+        let loaded = Settings::load(temp_file.path()).unwrap();
+
+        assert!(loaded.network.vpn_enabled);
+        assert_eq!(loaded.network.vpn_type, "Socks5");
+        assert_eq!(loaded.network.proxy_host, "proxy.example.com");
+        assert_eq!(loaded.network.proxy_port, 1080);
+        assert_eq!(loaded.network.dns_provider, "Custom");
+        assert_eq!(loaded.network.custom_dns_servers, "1.1.1.1, 9.9.9.9");
+    }
+
+    #[test]
+    fn test_settings_without_network_section() {
+        let old_settings_toml = r#"
+[general]
+homepage = "https://example.com"
+search_engine = "Google"
+"#;
+
+        let settings: Settings = toml::from_str(old_settings_toml).unwrap();
+
+        assert_eq!(settings.general.homepage, "https://example.com");
+        assert_eq!(settings.network.dns_provider, "System");
+        assert_eq!(settings.network.vpn_type, "Proxy");
+        assert!(!settings.network.vpn_enabled);
+        assert!(settings.network.proxy_host.is_empty());
+        assert_eq!(settings.network.proxy_port, 8080);
+    }
+
     #[test]
     fn test_settings_backward_compatibility() {
         // Test loading old settings file without https_only field
@@ -3,15 +3,20 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Default maximum size of the cache directory in bytes
+pub const DEFAULT_MAX_CACHE_BYTES: u64 = 256 * 1024 * 1024;
 
 /// User data types
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DataType {
     Cache,
     History,
     Bookmarks,
     Cookies,
     LocalStorage,
+    SiteSettings,
 }
 
 /// User data manager
@@ -37,6 +42,7 @@ impl UserDataManager {
             DataType::Bookmarks => "bookmarks",
             DataType::Cookies => "cookies",
             DataType::LocalStorage => "local_storage",
+            DataType::SiteSettings => "site_settings",
         };
 
         self.data_dir.join(dir_name)
@@ -55,6 +61,28 @@ impl UserDataManager {
         Ok(())
     }
 
+    /// Clear data of several types
+    pub fn clear_types(&self, data_types: &[DataType]) -> Result<()> {
+        for &data_type in data_types {
+            self.clear(data_type)?;
+        }
+        Ok(())
+    }
+
+    /// Remove the files of a data type last modified more than `age` ago,
+    /// returning how many were removed
+    pub fn clear_older_than(&self, data_type: DataType, age: Duration) -> Result<usize> {
+        let cutoff = SystemTime::now() - age;
+        let mut removed = 0;
+        for file in data_files(&self.path_for(data_type))? {
+            if file.modified < cutoff {
+                std::fs::remove_file(&file.path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
     /// Clear all user data
     pub fn clear_all(&self) -> Result<()> {
         if self.data_dir.exists() {
@@ -68,6 +96,81 @@ impl UserDataManager {
     pub fn data_dir(&self) -> &Path {
         &self.data_dir
     }
+
+    /// Get the total size of the files under the cache directory
+    pub fn cache_size_bytes(&self) -> Result<u64> {
+        Ok(data_files(&self.path_for(DataType::Cache))?
+            .iter()
+            .map(|file| file.size)
+            .sum())
+    }
+
+    /// Delete the least recently modified cache files until the cache fits
+    /// in `max_bytes`, returning the number of bytes freed
+    pub fn prune_cache(&self, max_bytes: u64) -> Result<u64> {
+        let mut files = data_files(&self.path_for(DataType::Cache))?;
+        let mut total: u64 = files.iter().map(|file| file.size).sum();
+        if total <= max_bytes {
+            return Ok(0);
+        }
+
+        files.sort_by_key(|file| file.modified);
+        let mut freed = 0;
+        for file in files {
+            if total <= max_bytes {
+                break;
+            }
+            std::fs::remove_file(&file.path)?;
+            total -= file.size;
+            freed += file.size;
+        }
+
+        tracing::info!("Pruned {} bytes from the cache", freed);
+        Ok(freed)
+    }
+}
+
+/// A file stored for a data type
+struct DataFile {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+/// List every file at a data path, which may be a file, a directory, or
+/// not exist yet
+fn data_files(path: &Path) -> Result<Vec<DataFile>> {
+    let mut files = Vec::new();
+    if path.is_file() {
+        let metadata = std::fs::metadata(path)?;
+        files.push(DataFile {
+            path: path.to_path_buf(),
+            size: metadata.len(),
+            modified: metadata.modified()?,
+        });
+        return Ok(files);
+    }
+    if !path.is_dir() {
+        return Ok(files);
+    }
+
+    let mut dirs = vec![path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                dirs.push(entry.path());
+            } else {
+                files.push(DataFile {
+                    path: entry.path(),
+                    size: metadata.len(),
+                    modified: metadata.modified()?,
+                });
+            }
+        }
+    }
+    Ok(files)
 }
 
 #[cfg(test)]
@@ -95,4 +198,89 @@ mod tests {
 
         assert!(manager.clear(DataType::Cache).is_ok());
     }
+
+    #[test]
+    fn test_prune_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = UserDataManager::new(temp_dir.path().to_path_buf()).unwrap();
+        assert_eq!(manager.cache_size_bytes().unwrap(), 0);
+
+        let cache_path = manager.path_for(DataType::Cache);
+        std::fs::create_dir_all(cache_path.join("images")).unwrap();
+        let now = SystemTime::now();
+        for (i, name) in ["old.bin", "images/older.bin", "new.bin", "newest.bin"]
+            .iter()
+            .enumerate()
+        {
+            let file = std::fs::File::create(cache_path.join(name)).unwrap();
+            file.set_len(100).unwrap();
+            let age = std::time::Duration::from_secs(match i {
+                0 => 300,
+                1 => 400,
+                2 => 200,
+                _ => 100,
+            });
+            file.set_modified(now - age).unwrap();
+        }
+        assert_eq!(manager.cache_size_bytes().unwrap(), 400);
+
+        assert_eq!(manager.prune_cache(250).unwrap(), 200);
+        assert_eq!(manager.cache_size_bytes().unwrap(), 200);
+        assert!(!cache_path.join("images/older.bin").exists());
+        assert!(!cache_path.join("old.bin").exists());
+        assert!(cache_path.join("new.bin").exists());
+        assert!(cache_path.join("newest.bin").exists());
+
+        // Already under the limit
+        assert_eq!(manager.prune_cache(250).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_clear_types() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = UserDataManager::new(temp_dir.path().to_path_buf()).unwrap();
+        for data_type in [DataType::Cache, DataType::Cookies, DataType::Bookmarks] {
+            let path = manager.path_for(data_type);
+            std::fs::create_dir_all(&path).unwrap();
+            std::fs::write(path.join("data"), b"data").unwrap();
+        }
+
+        manager
+            .clear_types(&[DataType::Cache, DataType::Cookies, DataType::History])
+            .unwrap();
+        assert!(!manager.path_for(DataType::Cache).exists());
+        assert!(!manager.path_for(DataType::Cookies).exists());
+        assert!(manager.path_for(DataType::Bookmarks).join("data").exists());
+    }
+
+    #[test]
+    fn test_clear_older_than() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = UserDataManager::new(temp_dir.path().to_path_buf()).unwrap();
+        let cookies = manager.path_for(DataType::Cookies);
+        std::fs::create_dir_all(cookies.join("site")).unwrap();
+
+        let now = SystemTime::now();
+        for (name, minutes) in [("recent", 5), ("site/hour_old", 90), ("day_old", 60 * 24)] {
+            let file = std::fs::File::create(cookies.join(name)).unwrap();
+            file.set_modified(now - Duration::from_secs(minutes * 60))
+                .unwrap();
+        }
+
+        let removed = manager
+            .clear_older_than(DataType::Cookies, Duration::from_secs(60 * 60))
+            .unwrap();
+        assert_eq!(removed, 2);
+        assert!(cookies.join("recent").exists());
+        assert!(!cookies.join("site/hour_old").exists());
+        assert!(!cookies.join("day_old").exists());
+
+        // Missing data is nothing to clear
+        assert_eq!(
+            manager
+                .clear_older_than(DataType::History, Duration::ZERO)
+                .unwrap(),
+            0
+        );
+    }
 }
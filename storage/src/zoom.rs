@@ -0,0 +1,236 @@
+//! Per-site page zoom levels backed by the user data directory
+
+use crate::userdata::{DataType, UserDataManager};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// File name used for zoom levels inside the site settings data directory
+const ZOOM_FILE: &str = "zoom.json";
+
+/// Smallest zoom level
+pub const MIN_ZOOM: f32 = 0.5;
+
+/// Largest zoom level
+pub const MAX_ZOOM: f32 = 3.0;
+
+/// Zoom level of sites without a saved level
+pub const DEFAULT_ZOOM: f32 = 1.0;
+
+/// Amount a zoom in or out step changes the level by
+pub const ZOOM_STEP: f32 = 0.1;
+
+/// Zoom levels by host
+pub struct ZoomLevels {
+    /// Zoom level for each host with a non-default level
+    levels: HashMap<String, f32>,
+    /// Path to the zoom levels file
+    storage_path: Option<PathBuf>,
+}
+
+impl ZoomLevels {
+    /// Create new, in-memory zoom levels
+    pub fn new() -> Self {
+        Self {
+            levels: HashMap::new(),
+            storage_path: None,
+        }
+    }
+
+    /// Create zoom levels with a storage path
+    pub fn with_storage_path(path: PathBuf) -> Result<Self> {
+        let mut zoom = Self::new();
+        zoom.storage_path = Some(path.clone());
+
+        // Load existing levels if file exists
+        if path.exists() {
+            zoom.load(&path)?;
+        }
+
+        Ok(zoom)
+    }
+
+    /// Create zoom levels stored in the user data site settings directory
+    pub fn from_user_data(user_data: &UserDataManager) -> Result<Self> {
+        let dir = user_data.path_for(DataType::SiteSettings);
+        if !dir.exists() {
+            std::fs::create_dir_all(&dir)?;
+        }
+
+        Self::with_storage_path(dir.join(ZOOM_FILE))
+    }
+
+    /// Get the zoom level for a host
+    pub fn get(&self, host: &str) -> f32 {
+        self.levels
+            .get(&host.to_lowercase())
+            .copied()
+            .unwrap_or(DEFAULT_ZOOM)
+    }
+
+    /// Set the zoom level for a host, clamped to `MIN_ZOOM`-`MAX_ZOOM`
+    ///
+    /// Returns the level that was set. Setting the default level forgets the
+    /// host.
+    pub fn set(&mut self, host: &str, level: f32) -> f32 {
+        let level = if level.is_nan() {
+            DEFAULT_ZOOM
+        } else {
+            level.clamp(MIN_ZOOM, MAX_ZOOM)
+        };
+
+        let host = host.to_lowercase();
+        if (level - DEFAULT_ZOOM).abs() < f32::EPSILON {
+            self.levels.remove(&host);
+        } else {
+            self.levels.insert(host, level);
+        }
+        level
+    }
+
+    /// Increase the zoom level for a host by one step
+    pub fn zoom_in(&mut self, host: &str) -> f32 {
+        self.set(host, round_step(self.get(host) + ZOOM_STEP))
+    }
+
+    /// Decrease the zoom level for a host by one step
+    pub fn zoom_out(&mut self, host: &str) -> f32 {
+        self.set(host, round_step(self.get(host) - ZOOM_STEP))
+    }
+
+    /// Reset a host to the default zoom level, returning whether it had another level
+    pub fn reset(&mut self, host: &str) -> bool {
+        self.levels.remove(&host.to_lowercase()).is_some()
+    }
+
+    /// Save zoom levels to file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.levels)?;
+        std::fs::write(path, json)?;
+        tracing::debug!("Saved zoom levels to {:?}", path);
+        Ok(())
+    }
+
+    /// Save to the configured storage path
+    pub fn save_to_storage(&self) -> Result<()> {
+        if let Some(path) = &self.storage_path {
+            self.save(path)
+        } else {
+            Err(anyhow!("No storage path configured"))
+        }
+    }
+
+    /// Load zoom levels from file
+    ///
+    /// Levels are clamped like those passed to `set`, since the file may have
+    /// been edited by hand.
+    pub fn load(&mut self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let json = std::fs::read_to_string(path)?;
+        let levels: HashMap<String, f32> = serde_json::from_str(&json)?;
+        self.levels.clear();
+        for (host, level) in levels {
+            self.set(&host, level);
+        }
+        tracing::debug!("Loaded zoom levels from {:?}", path);
+        Ok(())
+    }
+}
+
+impl Default for ZoomLevels {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Round a level to the nearest step so repeated steps don't drift
+fn round_step(level: f32) -> f32 {
+    (level / ZOOM_STEP).round() * ZOOM_STEP
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_set_clamps_level() {
+        let mut zoom = ZoomLevels::new();
+        assert_eq!(zoom.get("example.com"), DEFAULT_ZOOM);
+
+        assert_eq!(zoom.set("example.com", 10.0), MAX_ZOOM);
+        assert_eq!(zoom.get("example.com"), MAX_ZOOM);
+        assert_eq!(zoom.set("example.com", 0.1), MIN_ZOOM);
+        assert_eq!(zoom.set("example.com", f32::NAN), DEFAULT_ZOOM);
+        assert_eq!(zoom.get("example.com"), DEFAULT_ZOOM);
+
+        zoom.set("example.com", MAX_ZOOM);
+        assert_eq!(zoom.zoom_in("example.com"), MAX_ZOOM);
+        zoom.set("example.com", MIN_ZOOM);
+        assert_eq!(zoom.zoom_out("example.com"), MIN_ZOOM);
+    }
+
+    #[test]
+    fn test_hosts_are_independent() {
+        let mut zoom = ZoomLevels::new();
+        zoom.set("example.com", 1.5);
+        zoom.zoom_out("rust-lang.org");
+
+        assert_eq!(zoom.get("example.com"), 1.5);
+        assert_eq!(zoom.get("EXAMPLE.com"), 1.5);
+        assert!((zoom.get("rust-lang.org") - 0.9).abs() < 1e-6);
+        assert_eq!(zoom.get("docs.rs"), DEFAULT_ZOOM);
+
+        assert!(zoom.reset("example.com"));
+        assert!(!zoom.reset("example.com"));
+        assert_eq!(zoom.get("example.com"), DEFAULT_ZOOM);
+        assert!((zoom.get("rust-lang.org") - 0.9).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_steps_do_not_drift() {
+        let mut zoom = ZoomLevels::new();
+        for _ in 0..5 {
+            zoom.zoom_in("example.com");
+        }
+        assert_eq!(zoom.get("example.com"), 1.5);
+        for _ in 0..5 {
+            zoom.zoom_out("example.com");
+        }
+        assert_eq!(zoom.get("example.com"), DEFAULT_ZOOM);
+        assert!(!zoom.reset("example.com"));
+    }
+
+    #[test]
+    fn test_persistence_across_reload() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_data = UserDataManager::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let mut zoom = ZoomLevels::from_user_data(&user_data).unwrap();
+        zoom.set("example.com", 1.25);
+        zoom.save_to_storage().unwrap();
+
+        let reloaded = ZoomLevels::from_user_data(&user_data).unwrap();
+        assert_eq!(reloaded.get("example.com"), 1.25);
+    }
+
+    #[test]
+    fn test_load_clamps_stored_levels() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(ZOOM_FILE);
+        std::fs::write(
+            &path,
+            r#"{"tiny.com": 0.01, "huge.com": 40.0, "Mixed.COM": 1.5, "plain.com": 1.0}"#,
+        )
+        .unwrap();
+
+        let zoom = ZoomLevels::with_storage_path(path).unwrap();
+        assert_eq!(zoom.get("tiny.com"), MIN_ZOOM);
+        assert_eq!(zoom.get("huge.com"), MAX_ZOOM);
+        assert_eq!(zoom.get("mixed.com"), 1.5);
+        assert!(!zoom.levels.contains_key("plain.com"));
+    }
+}
@@ -0,0 +1,203 @@
+//! Address bar autocomplete selection
+//!
+//! Keyboard handling for the suggestion dropdown, kept free of egui so the
+//! selection state machine can be tested on its own.
+
+/// Keys handled by the autocomplete dropdown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuggestionKey {
+    /// Move the highlight down
+    Down,
+    /// Move the highlight up
+    Up,
+    /// Complete the input to the highlighted suggestion
+    Tab,
+    /// Navigate to the highlighted suggestion or the typed text
+    Enter,
+    /// Dismiss the dropdown
+    Escape,
+}
+
+/// What the address bar should do after a key press
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SuggestionOutcome {
+    /// Keep the dropdown open and show this text in the address bar
+    Show(String),
+    /// Navigate to this input
+    Navigate(String),
+    /// Close the dropdown and show this text in the address bar
+    Dismiss(String),
+}
+
+/// Apply a key press to the autocomplete selection
+///
+/// `typed` is the text the user typed before moving through suggestions and
+/// `highlighted` is the index of the highlighted suggestion; both are updated
+/// in place. Down/Up wrap around the ends of the list.
+pub fn handle_key(
+    key: SuggestionKey,
+    highlighted: &mut Option<usize>,
+    typed: &mut String,
+    suggestions: &[String],
+) -> SuggestionOutcome {
+    let count = suggestions.len();
+
+    // Drop a highlight that no longer points at a suggestion
+    if highlighted.is_some_and(|index| index >= count) {
+        *highlighted = None;
+    }
+
+    match key {
+        SuggestionKey::Down | SuggestionKey::Up if count == 0 => {
+            SuggestionOutcome::Show(typed.clone())
+        }
+        SuggestionKey::Down => {
+            let index = highlighted.map_or(0, |index| (index + 1) % count);
+            *highlighted = Some(index);
+            SuggestionOutcome::Show(suggestions[index].clone())
+        }
+        SuggestionKey::Up => {
+            let index = match *highlighted {
+                None | Some(0) => count - 1,
+                Some(index) => index - 1,
+            };
+            *highlighted = Some(index);
+            SuggestionOutcome::Show(suggestions[index].clone())
+        }
+        SuggestionKey::Tab => {
+            if let Some(index) = highlighted.take() {
+                *typed = suggestions[index].clone();
+            }
+            SuggestionOutcome::Show(typed.clone())
+        }
+        SuggestionKey::Enter => {
+            let target = match highlighted.take() {
+                Some(index) => suggestions[index].clone(),
+                None => typed.clone(),
+            };
+            SuggestionOutcome::Navigate(target)
+        }
+        SuggestionKey::Escape => {
+            *highlighted = None;
+            SuggestionOutcome::Dismiss(typed.clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Selection state as tracked by the address bar
+    struct Selection {
+        highlighted: Option<usize>,
+        typed: String,
+        suggestions: Vec<String>,
+    }
+
+    impl Selection {
+        fn new(typed: &str) -> Self {
+            Self {
+                highlighted: None,
+                typed: typed.to_string(),
+                suggestions: vec![
+                    "https://example.com".to_string(),
+                    "https://example.org".to_string(),
+                    "https://example.net".to_string(),
+                ],
+            }
+        }
+
+        fn press(&mut self, key: SuggestionKey) -> SuggestionOutcome {
+            handle_key(
+                key,
+                &mut self.highlighted,
+                &mut self.typed,
+                &self.suggestions,
+            )
+        }
+    }
+
+    #[test]
+    fn test_down_wraps() {
+        let mut selection = Selection::new("exa");
+
+        for expected in [0, 1, 2, 0] {
+            selection.press(SuggestionKey::Down);
+            assert_eq!(selection.highlighted, Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_up_wraps() {
+        let mut selection = Selection::new("exa");
+
+        let outcome = selection.press(SuggestionKey::Up);
+        assert_eq!(selection.highlighted, Some(2));
+        assert_eq!(
+            outcome,
+            SuggestionOutcome::Show("https://example.net".into())
+        );
+
+        selection.press(SuggestionKey::Up);
+        selection.press(SuggestionKey::Up);
+        assert_eq!(selection.highlighted, Some(0));
+
+        selection.press(SuggestionKey::Up);
+        assert_eq!(selection.highlighted, Some(2));
+    }
+
+    #[test]
+    fn test_escape_restores_typed_input() {
+        let mut selection = Selection::new("exa");
+
+        selection.press(SuggestionKey::Down);
+        let outcome = selection.press(SuggestionKey::Escape);
+
+        assert_eq!(outcome, SuggestionOutcome::Dismiss("exa".into()));
+        assert_eq!(selection.highlighted, None);
+    }
+
+    #[test]
+    fn test_tab_completes_without_navigating() {
+        let mut selection = Selection::new("exa");
+
+        selection.press(SuggestionKey::Down);
+        let outcome = selection.press(SuggestionKey::Tab);
+
+        assert_eq!(
+            outcome,
+            SuggestionOutcome::Show("https://example.com".into())
+        );
+        assert_eq!(selection.typed, "https://example.com");
+        assert_eq!(selection.highlighted, None);
+    }
+
+    #[test]
+    fn test_enter_navigates_to_highlight_or_typed() {
+        let mut selection = Selection::new("exa");
+
+        let outcome = selection.press(SuggestionKey::Enter);
+        assert_eq!(outcome, SuggestionOutcome::Navigate("exa".into()));
+
+        selection.press(SuggestionKey::Down);
+        selection.press(SuggestionKey::Down);
+        let outcome = selection.press(SuggestionKey::Enter);
+        assert_eq!(
+            outcome,
+            SuggestionOutcome::Navigate("https://example.org".into())
+        );
+        assert_eq!(selection.highlighted, None);
+    }
+
+    #[test]
+    fn test_no_suggestions_keeps_typed_input() {
+        let mut selection = Selection::new("exa");
+        selection.suggestions.clear();
+        selection.highlighted = Some(4);
+
+        let outcome = selection.press(SuggestionKey::Down);
+        assert_eq!(outcome, SuggestionOutcome::Show("exa".into()));
+        assert_eq!(selection.highlighted, None);
+    }
+}
@@ -0,0 +1,122 @@
+//! Favicon decoding and fallbacks
+//!
+//! Fetched favicons are decoded for display, and sites without a favicon get
+//! a colored tile showing their first letter.
+
+use crate::theme::Color;
+
+/// Tile background colors, chosen to stay readable with white text
+const TILE_COLORS: &[Color] = &[
+    Color::new(59, 130, 246), // Blue
+    Color::new(16, 185, 129), // Green
+    Color::new(239, 68, 68),  // Red
+    Color::new(245, 158, 11), // Amber
+    Color::new(139, 92, 246), // Violet
+    Color::new(236, 72, 153), // Pink
+    Color::new(20, 184, 166), // Teal
+    Color::new(249, 115, 22), // Orange
+    Color::new(99, 102, 241), // Indigo
+    Color::new(132, 204, 22), // Lime
+];
+
+/// Get the letter and background color of a domain's fallback tile
+///
+/// The letter is the first letter or digit of the domain, ignoring any
+/// scheme and `www.` prefix, and `?` if there is none. The color is derived
+/// from a hash of the domain, so the same domain always gets the same tile.
+pub fn letter_tile(domain: &str) -> (char, Color) {
+    let domain = domain.trim().to_lowercase();
+    let domain = domain
+        .split_once("://")
+        .map_or(domain.as_str(), |(_, rest)| rest);
+    let domain = domain.strip_prefix("www.").unwrap_or(domain);
+
+    let letter = domain
+        .chars()
+        .find(|c| c.is_alphanumeric())
+        .and_then(|c| c.to_uppercase().next())
+        .unwrap_or('?');
+
+    // FNV-1a, which unlike the std hasher is stable across Rust releases
+    let hash = domain.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    });
+    let color = TILE_COLORS[(hash % TILE_COLORS.len() as u64) as usize];
+
+    (letter, color)
+}
+
+/// Decode favicon file contents (ICO or PNG) into an image egui can display
+///
+/// Returns `None` if the bytes are not a supported image.
+pub fn decode(bytes: &[u8]) -> Option<egui::ColorImage> {
+    let image = match image::load_from_memory(bytes) {
+        Ok(image) => image.into_rgba8(),
+        Err(e) => {
+            tracing::debug!("Failed to decode favicon: {}", e);
+            return None;
+        }
+    };
+    let size = [image.width() as usize, image.height() as usize];
+    Some(egui::ColorImage::from_rgba_unmultiplied(
+        size,
+        image.as_raw(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_letter_tile_is_stable() {
+        assert_eq!(letter_tile("example.com"), letter_tile("example.com"));
+        assert_eq!(letter_tile("example.com"), letter_tile("www.Example.com"));
+        assert_eq!(
+            letter_tile("example.com"),
+            letter_tile("https://example.com")
+        );
+        assert_eq!(letter_tile("example.com").0, 'E');
+    }
+
+    #[test]
+    fn test_letter_tile_skips_insignificant_characters() {
+        assert_eq!(letter_tile("-_rust-lang.org").0, 'R');
+        assert_eq!(letter_tile("9gag.com").0, '9');
+        assert_eq!(letter_tile("").0, '?');
+    }
+
+    #[test]
+    fn test_letter_tile_colors_usually_differ() {
+        let domains = [
+            "example.com",
+            "rust-lang.org",
+            "github.com",
+            "wikipedia.org",
+            "mozilla.org",
+            "docs.rs",
+        ];
+        let mut colors: Vec<Color> = Vec::new();
+        for domain in domains {
+            let (_, color) = letter_tile(domain);
+            if !colors.contains(&color) {
+                colors.push(color);
+            }
+        }
+
+        assert!(colors.len() >= 3);
+    }
+
+    #[test]
+    fn test_decode() {
+        let mut png = Vec::new();
+        image::RgbaImage::from_pixel(2, 3, image::Rgba([255, 0, 0, 255]))
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+
+        let decoded = decode(&png).unwrap();
+        assert_eq!(decoded.size, [2, 3]);
+        assert_eq!(decoded.pixels[0], egui::Color32::RED);
+        assert!(decode(b"not an image").is_none());
+    }
+}
@@ -0,0 +1,129 @@
+//! Find in page module for Horizon Browser
+
+/// Count the occurrences of `needle` in `haystack`, including overlapping ones
+///
+/// An empty needle has no matches.
+pub fn count_matches(haystack: &str, needle: &str, case_sensitive: bool) -> usize {
+    if needle.is_empty() {
+        return 0;
+    }
+
+    if case_sensitive {
+        count_overlapping(haystack, needle)
+    } else {
+        count_overlapping(&haystack.to_lowercase(), &needle.to_lowercase())
+    }
+}
+
+/// Count matches starting at every character boundary
+fn count_overlapping(haystack: &str, needle: &str) -> usize {
+    haystack
+        .char_indices()
+        .filter(|(i, _)| haystack[*i..].starts_with(needle))
+        .count()
+}
+
+/// Find bar state
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FindState {
+    /// Text to find
+    pub query: String,
+    /// Whether letter case must match
+    pub case_sensitive: bool,
+    /// Number of matches on the page
+    pub matches: usize,
+    /// Index of the current match
+    pub current: usize,
+}
+
+impl FindState {
+    /// Create an empty find state
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Recount the matches in the page text, keeping the current match in range
+    pub fn update(&mut self, text: &str) {
+        self.matches = count_matches(text, &self.query, self.case_sensitive);
+        if self.current >= self.matches {
+            self.current = 0;
+        }
+    }
+
+    /// Move to the next match, wrapping to the first
+    pub fn next(&mut self) {
+        if self.matches > 0 {
+            self.current = (self.current + 1) % self.matches;
+        }
+    }
+
+    /// Move to the previous match, wrapping to the last
+    pub fn prev(&mut self) {
+        if self.matches > 0 {
+            self.current = self.current.checked_sub(1).unwrap_or(self.matches - 1);
+        }
+    }
+
+    /// Describe the current match, like "2 of 5"
+    pub fn status(&self) -> String {
+        if self.query.is_empty() {
+            String::new()
+        } else if self.matches == 0 {
+            "No matches".to_string()
+        } else {
+            format!("{} of {}", self.current + 1, self.matches)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_matches() {
+        assert_eq!(count_matches("the cat sat on the mat", "at", true), 3);
+        assert_eq!(count_matches("the cat", "dog", true), 0);
+        assert_eq!(count_matches("anything", "", true), 0);
+        assert_eq!(count_matches("", "a", false), 0);
+    }
+
+    #[test]
+    fn test_count_matches_overlapping() {
+        assert_eq!(count_matches("aaaa", "aa", true), 3);
+        assert_eq!(count_matches("abababa", "aba", true), 3);
+    }
+
+    #[test]
+    fn test_count_matches_case() {
+        assert_eq!(count_matches("Rust rust RUST", "rust", true), 1);
+        assert_eq!(count_matches("Rust rust RUST", "rust", false), 3);
+        assert_eq!(count_matches("Ünïcode ünïcode", "ÜNÏ", false), 2);
+    }
+
+    #[test]
+    fn test_next_and_prev_wrap() {
+        let mut find = FindState {
+            query: "a".to_string(),
+            ..FindState::new()
+        };
+        find.update("a a a");
+        assert_eq!(find.matches, 3);
+        assert_eq!(find.status(), "1 of 3");
+
+        find.prev();
+        assert_eq!(find.current, 2);
+        find.next();
+        assert_eq!(find.current, 0);
+
+        find.current = 2;
+        find.update("a a");
+        assert_eq!(find.current, 0);
+
+        find.update("none");
+        find.next();
+        find.prev();
+        assert_eq!(find.current, 0);
+        assert_eq!(find.status(), "No matches");
+    }
+}
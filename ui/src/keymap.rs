@@ -0,0 +1,501 @@
+//! Keyboard shortcuts module for Horizon Browser
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Browser commands that can be bound to keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BrowserAction {
+    NewTab,
+    NewWindow,
+    MergeWindows,
+    CloseTab,
+    NextTab,
+    PrevTab,
+    Reload,
+    Back,
+    Forward,
+    Home,
+    FocusAddressBar,
+    FindInPage,
+    ZoomIn,
+    ZoomOut,
+    ResetZoom,
+    CommandPalette,
+}
+
+impl BrowserAction {
+    /// Get the name the action is saved under
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NewTab => "NewTab",
+            Self::NewWindow => "NewWindow",
+            Self::MergeWindows => "MergeWindows",
+            Self::CloseTab => "CloseTab",
+            Self::NextTab => "NextTab",
+            Self::PrevTab => "PrevTab",
+            Self::Reload => "Reload",
+            Self::Back => "Back",
+            Self::Forward => "Forward",
+            Self::Home => "Home",
+            Self::FocusAddressBar => "FocusAddressBar",
+            Self::FindInPage => "FindInPage",
+            Self::ZoomIn => "ZoomIn",
+            Self::ZoomOut => "ZoomOut",
+            Self::ResetZoom => "ResetZoom",
+            Self::CommandPalette => "CommandPalette",
+        }
+    }
+
+    /// Parse a saved action name
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::all().iter().copied().find(|a| a.as_str() == name)
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::NewTab => "New tab",
+            Self::NewWindow => "New window",
+            Self::MergeWindows => "Merge all windows",
+            Self::CloseTab => "Close tab",
+            Self::NextTab => "Next tab",
+            Self::PrevTab => "Previous tab",
+            Self::Reload => "Reload",
+            Self::Back => "Back",
+            Self::Forward => "Forward",
+            Self::Home => "Home page",
+            Self::FocusAddressBar => "Focus address bar",
+            Self::FindInPage => "Find in page",
+            Self::ZoomIn => "Zoom in",
+            Self::ZoomOut => "Zoom out",
+            Self::ResetZoom => "Reset zoom",
+            Self::CommandPalette => "Command palette",
+        }
+    }
+
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::NewTab,
+            Self::NewWindow,
+            Self::MergeWindows,
+            Self::CloseTab,
+            Self::NextTab,
+            Self::PrevTab,
+            Self::Reload,
+            Self::Back,
+            Self::Forward,
+            Self::Home,
+            Self::FocusAddressBar,
+            Self::FindInPage,
+            Self::ZoomIn,
+            Self::ZoomOut,
+            Self::ResetZoom,
+            Self::CommandPalette,
+        ]
+    }
+}
+
+/// A key together with the modifiers held while pressing it
+///
+/// `Ctrl` stands for the command key, so it is `Cmd` on macOS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub modifiers: egui::Modifiers,
+    pub key: egui::Key,
+}
+
+impl KeyBinding {
+    /// Create a binding for a key with modifiers
+    pub const fn new(modifiers: egui::Modifiers, key: egui::Key) -> Self {
+        Self { modifiers, key }
+    }
+
+    /// Create a binding for the command key (`Ctrl`, or `Cmd` on macOS) and a key
+    pub const fn command(key: egui::Key) -> Self {
+        Self::new(egui::Modifiers::COMMAND, key)
+    }
+
+    /// Create a binding from a key press, treating `Ctrl` and `Cmd` alike
+    pub fn from_pressed(pressed: egui::Modifiers, key: egui::Key) -> Self {
+        let mut modifiers = if pressed.command || pressed.ctrl || pressed.mac_cmd {
+            egui::Modifiers::COMMAND
+        } else {
+            egui::Modifiers::NONE
+        };
+        modifiers.shift = pressed.shift;
+        modifiers.alt = pressed.alt;
+        Self::new(modifiers, key)
+    }
+
+    /// Parse a binding written like `Ctrl+Shift+Tab`, `Alt+Left`, or `F5`
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let key = egui::Key::from_name(parts.pop()?)?;
+
+        let mut modifiers = egui::Modifiers::NONE;
+        for part in parts {
+            match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "cmd" | "command" => modifiers = modifiers | egui::Modifiers::COMMAND,
+                "shift" => modifiers.shift = true,
+                "alt" | "option" => modifiers.alt = true,
+                _ => return None,
+            }
+        }
+
+        Some(Self::new(modifiers, key))
+    }
+
+    /// Check whether pressed modifiers trigger the binding
+    ///
+    /// The modifiers must match exactly, treating `Ctrl` and `Cmd` alike.
+    /// Extra `Shift` is allowed only for symbols typed with it, like `+`.
+    pub fn matches(&self, pressed: egui::Modifiers) -> bool {
+        let mut pressed = Self::from_pressed(pressed, self.key).modifiers;
+        let wanted = Self::from_pressed(self.modifiers, self.key).modifiers;
+        if is_shifted_symbol(self.key) && !wanted.shift {
+            pressed.shift = false;
+        }
+        pressed == wanted
+    }
+
+    /// Check whether the binding can be used as a shortcut
+    ///
+    /// A shortcut needs `Ctrl`, `Alt` or a function key, since keys pressed
+    /// with only `Shift` are typing.
+    pub fn is_shortcut(&self) -> bool {
+        let m = self.modifiers;
+        let key = self.key as usize;
+        m.command
+            || m.ctrl
+            || m.mac_cmd
+            || m.alt
+            || (egui::Key::F1 as usize..=egui::Key::F35 as usize).contains(&key)
+    }
+
+    /// Count the modifiers the binding requires
+    fn modifier_count(&self) -> usize {
+        let m = self.modifiers;
+        [m.command || m.ctrl || m.mac_cmd, m.shift, m.alt]
+            .iter()
+            .filter(|held| **held)
+            .count()
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let m = self.modifiers;
+        if m.command || m.ctrl || m.mac_cmd {
+            f.write_str("Ctrl+")?;
+        }
+        if m.alt {
+            f.write_str("Alt+")?;
+        }
+        if m.shift {
+            f.write_str("Shift+")?;
+        }
+        f.write_str(self.key.name())
+    }
+}
+
+/// Check whether a key is a symbol typed with `Shift` on common layouts
+fn is_shifted_symbol(key: egui::Key) -> bool {
+    use egui::Key;
+    matches!(key, Key::Plus | Key::Colon | Key::Pipe | Key::Questionmark)
+}
+
+/// Keys bound to each browser action
+///
+/// Saved as a table of action names to binding strings, so files written
+/// before an action existed load with that action's default bindings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    from = "BTreeMap<String, Vec<String>>",
+    into = "BTreeMap<String, Vec<String>>"
+)]
+pub struct Keymap {
+    bindings: BTreeMap<BrowserAction, Vec<KeyBinding>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use egui::{Key, Modifiers};
+
+        let bindings = [
+            (BrowserAction::NewTab, vec![KeyBinding::command(Key::T)]),
+            (BrowserAction::NewWindow, vec![KeyBinding::command(Key::N)]),
+            (
+                BrowserAction::MergeWindows,
+                vec![KeyBinding::new(
+                    Modifiers::COMMAND | Modifiers::SHIFT,
+                    Key::M,
+                )],
+            ),
+            (BrowserAction::CloseTab, vec![KeyBinding::command(Key::W)]),
+            (BrowserAction::NextTab, vec![KeyBinding::command(Key::Tab)]),
+            (
+                BrowserAction::PrevTab,
+                vec![KeyBinding::new(
+                    Modifiers::COMMAND | Modifiers::SHIFT,
+                    Key::Tab,
+                )],
+            ),
+            (
+                BrowserAction::Reload,
+                vec![
+                    KeyBinding::command(Key::R),
+                    KeyBinding::new(Modifiers::NONE, Key::F5),
+                ],
+            ),
+            (
+                BrowserAction::Back,
+                vec![KeyBinding::new(Modifiers::ALT, Key::ArrowLeft)],
+            ),
+            (
+                BrowserAction::Forward,
+                vec![KeyBinding::new(Modifiers::ALT, Key::ArrowRight)],
+            ),
+            (
+                BrowserAction::Home,
+                vec![KeyBinding::new(Modifiers::ALT, Key::Home)],
+            ),
+            (
+                BrowserAction::FocusAddressBar,
+                vec![KeyBinding::command(Key::L)],
+            ),
+            (BrowserAction::FindInPage, vec![KeyBinding::command(Key::F)]),
+            (
+                BrowserAction::ZoomIn,
+                vec![
+                    KeyBinding::command(Key::Plus),
+                    KeyBinding::command(Key::Equals),
+                ],
+            ),
+            (
+                BrowserAction::ZoomOut,
+                vec![KeyBinding::command(Key::Minus)],
+            ),
+            (
+                BrowserAction::ResetZoom,
+                vec![KeyBinding::command(Key::Num0)],
+            ),
+            (
+                BrowserAction::CommandPalette,
+                vec![KeyBinding::new(
+                    Modifiers::COMMAND | Modifiers::SHIFT,
+                    Key::P,
+                )],
+            ),
+        ];
+
+        Self {
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Create a keymap with the default bindings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the keys bound to an action
+    pub fn bindings(&self, action: BrowserAction) -> &[KeyBinding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Bind a key to an action, replacing the action's previous keys
+    ///
+    /// The key is removed from any other action it was bound to.
+    pub fn bind(&mut self, action: BrowserAction, binding: KeyBinding) {
+        for bindings in self.bindings.values_mut() {
+            bindings.retain(|b| *b != binding);
+        }
+        self.bindings.insert(action, vec![binding]);
+    }
+
+    /// Find the action a key press triggers
+    ///
+    /// When several bindings match, the one requiring the most modifiers
+    /// wins, so `Ctrl+Shift+Tab` is not taken for `Ctrl+Tab`.
+    pub fn resolve(&self, key: egui::Key, modifiers: egui::Modifiers) -> Option<BrowserAction> {
+        self.bindings
+            .iter()
+            .flat_map(|(action, bindings)| bindings.iter().map(move |b| (*action, b)))
+            .filter(|(_, binding)| binding.key == key && binding.matches(modifiers))
+            .max_by_key(|(_, binding)| binding.modifier_count())
+            .map(|(action, _)| action)
+    }
+
+    /// Get the actions triggered by key presses this frame, in press order
+    ///
+    /// Nothing is triggered while a text field has keyboard focus, so the
+    /// keys reach the field instead.
+    pub fn pressed(&self, ctx: &egui::Context) -> Vec<BrowserAction> {
+        if ctx.wants_keyboard_input() {
+            return Vec::new();
+        }
+
+        ctx.input(|input| {
+            input
+                .events
+                .iter()
+                .filter_map(|event| match event {
+                    egui::Event::Key {
+                        key,
+                        pressed: true,
+                        modifiers,
+                        ..
+                    } => self.resolve(*key, *modifiers),
+                    _ => None,
+                })
+                .collect()
+        })
+    }
+}
+
+impl From<BTreeMap<String, Vec<String>>> for Keymap {
+    fn from(saved: BTreeMap<String, Vec<String>>) -> Self {
+        let mut keymap = Self::default();
+        for (name, bindings) in saved {
+            match BrowserAction::parse(&name) {
+                Some(action) => {
+                    let bindings = bindings.iter().filter_map(|b| KeyBinding::parse(b));
+                    keymap.bindings.insert(action, bindings.collect());
+                }
+                None => tracing::warn!("Ignoring shortcuts for unknown action {}", name),
+            }
+        }
+        keymap
+    }
+}
+
+impl From<Keymap> for BTreeMap<String, Vec<String>> {
+    fn from(keymap: Keymap) -> Self {
+        keymap
+            .bindings
+            .into_iter()
+            .map(|(action, bindings)| {
+                let bindings = bindings.iter().map(ToString::to_string).collect();
+                (action.as_str().to_string(), bindings)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::{Key, Modifiers};
+
+    #[test]
+    fn test_default_bindings() {
+        let keymap = Keymap::default();
+        let ctrl = Modifiers::CTRL | Modifiers::COMMAND;
+
+        assert_eq!(keymap.resolve(Key::T, ctrl), Some(BrowserAction::NewTab));
+        assert_eq!(keymap.resolve(Key::W, ctrl), Some(BrowserAction::CloseTab));
+        assert_eq!(keymap.resolve(Key::Tab, ctrl), Some(BrowserAction::NextTab));
+        assert_eq!(
+            keymap.resolve(Key::Tab, ctrl | Modifiers::SHIFT),
+            Some(BrowserAction::PrevTab)
+        );
+        assert_eq!(
+            keymap.resolve(Key::F5, Modifiers::NONE),
+            Some(BrowserAction::Reload)
+        );
+        assert_eq!(
+            keymap.resolve(Key::ArrowLeft, Modifiers::ALT),
+            Some(BrowserAction::Back)
+        );
+        assert_eq!(
+            keymap.resolve(Key::Plus, ctrl | Modifiers::SHIFT),
+            Some(BrowserAction::ZoomIn)
+        );
+        assert_eq!(keymap.resolve(Key::T, Modifiers::NONE), None);
+        assert_eq!(keymap.resolve(Key::ArrowLeft, Modifiers::NONE), None);
+
+        for action in BrowserAction::all() {
+            assert!(!keymap.bindings(*action).is_empty(), "{:?}", action);
+        }
+    }
+
+    #[test]
+    fn test_remapped_action() {
+        let mut keymap = Keymap::default();
+        let ctrl = Modifiers::CTRL | Modifiers::COMMAND;
+
+        keymap.bind(BrowserAction::Reload, KeyBinding::command(Key::T));
+        assert_eq!(keymap.resolve(Key::T, ctrl), Some(BrowserAction::Reload));
+        assert_eq!(keymap.resolve(Key::R, ctrl), None);
+        assert_eq!(keymap.resolve(Key::F5, Modifiers::NONE), None);
+        assert!(keymap.bindings(BrowserAction::NewTab).is_empty());
+    }
+
+    #[test]
+    fn test_extra_modifiers_do_not_match() {
+        let keymap = Keymap::default();
+        let ctrl = Modifiers::CTRL | Modifiers::COMMAND;
+
+        assert_eq!(keymap.resolve(Key::T, ctrl | Modifiers::SHIFT), None);
+        assert_eq!(keymap.resolve(Key::R, ctrl | Modifiers::ALT), None);
+        assert_eq!(keymap.resolve(Key::F5, Modifiers::SHIFT), None);
+        assert_eq!(
+            keymap.resolve(Key::ArrowLeft, Modifiers::ALT | Modifiers::SHIFT),
+            None
+        );
+        assert_eq!(keymap.resolve(Key::Plus, ctrl), Some(BrowserAction::ZoomIn));
+    }
+
+    #[test]
+    fn test_is_shortcut() {
+        assert!(KeyBinding::command(Key::T).is_shortcut());
+        assert!(KeyBinding::new(Modifiers::ALT, Key::ArrowLeft).is_shortcut());
+        assert!(KeyBinding::new(Modifiers::NONE, Key::F5).is_shortcut());
+        assert!(KeyBinding::new(Modifiers::NONE, Key::F35).is_shortcut());
+        assert!(!KeyBinding::new(Modifiers::NONE, Key::T).is_shortcut());
+        assert!(!KeyBinding::new(Modifiers::SHIFT, Key::T).is_shortcut());
+    }
+
+    #[test]
+    fn test_key_binding_parse_and_display() {
+        let binding = KeyBinding::parse("Ctrl+Shift+Tab").unwrap();
+        assert_eq!(
+            binding,
+            KeyBinding::new(Modifiers::COMMAND | Modifiers::SHIFT, Key::Tab)
+        );
+        assert_eq!(binding.to_string(), "Ctrl+Shift+Tab");
+        assert_eq!(
+            KeyBinding::parse("alt + Left"),
+            Some(KeyBinding::new(Modifiers::ALT, Key::ArrowLeft))
+        );
+        assert_eq!(KeyBinding::parse("F5").unwrap().to_string(), "F5");
+        assert_eq!(
+            KeyBinding::from_pressed(Modifiers::CTRL | Modifiers::COMMAND, Key::Tab),
+            KeyBinding::command(Key::Tab)
+        );
+        assert_eq!(KeyBinding::parse("Hyper+T"), None);
+        assert_eq!(KeyBinding::parse("Ctrl+"), None);
+    }
+
+    #[test]
+    fn test_keymap_serde_keeps_missing_defaults() {
+        let mut keymap = Keymap::default();
+        keymap.bind(BrowserAction::FindInPage, KeyBinding::command(Key::G));
+
+        let json = serde_json::to_string(&keymap).unwrap();
+        assert_eq!(serde_json::from_str::<Keymap>(&json).unwrap(), keymap);
+
+        let partial: Keymap = serde_json::from_str(r#"{ "Reload": ["F9"] }"#).unwrap();
+        assert_eq!(
+            partial.bindings(BrowserAction::Reload),
+            &[KeyBinding::new(Modifiers::NONE, Key::F9)]
+        );
+        assert_eq!(
+            partial.bindings(BrowserAction::NewTab),
+            Keymap::default().bindings(BrowserAction::NewTab)
+        );
+    }
+}
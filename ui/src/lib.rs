@@ -3,17 +3,25 @@
 //! User interface layer for the Horizon Browser.
 //! Provides window management and UI components.
 
+pub mod autocomplete;
+pub mod favicon;
+pub mod find;
+pub mod keymap;
+pub mod palette;
+pub mod reader;
 pub mod settings;
 pub mod sidebar;
 pub mod tabs;
 pub mod theme;
 pub mod window;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use std::path::Path;
 
 /// UI manager coordinates all UI components
 pub struct UIManager {
     theme: theme::Theme,
+    themes: theme::ThemeRegistry,
 }
 
 impl UIManager {
@@ -21,6 +29,7 @@ impl UIManager {
     pub fn new() -> Self {
         Self {
             theme: theme::Theme::default(),
+            themes: theme::ThemeRegistry::new(),
         }
     }
 
@@ -35,9 +44,24 @@ impl UIManager {
         &self.theme
     }
 
-    /// Set the theme
-    pub fn set_theme(&mut self, theme: theme::Theme) {
-        self.theme = theme;
+    /// Get the registered themes
+    pub fn themes(&self) -> &theme::ThemeRegistry {
+        &self.themes
+    }
+
+    /// Load custom themes from a directory, returning how many were loaded
+    pub fn load_themes(&mut self, dir: &Path) -> Result<usize> {
+        self.themes.load_dir(dir)
+    }
+
+    /// Switch to a registered theme by name
+    pub fn set_theme(&mut self, name: &str) -> Result<()> {
+        let theme = self
+            .themes
+            .get(name)
+            .ok_or_else(|| anyhow!("Theme not found: {}", name))?;
+        self.theme = theme.clone();
+        Ok(())
     }
 }
 
@@ -62,4 +86,14 @@ mod tests {
         let mut manager = UIManager::new();
         assert!(manager.initialize().is_ok());
     }
+
+    #[test]
+    fn test_set_registered_theme() {
+        let mut manager = UIManager::new();
+        manager.set_theme("Light").unwrap();
+        assert_eq!(manager.theme().name(), "Light");
+
+        assert!(manager.set_theme("Missing").is_err());
+        assert_eq!(manager.theme().name(), "Light");
+    }
 }
@@ -0,0 +1,242 @@
+//! Command palette for Horizon Browser
+//!
+//! A quick switcher that fuzzy searches browser commands, open tabs,
+//! bookmarks, and history.
+
+use crate::keymap::BrowserAction;
+
+/// Score for each query character found in a candidate
+const MATCH_SCORE: i64 = 1;
+
+/// Bonus for a character matched right after the previous one
+const CONTIGUOUS_BONUS: i64 = 5;
+
+/// Bonus for a character matched at the start of a word
+const WORD_START_BONUS: i64 = 3;
+
+/// Most results shown at once
+pub const MAX_RESULTS: usize = 10;
+
+/// Score how well a query fuzzy matches a candidate, ignoring case
+///
+/// The query's characters must appear in the candidate in order, though not
+/// necessarily next to each other. Runs of adjacent characters and matches
+/// at word starts score higher. Returns `None` if the query does not match.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let mut query = query.chars().flat_map(char::to_lowercase).peekable();
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut previous_matched = false;
+
+    for c in candidate.chars().flat_map(char::to_lowercase) {
+        let Some(&wanted) = query.peek() else {
+            break;
+        };
+
+        let matched = c == wanted;
+        if matched {
+            query.next();
+            score += MATCH_SCORE;
+            if previous_matched {
+                score += CONTIGUOUS_BONUS;
+            }
+            if previous.is_none_or(|p| !p.is_alphanumeric()) {
+                score += WORD_START_BONUS;
+            }
+        }
+        previous_matched = matched;
+        previous = Some(c);
+    }
+
+    query.peek().is_none().then_some(score)
+}
+
+/// Rank candidates by how well they match a query, best first
+///
+/// Candidates that don't match are left out, and equal scores keep their
+/// original order.
+pub fn rank<T: AsRef<str>>(query: &str, candidates: impl IntoIterator<Item = T>) -> Vec<(i64, T)> {
+    let mut ranked: Vec<(i64, T)> = candidates
+        .into_iter()
+        .filter_map(|candidate| Some((fuzzy_score(query, candidate.as_ref())?, candidate)))
+        .collect();
+    ranked.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    ranked
+}
+
+/// What a palette result does when selected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteTarget {
+    /// Run a browser command
+    Command(BrowserAction),
+    /// Switch to the open tab at an index
+    Tab(usize),
+    /// Navigate the active tab to a URL
+    Url(String),
+}
+
+/// A single palette result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteItem {
+    /// Text matched against the query and shown for the result
+    pub label: String,
+    /// Secondary text, like a URL or shortcut
+    pub detail: String,
+    /// What selecting the result does
+    pub target: PaletteTarget,
+}
+
+impl AsRef<str> for PaletteItem {
+    fn as_ref(&self) -> &str {
+        &self.label
+    }
+}
+
+/// Command palette state, while the palette is open
+#[derive(Debug, Clone)]
+pub struct CommandPalette {
+    /// Text typed into the palette
+    pub query: String,
+    /// Index of the highlighted result
+    pub selected: usize,
+    /// Named commands the palette can run
+    commands: Vec<(String, BrowserAction)>,
+}
+
+impl CommandPalette {
+    /// Create a palette with every browser command registered
+    pub fn new() -> Self {
+        let mut palette = Self {
+            query: String::new(),
+            selected: 0,
+            commands: Vec::new(),
+        };
+        for action in BrowserAction::all() {
+            if *action != BrowserAction::CommandPalette {
+                palette.register(action.name(), *action);
+            }
+        }
+        palette
+    }
+
+    /// Register a command under a name
+    pub fn register(&mut self, name: impl Into<String>, action: BrowserAction) {
+        self.commands.push((name.into(), action));
+    }
+
+    /// Get the registered commands
+    pub fn commands(&self) -> &[(String, BrowserAction)] {
+        &self.commands
+    }
+
+    /// Get the best results for the query among the commands and other items
+    ///
+    /// Commands come before other items with the same score.
+    pub fn results(&self, items: impl IntoIterator<Item = PaletteItem>) -> Vec<PaletteItem> {
+        let commands = self.commands.iter().map(|(name, action)| PaletteItem {
+            label: name.clone(),
+            detail: "Command".to_string(),
+            target: PaletteTarget::Command(*action),
+        });
+
+        rank(&self.query, commands.chain(items))
+            .into_iter()
+            .take(MAX_RESULTS)
+            .map(|(_, item)| item)
+            .collect()
+    }
+
+    /// Move the highlight down, wrapping to the first of `count` results
+    pub fn select_next(&mut self, count: usize) {
+        if count > 0 {
+            self.selected = (self.selected + 1) % count;
+        }
+    }
+
+    /// Move the highlight up, wrapping to the last of `count` results
+    pub fn select_prev(&mut self, count: usize) {
+        if count > 0 {
+            self.selected = self.selected.checked_sub(1).unwrap_or(count - 1) % count;
+        }
+    }
+}
+
+impl Default for CommandPalette {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subsequence_match() {
+        assert!(fuzzy_score("nt", "New tab").is_some());
+        assert!(fuzzy_score("zmin", "Zoom in").is_some());
+        assert!(fuzzy_score("", "anything").is_some());
+        assert_eq!(fuzzy_score("tn", "New tab"), None);
+        assert_eq!(fuzzy_score("reloads", "Reload"), None);
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        assert_eq!(fuzzy_score("NEW", "new tab"), fuzzy_score("new", "New Tab"));
+        assert!(fuzzy_score("GITHUB", "github.com").is_some());
+    }
+
+    #[test]
+    fn test_contiguous_matches_rank_higher() {
+        let contiguous = fuzzy_score("tab", "Close tab").unwrap();
+        let scattered = fuzzy_score("tab", "Toggle a bookmark").unwrap();
+        assert!(contiguous > scattered);
+
+        let ranked = rank("tab", ["Toggle a bookmark", "Close tab", "Reload"]);
+        let names: Vec<&str> = ranked.iter().map(|(_, name)| *name).collect();
+        assert_eq!(names, vec!["Close tab", "Toggle a bookmark"]);
+    }
+
+    #[test]
+    fn test_word_starts_rank_higher() {
+        assert!(fuzzy_score("t", "Tab").unwrap() > fuzzy_score("t", "Cat").unwrap());
+        assert!(fuzzy_score("zi", "Zoom in").unwrap() > fuzzy_score("zi", "Lazy index").unwrap());
+    }
+
+    #[test]
+    fn test_results_include_commands_and_items() {
+        let palette = CommandPalette {
+            query: "reload".to_string(),
+            ..CommandPalette::new()
+        };
+        assert!(!palette
+            .commands()
+            .iter()
+            .any(|(_, action)| *action == BrowserAction::CommandPalette));
+
+        let results = palette.results([PaletteItem {
+            label: "https://reload.example".to_string(),
+            detail: "History".to_string(),
+            target: PaletteTarget::Url("https://reload.example".to_string()),
+        }]);
+        assert_eq!(
+            results[0].target,
+            PaletteTarget::Command(BrowserAction::Reload)
+        );
+        assert_eq!(
+            results[1].target,
+            PaletteTarget::Url("https://reload.example".to_string())
+        );
+    }
+
+    #[test]
+    fn test_selection_wraps() {
+        let mut palette = CommandPalette::new();
+        palette.select_prev(3);
+        assert_eq!(palette.selected, 2);
+        palette.select_next(3);
+        assert_eq!(palette.selected, 0);
+        palette.select_next(0);
+        assert_eq!(palette.selected, 0);
+    }
+}
@@ -0,0 +1,286 @@
+//! Reader mode
+//!
+//! Extracts the article from a page's content, dropping navigation, cookie
+//! notices, and other boilerplate. Content is plain text with blank lines
+//! between blocks, or HTML-like markup whose block tags separate blocks.
+
+/// Blocks with fewer words are treated as navigation or captions
+const MIN_PARAGRAPH_WORDS: usize = 12;
+
+/// Pages with fewer words of paragraphs are not articles
+const MIN_ARTICLE_WORDS: usize = 80;
+
+/// Titles and bylines are at most this many words
+const MAX_HEADING_WORDS: usize = 20;
+
+/// Blocks mentioning these are boilerplate unless they are long
+const BOILERPLATE_PHRASES: &[&str] = &[
+    "cookie",
+    "subscribe",
+    "newsletter",
+    "sign in",
+    "sign up",
+    "log in",
+    "advertisement",
+    "all rights reserved",
+    "privacy policy",
+    "terms of service",
+    "share this",
+    "follow us",
+    "related articles",
+    "read more",
+    "©",
+];
+
+/// Boilerplate phrases are ignored in blocks with at least this many words
+const BOILERPLATE_MAX_WORDS: usize = 40;
+
+/// Tags that start a new block
+const BLOCK_TAGS: &[&str] = &[
+    "p",
+    "div",
+    "br",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "li",
+    "ul",
+    "ol",
+    "section",
+    "article",
+    "header",
+    "footer",
+    "nav",
+    "aside",
+    "main",
+    "blockquote",
+    "pre",
+    "table",
+    "tr",
+    "figure",
+];
+
+/// A page simplified for reading
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Article {
+    /// Article headline
+    pub title: Option<String>,
+    /// Author line, like "By Jane Doe"
+    pub byline: Option<String>,
+    /// Body paragraphs in page order
+    pub paragraphs: Vec<String>,
+}
+
+impl Article {
+    /// Extract the article from page content
+    ///
+    /// Returns `None` if the page does not look like an article.
+    pub fn extract(content: &str) -> Option<Self> {
+        let mut title = None;
+        let mut byline = None;
+        let mut paragraphs = Vec::new();
+
+        for block in blocks(&strip_markup(content)) {
+            let words = block.split_whitespace().count();
+            if is_boilerplate(&block, words) {
+                continue;
+            }
+
+            if words >= MIN_PARAGRAPH_WORDS && !is_byline(&block, words) {
+                paragraphs.push(block);
+            } else if paragraphs.is_empty() {
+                // Headings come before the body
+                if byline.is_none() && is_byline(&block, words) {
+                    byline = Some(block);
+                } else if title.is_none() && words <= MAX_HEADING_WORDS {
+                    title = Some(block);
+                }
+            }
+        }
+
+        let total_words: usize = paragraphs
+            .iter()
+            .map(|paragraph| paragraph.split_whitespace().count())
+            .sum();
+        if paragraphs.len() < 2 || total_words < MIN_ARTICLE_WORDS {
+            return None;
+        }
+
+        Some(Self {
+            title,
+            byline,
+            paragraphs,
+        })
+    }
+
+    /// Get the article's text, one block per line
+    pub fn text(&self) -> String {
+        self.title
+            .iter()
+            .chain(&self.byline)
+            .chain(&self.paragraphs)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Remove tags, turning block tags into blank lines, and decode common
+/// entities
+fn strip_markup(content: &str) -> String {
+    let mut text = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            // A lone `<` is text
+            text.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+
+        let tag = &rest[start + 1..start + end];
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if BLOCK_TAGS.contains(&name.as_str()) {
+            text.push_str("\n\n");
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Split text into blocks at blank lines, joining each block's lines
+fn blocks(text: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            if !current.is_empty() {
+                blocks.push(current.join(" "));
+                current.clear();
+            }
+        } else {
+            current.extend(line.split_whitespace());
+        }
+    }
+    if !current.is_empty() {
+        blocks.push(current.join(" "));
+    }
+
+    blocks
+}
+
+/// Check whether a block is navigation, a notice, or other boilerplate
+fn is_boilerplate(block: &str, words: usize) -> bool {
+    // Link lists like "Home | News | Sports"
+    let separators = block.matches(['|', '·', '»']).count();
+    if separators >= 2 && separators * 4 >= words {
+        return true;
+    }
+
+    let lower = block.to_lowercase();
+    words < BOILERPLATE_MAX_WORDS
+        && BOILERPLATE_PHRASES
+            .iter()
+            .any(|phrase| lower.contains(phrase))
+}
+
+/// Check whether a block is an author line
+fn is_byline(block: &str, words: usize) -> bool {
+    let lower = block.to_lowercase();
+    words <= MAX_HEADING_WORDS && (lower.starts_with("by ") || lower.contains("written by"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: [&str; 3] = [
+        "The city council voted on Tuesday to expand the bike lane network across \
+         the downtown core, adding twelve miles of protected lanes by next summer.",
+        "Supporters said the plan would make cycling safer for commuters, while \
+         some business owners worried about losing parking spaces in front of \
+         their shops during the construction period.",
+        "Construction is expected to begin in the spring, and the council will \
+         review progress at a public meeting in the fall before approving the \
+         second phase of the project.",
+    ];
+
+    #[test]
+    fn test_extract_drops_boilerplate() {
+        let page = format!(
+            "Home | News | Sports | Weather\n\n\
+             Council Expands Bike Lanes\n\n\
+             By Jane Doe\n\n\
+             We use cookies to improve your experience. Accept all cookies?\n\n\
+             {}\n\n\
+             Advertisement\n\n\
+             {}\n\n\
+             {}\n\n\
+             Subscribe to our newsletter for daily updates.\n\n\
+             © 2024 Daily News. All rights reserved.",
+            BODY[0], BODY[1], BODY[2]
+        );
+
+        let article = Article::extract(&page).unwrap();
+        assert_eq!(article.title.as_deref(), Some("Council Expands Bike Lanes"));
+        assert_eq!(article.byline.as_deref(), Some("By Jane Doe"));
+        assert_eq!(article.paragraphs, BODY);
+    }
+
+    #[test]
+    fn test_extract_joins_wrapped_lines() {
+        let page = format!(
+            "Title\n\nThe city council voted on Tuesday to expand\nthe bike lane network \
+             across the downtown core, adding twelve miles of protected lanes by next \
+             summer.\n\n{}\n\n{}",
+            BODY[1], BODY[2]
+        );
+        let article = Article::extract(&page).unwrap();
+        assert_eq!(article.paragraphs, BODY);
+    }
+
+    #[test]
+    fn test_extract_markup() {
+        let page = format!(
+            "<html><body><nav><a href=\"/\">Home</a> | <a href=\"/news\">News</a> | \
+             <a href=\"/about\">About</a></nav><article><h1>Bikes &amp; Buses</h1>\
+             <p class=\"byline\">By Jane Doe</p><p>{}</p><p>{}</p><p>{}</p></article>\
+             <footer>Privacy Policy</footer></body></html>",
+            BODY[0], BODY[1], BODY[2]
+        );
+
+        let article = Article::extract(&page).unwrap();
+        assert_eq!(article.title.as_deref(), Some("Bikes & Buses"));
+        assert_eq!(article.byline.as_deref(), Some("By Jane Doe"));
+        assert_eq!(article.paragraphs, BODY);
+        assert!(article
+            .text()
+            .starts_with("Bikes & Buses\nBy Jane Doe\nThe city"));
+    }
+
+    #[test]
+    fn test_non_articles_are_rejected() {
+        assert_eq!(Article::extract(""), None);
+        assert_eq!(Article::extract("Home | News | Sports\n\nWelcome!"), None);
+        // A single paragraph is not an article
+        assert_eq!(Article::extract(BODY[0]), None);
+    }
+}
@@ -1,9 +1,20 @@
 //! Settings UI module for Horizon Browser
 
+use anyhow::{anyhow, Result};
+use horizon_url::ParsedUrl;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
+use std::path::Path;
+
+/// Smallest font size that can be chosen, in points
+pub const MIN_FONT_SIZE: u16 = 10;
+
+/// Largest font size that can be chosen, in points
+pub const MAX_FONT_SIZE: u16 = 20;
 
 /// Settings UI state
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SettingsUI {
     /// General settings
     pub general: GeneralSettings,
@@ -17,6 +28,9 @@ pub struct SettingsUI {
     pub downloads: DownloadsSettings,
     /// Advanced settings
     pub advanced: AdvancedSettings,
+    /// Keyboard shortcuts
+    #[serde(default)]
+    pub keymap: crate::keymap::Keymap,
     /// Currently selected panel
     #[serde(skip)]
     pub selected_panel: SettingsPanel,
@@ -37,7 +51,7 @@ pub enum SettingsPanel {
 }
 
 /// General settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GeneralSettings {
     /// Homepage URL
     pub homepage: String,
@@ -45,6 +59,10 @@ pub struct GeneralSettings {
     pub search_engine: SearchEngine,
     /// Restore tabs on startup
     pub restore_tabs_on_startup: bool,
+    /// Activate the left neighbor instead of the right one when closing a tab
+    pub select_left_on_close: bool,
+    /// Switch to a tab after hovering it for this many milliseconds (None to disable)
+    pub hover_switch_ms: Option<u64>,
 }
 
 impl Default for GeneralSettings {
@@ -53,26 +71,70 @@ impl Default for GeneralSettings {
             homepage: "about:home".to_string(),
             search_engine: SearchEngine::DuckDuckGo,
             restore_tabs_on_startup: false,
+            select_left_on_close: false,
+            hover_switch_ms: None,
+        }
+    }
+}
+
+impl GeneralSettings {
+    /// Which neighbor to activate after closing the active tab
+    pub fn close_direction(&self) -> crate::tabs::CloseDirection {
+        if self.select_left_on_close {
+            crate::tabs::CloseDirection::Left
+        } else {
+            crate::tabs::CloseDirection::Right
         }
     }
 }
 
+/// Placeholder replaced by the search terms in a custom search URL template
+pub const QUERY_PLACEHOLDER: &str = "{query}";
+
 /// Search engine options
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SearchEngine {
     DuckDuckGo,
     Google,
     Bing,
     Brave,
+    /// User-defined engine searching a URL template
+    Custom {
+        name: String,
+        url_template: String,
+    },
 }
 
 impl SearchEngine {
+    /// Create a custom search engine
+    ///
+    /// Fails if the name is empty or the template has no `{query}` placeholder.
+    pub fn custom(name: &str, url_template: &str) -> Result<Self> {
+        let name = name.trim();
+        let url_template = url_template.trim();
+        if name.is_empty() {
+            return Err(anyhow!("Custom search engine needs a name"));
+        }
+        if !url_template.contains(QUERY_PLACEHOLDER) {
+            return Err(anyhow!(
+                "Search URL must contain the {} placeholder",
+                QUERY_PLACEHOLDER
+            ));
+        }
+
+        Ok(Self::Custom {
+            name: name.to_string(),
+            url_template: url_template.to_string(),
+        })
+    }
+
     pub fn name(&self) -> &str {
         match self {
             Self::DuckDuckGo => "DuckDuckGo",
             Self::Google => "Google",
             Self::Bing => "Bing",
             Self::Brave => "Brave",
+            Self::Custom { name, .. } => name,
         }
     }
 
@@ -83,16 +145,28 @@ impl SearchEngine {
             Self::Google => format!("https://www.google.com/search?q={}", encoded_query),
             Self::Bing => format!("https://www.bing.com/search?q={}", encoded_query),
             Self::Brave => format!("https://search.brave.com/search?q={}", encoded_query),
+            Self::Custom { url_template, .. } => {
+                url_template.replace(QUERY_PLACEHOLDER, &encoded_query)
+            }
+        }
+    }
+
+    /// Get the URL template of a custom engine
+    pub fn url_template(&self) -> Option<&str> {
+        match self {
+            Self::Custom { url_template, .. } => Some(url_template),
+            _ => None,
         }
     }
 
+    /// Get the built-in search engines
     pub fn all() -> &'static [Self] {
         &[Self::DuckDuckGo, Self::Google, Self::Bing, Self::Brave]
     }
 }
 
 /// Privacy settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PrivacySettings {
     /// Enable tracking protection
     pub tracking_protection: bool,
@@ -119,10 +193,13 @@ impl Default for PrivacySettings {
 }
 
 /// Appearance settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AppearanceSettings {
     /// Theme selection
     pub theme: Theme,
+    /// Name of a theme from the themes folder, used instead of `theme`
+    #[serde(default)]
+    pub custom_theme: Option<String>,
     /// Font size
     pub font_size: u16,
     /// Show bookmarks bar
@@ -133,6 +210,7 @@ impl Default for AppearanceSettings {
     fn default() -> Self {
         Self {
             theme: Theme::Dark,
+            custom_theme: None,
             font_size: 14,
             show_bookmarks_bar: false,
         }
@@ -144,6 +222,8 @@ impl Default for AppearanceSettings {
 pub enum Theme {
     Dark,
     Light,
+    /// Follow the operating system's light or dark preference
+    Auto,
 }
 
 impl Theme {
@@ -151,16 +231,36 @@ impl Theme {
         match self {
             Self::Dark => "Dark",
             Self::Light => "Light",
+            Self::Auto => "Auto",
         }
     }
 
     pub fn all() -> &'static [Self] {
-        &[Self::Dark, Self::Light]
+        &[Self::Dark, Self::Light, Self::Auto]
+    }
+
+    /// Resolve `Auto` to the system theme, keeping concrete themes as they are
+    pub fn resolve(self, system: Theme) -> Theme {
+        match self {
+            Self::Auto => system,
+            theme => theme,
+        }
+    }
+}
+
+/// Detect the operating system's theme preference, defaulting to dark
+///
+/// eframe refreshes the preference in the frame input, so this is cheap to
+/// call every frame.
+pub fn detect_system_theme(ctx: &egui::Context) -> Theme {
+    match ctx.system_theme() {
+        Some(egui::Theme::Light) => Theme::Light,
+        _ => Theme::Dark,
     }
 }
 
 /// Downloads settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DownloadsSettings {
     /// Default download directory
     pub download_directory: String,
@@ -188,7 +288,7 @@ impl Default for DownloadsSettings {
 }
 
 /// Advanced settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AdvancedSettings {
     /// Enable developer tools
     pub enable_developer_tools: bool,
@@ -196,10 +296,19 @@ pub struct AdvancedSettings {
     pub hardware_acceleration: bool,
     /// Enable experimental features
     pub experimental_features: bool,
+    /// Resolve and connect to the homepage and search engine hosts on startup
+    pub preconnect_on_startup: bool,
+    /// Maximum size of the cache directory in bytes
+    #[serde(default = "default_max_cache_bytes")]
+    pub max_cache_bytes: u64,
+}
+
+fn default_max_cache_bytes() -> u64 {
+    horizon_storage::userdata::DEFAULT_MAX_CACHE_BYTES
 }
 
 /// Network settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NetworkSettings {
     /// DNS provider
     pub dns_provider: DnsProvider,
@@ -228,6 +337,38 @@ impl Default for NetworkSettings {
     }
 }
 
+impl NetworkSettings {
+    /// Get the proxy traffic should be sent through, if the VPN is enabled
+    ///
+    /// OpenVPN has no configuration file in the settings yet, so it sends
+    /// traffic directly.
+    pub fn vpn_config(&self) -> Option<horizon_networking::vpn::VpnConfig> {
+        use horizon_networking::vpn::{ProxyProtocol, VpnConfig};
+
+        if !self.vpn_enabled {
+            return None;
+        }
+        let host = self.proxy_host.trim().to_string();
+        match self.vpn_type {
+            VpnType::Proxy => Some(VpnConfig::Proxy {
+                protocol: ProxyProtocol::Http,
+                host,
+                port: self.proxy_port,
+                username: None,
+                password: None,
+            }),
+            VpnType::Socks5 => Some(VpnConfig::Socks {
+                version: 5,
+                host,
+                port: self.proxy_port,
+                username: None,
+                password: None,
+            }),
+            VpnType::OpenVpn => None,
+        }
+    }
+}
+
 /// DNS provider options
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DnsProvider {
@@ -239,6 +380,22 @@ pub enum DnsProvider {
 }
 
 impl DnsProvider {
+    /// Get the name the provider is saved under
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::System => "System",
+            Self::Google => "Google",
+            Self::Cloudflare => "Cloudflare",
+            Self::Quad9 => "Quad9",
+            Self::Custom => "Custom",
+        }
+    }
+
+    /// Parse a saved provider name
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::all().iter().copied().find(|p| p.as_str() == name)
+    }
+
     pub fn name(&self) -> &str {
         match self {
             Self::System => "System Default",
@@ -269,6 +426,20 @@ pub enum VpnType {
 }
 
 impl VpnType {
+    /// Get the name the VPN type is saved under
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Proxy => "Proxy",
+            Self::Socks5 => "Socks5",
+            Self::OpenVpn => "OpenVpn",
+        }
+    }
+
+    /// Parse a saved VPN type name
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::all().iter().copied().find(|t| t.as_str() == name)
+    }
+
     pub fn name(&self) -> &str {
         match self {
             Self::Proxy => "HTTP/HTTPS Proxy",
@@ -288,6 +459,8 @@ impl Default for AdvancedSettings {
             enable_developer_tools: false,
             hardware_acceleration: true,
             experimental_features: false,
+            preconnect_on_startup: false,
+            max_cache_bytes: default_max_cache_bytes(),
         }
     }
 }
@@ -302,12 +475,21 @@ impl SettingsUI {
             network: NetworkSettings::default(),
             downloads: DownloadsSettings::default(),
             advanced: AdvancedSettings::default(),
+            keymap: crate::keymap::Keymap::default(),
             selected_panel: SettingsPanel::default(),
         }
     }
 
-    /// Get the settings file path
-    fn get_settings_path() -> std::path::PathBuf {
+    /// Get the data directory of the active profile
+    ///
+    /// The profile is resolved on first use and reused afterwards.
+    pub(crate) fn data_dir() -> std::path::PathBuf {
+        static PROFILE_DIR: std::sync::OnceLock<std::path::PathBuf> = std::sync::OnceLock::new();
+        PROFILE_DIR.get_or_init(Self::resolve_data_dir).clone()
+    }
+
+    /// Find the data directory of the active profile
+    fn resolve_data_dir() -> std::path::PathBuf {
         let data_dir = if cfg!(target_os = "windows") {
             dirs::data_dir()
                 .unwrap_or_else(|| std::path::PathBuf::from("C:\\ProgramData"))
@@ -327,7 +509,18 @@ impl SettingsUI {
             let _ = std::fs::create_dir_all(&data_dir);
         }
 
-        data_dir.join("settings.toml")
+        match horizon_storage::profile::active_profile_dir(&data_dir) {
+            Ok(profile_dir) => profile_dir,
+            Err(e) => {
+                tracing::warn!("Failed to open active profile: {}", e);
+                data_dir
+            }
+        }
+    }
+
+    /// Get the settings file path
+    fn get_settings_path() -> std::path::PathBuf {
+        Self::data_dir().join("settings.toml")
     }
 
     /// Load settings from storage
@@ -353,16 +546,22 @@ impl SettingsUI {
 
     /// Convert from storage settings
     pub fn from_storage(storage_settings: &horizon_storage::settings::Settings) -> Self {
-        let search_engine = match storage_settings.general.search_engine.as_str() {
-            "Google" => SearchEngine::Google,
-            "Bing" => SearchEngine::Bing,
-            "Brave" => SearchEngine::Brave,
+        let general = &storage_settings.general;
+        let search_engine = match (general.search_engine.as_str(), &general.custom_search_url) {
+            (name, Some(template)) => {
+                SearchEngine::custom(name, template).unwrap_or(SearchEngine::DuckDuckGo)
+            }
+            ("Google", None) => SearchEngine::Google,
+            ("Bing", None) => SearchEngine::Bing,
+            ("Brave", None) => SearchEngine::Brave,
             _ => SearchEngine::DuckDuckGo,
         };
 
-        let theme = match storage_settings.appearance.theme.as_str() {
-            "Light" => Theme::Light,
-            _ => Theme::Dark,
+        let (theme, custom_theme) = match storage_settings.appearance.theme.as_str() {
+            "Dark" => (Theme::Dark, None),
+            "Light" => (Theme::Light, None),
+            "Auto" => (Theme::Auto, None),
+            name => (Theme::Dark, Some(name.to_string())),
         };
 
         Self {
@@ -370,6 +569,8 @@ impl SettingsUI {
                 homepage: storage_settings.general.homepage.clone(),
                 search_engine,
                 restore_tabs_on_startup: storage_settings.general.restore_tabs_on_startup,
+                select_left_on_close: storage_settings.general.select_left_on_close,
+                hover_switch_ms: storage_settings.general.hover_switch_ms,
             },
             privacy: PrivacySettings {
                 tracking_protection: storage_settings.privacy.tracking_protection,
@@ -380,10 +581,20 @@ impl SettingsUI {
             },
             appearance: AppearanceSettings {
                 theme,
+                custom_theme,
                 font_size: storage_settings.appearance.font_size,
                 show_bookmarks_bar: storage_settings.appearance.show_bookmarks_bar,
             },
-            network: NetworkSettings::default(), // Use defaults for new settings
+            network: NetworkSettings {
+                dns_provider: DnsProvider::parse(&storage_settings.network.dns_provider)
+                    .unwrap_or(DnsProvider::System),
+                custom_dns_servers: storage_settings.network.custom_dns_servers.clone(),
+                vpn_enabled: storage_settings.network.vpn_enabled,
+                vpn_type: VpnType::parse(&storage_settings.network.vpn_type)
+                    .unwrap_or(VpnType::Proxy),
+                proxy_host: storage_settings.network.proxy_host.clone(),
+                proxy_port: storage_settings.network.proxy_port,
+            },
             downloads: DownloadsSettings {
                 download_directory: storage_settings.general.download_directory.clone(),
                 ask_where_to_save: storage_settings.general.ask_where_to_save,
@@ -392,7 +603,10 @@ impl SettingsUI {
                 enable_developer_tools: storage_settings.advanced.enable_developer_tools,
                 hardware_acceleration: storage_settings.advanced.hardware_acceleration,
                 experimental_features: storage_settings.advanced.experimental_features,
+                preconnect_on_startup: storage_settings.advanced.preconnect_on_startup,
+                max_cache_bytes: storage_settings.advanced.max_cache_bytes,
             },
+            keymap: storage_settings.shortcuts.clone().into(),
             selected_panel: SettingsPanel::default(),
         }
     }
@@ -403,9 +617,16 @@ impl SettingsUI {
             general: horizon_storage::settings::GeneralSettings {
                 homepage: self.general.homepage.clone(),
                 search_engine: self.general.search_engine.name().to_string(),
+                custom_search_url: self
+                    .general
+                    .search_engine
+                    .url_template()
+                    .map(str::to_string),
                 download_directory: self.downloads.download_directory.clone(),
                 restore_tabs_on_startup: self.general.restore_tabs_on_startup,
                 ask_where_to_save: self.downloads.ask_where_to_save,
+                select_left_on_close: self.general.select_left_on_close,
+                hover_switch_ms: self.general.hover_switch_ms,
             },
             privacy: horizon_storage::settings::PrivacySettings {
                 tracking_protection: self.privacy.tracking_protection,
@@ -415,7 +636,11 @@ impl SettingsUI {
                 https_only: self.privacy.https_only,
             },
             appearance: horizon_storage::settings::AppearanceSettings {
-                theme: self.appearance.theme.name().to_string(),
+                theme: self
+                    .appearance
+                    .custom_theme
+                    .clone()
+                    .unwrap_or_else(|| self.appearance.theme.name().to_string()),
                 font_size: self.appearance.font_size,
                 show_bookmarks_bar: self.appearance.show_bookmarks_bar,
             },
@@ -423,8 +648,90 @@ impl SettingsUI {
                 enable_developer_tools: self.advanced.enable_developer_tools,
                 hardware_acceleration: self.advanced.hardware_acceleration,
                 experimental_features: self.advanced.experimental_features,
+                preconnect_on_startup: self.advanced.preconnect_on_startup,
+                max_cache_bytes: self.advanced.max_cache_bytes,
             },
+            network: horizon_storage::settings::NetworkSettings {
+                dns_provider: self.network.dns_provider.as_str().to_string(),
+                custom_dns_servers: self.network.custom_dns_servers.clone(),
+                vpn_enabled: self.network.vpn_enabled,
+                vpn_type: self.network.vpn_type.as_str().to_string(),
+                proxy_host: self.network.proxy_host.clone(),
+                proxy_port: self.network.proxy_port,
+            },
+            shortcuts: self.keymap.clone().into(),
+        }
+    }
+
+    /// Check the settings for values that cannot be used
+    ///
+    /// Returns every problem found, in the order the panels are shown.
+    pub fn validate(&self) -> Result<(), Vec<SettingsError>> {
+        let mut errors = Vec::new();
+
+        let homepage = self.general.homepage.trim();
+        if !homepage.starts_with("about:") && ParsedUrl::from_user_input(homepage).is_none() {
+            errors.push(SettingsError::InvalidHomepage(homepage.to_string()));
+        }
+
+        if !(MIN_FONT_SIZE..=MAX_FONT_SIZE).contains(&self.appearance.font_size) {
+            errors.push(SettingsError::FontSizeOutOfRange(self.appearance.font_size));
+        }
+
+        if self.network.dns_provider == DnsProvider::Custom {
+            let servers: Vec<&str> = self
+                .network
+                .custom_dns_servers
+                .split(',')
+                .map(str::trim)
+                .filter(|server| !server.is_empty())
+                .collect();
+            if servers.is_empty() {
+                errors.push(SettingsError::NoDnsServers);
+            }
+            errors.extend(
+                servers
+                    .into_iter()
+                    .filter(|server| server.parse::<IpAddr>().is_err())
+                    .map(|server| SettingsError::InvalidDnsServer(server.to_string())),
+            );
+        }
+
+        if self.network.proxy_port == 0 {
+            errors.push(SettingsError::InvalidProxyPort(self.network.proxy_port));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Export all settings to a TOML file
+    pub fn export(&self, path: &Path) -> Result<()> {
+        let contents = toml::to_string_pretty(self)?;
+        std::fs::write(path, contents)?;
+        tracing::info!("Exported settings to {:?}", path);
+        Ok(())
+    }
+
+    /// Replace the settings with ones exported by [`SettingsUI::export`]
+    ///
+    /// Fails without changing the current settings if the file cannot be
+    /// parsed or holds invalid values.
+    pub fn import(&mut self, path: &Path) -> Result<()> {
+        let contents = std::fs::read_to_string(path)?;
+        let mut imported: Self = toml::from_str(&contents)?;
+        if let Err(errors) = imported.validate() {
+            let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+            return Err(anyhow!("Invalid settings: {}", messages.join("; ")));
         }
+
+        imported.selected_panel = self.selected_panel;
+        *self = imported;
+        tracing::info!("Imported settings from {:?}", path);
+        Ok(())
     }
 
     /// Save settings to storage
@@ -448,3 +755,303 @@ impl Default for SettingsUI {
         Self::new()
     }
 }
+
+/// A settings value that cannot be saved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingsError {
+    /// Homepage is neither a URL nor an `about:` page
+    InvalidHomepage(String),
+    /// Font size is outside the supported range
+    FontSizeOutOfRange(u16),
+    /// Custom DNS is selected without any servers
+    NoDnsServers,
+    /// Custom DNS server is not an IP address
+    InvalidDnsServer(String),
+    /// Proxy port is not in 1-65535
+    InvalidProxyPort(u16),
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHomepage(homepage) => {
+                write!(f, "Homepage \"{}\" is not a valid URL", homepage)
+            }
+            Self::FontSizeOutOfRange(size) => write!(
+                f,
+                "Font size {} must be between {} and {}",
+                size, MIN_FONT_SIZE, MAX_FONT_SIZE
+            ),
+            Self::NoDnsServers => write!(f, "Enter at least one custom DNS server"),
+            Self::InvalidDnsServer(server) => {
+                write!(f, "DNS server \"{}\" is not an IP address", server)
+            }
+            Self::InvalidProxyPort(port) => {
+                write!(f, "Proxy port {} must be between 1 and 65535", port)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_search_url() {
+        let engine =
+            SearchEngine::custom("Docs", "https://docs.rs/releases/search?query={query}").unwrap();
+        assert_eq!(engine.name(), "Docs");
+        assert_eq!(
+            engine.search_url("serde json&yaml"),
+            "https://docs.rs/releases/search?query=serde%20json%26yaml"
+        );
+    }
+
+    #[test]
+    fn test_custom_search_engine_validation() {
+        assert!(SearchEngine::custom("Docs", "https://docs.rs/search?q=").is_err());
+        assert!(SearchEngine::custom(" ", "https://docs.rs/search?q={query}").is_err());
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("settings-export.toml");
+
+        let mut settings = SettingsUI::new();
+        settings.general.homepage = "https://example.com".to_string();
+        settings.general.search_engine =
+            SearchEngine::custom("Docs", "https://docs.rs/search?q={query}").unwrap();
+        settings.general.hover_switch_ms = Some(300);
+        settings.privacy.https_only = true;
+        settings.appearance.theme = Theme::Light;
+        settings.network.dns_provider = DnsProvider::Custom;
+        settings.network.custom_dns_servers = "9.9.9.9".to_string();
+        settings.network.vpn_enabled = true;
+        settings.network.proxy_port = 3128;
+        settings.advanced.experimental_features = true;
+        settings.keymap.bind(
+            crate::keymap::BrowserAction::Reload,
+            crate::keymap::KeyBinding::command(egui::Key::G),
+        );
+        settings.export(&path).unwrap();
+
+        let mut imported = SettingsUI::new();
+        imported.import(&path).unwrap();
+        assert_eq!(imported, settings);
+    }
+
+    #[test]
+    fn test_import_rejects_bad_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("settings-export.toml");
+        let mut settings = SettingsUI::new();
+        settings.general.homepage = "https://example.com".to_string();
+
+        std::fs::write(&path, "general = \"not a table\"").unwrap();
+        assert!(settings.import(&path).is_err());
+
+        let mut invalid = SettingsUI::new();
+        invalid.network.proxy_port = 0;
+        invalid.export(&path).unwrap();
+        assert!(settings.import(&path).is_err());
+
+        assert!(settings.import(&dir.path().join("missing.toml")).is_err());
+        assert_eq!(settings.general.homepage, "https://example.com");
+        assert_eq!(settings.network.proxy_port, 8080);
+    }
+
+    #[test]
+    fn test_validate_defaults() {
+        assert_eq!(SettingsUI::new().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_homepage() {
+        let mut settings = SettingsUI::new();
+        for homepage in ["about:blank", "https://example.com/start", "example.com"] {
+            settings.general.homepage = homepage.to_string();
+            assert_eq!(settings.validate(), Ok(()), "{}", homepage);
+        }
+
+        settings.general.homepage = "not a url".to_string();
+        assert_eq!(
+            settings.validate(),
+            Err(vec![SettingsError::InvalidHomepage(
+                "not a url".to_string()
+            )])
+        );
+    }
+
+    #[test]
+    fn test_validate_dns_servers() {
+        let mut settings = SettingsUI::new();
+        settings.network.dns_provider = DnsProvider::Custom;
+        settings.network.custom_dns_servers = "1.1.1.1, 2606:4700:4700::1111".to_string();
+        assert_eq!(settings.validate(), Ok(()));
+
+        settings.network.custom_dns_servers = "1.1.1.1, dns.example, 999.1.1.1".to_string();
+        assert_eq!(
+            settings.validate(),
+            Err(vec![
+                SettingsError::InvalidDnsServer("dns.example".to_string()),
+                SettingsError::InvalidDnsServer("999.1.1.1".to_string()),
+            ])
+        );
+
+        settings.network.custom_dns_servers = " , ".to_string();
+        assert_eq!(settings.validate(), Err(vec![SettingsError::NoDnsServers]));
+
+        // Servers are only used with the custom provider
+        settings.network.dns_provider = DnsProvider::System;
+        assert_eq!(settings.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_proxy_port() {
+        let mut settings = SettingsUI::new();
+        settings.network.proxy_port = 65535;
+        assert_eq!(settings.validate(), Ok(()));
+
+        settings.network.proxy_port = 0;
+        assert_eq!(
+            settings.validate(),
+            Err(vec![SettingsError::InvalidProxyPort(0)])
+        );
+    }
+
+    #[test]
+    fn test_validate_font_size() {
+        let mut settings = SettingsUI::new();
+        settings.appearance.font_size = MAX_FONT_SIZE;
+        assert_eq!(settings.validate(), Ok(()));
+
+        settings.appearance.font_size = 72;
+        let errors = settings.validate().unwrap_err();
+        assert_eq!(errors, vec![SettingsError::FontSizeOutOfRange(72)]);
+        assert_eq!(
+            errors[0].to_string(),
+            "Font size 72 must be between 10 and 20"
+        );
+    }
+
+    #[test]
+    fn test_keymap_storage_round_trip() {
+        use crate::keymap::{BrowserAction, KeyBinding};
+
+        let mut settings = SettingsUI::new();
+        settings
+            .keymap
+            .bind(BrowserAction::FindInPage, KeyBinding::command(egui::Key::G));
+
+        let storage = settings.to_storage();
+        assert_eq!(storage.shortcuts["FindInPage"], vec!["Ctrl+G"]);
+        assert_eq!(SettingsUI::from_storage(&storage).keymap, settings.keymap);
+    }
+
+    #[test]
+    fn test_network_settings_storage_round_trip() {
+        let mut settings = SettingsUI::new();
+        settings.network.dns_provider = DnsProvider::Quad9;
+        settings.network.vpn_enabled = true;
+        settings.network.vpn_type = VpnType::Socks5;
+        settings.network.proxy_host = "127.0.0.1".to_string();
+        settings.network.proxy_port = 9050;
+
+        let restored = SettingsUI::from_storage(&settings.to_storage()).network;
+        assert_eq!(restored.dns_provider, DnsProvider::Quad9);
+        assert!(restored.vpn_enabled);
+        assert_eq!(restored.vpn_type, VpnType::Socks5);
+        assert_eq!(restored.proxy_host, "127.0.0.1");
+        assert_eq!(restored.proxy_port, 9050);
+    }
+
+    #[test]
+    fn test_vpn_config_from_settings() {
+        use horizon_networking::client::HttpClient;
+
+        let mut network = NetworkSettings {
+            proxy_host: " 127.0.0.1 ".to_string(),
+            proxy_port: 9050,
+            ..NetworkSettings::default()
+        };
+        assert!(network.vpn_config().is_none());
+
+        network.vpn_enabled = true;
+        network.vpn_type = VpnType::Socks5;
+        let client = HttpClient::with_proxy(&network.vpn_config().unwrap()).unwrap();
+        assert_eq!(client.proxy_url(), Some("socks5h://127.0.0.1:9050"));
+
+        network.vpn_type = VpnType::Proxy;
+        let client = HttpClient::with_proxy(&network.vpn_config().unwrap()).unwrap();
+        assert_eq!(client.proxy_url(), Some("http://127.0.0.1:9050"));
+
+        network.vpn_type = VpnType::OpenVpn;
+        assert!(network.vpn_config().is_none());
+    }
+
+    #[test]
+    fn test_custom_search_engine_storage_round_trip() {
+        let mut settings = SettingsUI::new();
+        settings.general.search_engine =
+            SearchEngine::custom("Docs", "https://docs.rs/search?q={query}").unwrap();
+
+        let restored = SettingsUI::from_storage(&settings.to_storage());
+        assert_eq!(
+            restored.general.search_engine,
+            settings.general.search_engine
+        );
+
+        settings.general.search_engine = SearchEngine::Brave;
+        let restored = SettingsUI::from_storage(&settings.to_storage());
+        assert_eq!(restored.general.search_engine, SearchEngine::Brave);
+    }
+
+    #[test]
+    fn test_custom_theme_storage_round_trip() {
+        let mut settings = SettingsUI::new();
+        settings.appearance.custom_theme = Some("Solarized".to_string());
+
+        let storage = settings.to_storage();
+        assert_eq!(storage.appearance.theme, "Solarized");
+        let restored = SettingsUI::from_storage(&storage);
+        assert_eq!(
+            restored.appearance.custom_theme.as_deref(),
+            Some("Solarized")
+        );
+
+        settings.appearance.custom_theme = None;
+        settings.appearance.theme = Theme::Light;
+        let restored = SettingsUI::from_storage(&settings.to_storage());
+        assert_eq!(restored.appearance.theme, Theme::Light);
+        assert!(restored.appearance.custom_theme.is_none());
+    }
+
+    #[test]
+    fn test_all_includes_auto() {
+        assert!(Theme::all().contains(&Theme::Auto));
+        assert_eq!(Theme::Auto.name(), "Auto");
+    }
+
+    #[test]
+    fn test_auto_resolves_to_system_theme() {
+        assert_eq!(Theme::Auto.resolve(Theme::Light), Theme::Light);
+        assert_eq!(Theme::Auto.resolve(Theme::Dark), Theme::Dark);
+        assert_eq!(Theme::Dark.resolve(Theme::Light), Theme::Dark);
+        assert_eq!(Theme::Light.resolve(Theme::Dark), Theme::Light);
+    }
+
+    #[test]
+    fn test_detect_system_theme() {
+        let ctx = egui::Context::default();
+        assert_eq!(detect_system_theme(&ctx), Theme::Dark);
+
+        ctx.begin_pass(egui::RawInput {
+            system_theme: Some(egui::Theme::Light),
+            ..Default::default()
+        });
+        assert_eq!(detect_system_theme(&ctx), Theme::Light);
+        let _ = ctx.end_pass();
+    }
+}
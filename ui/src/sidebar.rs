@@ -4,6 +4,15 @@
 
 use serde::{Deserialize, Serialize};
 
+/// Narrowest the expanded sidebar can be resized to
+pub const MIN_WIDTH: f32 = 180.0;
+
+/// Widest the expanded sidebar can be resized to
+pub const MAX_WIDTH: f32 = 400.0;
+
+/// Width of the collapsed icon rail
+pub const COLLAPSED_WIDTH: f32 = 60.0;
+
 /// Sidebar navigation item
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SidebarItem {
@@ -90,10 +99,17 @@ impl Sidebar {
         self.selected_item = Some(item);
     }
 
+    /// Set the expanded width, clamped to `MIN_WIDTH`-`MAX_WIDTH`
+    pub fn set_width(&mut self, width: f32) {
+        if !width.is_nan() {
+            self.width = width.clamp(MIN_WIDTH, MAX_WIDTH);
+        }
+    }
+
     /// Get the effective width (collapsed or expanded)
     pub fn effective_width(&self) -> f32 {
         if self.collapsed {
-            60.0 // Show only icons when collapsed
+            COLLAPSED_WIDTH // Show only icons when collapsed
         } else {
             self.width
         }
@@ -128,6 +144,25 @@ mod tests {
         assert_eq!(sidebar.effective_width(), 60.0);
     }
 
+    #[test]
+    fn test_sidebar_set_width_clamped() {
+        let mut sidebar = Sidebar::new();
+        sidebar.set_width(320.0);
+        assert_eq!(sidebar.width, 320.0);
+        sidebar.set_width(50.0);
+        assert_eq!(sidebar.width, MIN_WIDTH);
+        sidebar.set_width(1000.0);
+        assert_eq!(sidebar.width, MAX_WIDTH);
+        sidebar.set_width(f32::NAN);
+        assert_eq!(sidebar.width, MAX_WIDTH);
+
+        sidebar.toggle_collapsed();
+        sidebar.set_width(300.0);
+        assert_eq!(sidebar.effective_width(), COLLAPSED_WIDTH);
+        sidebar.toggle_collapsed();
+        assert_eq!(sidebar.effective_width(), 300.0);
+    }
+
     #[test]
     fn test_sidebar_item_select() {
         let mut sidebar = Sidebar::new();
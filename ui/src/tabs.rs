@@ -1,8 +1,67 @@
 //! Tab management for the Horizon Browser
 
+use crate::theme::Color;
+use anyhow::Result;
+use horizon_storage::cookies::CookieStore;
+use horizon_storage::history::HistoryStore;
+use horizon_storage::userdata::UserDataManager;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+/// Why a navigation failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NavigationError {
+    /// The host name could not be resolved
+    DnsFailed,
+    /// The server refused the connection
+    ConnectionRefused,
+    /// The server took too long to respond
+    Timeout,
+    /// The secure connection could not be established
+    TlsError,
+    /// The browser blocked the page
+    Blocked,
+    /// The server responded with an error status
+    HttpStatus(u16),
+}
+
+impl NavigationError {
+    /// Get the heading shown on the error page
+    pub fn title(&self) -> &'static str {
+        match self {
+            NavigationError::DnsFailed => "Server not found",
+            NavigationError::ConnectionRefused => "Unable to connect",
+            NavigationError::Timeout => "The connection timed out",
+            NavigationError::TlsError => "Secure connection failed",
+            NavigationError::Blocked => "Page blocked",
+            NavigationError::HttpStatus(_) => "The server returned an error",
+        }
+    }
+
+    /// Get a description of the failure for the error page
+    pub fn description(&self) -> String {
+        match self {
+            NavigationError::DnsFailed => {
+                "The site's address could not be found. Check the address for typos.".to_string()
+            }
+            NavigationError::ConnectionRefused => {
+                "The server refused the connection. The site may be down.".to_string()
+            }
+            NavigationError::Timeout => {
+                "The server took too long to respond. Try again in a moment.".to_string()
+            }
+            NavigationError::TlsError => {
+                "The site's identity could not be verified, so the connection was stopped."
+                    .to_string()
+            }
+            NavigationError::Blocked => "Horizon blocked this page.".to_string(),
+            NavigationError::HttpStatus(status) => {
+                format!("The server responded with HTTP status {}.", status)
+            }
+        }
+    }
+}
+
 /// Represents a single browser tab
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tab {
@@ -18,6 +77,33 @@ pub struct Tab {
     pub history_index: usize,
     /// Loading state
     pub is_loading: bool,
+    /// Loading progress from 0.0 to 1.0
+    #[serde(default)]
+    pub progress: f32,
+    /// Why the last navigation failed, if it did
+    #[serde(default)]
+    pub error: Option<NavigationError>,
+    /// Favicon image file contents, refetched rather than saved with sessions
+    #[serde(skip)]
+    pub favicon: Option<Vec<u8>>,
+    /// URL the favicon was fetched from
+    #[serde(skip)]
+    pub favicon_url: Option<String>,
+    /// Whether the page is playing audio
+    #[serde(skip)]
+    pub is_audible: bool,
+    /// Whether the user muted the tab
+    #[serde(default)]
+    pub is_muted: bool,
+    /// Group the tab belongs to, if any
+    #[serde(default)]
+    pub group_id: Option<String>,
+    /// Whether the page is shown simplified for reading
+    #[serde(default)]
+    pub reader_mode: bool,
+    /// Whether the tab is pinned to the front of the tab strip
+    #[serde(default)]
+    pub is_pinned: bool,
 }
 
 impl Tab {
@@ -31,6 +117,15 @@ impl Tab {
             history: vec![url],
             history_index: 0,
             is_loading: false,
+            progress: 0.0,
+            error: None,
+            favicon: None,
+            favicon_url: None,
+            is_audible: false,
+            is_muted: false,
+            group_id: None,
+            reader_mode: false,
+            is_pinned: false,
         }
     }
 
@@ -48,6 +143,9 @@ impl Tab {
         self.history_index = self.history.len() - 1;
         self.url = url;
         self.is_loading = true;
+        self.progress = 0.0;
+        self.error = None;
+        self.reader_mode = false;
     }
 
     /// Navigate back in history
@@ -56,6 +154,9 @@ impl Tab {
             self.history_index -= 1;
             self.url = self.history[self.history_index].clone();
             self.is_loading = true;
+            self.progress = 0.0;
+            self.error = None;
+            self.reader_mode = false;
             true
         } else {
             false
@@ -68,6 +169,9 @@ impl Tab {
             self.history_index += 1;
             self.url = self.history[self.history_index].clone();
             self.is_loading = true;
+            self.progress = 0.0;
+            self.error = None;
+            self.reader_mode = false;
             true
         } else {
             false
@@ -87,6 +191,8 @@ impl Tab {
     /// Reload the current page
     pub fn reload(&mut self) {
         self.is_loading = true;
+        self.progress = 0.0;
+        self.error = None;
     }
 
     /// Update the tab title
@@ -97,6 +203,51 @@ impl Tab {
     /// Mark loading as complete
     pub fn finish_loading(&mut self) {
         self.is_loading = false;
+        self.progress = 1.0;
+        self.error = None;
+    }
+
+    /// Update loading progress, clamped to 0.0–1.0
+    ///
+    /// Reaching 1.0 finishes loading.
+    pub fn set_progress(&mut self, progress: f32) {
+        self.progress = progress.clamp(0.0, 1.0);
+        if self.progress >= 1.0 {
+            self.finish_loading();
+        }
+    }
+
+    /// Mark the current navigation as failed
+    pub fn set_error(&mut self, error: NavigationError) {
+        self.is_loading = false;
+        self.error = Some(error);
+    }
+
+    /// Set the favicon fetched for the current page
+    pub fn set_favicon(&mut self, url: impl Into<String>, bytes: Vec<u8>) {
+        self.favicon_url = Some(url.into());
+        self.favicon = Some(bytes);
+    }
+
+    /// Record whether the page is playing audio
+    ///
+    /// This does not change whether the tab is muted.
+    pub fn set_audible(&mut self, audible: bool) {
+        self.is_audible = audible;
+    }
+
+    /// Mute or unmute the tab, returning whether it is now muted
+    pub fn toggle_mute(&mut self) -> bool {
+        self.is_muted = !self.is_muted;
+        self.is_muted
+    }
+
+    /// Turn reader mode on or off, returning whether it is now on
+    ///
+    /// Reader mode turns off when the tab navigates to another page.
+    pub fn toggle_reader_mode(&mut self) -> bool {
+        self.reader_mode = !self.reader_mode;
+        self.reader_mode
     }
 
     /// Get display title (truncated if too long)
@@ -109,6 +260,26 @@ impl Tab {
     }
 }
 
+/// Which neighbor becomes active after closing the active tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CloseDirection {
+    /// Activate the tab to the left of the closed one
+    Left,
+    /// Activate the tab to the right of the closed one
+    Right,
+}
+
+/// A named, colored group of tabs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TabGroup {
+    /// Unique identifier for the group
+    pub id: String,
+    /// Group name shown in the tab strip
+    pub name: String,
+    /// Group color
+    pub color: Color,
+}
+
 /// Manages all browser tabs
 #[derive(Debug, Clone)]
 pub struct TabManager {
@@ -116,6 +287,10 @@ pub struct TabManager {
     tabs: Vec<Tab>,
     /// Index of the active tab
     active_tab_index: usize,
+    /// Tab groups
+    groups: Vec<TabGroup>,
+    /// Whether browsing leaves no history, cookies, or saved passwords
+    private: bool,
 }
 
 impl TabManager {
@@ -124,9 +299,45 @@ impl TabManager {
         Self {
             tabs: vec![Tab::new("about:home")],
             active_tab_index: 0,
+            groups: Vec::new(),
+            private: false,
         }
     }
 
+    /// Create a tab manager for a private window
+    pub fn new_private() -> Self {
+        Self {
+            private: true,
+            ..Self::new()
+        }
+    }
+
+    /// Check whether the tabs are private
+    pub fn is_private(&self) -> bool {
+        self.private
+    }
+
+    /// Open the cookie jar for the tabs, kept only in memory if they are private
+    pub fn cookie_store(&self, user_data: &UserDataManager) -> Result<CookieStore> {
+        if self.private {
+            Ok(CookieStore::new())
+        } else {
+            CookieStore::from_user_data(user_data)
+        }
+    }
+
+    /// Record a visit in history, unless the tabs are private or the URL is
+    /// an internal `about:` page
+    ///
+    /// Returns whether the visit was recorded.
+    pub fn record_visit(&self, history: &mut HistoryStore, url: &str, title: &str) -> bool {
+        if self.private || url.starts_with("about:") {
+            return false;
+        }
+        history.record_visit(url, title);
+        true
+    }
+
     /// Get the active tab
     ///
     /// # Panics
@@ -158,6 +369,11 @@ impl TabManager {
         &self.tabs
     }
 
+    /// Get a tab by index mutably
+    pub fn tab_mut(&mut self, index: usize) -> Option<&mut Tab> {
+        self.tabs.get_mut(index)
+    }
+
     /// Get active tab index
     pub fn active_tab_index(&self) -> usize {
         self.active_tab_index
@@ -172,24 +388,288 @@ impl TabManager {
 
     /// Close a tab by index
     pub fn close_tab(&mut self, index: usize) -> bool {
-        if self.tabs.len() <= 1 {
-            // Don't close the last tab
+        self.take_tab(index).is_some()
+    }
+
+    /// Remove a tab and return it with its history, e.g. to move it to
+    /// another window
+    ///
+    /// The last tab is never taken. The taken tab leaves its group, since
+    /// groups belong to a window.
+    pub fn take_tab(&mut self, index: usize) -> Option<Tab> {
+        if self.tabs.len() <= 1 || index >= self.tabs.len() {
+            return None;
+        }
+
+        let mut tab = self.tabs.remove(index);
+        self.remove_group_if_empty(tab.group_id.take().as_deref());
+
+        // Adjust active tab index if needed
+        if self.active_tab_index >= self.tabs.len() {
+            self.active_tab_index = self.tabs.len() - 1;
+        } else if index <= self.active_tab_index && self.active_tab_index > 0 {
+            self.active_tab_index -= 1;
+        }
+
+        Some(tab)
+    }
+
+    /// Close every tab except one, returning how many were closed
+    ///
+    /// Pinned tabs are kept too. The kept tab becomes active unless the
+    /// active tab is pinned.
+    pub fn close_others(&mut self, index: usize) -> usize {
+        if index >= self.tabs.len() {
+            return 0;
+        }
+        self.close_where(index, |i, tab| i != index && !tab.is_pinned)
+    }
+
+    /// Close every tab after a tab, returning how many were closed
+    ///
+    /// Pinned tabs are kept. The tab becomes active if the active tab closes.
+    pub fn close_to_right(&mut self, index: usize) -> usize {
+        if index >= self.tabs.len() {
+            return 0;
+        }
+        self.close_where(index, |i, tab| i > index && !tab.is_pinned)
+    }
+
+    /// Close the tabs matching `close`, which is given each tab's index
+    ///
+    /// The active tab stays active if it is kept; otherwise the tab at
+    /// `fallback`, which must be kept, becomes active.
+    fn close_where(&mut self, fallback: usize, close: impl Fn(usize, &Tab) -> bool) -> usize {
+        let active_id = self.active_tab().id.clone();
+        let fallback_id = self.tabs[fallback].id.clone();
+        let before = self.tabs.len();
+
+        let mut index = 0;
+        self.tabs.retain(|tab| {
+            let keep = !close(index, tab);
+            index += 1;
+            keep
+        });
+        let tabs = &self.tabs;
+        self.groups.retain(|group| {
+            tabs.iter()
+                .any(|tab| tab.group_id.as_deref() == Some(&group.id))
+        });
+
+        self.active_tab_index = [active_id, fallback_id]
+            .iter()
+            .find_map(|id| self.tabs.iter().position(|tab| &tab.id == id))
+            .unwrap_or(0);
+        before - self.tabs.len()
+    }
+
+    /// Duplicate a tab with its history next to the original and switch to
+    /// the copy, returning the copy's index
+    pub fn duplicate_tab(&mut self, index: usize) -> Option<usize> {
+        let mut tab = self.tabs.get(index)?.clone();
+        tab.id = Uuid::new_v4().to_string();
+        tab.is_audible = false;
+
+        self.tabs.insert(index + 1, tab);
+        self.active_tab_index = index + 1;
+        Some(index + 1)
+    }
+
+    /// Pin or unpin a tab, returning whether it is now pinned
+    ///
+    /// Pinned tabs sit in front of the others and leave their group. A
+    /// pinned tab goes after the other pinned tabs, and an unpinned one goes
+    /// first among the unpinned tabs.
+    pub fn toggle_pin(&mut self, index: usize) -> bool {
+        let Some(tab) = self.tabs.get_mut(index) else {
+            return false;
+        };
+        tab.is_pinned = !tab.is_pinned;
+        let pinned = tab.is_pinned;
+        let group_id = tab.group_id.take();
+        self.remove_group_if_empty(group_id.as_deref());
+
+        let pinned_count = self.tabs.iter().filter(|tab| tab.is_pinned).count();
+        let to = if pinned {
+            pinned_count - 1
+        } else {
+            pinned_count
+        };
+        self.move_tab(index, to);
+        pinned
+    }
+
+    /// Append a tab taken from another window and make it active
+    pub fn adopt_tab(&mut self, tab: Tab) {
+        self.tabs.push(tab);
+        self.active_tab_index = self.tabs.len() - 1;
+    }
+
+    /// Close a tab by index, choosing which neighbor to activate
+    ///
+    /// The direction only applies when the closed tab is the active one; if
+    /// there is no neighbor in that direction, the other neighbor is used.
+    /// Closing an inactive tab keeps the current tab active.
+    pub fn close_and_select(&mut self, index: usize, direction: CloseDirection) -> bool {
+        if self.tabs.len() <= 1 || index >= self.tabs.len() {
             return false;
         }
 
-        if index < self.tabs.len() {
-            self.tabs.remove(index);
+        let tab = self.tabs.remove(index);
+        self.remove_group_if_empty(tab.group_id.as_deref());
 
-            // Adjust active tab index if needed
-            if self.active_tab_index >= self.tabs.len() {
-                self.active_tab_index = self.tabs.len() - 1;
-            } else if index <= self.active_tab_index && self.active_tab_index > 0 {
-                self.active_tab_index -= 1;
+        if index == self.active_tab_index {
+            self.active_tab_index = match direction {
+                CloseDirection::Left => index.saturating_sub(1),
+                CloseDirection::Right => index.min(self.tabs.len() - 1),
+            };
+        } else if index < self.active_tab_index {
+            self.active_tab_index -= 1;
+        }
+
+        true
+    }
+
+    /// Move every tab from other tab managers into this one
+    ///
+    /// Used to merge windows: tabs are appended in window order, keeping each
+    /// window's tab order, then pinned tabs are moved in front of the rest.
+    /// The currently active tab stays active. Private and normal windows are
+    /// never merged, so managers whose mode differs from this one are
+    /// returned untouched; the rest are consumed.
+    pub fn merge_from(&mut self, others: impl IntoIterator<Item = TabManager>) -> Vec<TabManager> {
+        let mut refused = Vec::new();
+        for other in others {
+            if other.private != self.private {
+                refused.push(other);
+                continue;
             }
+            self.tabs.extend(other.tabs);
+            self.groups.extend(other.groups);
+        }
 
-            true
-        } else {
-            false
+        // Keep pinned tabs first, preserving the order within each part
+        let active = self.active_tab_index;
+        let mut order: Vec<usize> = (0..self.tabs.len()).collect();
+        order.sort_by_key(|&index| !self.tabs[index].is_pinned);
+        self.active_tab_index = order.iter().position(|&index| index == active).unwrap_or(0);
+
+        let mut tabs: Vec<Option<Tab>> = self.tabs.drain(..).map(Some).collect();
+        self.tabs = order
+            .into_iter()
+            .filter_map(|index| tabs[index].take())
+            .collect();
+
+        refused
+    }
+
+    /// Move a tab to a new position
+    ///
+    /// A tab moved between two tabs of the same group joins that group, and
+    /// a tab moved away from the rest of its group leaves it.
+    pub fn move_tab(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.tabs.len() || to >= self.tabs.len() {
+            return false;
+        }
+
+        let tab = self.tabs.remove(from);
+        self.tabs.insert(to, tab);
+
+        if self.active_tab_index == from {
+            self.active_tab_index = to;
+        } else if from < self.active_tab_index && self.active_tab_index <= to {
+            self.active_tab_index -= 1;
+        } else if to <= self.active_tab_index && self.active_tab_index < from {
+            self.active_tab_index += 1;
+        }
+
+        // Keep each group's tabs next to each other
+        let left = to
+            .checked_sub(1)
+            .and_then(|index| self.tabs[index].group_id.clone());
+        let right = self.tabs.get(to + 1).and_then(|tab| tab.group_id.clone());
+        let old_group = self.tabs[to].group_id.clone();
+        let new_group = match &old_group {
+            _ if left.is_some() && left == right => left,
+            Some(id) if old_group != left && old_group != right => {
+                // Only a group's last tab can move away from it
+                (self.tabs_in_group(id).len() == 1).then(|| id.clone())
+            }
+            _ => old_group.clone(),
+        };
+
+        if new_group != old_group {
+            self.tabs[to].group_id = new_group;
+            self.remove_group_if_empty(old_group.as_deref());
+        }
+        true
+    }
+
+    /// Get all tab groups
+    pub fn groups(&self) -> &[TabGroup] {
+        &self.groups
+    }
+
+    /// Get a tab group by ID
+    pub fn group(&self, id: &str) -> Option<&TabGroup> {
+        self.groups.iter().find(|group| group.id == id)
+    }
+
+    /// Create an empty tab group, returning its ID
+    pub fn create_group(&mut self, name: impl Into<String>, color: Color) -> String {
+        let id = Uuid::new_v4().to_string();
+        self.groups.push(TabGroup {
+            id: id.clone(),
+            name: name.into(),
+            color,
+        });
+        id
+    }
+
+    /// Add a tab to a group, moving it out of any group it was in
+    pub fn assign_to_group(&mut self, tab_index: usize, group_id: &str) -> bool {
+        if tab_index >= self.tabs.len() || self.group(group_id).is_none() {
+            return false;
+        }
+
+        let old_group = self.tabs[tab_index].group_id.replace(group_id.to_string());
+        if old_group.as_deref() != Some(group_id) {
+            self.remove_group_if_empty(old_group.as_deref());
+        }
+        true
+    }
+
+    /// Remove a tab from its group
+    ///
+    /// Groups are deleted once their last tab leaves.
+    pub fn ungroup(&mut self, tab_index: usize) -> bool {
+        let Some(tab) = self.tabs.get_mut(tab_index) else {
+            return false;
+        };
+        let Some(group_id) = tab.group_id.take() else {
+            return false;
+        };
+        self.remove_group_if_empty(Some(&group_id));
+        true
+    }
+
+    /// Get the indices of the tabs in a group
+    pub fn tabs_in_group(&self, group_id: &str) -> Vec<usize> {
+        self.tabs
+            .iter()
+            .enumerate()
+            .filter(|(_, tab)| tab.group_id.as_deref() == Some(group_id))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Delete a group that no tab belongs to anymore
+    fn remove_group_if_empty(&mut self, group_id: Option<&str>) {
+        let Some(group_id) = group_id else {
+            return;
+        };
+        if self.tabs_in_group(group_id).is_empty() {
+            self.groups.retain(|group| group.id != group_id);
         }
     }
 
@@ -203,6 +683,31 @@ impl TabManager {
         }
     }
 
+    /// Switch to the next tab, wrapping from the last tab to the first
+    pub fn next_tab(&mut self) {
+        self.active_tab_index = (self.active_tab_index + 1) % self.tabs.len();
+    }
+
+    /// Switch to the previous tab, wrapping from the first tab to the last
+    pub fn prev_tab(&mut self) {
+        self.active_tab_index = self
+            .active_tab_index
+            .checked_sub(1)
+            .unwrap_or(self.tabs.len() - 1);
+    }
+
+    /// Switch to a tab by its 1-based number, as with Ctrl+1 to Ctrl+9
+    ///
+    /// Numbers 1-8 pick that tab if it exists, and 9 always picks the last
+    /// tab. Other numbers do nothing.
+    pub fn switch_to_tab_number(&mut self, number: usize) -> bool {
+        match number {
+            1..=8 => self.switch_to_tab(number - 1),
+            9 => self.switch_to_tab(self.tabs.len() - 1),
+            _ => false,
+        }
+    }
+
     /// Get tab count
     pub fn tab_count(&self) -> usize {
         self.tabs.len()
@@ -265,6 +770,107 @@ mod tests {
         assert_eq!(tab.url, "https://example.com/page3");
     }
 
+    #[test]
+    fn test_tab_progress() {
+        let mut tab = Tab::new("https://example.com");
+        tab.navigate_to("https://example.com/page2");
+        assert_eq!(tab.progress, 0.0);
+
+        tab.set_progress(-0.5);
+        assert_eq!(tab.progress, 0.0);
+        tab.set_progress(0.4);
+        assert_eq!(tab.progress, 0.4);
+        assert!(tab.is_loading);
+
+        tab.set_progress(1.5);
+        assert_eq!(tab.progress, 1.0);
+        assert!(!tab.is_loading);
+
+        tab.reload();
+        assert_eq!(tab.progress, 0.0);
+        assert!(tab.is_loading);
+    }
+
+    #[test]
+    fn test_tab_favicon() {
+        let mut tab = Tab::new("https://example.com");
+        assert!(tab.favicon.is_none());
+
+        tab.set_favicon("https://example.com/favicon.ico", vec![0, 0, 1, 0]);
+        assert_eq!(tab.favicon.as_deref(), Some(&[0, 0, 1, 0][..]));
+        assert_eq!(
+            tab.favicon_url.as_deref(),
+            Some("https://example.com/favicon.ico")
+        );
+
+        // Favicons are not saved with the tab
+        let json = serde_json::to_string(&tab).unwrap();
+        let restored: Tab = serde_json::from_str(&json).unwrap();
+        assert!(restored.favicon.is_none());
+        assert!(restored.favicon_url.is_none());
+    }
+
+    #[test]
+    fn test_tab_mute() {
+        let mut tab = Tab::new("https://example.com");
+        assert!(!tab.is_muted);
+        assert!(tab.toggle_mute());
+        assert!(tab.is_muted);
+
+        // Audio starting does not unmute the tab
+        tab.set_audible(true);
+        assert!(tab.is_audible);
+        assert!(tab.is_muted);
+
+        assert!(!tab.toggle_mute());
+        assert!(!tab.is_muted);
+        assert!(tab.is_audible);
+    }
+
+    #[test]
+    fn test_tab_mute_is_saved() {
+        let mut tab = Tab::new("https://example.com");
+        tab.set_audible(true);
+        tab.toggle_mute();
+
+        let json = serde_json::to_string(&tab).unwrap();
+        let restored: Tab = serde_json::from_str(&json).unwrap();
+        assert!(restored.is_muted);
+        assert!(!restored.is_audible);
+    }
+
+    #[test]
+    fn test_tab_reader_mode() {
+        let mut tab = Tab::new("https://example.com/article");
+        assert!(tab.toggle_reader_mode());
+        tab.reload();
+        assert!(tab.reader_mode);
+
+        tab.navigate_to("https://example.com/next");
+        assert!(!tab.reader_mode);
+        tab.toggle_reader_mode();
+        tab.go_back();
+        assert!(!tab.reader_mode);
+    }
+
+    #[test]
+    fn test_tab_error() {
+        let mut tab = Tab::new("https://example.com");
+        tab.navigate_to("https://missing.example");
+        tab.set_error(NavigationError::DnsFailed);
+        assert_eq!(tab.error, Some(NavigationError::DnsFailed));
+        assert!(!tab.is_loading);
+
+        tab.navigate_to("https://example.com/page2");
+        tab.finish_loading();
+        assert_eq!(tab.error, None);
+
+        tab.set_error(NavigationError::HttpStatus(503));
+        assert!(tab.error.unwrap().description().contains("503"));
+        tab.reload();
+        assert_eq!(tab.error, None);
+    }
+
     #[test]
     fn test_tab_manager_creation() {
         let manager = TabManager::new();
@@ -299,6 +905,357 @@ mod tests {
         assert_eq!(manager.tab_count(), 1);
     }
 
+    #[test]
+    fn test_close_and_select_left() {
+        let mut manager = TabManager::new();
+        manager.new_tab("https://a.com");
+        manager.new_tab("https://b.com");
+        manager.switch_to_tab(1);
+
+        assert!(manager.close_and_select(1, CloseDirection::Left));
+        assert_eq!(manager.active_tab().url, "about:home");
+    }
+
+    #[test]
+    fn test_close_and_select_right() {
+        let mut manager = TabManager::new();
+        manager.new_tab("https://a.com");
+        manager.new_tab("https://b.com");
+        manager.switch_to_tab(1);
+
+        assert!(manager.close_and_select(1, CloseDirection::Right));
+        assert_eq!(manager.active_tab().url, "https://b.com");
+    }
+
+    #[test]
+    fn test_close_and_select_at_edges() {
+        let mut manager = TabManager::new();
+        manager.new_tab("https://a.com");
+        manager.new_tab("https://b.com");
+
+        // No right neighbor for the last tab, so the left one is used
+        assert!(manager.close_and_select(2, CloseDirection::Right));
+        assert_eq!(manager.active_tab().url, "https://a.com");
+
+        // No left neighbor for the first tab, so the right one is used
+        manager.switch_to_tab(0);
+        assert!(manager.close_and_select(0, CloseDirection::Left));
+        assert_eq!(manager.active_tab().url, "https://a.com");
+        assert!(!manager.close_and_select(0, CloseDirection::Left));
+    }
+
+    #[test]
+    fn test_close_and_select_inactive_tab_keeps_active() {
+        let mut manager = TabManager::new();
+        manager.new_tab("https://a.com");
+        manager.new_tab("https://b.com");
+
+        assert!(manager.close_and_select(0, CloseDirection::Right));
+        assert_eq!(manager.active_tab().url, "https://b.com");
+    }
+
+    #[test]
+    fn test_merge_from_preserves_order_and_active_tab() {
+        let mut current = TabManager::new();
+        current.new_tab("https://a.com");
+        current.switch_to_tab(0);
+
+        let mut second = TabManager::new();
+        second.active_tab_mut().navigate_to("https://b.com");
+        second.new_tab("https://c.com");
+        let mut third = TabManager::new();
+        third.active_tab_mut().navigate_to("https://d.com");
+
+        current.merge_from(vec![second, third]);
+
+        let urls: Vec<&str> = current.tabs().iter().map(|t| t.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "about:home",
+                "https://a.com",
+                "https://b.com",
+                "https://c.com",
+                "https://d.com",
+            ]
+        );
+        assert_eq!(current.active_tab_index(), 0);
+        assert_eq!(current.active_tab().url, "about:home");
+    }
+
+    #[test]
+    fn test_merge_from_nothing() {
+        let mut current = TabManager::new();
+        current.merge_from(Vec::new());
+        assert_eq!(current.tab_count(), 1);
+    }
+
+    #[test]
+    fn test_merge_from_keeps_pinned_tabs_first() {
+        let mut current = manager_with_tabs(&["a", "b"]);
+        current.toggle_pin(0);
+        current.switch_to_tab(1);
+
+        let mut other = manager_with_tabs(&["c", "d"]);
+        other.toggle_pin(1);
+
+        assert!(current.merge_from(vec![other]).is_empty());
+
+        let urls: Vec<&str> = current.tabs().iter().map(|t| t.url.as_str()).collect();
+        assert_eq!(urls, vec!["a", "d", "b", "c"]);
+        assert!(current.tabs()[1].is_pinned);
+        assert_eq!(current.active_tab().url, "b");
+    }
+
+    #[test]
+    fn test_merge_from_refuses_other_mode() {
+        let mut current = TabManager::new();
+        let private = TabManager::new_private();
+        let normal = manager_with_tabs(&["a"]);
+
+        let refused = current.merge_from(vec![private, normal]);
+        assert_eq!(refused.len(), 1);
+        assert!(refused[0].is_private());
+        assert_eq!(current.tab_count(), 2);
+
+        let mut private = TabManager::new_private();
+        assert_eq!(private.merge_from(vec![TabManager::new()]).len(), 1);
+        assert_eq!(private.tab_count(), 1);
+    }
+
+    /// Create a manager with tabs for the given pages
+    fn manager_with_tabs(urls: &[&str]) -> TabManager {
+        let mut manager = TabManager::new();
+        manager.active_tab_mut().navigate_to(urls[0]);
+        for url in &urls[1..] {
+            manager.new_tab(*url);
+        }
+        manager
+    }
+
+    #[test]
+    fn test_take_tab() {
+        let mut manager = manager_with_tabs(&["a", "b", "c"]);
+        manager.tab_mut(1).unwrap().navigate_to("b2");
+        manager.tab_mut(1).unwrap().go_back();
+        manager.switch_to_tab(2);
+
+        let tab = manager.take_tab(1).unwrap();
+        assert_eq!(tab.url, "b");
+        assert_eq!(tab.history, vec!["b".to_string(), "b2".to_string()]);
+        assert!(tab.can_go_forward());
+
+        // The active tab stays active at its new index
+        assert_eq!(manager.tab_count(), 2);
+        assert_eq!(manager.active_tab_index(), 1);
+        assert_eq!(manager.active_tab().url, "c");
+
+        assert!(manager.take_tab(2).is_none());
+        manager.take_tab(0).unwrap();
+        assert!(manager.take_tab(0).is_none());
+        assert_eq!(manager.active_tab().url, "c");
+    }
+
+    #[test]
+    fn test_close_others() {
+        let mut manager = manager_with_tabs(&["a", "b", "c", "d"]);
+        manager.switch_to_tab(3);
+
+        assert_eq!(manager.close_others(1), 3);
+        assert_eq!(manager.tab_count(), 1);
+        assert_eq!(manager.active_tab_index(), 0);
+        assert_eq!(manager.active_tab().url, "b");
+
+        assert_eq!(manager.close_others(0), 0);
+        assert_eq!(manager.close_others(5), 0);
+        assert_eq!(manager.tab_count(), 1);
+    }
+
+    #[test]
+    fn test_close_to_right() {
+        let mut manager = manager_with_tabs(&["a", "b", "c", "d"]);
+        manager.switch_to_tab(3);
+
+        assert_eq!(manager.close_to_right(1), 2);
+        let urls: Vec<&str> = manager.tabs().iter().map(|tab| tab.url.as_str()).collect();
+        assert_eq!(urls, vec!["a", "b"]);
+        // The active tab was closed, so the tab closed from becomes active
+        assert_eq!(manager.active_tab().url, "b");
+
+        manager.switch_to_tab(0);
+        assert_eq!(manager.close_to_right(1), 0);
+        assert_eq!(manager.close_to_right(0), 1);
+        assert_eq!(manager.active_tab().url, "a");
+    }
+
+    #[test]
+    fn test_pinned_tabs() {
+        let mut manager = manager_with_tabs(&["a", "b", "c"]);
+        assert!(manager.toggle_pin(2));
+        assert!(manager.toggle_pin(2));
+        let urls: Vec<&str> = manager.tabs().iter().map(|tab| tab.url.as_str()).collect();
+        assert_eq!(urls, vec!["c", "b", "a"]);
+        assert_eq!(manager.active_tab().url, "c");
+
+        // Pinned tabs survive closing the others
+        assert_eq!(manager.close_others(2), 0);
+        manager.new_tab("d");
+        assert_eq!(manager.close_others(0), 2);
+        assert_eq!(manager.tab_count(), 2);
+
+        assert!(!manager.toggle_pin(0));
+        let urls: Vec<&str> = manager.tabs().iter().map(|tab| tab.url.as_str()).collect();
+        assert_eq!(urls, vec!["b", "c"]);
+        assert!(manager.tabs()[0].is_pinned);
+        assert!(!manager.tabs()[1].is_pinned);
+    }
+
+    #[test]
+    fn test_duplicate_tab() {
+        let mut manager = manager_with_tabs(&["a", "b"]);
+        manager.tab_mut(0).unwrap().navigate_to("a2");
+
+        assert_eq!(manager.duplicate_tab(0), Some(1));
+        assert_eq!(manager.active_tab_index(), 1);
+        let copy = manager.active_tab();
+        assert_eq!(copy.url, "a2");
+        assert_eq!(copy.history, manager.tabs()[0].history);
+        assert_ne!(copy.id, manager.tabs()[0].id);
+        assert_eq!(manager.tabs()[2].url, "b");
+        assert_eq!(manager.duplicate_tab(3), None);
+    }
+
+    #[test]
+    fn test_adopt_tab() {
+        let mut source = manager_with_tabs(&["a", "b"]);
+        let work = source.create_group("Work", Color::new(59, 130, 246));
+        source.assign_to_group(1, &work);
+        let tab = source.take_tab(1).unwrap();
+        assert_eq!(tab.group_id, None);
+        assert!(source.groups().is_empty());
+
+        let mut target = manager_with_tabs(&["c", "d"]);
+        target.switch_to_tab(0);
+        target.adopt_tab(tab);
+        assert_eq!(target.tab_count(), 3);
+        assert_eq!(target.active_tab_index(), 2);
+        assert_eq!(target.active_tab().url, "b");
+    }
+
+    #[test]
+    fn test_tab_groups() {
+        let mut manager = manager_with_tabs(&["a", "b", "c"]);
+        let work = manager.create_group("Work", Color::new(59, 130, 246));
+        assert_eq!(manager.group(&work).unwrap().name, "Work");
+        assert!(manager.tabs_in_group(&work).is_empty());
+
+        assert!(manager.assign_to_group(0, &work));
+        assert!(manager.assign_to_group(1, &work));
+        assert_eq!(manager.tabs_in_group(&work), vec![0, 1]);
+        assert!(!manager.assign_to_group(5, &work));
+        assert!(!manager.assign_to_group(2, "missing"));
+
+        assert!(manager.ungroup(0));
+        assert!(!manager.ungroup(0));
+        assert_eq!(manager.tabs_in_group(&work), vec![1]);
+
+        // The group goes away with its last tab
+        assert!(manager.ungroup(1));
+        assert!(manager.group(&work).is_none());
+        assert!(manager.groups().is_empty());
+    }
+
+    #[test]
+    fn test_closing_grouped_tabs() {
+        let mut manager = manager_with_tabs(&["a", "b", "c"]);
+        let group = manager.create_group("Reading", Color::new(16, 185, 129));
+        manager.assign_to_group(1, &group);
+        manager.assign_to_group(2, &group);
+
+        assert!(manager.close_tab(1));
+        assert_eq!(manager.tabs_in_group(&group), vec![1]);
+        assert!(manager.group(&group).is_some());
+
+        assert!(manager.close_and_select(1, CloseDirection::Left));
+        assert!(manager.tabs_in_group(&group).is_empty());
+        assert!(manager.group(&group).is_none());
+    }
+
+    #[test]
+    fn test_move_tab_keeps_groups_together() {
+        let mut manager = manager_with_tabs(&["a", "b", "c", "d"]);
+        let group = manager.create_group("Group", Color::new(239, 68, 68));
+        manager.assign_to_group(1, &group);
+        manager.assign_to_group(2, &group);
+        manager.switch_to_tab(0);
+
+        // Moving between two grouped tabs joins the group
+        assert!(manager.move_tab(3, 2));
+        assert_eq!(manager.tabs()[2].url, "d");
+        assert_eq!(manager.tabs_in_group(&group), vec![1, 2, 3]);
+
+        // Moving within the group keeps it
+        assert!(manager.move_tab(1, 3));
+        assert_eq!(manager.tabs_in_group(&group), vec![1, 2, 3]);
+
+        // Moving away from the rest of the group leaves it
+        assert!(manager.move_tab(2, 0));
+        assert_eq!(manager.tabs()[0].group_id, None);
+        assert_eq!(manager.tabs_in_group(&group), vec![2, 3]);
+        assert_eq!(manager.active_tab().url, "a");
+
+        assert!(!manager.move_tab(0, 4));
+    }
+
+    #[test]
+    fn test_private_tabs_record_no_history() {
+        let mut history = HistoryStore::new();
+
+        let mut manager = TabManager::new();
+        manager.active_tab_mut().navigate_to("https://example.com");
+        assert!(!manager.is_private());
+        assert!(manager.record_visit(&mut history, "https://example.com", ""));
+        assert_eq!(history.count(), 1);
+
+        let mut private = TabManager::new_private();
+        private
+            .active_tab_mut()
+            .navigate_to("https://secret.example");
+        private.new_tab("https://other.example");
+        assert!(private.is_private());
+        assert!(!private.record_visit(&mut history, "https://secret.example", ""));
+        assert!(!private.record_visit(&mut history, "https://other.example", ""));
+        assert_eq!(history.count(), 1);
+    }
+
+    #[test]
+    fn test_about_pages_record_no_history() {
+        let mut history = HistoryStore::new();
+        let manager = TabManager::new();
+
+        assert!(!manager.record_visit(&mut history, "about:home", ""));
+        assert!(!manager.record_visit(&mut history, "about:settings", ""));
+        assert_eq!(history.count(), 0);
+    }
+
+    #[test]
+    fn test_private_cookies_are_not_persisted() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let user_data = UserDataManager::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let mut cookies = TabManager::new_private().cookie_store(&user_data).unwrap();
+        assert!(!cookies.is_persistent());
+        cookies
+            .set_from_header("https://example.com/", "sid=1; Max-Age=3600")
+            .unwrap();
+        assert!(cookies.cookie_header("https://example.com/").is_some());
+        assert!(cookies.save_to_storage().is_err());
+
+        let saved = TabManager::new().cookie_store(&user_data).unwrap();
+        assert!(saved.is_persistent());
+        assert!(saved.cookies().is_empty());
+    }
+
     #[test]
     fn test_tab_manager_switch_tab() {
         let mut manager = TabManager::new();
@@ -308,4 +1265,64 @@ mod tests {
         assert_eq!(manager.active_tab_index(), 0);
         assert_eq!(manager.active_tab().url, "about:home");
     }
+
+    #[test]
+    fn test_switch_to_tab_out_of_range() {
+        let mut manager = TabManager::new();
+        manager.new_tab("https://example.com");
+
+        assert!(manager.switch_to_tab(1));
+        assert!(!manager.switch_to_tab(2));
+        assert!(!manager.switch_to_tab(usize::MAX));
+        assert_eq!(manager.active_tab_index(), 1);
+    }
+
+    #[test]
+    fn test_next_and_prev_tab_wrap_around() {
+        let mut manager = TabManager::new();
+        manager.new_tab("https://example.com/1");
+        manager.new_tab("https://example.com/2");
+        assert_eq!(manager.active_tab_index(), 2);
+
+        manager.next_tab();
+        assert_eq!(manager.active_tab_index(), 0);
+        manager.next_tab();
+        assert_eq!(manager.active_tab_index(), 1);
+
+        manager.switch_to_tab(0);
+        manager.prev_tab();
+        assert_eq!(manager.active_tab_index(), 2);
+        manager.prev_tab();
+        assert_eq!(manager.active_tab_index(), 1);
+
+        let mut single = TabManager::new();
+        single.next_tab();
+        single.prev_tab();
+        assert_eq!(single.active_tab_index(), 0);
+    }
+
+    #[test]
+    fn test_switch_to_tab_number() {
+        let mut manager = TabManager::new();
+        for i in 1..10 {
+            manager.new_tab(format!("https://example.com/{}", i));
+        }
+        assert_eq!(manager.tab_count(), 10);
+
+        assert!(manager.switch_to_tab_number(1));
+        assert_eq!(manager.active_tab_index(), 0);
+        assert!(manager.switch_to_tab_number(8));
+        assert_eq!(manager.active_tab_index(), 7);
+        assert!(manager.switch_to_tab_number(9));
+        assert_eq!(manager.active_tab_index(), 9);
+
+        assert!(!manager.switch_to_tab_number(0));
+        assert!(!manager.switch_to_tab_number(10));
+        assert_eq!(manager.active_tab_index(), 9);
+
+        let mut single = TabManager::new();
+        assert!(!single.switch_to_tab_number(3));
+        assert!(single.switch_to_tab_number(9));
+        assert_eq!(single.active_tab_index(), 0);
+    }
 }
@@ -1,33 +1,207 @@
 //! Theme system for Horizon Browser
 //!
 //! Provides theming capabilities with support for dark and light modes.
+//! Custom themes can be loaded from TOML or JSON files.
 
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
-/// Color in RGB format
+/// Folder custom theme files are loaded from inside the data directory
+pub const THEMES_DIR: &str = "themes";
+
+/// Heading size relative to body text
+pub const HEADING_SCALE: f32 = 1.5;
+
+/// Small text size relative to body text
+pub const SMALL_SCALE: f32 = 0.75;
+
+/// Body text size of the default theme, which font size settings are relative to
+pub const DEFAULT_BASE_SIZE: u16 = 14;
+
+/// Generic font family names that mean egui's built-in monospace font
+const MONOSPACE_FAMILIES: &[&str] = &["monospace", "ui-monospace"];
+
+/// Generic font family names that mean egui's built-in proportional font
+const PROPORTIONAL_FAMILIES: &[&str] = &[
+    "system-ui",
+    "-apple-system",
+    "blinkmacsystemfont",
+    "ui-sans-serif",
+    "sans-serif",
+    "serif",
+    "ui-serif",
+    "cursive",
+    "fantasy",
+];
+
+/// System folders searched for fonts named in a font family list
+const SYSTEM_FONT_DIRS: &[&str] = &[
+    "/usr/share/fonts",
+    "/usr/local/share/fonts",
+    "/Library/Fonts",
+    "/System/Library/Fonts",
+    "C:\\Windows\\Fonts",
+];
+
+/// Font folders inside the home directory
+const USER_FONT_DIRS: &[&str] = &[".local/share/fonts", ".fonts", "Library/Fonts"];
+
+/// How deep to look inside font folders
+const MAX_FONT_DIR_DEPTH: usize = 4;
+
+/// Color in RGBA format, with alpha not premultiplied
+///
+/// Deserializes from either a hex string (`"#3b82f6"`) or an `r`/`g`/`b`/`a`
+/// table.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(try_from = "ColorRepr")]
 pub struct Color {
     pub r: u8,
     pub g: u8,
     pub b: u8,
+    /// Opacity, where 255 is opaque
+    #[serde(default = "opaque")]
+    pub a: u8,
+}
+
+/// Default alpha for colors saved without one
+fn opaque() -> u8 {
+    255
+}
+
+/// Accepted serialized forms of a color
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorRepr {
+    Hex(String),
+    Channels {
+        r: u8,
+        g: u8,
+        b: u8,
+        #[serde(default = "opaque")]
+        a: u8,
+    },
+}
+
+impl TryFrom<ColorRepr> for Color {
+    type Error = String;
+
+    fn try_from(repr: ColorRepr) -> Result<Self, Self::Error> {
+        match repr {
+            ColorRepr::Hex(hex) => {
+                Self::from_hex(&hex).ok_or_else(|| format!("invalid hex color: {}", hex))
+            }
+            ColorRepr::Channels { r, g, b, a } => Ok(Self::new_rgba(r, g, b, a)),
+        }
+    }
 }
 
 impl Color {
-    /// Create a new color
+    /// Create a new opaque color
     pub const fn new(r: u8, g: u8, b: u8) -> Self {
-        Self { r, g, b }
+        Self::new_rgb(r, g, b)
+    }
+
+    /// Create a new opaque color
+    pub const fn new_rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    /// Create a new color with an alpha channel
+    pub const fn new_rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Get the same color with a different alpha
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Self { a, ..self }
+    }
+
+    /// Convert to an egui color
+    pub fn to_egui(&self) -> egui::Color32 {
+        egui::Color32::from_rgba_unmultiplied(self.r, self.g, self.b, self.a)
     }
 
-    /// Create color from hex string
+    /// Convert to an egui color, taking the channels as already multiplied
+    /// by alpha
+    ///
+    /// Translucent colors come out brighter than with `to_egui`, letting more
+    /// of what is behind them show through.
+    pub fn to_egui_premultiplied(&self) -> egui::Color32 {
+        egui::Color32::from_rgba_premultiplied(self.r, self.g, self.b, self.a)
+    }
+
+    /// Create color from a `rrggbbaa`, `rrggbb`, or `rgb` hex string, with an
+    /// optional leading `#`
     pub fn from_hex(hex: &str) -> Option<Self> {
-        let hex = hex.trim_start_matches('#');
-        if hex.len() != 6 {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
             return None;
         }
-        let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
-        let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
-        let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
-        Some(Self { r, g, b })
+
+        let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+        match hex.len() {
+            6 | 8 => Some(Self {
+                r: channel(&hex[0..2])?,
+                g: channel(&hex[2..4])?,
+                b: channel(&hex[4..6])?,
+                a: hex.get(6..8).map_or(Some(255), channel)?,
+            }),
+            // Shorthand: each digit is doubled, so `abc` is `aabbcc`
+            3 => Some(Self::new_rgb(
+                channel(&hex[0..1])? * 0x11,
+                channel(&hex[1..2])? * 0x11,
+                channel(&hex[2..3])? * 0x11,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Blend toward another color by `t`, from 0.0 (this color) to 1.0 (`other`)
+    ///
+    /// Every channel, including alpha, is interpolated; `t` is clamped to 0-1.
+    pub fn mix(&self, other: Color, t: f32) -> Self {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let blend = |from: u8, to: u8| {
+            let value = f32::from(from) + (f32::from(to) - f32::from(from)) * t;
+            value.round().clamp(0.0, 255.0) as u8
+        };
+
+        Self::new_rgba(
+            blend(self.r, other.r),
+            blend(self.g, other.g),
+            blend(self.b, other.b),
+            blend(self.a, other.a),
+        )
+    }
+
+    /// Blend toward white by `factor` (0.0-1.0), keeping alpha
+    pub fn lighten(&self, factor: f32) -> Self {
+        self.mix(Color::new_rgba(255, 255, 255, self.a), factor)
+    }
+
+    /// Blend toward black by `factor` (0.0-1.0), keeping alpha
+    pub fn darken(&self, factor: f32) -> Self {
+        self.mix(Color::new_rgba(0, 0, 0, self.a), factor)
+    }
+
+    /// Get the relative luminance, from 0.0 (black) to 1.0 (white)
+    pub fn luminance(&self) -> f32 {
+        (0.2126 * f32::from(self.r) + 0.7152 * f32::from(self.g) + 0.0722 * f32::from(self.b))
+            / 255.0
+    }
+
+    /// Format the color as a lowercase hex string
+    ///
+    /// Opaque colors are `#rrggbb`; translucent ones are `#rrggbbaa`.
+    pub fn to_hex(&self) -> String {
+        if self.a == 255 {
+            format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        }
     }
 }
 
@@ -46,7 +220,9 @@ pub struct ColorPalette {
 
     // Accent colors
     pub accent: Color,
-    pub accent_hover: Color,
+    /// Hover state of the accent, derived from `accent` when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent_hover: Option<Color>,
 
     // Status colors
     pub success: Color,
@@ -58,6 +234,25 @@ pub struct ColorPalette {
 }
 
 impl ColorPalette {
+    /// Check whether the window background is dark
+    pub fn is_dark(&self) -> bool {
+        self.bg_window.luminance() < 0.5
+    }
+
+    /// Get the accent hover color
+    ///
+    /// Without an explicit hover color, the accent is lightened on dark
+    /// backgrounds and darkened on light ones.
+    pub fn accent_hover(&self) -> Color {
+        self.accent_hover.unwrap_or_else(|| {
+            if self.is_dark() {
+                self.accent.lighten(0.2)
+            } else {
+                self.accent.darken(0.2)
+            }
+        })
+    }
+
     /// Create Firefox-inspired dark theme palette
     pub fn dark() -> Self {
         Self {
@@ -73,7 +268,7 @@ impl ColorPalette {
 
             // Accent colors (Firefox-inspired blue)
             accent: Color::new(59, 130, 246),         // #3B82F6 - Primary accent
-            accent_hover: Color::new(96, 165, 250),   // #60A5FA - Hover state
+            accent_hover: Some(Color::new(96, 165, 250)), // #60A5FA - Hover state
 
             // Status colors
             success: Color::new(34, 197, 94),         // #22C55E - Success/green
@@ -100,7 +295,7 @@ impl ColorPalette {
 
             // Accent colors (Firefox-inspired blue)
             accent: Color::new(37, 99, 235),          // #2563EB - Primary accent
-            accent_hover: Color::new(29, 78, 216),    // #1D4ED8 - Hover state
+            accent_hover: Some(Color::new(29, 78, 216)), // #1D4ED8 - Hover state
 
             // Status colors
             success: Color::new(22, 163, 74),         // #16A34A - Success/green
@@ -165,7 +360,7 @@ pub struct Typography {
 impl Default for Typography {
     fn default() -> Self {
         Self {
-            base_size: 14,
+            base_size: DEFAULT_BASE_SIZE,
             tab_size: 13,
             menu_size: 13,
             font_family: "system-ui, -apple-system, BlinkMacSystemFont, 'Segoe UI', sans-serif".to_string(),
@@ -174,12 +369,18 @@ impl Default for Typography {
 }
 
 /// Main theme structure
+///
+/// Every palette color except `accent_hover` is required in a theme file;
+/// spacing, radii, and typography fall back to their defaults.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Theme {
     name: String,
     palette: ColorPalette,
+    #[serde(default)]
     spacing: Spacing,
+    #[serde(default)]
     radii: Radii,
+    #[serde(default)]
     typography: Typography,
 }
 
@@ -229,6 +430,156 @@ impl Theme {
     pub fn font_size(&self) -> u16 {
         self.typography.base_size
     }
+
+    /// Load a theme from a `.json` file, or a TOML file with any other extension
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let theme: Self = if is_json(path) {
+            serde_json::from_str(&contents)?
+        } else {
+            toml::from_str(&contents)?
+        };
+
+        theme
+            .validate()
+            .map_err(|e| anyhow!("Invalid theme {:?}: {}", path, e))?;
+        Ok(theme)
+    }
+
+    /// Save the theme to a `.json` file, or a TOML file with any other extension
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = if is_json(path) {
+            serde_json::to_string_pretty(self)?
+        } else {
+            toml::to_string_pretty(self)?
+        };
+        std::fs::write(path, contents)?;
+        tracing::debug!("Saved theme {} to {:?}", self.name, path);
+        Ok(())
+    }
+
+    /// Check that the name is set and sizes are within usable ranges
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(anyhow!("Theme name is required"));
+        }
+
+        let typography = &self.typography;
+        for (field, size) in [
+            ("base_size", typography.base_size),
+            ("tab_size", typography.tab_size),
+            ("menu_size", typography.menu_size),
+        ] {
+            if !(6..=72).contains(&size) {
+                return Err(anyhow!("typography.{} must be 6-72, got {}", field, size));
+            }
+        }
+
+        let spacing = &self.spacing;
+        for (field, value) in [
+            ("unit", spacing.unit),
+            ("small", spacing.small),
+            ("standard", spacing.standard),
+            ("large", spacing.large),
+        ] {
+            if !(0..=64).contains(&value) {
+                return Err(anyhow!("spacing.{} must be 0-64, got {}", field, value));
+            }
+        }
+
+        let radii = &self.radii;
+        for (field, value) in [
+            ("button", radii.button),
+            ("tab", radii.tab),
+            ("field", radii.field),
+            ("panel", radii.panel),
+        ] {
+            if !(0.0..=32.0).contains(&value) {
+                return Err(anyhow!("radii.{} must be 0-32, got {}", field, value));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Check whether a path names a JSON file
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+/// Themes available to the UI, by name
+///
+/// Starts with the built-in Dark and Light themes.
+#[derive(Debug, Clone)]
+pub struct ThemeRegistry {
+    themes: BTreeMap<String, Theme>,
+}
+
+impl ThemeRegistry {
+    /// Create a registry holding the built-in themes
+    pub fn new() -> Self {
+        let mut registry = Self {
+            themes: BTreeMap::new(),
+        };
+        registry.register(Theme::default());
+        registry.register(Theme::new("Light", ColorPalette::light()));
+        registry
+    }
+
+    /// Add a theme, replacing any theme with the same name
+    pub fn register(&mut self, theme: Theme) {
+        self.themes.insert(theme.name().to_string(), theme);
+    }
+
+    /// Load every `.toml` and `.json` theme file in a directory
+    ///
+    /// Invalid files are skipped with a warning. Returns how many themes were
+    /// loaded; a missing directory loads none.
+    pub fn load_dir(&mut self, dir: &Path) -> Result<usize> {
+        if !dir.exists() {
+            return Ok(0);
+        }
+
+        let mut loaded = 0;
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            let is_theme_file = path.extension().is_some_and(|ext| {
+                ext.eq_ignore_ascii_case("toml") || ext.eq_ignore_ascii_case("json")
+            });
+            if !path.is_file() || !is_theme_file {
+                continue;
+            }
+
+            match Theme::from_file(&path) {
+                Ok(theme) => {
+                    tracing::info!("Loaded theme {} from {:?}", theme.name(), path);
+                    self.register(theme);
+                    loaded += 1;
+                }
+                Err(e) => tracing::warn!("Skipping theme file {:?}: {}", path, e),
+            }
+        }
+
+        Ok(loaded)
+    }
+
+    /// Get a theme by name
+    pub fn get(&self, name: &str) -> Option<&Theme> {
+        self.themes.get(name)
+    }
+
+    /// List theme names in alphabetical order
+    pub fn names(&self) -> Vec<&str> {
+        self.themes.keys().map(String::as_str).collect()
+    }
+}
+
+impl Default for ThemeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Default for Theme {
@@ -237,6 +588,200 @@ impl Default for Theme {
     }
 }
 
+/// Build the egui style for a theme
+///
+/// Starts from egui's dark or light visuals, matching the window background,
+/// and applies the palette colors and widget radius on top.
+pub fn build_visuals(theme: &Theme) -> egui::Style {
+    let palette = theme.palette();
+    let mut style = egui::Style {
+        visuals: if palette.is_dark() {
+            egui::Visuals::dark()
+        } else {
+            egui::Visuals::light()
+        },
+        ..Default::default()
+    };
+
+    let visuals = &mut style.visuals;
+    visuals.widgets.noninteractive.bg_fill = palette.bg_window.to_egui();
+    visuals.widgets.inactive.bg_fill = palette.bg_toolbar.to_egui();
+    visuals.widgets.hovered.bg_fill = palette.border_subtle.to_egui();
+    visuals.widgets.active.bg_fill = palette.accent.to_egui();
+    visuals.widgets.noninteractive.bg_stroke.color = palette.border_subtle.to_egui();
+    visuals.widgets.noninteractive.fg_stroke.color = palette.text_secondary.to_egui();
+    visuals.widgets.inactive.fg_stroke.color = palette.text_primary.to_egui();
+    visuals.extreme_bg_color = palette.bg_window.to_egui();
+    visuals.window_fill = palette.bg_window.to_egui();
+    visuals.panel_fill = palette.bg_window.to_egui();
+    visuals.hyperlink_color = palette.accent_hover().to_egui();
+
+    let rounding = egui::Rounding::same(theme.radii().button);
+    visuals.widgets.noninteractive.rounding = rounding;
+    visuals.widgets.inactive.rounding = rounding;
+    visuals.widgets.hovered.rounding = rounding;
+    visuals.widgets.active.rounding = rounding;
+
+    visuals.selection.bg_fill = palette.accent.with_alpha(80).to_egui();
+    visuals.selection.stroke = egui::Stroke::new(1.0, palette.accent.to_egui());
+
+    style
+}
+
+/// Get the body text size for a theme's base size and the user's font size
+///
+/// The user's font size is scaled by how far the theme's base size is from
+/// the default one, so themes with larger text stay larger.
+pub fn body_text_size(base_size: u16, font_size: u16) -> f32 {
+    f32::from(font_size) * f32::from(base_size) / f32::from(DEFAULT_BASE_SIZE)
+}
+
+/// Map a body font size to the size of every egui text style
+///
+/// Text other than `Monospace` uses `family`.
+pub fn text_styles(
+    base_size: f32,
+    family: egui::FontFamily,
+) -> BTreeMap<egui::TextStyle, egui::FontId> {
+    use egui::{FontFamily, FontId, TextStyle};
+
+    BTreeMap::from([
+        (
+            TextStyle::Small,
+            FontId::new(base_size * SMALL_SCALE, family.clone()),
+        ),
+        (TextStyle::Body, FontId::new(base_size, family.clone())),
+        (TextStyle::Button, FontId::new(base_size, family.clone())),
+        (
+            TextStyle::Monospace,
+            FontId::new(base_size, FontFamily::Monospace),
+        ),
+        (
+            TextStyle::Heading,
+            FontId::new(base_size * HEADING_SCALE, family),
+        ),
+    ])
+}
+
+/// Build egui font definitions for a CSS-style font family list, along with
+/// the family text should use
+///
+/// Entries are tried in order. A `.ttf` or `.otf` path, or the name of a font
+/// installed in the system or user font folders, is loaded ahead of egui's
+/// built-in proportional font. Generic names like `sans-serif` or `monospace`
+/// pick the matching built-in font.
+pub fn font_definitions(font_family: &str) -> (egui::FontDefinitions, egui::FontFamily) {
+    let mut fonts = egui::FontDefinitions::default();
+    let font_dirs = font_dirs();
+
+    for entry in font_family.split(',') {
+        let entry = entry.trim().trim_matches(['\'', '"']);
+        let generic = entry.to_ascii_lowercase();
+        if MONOSPACE_FAMILIES.contains(&generic.as_str()) {
+            return (fonts, egui::FontFamily::Monospace);
+        }
+        if PROPORTIONAL_FAMILIES.contains(&generic.as_str()) {
+            break;
+        }
+
+        let Some(path) = font_file(entry, &font_dirs) else {
+            continue;
+        };
+        match std::fs::read(&path) {
+            Ok(data) if is_font_data(&data) => {
+                fonts
+                    .font_data
+                    .insert(entry.to_string(), egui::FontData::from_owned(data));
+                fonts
+                    .families
+                    .entry(egui::FontFamily::Proportional)
+                    .or_default()
+                    .insert(0, entry.to_string());
+                tracing::info!("Loaded font {:?}", path);
+                break;
+            }
+            Ok(_) => tracing::warn!("Skipping font {:?}: not a TrueType or OpenType font", path),
+            Err(e) => tracing::warn!("Skipping font {:?}: {}", path, e),
+        }
+    }
+
+    (fonts, egui::FontFamily::Proportional)
+}
+
+/// Get the folders fonts are looked up in
+fn font_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = SYSTEM_FONT_DIRS.iter().map(PathBuf::from).collect();
+    if let Some(home) = dirs::home_dir() {
+        dirs.extend(USER_FONT_DIRS.iter().map(|dir| home.join(dir)));
+    }
+    dirs
+}
+
+/// Find the file for a font family entry
+///
+/// The entry is either a path to a font file or the name of a font in one of
+/// `font_dirs`, matched against file names ignoring case, spaces and dashes,
+/// with or without a `Regular` suffix.
+fn font_file(entry: &str, font_dirs: &[PathBuf]) -> Option<PathBuf> {
+    if is_font_path(Path::new(entry)) {
+        return Some(PathBuf::from(entry));
+    }
+
+    let name = normalize_font_name(entry);
+    if name.is_empty() {
+        return None;
+    }
+    font_dirs
+        .iter()
+        .find_map(|dir| find_font_in(dir, &name, MAX_FONT_DIR_DEPTH))
+}
+
+/// Search a folder and its subfolders for a font file with a matching name
+fn find_font_in(dir: &Path, name: &str, depth: usize) -> Option<PathBuf> {
+    let mut subdirs = Vec::new();
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            subdirs.push(path);
+        } else if is_font_path(&path) {
+            let stem = path
+                .file_stem()
+                .map(|stem| normalize_font_name(&stem.to_string_lossy()))
+                .unwrap_or_default();
+            if stem == name || stem.strip_suffix("regular") == Some(name) {
+                return Some(path);
+            }
+        }
+    }
+
+    if depth == 0 {
+        return None;
+    }
+    subdirs.sort();
+    subdirs
+        .iter()
+        .find_map(|subdir| find_font_in(subdir, name, depth - 1))
+}
+
+/// Check whether a path names a TrueType or OpenType font file
+fn is_font_path(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ttf") || ext.eq_ignore_ascii_case("otf"))
+}
+
+/// Lowercase a font name and drop everything but letters and digits
+fn normalize_font_name(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Check the signature of TrueType and OpenType font data
+fn is_font_data(data: &[u8]) -> bool {
+    matches!(data.get(..4), Some([0, 1, 0, 0] | b"true" | b"OTTO"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -255,6 +800,235 @@ mod tests {
         assert_eq!(color.r, 255);
         assert_eq!(color.g, 128);
         assert_eq!(color.b, 0);
+
+        assert_eq!(Color::from_hex("FF8000"), Some(color));
+        assert_eq!(Color::from_hex("##ff8000"), None);
+        assert_eq!(Color::from_hex("#ff800"), None);
+        assert_eq!(Color::from_hex("#gg8000"), None);
+        assert_eq!(Color::from_hex("a\u{e9}\u{20ac}"), None);
+    }
+
+    #[test]
+    fn test_color_hex_shorthand() {
+        assert_eq!(Color::from_hex("#abc"), Color::from_hex("#aabbcc"));
+        assert_eq!(Color::from_hex("fff"), Some(Color::new(255, 255, 255)));
+        assert_eq!(Color::from_hex("#000"), Some(Color::new(0, 0, 0)));
+        assert_eq!(Color::from_hex("#F80"), Some(Color::new(255, 136, 0)));
+    }
+
+    #[test]
+    fn test_color_hex_round_trip() {
+        assert_eq!(Color::new(255, 128, 0).to_hex(), "#ff8000");
+        assert_eq!(Color::new(0, 0, 0).to_hex(), "#000000");
+
+        for r in (0..=255).step_by(15) {
+            for g in (0..=255).step_by(51) {
+                for b in [0, 1, 127, 128, 254, 255] {
+                    let color = Color::new(r, g, b);
+                    assert_eq!(Color::from_hex(&color.to_hex()), Some(color));
+                    let bare = color.to_hex().trim_start_matches('#').to_string();
+                    assert_eq!(Color::from_hex(&bare), Some(color));
+                }
+            }
+        }
+
+        let palette = ColorPalette::dark();
+        assert_eq!(palette.bg_window.to_hex(), "#111827");
+        assert_eq!(Color::from_hex("#111827"), Some(palette.bg_window));
+    }
+
+    #[test]
+    fn test_color_alpha() {
+        assert_eq!(Color::new(1, 2, 3).a, 255);
+        assert_eq!(Color::new(1, 2, 3), Color::new_rgb(1, 2, 3));
+        assert_eq!(
+            Color::from_hex("#ff800080"),
+            Some(Color::new_rgba(255, 128, 0, 128))
+        );
+        assert_eq!(Color::from_hex("ff8000ff"), Some(Color::new(255, 128, 0)));
+        assert_eq!(Color::from_hex("#ff80008"), None);
+
+        let translucent = Color::new(31, 41, 51).with_alpha(200);
+        assert_eq!(translucent.to_hex(), "#1f2933c8");
+        assert_eq!(Color::from_hex(&translucent.to_hex()), Some(translucent));
+        for a in [0, 1, 127, 254] {
+            let color = Color::new(10, 20, 30).with_alpha(a);
+            assert_eq!(Color::from_hex(&color.to_hex()), Some(color));
+        }
+    }
+
+    #[test]
+    fn test_color_to_egui() {
+        assert_eq!(
+            Color::new(255, 128, 0).to_egui(),
+            egui::Color32::from_rgb(255, 128, 0)
+        );
+        assert_eq!(
+            Color::new(255, 255, 255).with_alpha(0).to_egui(),
+            egui::Color32::TRANSPARENT
+        );
+        assert_eq!(Color::new(0, 0, 0).with_alpha(128).to_egui().a(), 128);
+        assert_eq!(
+            Color::new_rgba(31, 41, 51, 200).to_egui_premultiplied(),
+            egui::Color32::from_rgba_premultiplied(31, 41, 51, 200)
+        );
+    }
+
+    #[test]
+    fn test_color_without_alpha_deserializes_opaque() {
+        let color: Color = serde_json::from_str(r#"{"r":1,"g":2,"b":3}"#).unwrap();
+        assert_eq!(color, Color::new(1, 2, 3));
+    }
+
+    const SAMPLE_THEME: &str = r##"
+name = "Solarized"
+
+[palette]
+bg_window = "#002b36"
+bg_toolbar = "#073642"
+bg_tab_active = "#002b36"
+bg_tab_inactive = "#073642"
+text_primary = "#fdf6e3"
+text_secondary = "#93a1a1"
+accent = "#268bd2"
+accent_hover = { r = 38, g = 139, b = 210, a = 200 }
+success = "#859900"
+warning = "#b58900"
+error = "#dc322f"
+border_subtle = "#586e75"
+
+[typography]
+base_size = 15
+tab_size = 13
+menu_size = 13
+font_family = "monospace"
+"##;
+
+    #[test]
+    fn test_load_theme_from_toml() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("solarized.toml");
+        std::fs::write(&path, SAMPLE_THEME).unwrap();
+
+        let theme = Theme::from_file(&path).unwrap();
+        assert_eq!(theme.name(), "Solarized");
+        assert_eq!(theme.palette().bg_window, Color::new(0, 43, 54));
+        assert_eq!(theme.palette().accent, Color::new(38, 139, 210));
+        assert_eq!(
+            theme.palette().accent_hover(),
+            Color::new_rgba(38, 139, 210, 200)
+        );
+        assert_eq!(theme.font_size(), 15);
+        assert_eq!(theme.radii().panel, Radii::default().panel);
+    }
+
+    #[test]
+    fn test_accent_hover_derived_when_unset() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("solarized.toml");
+        let without_hover =
+            SAMPLE_THEME.replace("accent_hover = { r = 38, g = 139, b = 210, a = 200 }\n", "");
+        std::fs::write(&path, without_hover).unwrap();
+
+        let palette = Theme::from_file(&path).unwrap().palette().clone();
+        assert_eq!(palette.accent_hover, None);
+        assert_eq!(palette.accent_hover(), palette.accent.lighten(0.2));
+
+        let light = ColorPalette {
+            accent_hover: None,
+            ..ColorPalette::light()
+        };
+        assert_eq!(light.accent_hover(), light.accent.darken(0.2));
+        assert_eq!(
+            ColorPalette::dark().accent_hover(),
+            Color::new(96, 165, 250)
+        );
+    }
+
+    #[test]
+    fn test_color_blending() {
+        let accent = Color::new(59, 130, 246);
+        assert_eq!(accent.lighten(0.0), accent);
+        assert_eq!(accent.darken(0.0), accent);
+        assert_eq!(accent.lighten(1.0), Color::new(255, 255, 255));
+        assert_eq!(accent.darken(1.0), Color::new(0, 0, 0));
+        assert_eq!(accent.darken(5.0), Color::new(0, 0, 0));
+        assert_eq!(accent.lighten(-1.0), accent);
+        assert_eq!(accent.with_alpha(100).darken(0.5).a, 100);
+
+        let black = Color::new(0, 0, 0);
+        let white = Color::new(255, 255, 255);
+        assert_eq!(black.mix(white, 0.5), Color::new(128, 128, 128));
+        assert_eq!(
+            Color::new(10, 20, 30).mix(Color::new(30, 40, 50), 0.5),
+            Color::new(20, 30, 40)
+        );
+        assert_eq!(black.mix(white, 0.0), black);
+        assert_eq!(black.mix(white, 1.0), white);
+        assert_eq!(black.mix(white, f32::NAN), black);
+    }
+
+    #[test]
+    fn test_theme_save_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        for file in ["light.toml", "light.json"] {
+            let path = dir.path().join(file);
+            let theme = Theme::new("Light", ColorPalette::light());
+            theme.save(&path).unwrap();
+
+            let loaded = Theme::from_file(&path).unwrap();
+            assert_eq!(loaded.name(), "Light");
+            assert_eq!(loaded.palette().accent, ColorPalette::light().accent);
+        }
+    }
+
+    #[test]
+    fn test_invalid_theme_files_rejected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let write = |name: &str, contents: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, contents).unwrap();
+            path
+        };
+
+        let missing_color = write(
+            "missing.toml",
+            &SAMPLE_THEME.replace("error = \"#dc322f\"\n", ""),
+        );
+        assert!(Theme::from_file(&missing_color).is_err());
+
+        let channel_out_of_range =
+            write("channel.toml", &SAMPLE_THEME.replace("a = 200", "a = 300"));
+        assert!(Theme::from_file(&channel_out_of_range).is_err());
+
+        let bad_hex = write("hex.toml", &SAMPLE_THEME.replace("#002b36", "#002b3z"));
+        assert!(Theme::from_file(&bad_hex).is_err());
+
+        let font_out_of_range = write(
+            "font.toml",
+            &SAMPLE_THEME.replace("base_size = 15", "base_size = 500"),
+        );
+        assert!(Theme::from_file(&font_out_of_range).is_err());
+    }
+
+    #[test]
+    fn test_theme_registry() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("solarized.toml"), SAMPLE_THEME).unwrap();
+        std::fs::write(dir.path().join("broken.toml"), "name = \"Broken\"").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a theme").unwrap();
+
+        let mut registry = ThemeRegistry::new();
+        assert_eq!(registry.names(), vec!["Dark", "Light"]);
+
+        assert_eq!(registry.load_dir(dir.path()).unwrap(), 1);
+        assert_eq!(registry.names(), vec!["Dark", "Light", "Solarized"]);
+        assert_eq!(
+            registry.get("Solarized").unwrap().palette().text_primary,
+            Color::new(253, 246, 227)
+        );
+        assert!(registry.get("Broken").is_none());
+        assert_eq!(registry.load_dir(&dir.path().join("missing")).unwrap(), 0);
     }
 
     #[test]
@@ -270,4 +1044,112 @@ mod tests {
         assert_eq!(theme.name(), "Light");
         assert_eq!(theme.palette().bg_window.r, 249);
     }
+
+    #[test]
+    fn test_build_visuals() {
+        let dark = build_visuals(&Theme::default());
+        assert!(dark.visuals.dark_mode);
+        assert_eq!(dark.visuals.panel_fill, egui::Color32::from_rgb(17, 24, 39));
+        assert_eq!(
+            dark.visuals.widgets.active.bg_fill,
+            egui::Color32::from_rgb(59, 130, 246)
+        );
+        assert_eq!(
+            dark.visuals.widgets.inactive.fg_stroke.color,
+            egui::Color32::from_rgb(249, 250, 251)
+        );
+
+        let light = build_visuals(&Theme::new("Light", ColorPalette::light()));
+        assert!(!light.visuals.dark_mode);
+        assert_eq!(
+            light.visuals.panel_fill,
+            egui::Color32::from_rgb(249, 250, 251)
+        );
+        assert_eq!(
+            light.visuals.widgets.active.bg_fill,
+            egui::Color32::from_rgb(37, 99, 235)
+        );
+        assert_eq!(
+            light.visuals.widgets.inactive.fg_stroke.color,
+            egui::Color32::from_rgb(17, 24, 39)
+        );
+    }
+
+    #[test]
+    fn test_text_styles_scale_from_base_size() {
+        for base_size in [10.0, 14.0, 20.0] {
+            let styles = text_styles(base_size, egui::FontFamily::Proportional);
+            let body = styles[&egui::TextStyle::Body].size;
+            assert_eq!(body, base_size);
+            assert_eq!(styles[&egui::TextStyle::Heading].size / body, HEADING_SCALE);
+            assert_eq!(styles[&egui::TextStyle::Small].size / body, SMALL_SCALE);
+            assert_eq!(styles[&egui::TextStyle::Button].size, body);
+            assert_eq!(
+                styles[&egui::TextStyle::Monospace].family,
+                egui::FontFamily::Monospace
+            );
+        }
+        assert_eq!(text_styles(14.0, egui::FontFamily::Proportional).len(), 5);
+
+        let styles = text_styles(14.0, egui::FontFamily::Monospace);
+        assert_eq!(
+            styles[&egui::TextStyle::Body].family,
+            egui::FontFamily::Monospace
+        );
+    }
+
+    #[test]
+    fn test_body_text_size_follows_theme_base_size() {
+        assert_eq!(body_text_size(DEFAULT_BASE_SIZE, 14), 14.0);
+        assert_eq!(body_text_size(DEFAULT_BASE_SIZE, 20), 20.0);
+        assert_eq!(body_text_size(21, 14), 21.0);
+        assert_eq!(body_text_size(21, 20), 30.0);
+    }
+
+    #[test]
+    fn test_font_definitions_skip_missing_fonts() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let not_a_font = dir.path().join("fake.ttf");
+        std::fs::write(&not_a_font, "not a font").unwrap();
+        let family = format!(
+            "'{}', {}, sans-serif",
+            not_a_font.display(),
+            dir.path().join("missing.otf").display()
+        );
+
+        let defaults = egui::FontDefinitions::default();
+        for family in [Typography::default().font_family, family] {
+            let (fonts, text_family) = font_definitions(&family);
+            assert_eq!(fonts.families, defaults.families);
+            assert_eq!(fonts.font_data.len(), defaults.font_data.len());
+            assert_eq!(text_family, egui::FontFamily::Proportional);
+        }
+    }
+
+    #[test]
+    fn test_font_definitions_resolve_generic_families() {
+        let (_, family) = font_definitions("'No Such Font', monospace, sans-serif");
+        assert_eq!(family, egui::FontFamily::Monospace);
+
+        let (_, family) = font_definitions("No Such Font, serif, monospace");
+        assert_eq!(family, egui::FontFamily::Proportional);
+    }
+
+    #[test]
+    fn test_font_file_finds_installed_fonts_by_name() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let font_dir = dir.path().join("truetype").join("fira");
+        std::fs::create_dir_all(&font_dir).unwrap();
+        let font = font_dir.join("FiraSans-Regular.ttf");
+        std::fs::write(&font, b"true").unwrap();
+        let font_dirs = vec![dir.path().to_path_buf()];
+
+        assert_eq!(font_file("Fira Sans", &font_dirs), Some(font.clone()));
+        assert_eq!(font_file("fira-sans", &font_dirs), Some(font));
+        assert_eq!(font_file("Fira Mono", &font_dirs), None);
+        assert_eq!(
+            font_file("/fonts/Custom.otf", &[]),
+            Some(PathBuf::from("/fonts/Custom.otf"))
+        );
+    }
 }
@@ -1,8 +1,27 @@
 //! Window management module
 
-use crate::tabs::TabManager;
+use crate::autocomplete::{self, SuggestionKey, SuggestionOutcome};
+use crate::find::FindState;
+use crate::keymap::{BrowserAction, KeyBinding};
+use crate::palette::{CommandPalette, PaletteItem, PaletteTarget};
+use crate::reader::Article;
+use crate::tabs::{NavigationError, TabManager};
 use anyhow::Result;
 use eframe::egui;
+use horizon_engine::renderer::{Renderer, RendererBackend, RendererConfig};
+use horizon_networking::client::HttpClient;
+use horizon_networking::tracking::Blocklist;
+use horizon_networking::NetworkManager;
+use horizon_sandbox::policy::SecurityPolicy;
+use horizon_storage::bookmarks::{Bookmark, BookmarkManager};
+use horizon_storage::cookies::CookieStore;
+use horizon_storage::downloads::{Download, DownloadManager, DownloadState};
+use horizon_storage::history::{self, ClearWindow, HistoryStore};
+use horizon_storage::userdata::UserDataManager;
+use horizon_storage::zoom::{ZoomLevels, DEFAULT_ZOOM};
+use horizon_url::ParsedUrl;
+use std::collections::HashMap;
+use std::time::SystemTime;
 
 /// Window configuration
 #[derive(Debug, Clone)]
@@ -12,6 +31,15 @@ pub struct WindowConfig {
     pub height: f32,
     pub resizable: bool,
     pub decorated: bool,
+    /// Policy deciding which URLs pages may load
+    pub security_policy: SecurityPolicy,
+    /// Whether the window is private, leaving no history, cookies, or saved
+    /// passwords behind
+    pub private: bool,
+    /// Graphics backend to ask eframe for
+    pub renderer_backend: RendererBackend,
+    /// Themes the window can be drawn with
+    pub themes: crate::theme::ThemeRegistry,
 }
 
 impl Default for WindowConfig {
@@ -22,6 +50,10 @@ impl Default for WindowConfig {
             height: 720.0,
             resizable: true,
             decorated: true,
+            security_policy: SecurityPolicy::default(),
+            private: false,
+            renderer_backend: RendererBackend::Auto,
+            themes: crate::theme::ThemeRegistry::new(),
         }
     }
 }
@@ -39,93 +71,2044 @@ impl BrowserWindow {
 
     /// Run the browser window (blocking)
     pub fn run(self) -> Result<()> {
+        let viewport = egui::ViewportBuilder::default()
+            .with_title(self.config.title.clone())
+            .with_inner_size([self.config.width, self.config.height])
+            .with_resizable(self.config.resizable)
+            .with_decorations(self.config.decorated);
         let options = eframe::NativeOptions {
-            viewport: egui::ViewportBuilder::default()
-                .with_inner_size([self.config.width, self.config.height])
-                .with_resizable(self.config.resizable)
-                .with_decorations(self.config.decorated),
+            viewport: viewport.clone(),
+            hardware_acceleration: hardware_acceleration(self.config.renderer_backend),
             ..Default::default()
         };
+        let security_policy = self.config.security_policy.clone();
+        let private = self.config.private;
+        let themes = self.config.themes.clone();
 
         eframe::run_native(
             &self.config.title,
             options,
-            Box::new(|_cc| Ok(Box::new(BrowserApp::new()))),
+            Box::new(|cc| {
+                // Keyboard zoom scales the page, not egui's whole UI
+                cc.egui_ctx.options_mut(|o| o.zoom_with_keyboard = false);
+                let mut app = BrowserApp::new(private)
+                    .with_security_policy(security_policy)
+                    .with_window_viewport(viewport)
+                    .with_themes(themes);
+                match cc.gl.as_deref().map(gl_backend) {
+                    Some(backend) => app.renderer.initialize_on(backend),
+                    None => {
+                        if let Err(e) = app.renderer.initialize() {
+                            tracing::error!("Failed to initialize renderer: {}", e);
+                        }
+                    }
+                }
+                Ok(Box::new(app))
+            }),
         )
         .map_err(|e| anyhow::anyhow!("Failed to run window: {}", e))
     }
-}
+}
+
+/// Map a renderer backend onto the hardware acceleration eframe asks for
+fn hardware_acceleration(backend: RendererBackend) -> eframe::HardwareAcceleration {
+    match backend {
+        RendererBackend::Auto => eframe::HardwareAcceleration::Preferred,
+        RendererBackend::Hardware => eframe::HardwareAcceleration::Required,
+        RendererBackend::Software => eframe::HardwareAcceleration::Off,
+    }
+}
+
+/// Get the backend an OpenGL context renders with
+fn gl_backend(gl: &eframe::glow::Context) -> RendererBackend {
+    use eframe::glow::HasContext;
+
+    // SAFETY: eframe made the context current before creating the app
+    let name = unsafe { gl.get_parameter_string(eframe::glow::RENDERER) };
+    tracing::debug!("OpenGL renderer: {}", name);
+    if is_software_gl_renderer(&name) {
+        RendererBackend::Software
+    } else {
+        RendererBackend::Hardware
+    }
+}
+
+/// Lowercase parts of the OpenGL renderer names of software rasterizers
+const SOFTWARE_GL_RENDERERS: [&str; 6] = [
+    "llvmpipe",
+    "softpipe",
+    "swrast",
+    "swiftshader",
+    "software",
+    "gdi generic",
+];
+
+/// Check whether an OpenGL renderer name belongs to a software rasterizer
+fn is_software_gl_renderer(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SOFTWARE_GL_RENDERERS
+        .iter()
+        .any(|software| name.contains(software))
+}
+
+/// Simulated content shown on web pages until a web engine renders them
+const WEB_PAGE_PLACEHOLDER_TEXT: [&str; 6] = [
+    "Simulated Web Content",
+    "Lorem ipsum dolor sit amet, consectetur adipiscing elit.",
+    "Sed do eiusmod tempor incididunt ut labore et dolore magna aliqua.",
+    "• List item 1",
+    "• List item 2",
+    "• List item 3",
+];
+
+/// Color mixed into the tab bar of private windows
+const PRIVATE_TINT: crate::theme::Color = crate::theme::Color::new(139, 92, 246);
+
+/// Widest the reader view's text column gets, in points
+const READER_MAX_WIDTH: f32 = 680.0;
+
+// Constants for tab title display
+const MAX_TAB_TITLE_LENGTH: usize = 25;
+const TRUNCATE_AT: usize = 22;
+
+/// Maximum number of address bar suggestions shown in the dropdown
+const MAX_SUGGESTIONS: usize = 6;
+
+/// Hover delay used when hover-to-switch is first enabled
+const DEFAULT_HOVER_SWITCH_MS: u64 = 500;
+
+/// Truncate a tab title so it fits in the tab strip
+fn truncate_tab_title(title: &str) -> String {
+    if title.chars().count() > MAX_TAB_TITLE_LENGTH {
+        let truncated: String = title.chars().take(TRUNCATE_AT).collect();
+        format!("{}...", truncated)
+    } else {
+        title.to_string()
+    }
+}
+
+/// Count how many bookmarks fit on the bookmarks bar
+///
+/// `widths` are the bookmarks' widths in bar order, with `spacing` between
+/// neighbors. When they don't all fit, room is kept for the overflow button.
+fn bookmarks_bar_fit(widths: &[f32], available: f32, spacing: f32, overflow_width: f32) -> usize {
+    let total = widths.iter().sum::<f32>() + spacing * widths.len().saturating_sub(1) as f32;
+    if total <= available {
+        return widths.len();
+    }
+
+    let limit = available - overflow_width - spacing;
+    let mut used = 0.0;
+    let mut count = 0;
+    for width in widths {
+        let next = if count == 0 {
+            *width
+        } else {
+            used + spacing + width
+        };
+        if next > limit {
+            break;
+        }
+        used = next;
+        count += 1;
+    }
+    count
+}
+
+/// How secure the connection to a page is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SecurityLevel {
+    /// Loaded over HTTPS
+    Secure,
+    /// Loaded over plain HTTP
+    Insecure,
+    /// A built-in `about:` page
+    Internal,
+    /// A file on this computer
+    Local,
+    /// Any other or unparseable URL
+    Unknown,
+}
+
+impl SecurityLevel {
+    /// Get the icon shown for the level
+    fn icon(self) -> &'static str {
+        match self {
+            SecurityLevel::Secure => "🔒",
+            SecurityLevel::Insecure => "⚠",
+            SecurityLevel::Internal => "ℹ",
+            SecurityLevel::Local | SecurityLevel::Unknown => "🌐",
+        }
+    }
+
+    /// Get the description shown for the level
+    fn description(self) -> &'static str {
+        match self {
+            SecurityLevel::Secure => "Secure connection (HTTPS)",
+            SecurityLevel::Insecure => "Not secure (HTTP)",
+            SecurityLevel::Internal => "Internal page",
+            SecurityLevel::Local => "Local file",
+            SecurityLevel::Unknown => "Unknown connection",
+        }
+    }
+
+    /// Get the color the level's icon is drawn in
+    fn color(self, palette: &crate::theme::ColorPalette) -> crate::theme::Color {
+        match self {
+            SecurityLevel::Secure => palette.success,
+            SecurityLevel::Insecure => palette.warning,
+            SecurityLevel::Internal => palette.accent,
+            SecurityLevel::Local | SecurityLevel::Unknown => palette.text_secondary,
+        }
+    }
+}
+
+/// Classify how secure the connection to a URL is, by its scheme
+fn classify_security(url: &str) -> SecurityLevel {
+    let Ok(parsed) = ParsedUrl::parse(url) else {
+        return SecurityLevel::Unknown;
+    };
+    match parsed.scheme() {
+        "https" => SecurityLevel::Secure,
+        "http" => SecurityLevel::Insecure,
+        "about" => SecurityLevel::Internal,
+        "file" => SecurityLevel::Local,
+        _ => SecurityLevel::Unknown,
+    }
+}
+
+/// What the status bar at the bottom of the window shows
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StatusBarState {
+    /// Target of the link under the pointer
+    hovered_url: Option<String>,
+    /// Link hovered in the page content, which is drawn after the status
+    /// bar and so is shown on the next frame
+    page_hovered_url: Option<String>,
+    /// Security of the active page
+    security: SecurityLevel,
+}
+
+impl StatusBarState {
+    /// Check whether there is nothing to show, so the bar is hidden
+    fn is_empty(&self) -> bool {
+        self.hovered_url.is_none()
+    }
+
+    /// Start a frame, carrying over the link hovered in the page last frame
+    fn begin_frame(&mut self) {
+        self.hovered_url = self.page_hovered_url.take();
+    }
+
+    /// Record the link hovered in the page content this frame, returning
+    /// whether the bar must be redrawn to show it
+    fn hover_in_page(&mut self, url: Option<String>) -> bool {
+        let changed = url != self.hovered_url;
+        self.page_hovered_url = url;
+        changed
+    }
+}
+
+impl Default for StatusBarState {
+    fn default() -> Self {
+        Self {
+            hovered_url: None,
+            page_hovered_url: None,
+            security: SecurityLevel::Unknown,
+        }
+    }
+}
+
+/// Action chosen from a tab's context menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TabMenuAction {
+    Reload,
+    Duplicate,
+    TogglePin,
+    ToggleMute,
+    Close,
+    CloseOthers,
+    CloseToRight,
+    MoveToNewWindow,
+}
+
+/// Render a tab's context menu, returning the chosen action
+fn render_tab_menu(
+    ui: &mut egui::Ui,
+    tab: &crate::tabs::Tab,
+    index: usize,
+    tab_count: usize,
+) -> Option<TabMenuAction> {
+    let pin_label = if tab.is_pinned { "Unpin" } else { "Pin" };
+    let mute_label = if tab.is_muted {
+        "Unmute Tab"
+    } else {
+        "Mute Tab"
+    };
+    let items = [
+        (TabMenuAction::Reload, "Reload", true),
+        (TabMenuAction::Duplicate, "Duplicate", true),
+        (TabMenuAction::TogglePin, pin_label, true),
+        (TabMenuAction::ToggleMute, mute_label, true),
+        (TabMenuAction::Close, "Close", tab_count > 1),
+        (TabMenuAction::CloseOthers, "Close Others", tab_count > 1),
+        (
+            TabMenuAction::CloseToRight,
+            "Close to the Right",
+            index + 1 < tab_count,
+        ),
+        (
+            TabMenuAction::MoveToNewWindow,
+            "Move to New Window",
+            tab_count > 1,
+        ),
+    ];
+
+    let mut chosen = None;
+    for (action, label, enabled) in items {
+        // Separate reloading and pinning from closing and moving
+        if action == TabMenuAction::Close {
+            ui.separator();
+        }
+        if ui.add_enabled(enabled, egui::Button::new(label)).clicked() {
+            chosen = Some(action);
+            ui.close_menu();
+        }
+    }
+    chosen
+}
+
+/// Action chosen from the page's context menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PageMenuAction {
+    Back,
+    Forward,
+    Reload,
+    CopyUrl,
+    ViewSource,
+}
+
+/// Get the items of the page context menu for a tab, with whether each is
+/// enabled
+fn page_menu_items(tab: &crate::tabs::Tab) -> [(PageMenuAction, &'static str, bool); 5] {
+    let is_web = ParsedUrl::parse(&tab.url).is_ok_and(|url| url.is_web());
+    [
+        (PageMenuAction::Back, "Back", tab.can_go_back()),
+        (PageMenuAction::Forward, "Forward", tab.can_go_forward()),
+        (PageMenuAction::Reload, "Reload", true),
+        (PageMenuAction::CopyUrl, "Copy Page URL", true),
+        (PageMenuAction::ViewSource, "View Page Source", is_web),
+    ]
+}
+
+/// Render the page context menu, returning the chosen action
+fn render_page_menu(ui: &mut egui::Ui, tab: &crate::tabs::Tab) -> Option<PageMenuAction> {
+    let mut chosen = None;
+    for (action, label, enabled) in page_menu_items(tab) {
+        // Separate navigation from the page's own actions
+        if action == PageMenuAction::CopyUrl {
+            ui.separator();
+        }
+        if ui.add_enabled(enabled, egui::Button::new(label)).clicked() {
+            chosen = Some(action);
+            ui.close_menu();
+        }
+    }
+    chosen
+}
+
+/// Get the text copied for a page's URL
+///
+/// Web URLs are copied in their normalized form, e.g. with a lowercase host
+/// and a path; other URLs are copied as shown.
+fn url_for_clipboard(url: &str) -> String {
+    match ParsedUrl::parse(url) {
+        Ok(parsed) if parsed.is_web() => parsed.as_str().to_string(),
+        _ => url.trim().to_string(),
+    }
+}
+
+/// Start of the URL of a page source view, followed by the encoded page URL
+const SOURCE_URL_PREFIX: &str = "about:source?url=";
+
+/// Get the URL of the view of a page's source
+fn source_url(url: &str) -> String {
+    format!("{}{}", SOURCE_URL_PREFIX, urlencoding::encode(url))
+}
+
+/// Get the URL of the page a source view shows, if `url` is a source view
+fn source_target(url: &str) -> Option<String> {
+    let encoded = url.strip_prefix(SOURCE_URL_PREFIX)?;
+    let decoded = urlencoding::decode(encoded).ok()?;
+    Some(decoded.into_owned())
+}
+
+/// How often the toolbar redraws while downloads are active
+const DOWNLOAD_REFRESH_MS: u64 = 250;
+
+/// Action requested from a row of the downloads popover
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DownloadAction {
+    Pause,
+    Resume,
+    Cancel,
+}
+
+/// Format a byte count for display, e.g. `1.5 MB`
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Describe a download's state and progress for the downloads popover
+fn download_status_text(download: &Download) -> String {
+    let progress = match download.total {
+        Some(total) => format!(
+            "{} of {}",
+            format_bytes(download.bytes_done),
+            format_bytes(total)
+        ),
+        None => format_bytes(download.bytes_done),
+    };
+
+    match download.state {
+        DownloadState::Queued => "Waiting to start".to_string(),
+        DownloadState::Active => progress,
+        DownloadState::Paused => format!("Paused, {}", progress),
+        DownloadState::Completed => format!("Done, {}", format_bytes(download.bytes_done)),
+        DownloadState::Failed => match &download.error {
+            Some(error) => format!("Failed: {}", error),
+            None => "Failed".to_string(),
+        },
+    }
+}
+
+/// Render a row for each download, returning the action a row's button
+/// asked for
+fn render_download_rows(
+    ui: &mut egui::Ui,
+    downloads: &[Download],
+    palette: &crate::theme::ColorPalette,
+) -> Option<(String, DownloadAction)> {
+    let mut action = None;
+    ui.set_min_width(300.0);
+
+    for (index, download) in downloads.iter().enumerate() {
+        ui.horizontal(|ui| {
+            ui.vertical(|ui| {
+                ui.label(
+                    egui::RichText::new(&download.filename)
+                        .strong()
+                        .color(palette.text_primary.to_egui()),
+                )
+                .on_hover_text(&download.url);
+                ui.label(
+                    egui::RichText::new(download_status_text(download))
+                        .size(11.0)
+                        .color(palette.text_secondary.to_egui()),
+                );
+                let unfinished =
+                    download.state.is_active() || download.state == DownloadState::Paused;
+                if let Some(total) = download.total.filter(|total| unfinished && *total > 0) {
+                    ui.add(
+                        egui::ProgressBar::new(download.bytes_done as f32 / total as f32)
+                            .desired_width(220.0),
+                    );
+                }
+            });
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let cancel_tooltip = if download.state == DownloadState::Completed {
+                    "Remove from list"
+                } else {
+                    "Cancel download"
+                };
+                if ui.small_button("✕").on_hover_text(cancel_tooltip).clicked() {
+                    action = Some((download.id.clone(), DownloadAction::Cancel));
+                }
+
+                match download.state {
+                    DownloadState::Queued | DownloadState::Active => {
+                        if ui.small_button("⏸").on_hover_text("Pause").clicked() {
+                            action = Some((download.id.clone(), DownloadAction::Pause));
+                        }
+                    }
+                    DownloadState::Paused | DownloadState::Failed => {
+                        if ui.small_button("▶").on_hover_text("Resume").clicked() {
+                            action = Some((download.id.clone(), DownloadAction::Resume));
+                        }
+                    }
+                    DownloadState::Completed => {}
+                }
+            });
+        });
+
+        if index + 1 < downloads.len() {
+            ui.separator();
+        }
+    }
+
+    action
+}
+
+/// What a row of a bookmark or history list asks for
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LinkRowAction {
+    /// Open the row's URL in the active tab
+    Open(String),
+    /// Remove the row's URL from the list
+    Remove(String),
+}
+
+/// Render a link to a URL with a button removing it, noting the URL in
+/// `hovered_url` while the link is hovered
+fn render_link_row(
+    ui: &mut egui::Ui,
+    title: &str,
+    url: &str,
+    remove_tooltip: &str,
+    palette: &crate::theme::ColorPalette,
+    hovered_url: &mut Option<String>,
+) -> Option<LinkRowAction> {
+    let title = if title.is_empty() { url } else { title };
+    let mut action = None;
+
+    ui.horizontal(|ui| {
+        let link = ui
+            .link(egui::RichText::new(title).color(palette.text_primary.to_egui()))
+            .on_hover_text(url);
+        if link.hovered() {
+            *hovered_url = Some(url.to_string());
+        }
+        if link.clicked() {
+            action = Some(LinkRowAction::Open(url.to_string()));
+        }
+        if ui.small_button("✕").on_hover_text(remove_tooltip).clicked() {
+            action = Some(LinkRowAction::Remove(url.to_string()));
+        }
+    });
+
+    action
+}
+
+/// Render bookmarks grouped by folder, returning the action a row asked for
+fn render_bookmark_list(
+    ui: &mut egui::Ui,
+    bookmarks: &BookmarkManager,
+    palette: &crate::theme::ColorPalette,
+    hovered_url: &mut Option<String>,
+) -> Option<LinkRowAction> {
+    let groups = bookmarks.grouped_by_folder();
+    if groups.is_empty() {
+        ui.label(
+            egui::RichText::new("No bookmarks yet. Add one with ☆.")
+                .size(13.0)
+                .color(palette.text_secondary.to_egui()),
+        );
+    }
+
+    let mut action = None;
+    for (folder, bookmarks) in groups {
+        if let Some(folder) = folder {
+            ui.add_space(8.0);
+            ui.label(
+                egui::RichText::new(format!("📁 {}", folder))
+                    .size(14.0)
+                    .strong()
+                    .color(palette.text_secondary.to_egui()),
+            );
+        }
+
+        for bookmark in bookmarks {
+            let (title, url) = (&bookmark.title, &bookmark.url);
+            let tooltip = "Remove bookmark";
+            if let Some(row) = render_link_row(ui, title, url, tooltip, palette, hovered_url) {
+                action = Some(row);
+            }
+        }
+    }
+
+    action
+}
+
+/// Get the name and URL template to show in the custom search engine fields,
+/// both empty unless the engine is a custom one
+fn custom_engine_fields(search_engine: &crate::settings::SearchEngine) -> (String, String) {
+    match search_engine.url_template() {
+        Some(template) => (search_engine.name().to_string(), template.to_string()),
+        None => (String::new(), String::new()),
+    }
+}
+
+/// Get the history entries matching a search query, or every entry if the
+/// query is blank
+fn matching_history(history: &HistoryStore, query: &str) -> Vec<history::HistoryEntry> {
+    if query.trim().is_empty() {
+        history.recent(usize::MAX)
+    } else {
+        history.search(query.trim())
+    }
+}
+
+/// History entries matching a search, grouped by day and kept between frames
+#[derive(Debug, Default)]
+struct HistoryList {
+    /// History revision and query the days were built from
+    built_from: Option<(u64, String)>,
+    /// Matching entries grouped by day, most recent first
+    days: Vec<history::HistoryDay>,
+}
+
+impl HistoryList {
+    /// Get the days matching a query, regrouping only if the history or the
+    /// query changed since the last call
+    fn days(&mut self, history: &HistoryStore, query: &str) -> &[history::HistoryDay] {
+        let up_to_date = self
+            .built_from
+            .as_ref()
+            .is_some_and(|(revision, built_query)| {
+                *revision == history.revision() && built_query == query
+            });
+        if !up_to_date {
+            self.days = history::group_by_day(matching_history(history, query));
+            self.built_from = Some((history.revision(), query.to_string()));
+        }
+        &self.days
+    }
+}
+
+/// Render the history search box and clear buttons, returning the time
+/// window to clear if one was clicked
+fn render_history_controls(
+    ui: &mut egui::Ui,
+    query: &mut String,
+    palette: &crate::theme::ColorPalette,
+) -> Option<ClearWindow> {
+    let mut clear = None;
+    ui.add(
+        egui::TextEdit::singleline(query)
+            .hint_text("Search history")
+            .desired_width(f32::INFINITY),
+    );
+    ui.horizontal(|ui| {
+        ui.label(
+            egui::RichText::new("Clear:")
+                .size(13.0)
+                .color(palette.text_secondary.to_egui()),
+        );
+        for window in ClearWindow::all() {
+            if ui.small_button(window.label()).clicked() {
+                clear = Some(*window);
+            }
+        }
+    });
+    clear
+}
+
+/// Render history entries grouped by day, most recent first, returning the
+/// action a row asked for
+fn render_history_list(
+    ui: &mut egui::Ui,
+    days: &[history::HistoryDay],
+    palette: &crate::theme::ColorPalette,
+    hovered_url: &mut Option<String>,
+) -> Option<LinkRowAction> {
+    if days.is_empty() {
+        ui.label(
+            egui::RichText::new("No history found.")
+                .size(13.0)
+                .color(palette.text_secondary.to_egui()),
+        );
+    }
+
+    let mut action = None;
+    let today = history::day_of(SystemTime::now());
+    for day in days {
+        let label = match today.checked_sub(day.day) {
+            Some(0) => "Today".to_string(),
+            Some(1) => "Yesterday".to_string(),
+            _ => history::format_day(day.day),
+        };
+        ui.add_space(8.0);
+        ui.label(
+            egui::RichText::new(label)
+                .size(14.0)
+                .strong()
+                .color(palette.text_secondary.to_egui()),
+        );
+
+        for entry in &day.entries {
+            let (title, url) = (&entry.title, &entry.url);
+            let tooltip = "Delete from history";
+            if let Some(row) = render_link_row(ui, title, url, tooltip, palette, hovered_url) {
+                action = Some(row);
+            }
+        }
+    }
+
+    action
+}
+
+/// A built-in page with an `about:` URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum InternalPage {
+    Home,
+    Settings,
+    Blank,
+    Downloads,
+    History,
+    Bookmarks,
+    /// The source of the page at a URL
+    Source(String),
+    /// An `about:` page Horizon doesn't have, by name
+    Unknown(String),
+}
+
+impl InternalPage {
+    /// Parse an `about:` URL, or return `None` for any other URL
+    ///
+    /// Page names ignore case, and any query or fragment.
+    fn parse(url: &str) -> Option<Self> {
+        if let Some(target) = source_target(url) {
+            return Some(Self::Source(target));
+        }
+
+        let name = url.strip_prefix("about:")?;
+        let name = name.split(['?', '#']).next().unwrap_or_default();
+        Some(match name.to_ascii_lowercase().as_str() {
+            "home" => Self::Home,
+            "settings" => Self::Settings,
+            "blank" => Self::Blank,
+            "downloads" => Self::Downloads,
+            "history" => Self::History,
+            "bookmarks" => Self::Bookmarks,
+            _ => Self::Unknown(name.to_string()),
+        })
+    }
+}
+
+/// URLs of the built-in pages, offered as address bar suggestions
+const INTERNAL_PAGE_URLS: &[&str] = &[
+    "about:home",
+    "about:settings",
+    "about:downloads",
+    "about:history",
+    "about:bookmarks",
+    "about:blank",
+];
+
+/// Get address bar suggestions for typed text
+///
+/// History matches come first, followed by the built-in pages whose URL
+/// starts with the text. `about:` pages are kept out of history, so this is
+/// how they get suggested.
+fn address_suggestions(history: &HistoryStore, input: &str, limit: usize) -> Vec<String> {
+    let typed = input.trim().to_ascii_lowercase();
+    let mut suggestions = history.suggest(input, limit);
+    if !typed.is_empty() {
+        for url in INTERNAL_PAGE_URLS {
+            if url.starts_with(&typed) && !suggestions.iter().any(|s| s == url) {
+                suggestions.push(url.to_string());
+            }
+        }
+    }
+    suggestions.truncate(limit);
+    suggestions
+}
+
+/// Explain that an `about:` page doesn't exist
+fn unknown_page_message(name: &str) -> String {
+    format!("Horizon has no about:{} page.", name)
+}
+
+/// Render the banner at the top of an internal page
+fn render_page_banner(
+    ui: &mut egui::Ui,
+    palette: &crate::theme::ColorPalette,
+    title: &str,
+    subtitle: &str,
+) {
+    egui::Frame::none()
+        .fill(palette.bg_toolbar.to_egui())
+        .inner_margin(egui::Margin::symmetric(20.0, 16.0))
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.heading(
+                egui::RichText::new(title)
+                    .size(28.0)
+                    .strong()
+                    .color(palette.accent.to_egui()),
+            );
+            ui.add_space(4.0);
+            ui.label(
+                egui::RichText::new(subtitle)
+                    .size(14.0)
+                    .color(palette.text_secondary.to_egui()),
+            );
+        });
+    ui.add_space(10.0);
+}
+
+/// Frame around the body of an internal page, below its banner
+fn page_body_frame() -> egui::Frame {
+    egui::Frame::none().inner_margin(egui::Margin::symmetric(20.0, 0.0))
+}
+
+/// Turn address bar input into a URL to navigate to
+///
+/// `about:` pages, local file paths, and anything that looks like an
+/// address are opened directly; everything else is searched with the
+/// search engine.
+fn process_url_input(input: &str, search_engine: &crate::settings::SearchEngine) -> String {
+    let trimmed = input.trim();
+
+    // Check for special URLs
+    if trimmed.starts_with("about:") {
+        return trimmed.to_string();
+    }
+
+    // Absolute paths like /home/me/page.html or C:\docs\page.html
+    if let Some(url) = ParsedUrl::from_file_input(trimmed) {
+        return url.to_string();
+    }
+
+    // Explicit http(s) URLs and bare hosts like example.com or localhost:8080
+    if let Some(url) = ParsedUrl::from_user_input(trimmed) {
+        return url.to_string();
+    }
+
+    // Treat as search query
+    search_engine.search_url(trimmed)
+}
+
+/// Explain why the security policy blocked a URL
+fn blocked_message(url: &str) -> String {
+    if url.starts_with("file://") {
+        format!(
+            "Opening local files is disabled by the security policy: {}",
+            url
+        )
+    } else {
+        format!("{} was blocked by the security policy", url)
+    }
+}
+
+/// Get the character range that selects all of a text field's text
+fn select_all_range(text: &str) -> std::ops::Range<usize> {
+    0..text.chars().count()
+}
+
+/// Build the hover tooltip text for a tab: the full title followed by the URL
+fn tab_tooltip_text(title: &str, url: &str) -> String {
+    if title.is_empty() || title == "New Tab" || title == url {
+        url.to_string()
+    } else {
+        format!("{}\n{}", title, url)
+    }
+}
+
+/// Get the text of a web page's placeholder content
+fn web_page_text(url: &str) -> String {
+    format!(
+        "Web Page Preview\n📄 {}\n{}",
+        url,
+        WEB_PAGE_PLACEHOLDER_TEXT.join("\n")
+    )
+}
+
+/// Render a site's letter tile in place of its favicon
+fn render_letter_tile(ui: &mut egui::Ui, domain: &str, size: f32) {
+    let (letter, color) = crate::favicon::letter_tile(domain);
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(size, size), egui::Sense::hover());
+    ui.painter()
+        .rect_filled(rect, egui::Rounding::same(3.0), color.to_egui());
+    ui.painter().text(
+        rect.center(),
+        egui::Align2::CENTER_CENTER,
+        letter,
+        egui::FontId::proportional(size * 0.7),
+        egui::Color32::WHITE,
+    );
+}
+
+/// Get a tab's favicon texture, decoding and uploading it on first use
+fn favicon_texture(
+    textures: &mut HashMap<String, Option<egui::TextureHandle>>,
+    ctx: &egui::Context,
+    tab: &crate::tabs::Tab,
+) -> Option<egui::TextureHandle> {
+    let (url, bytes) = (tab.favicon_url.as_ref()?, tab.favicon.as_ref()?);
+    textures
+        .entry(url.clone())
+        .or_insert_with(|| {
+            crate::favicon::decode(bytes)
+                .map(|image| ctx.load_texture(url, image, egui::TextureOptions::LINEAR))
+        })
+        .clone()
+}
+
+/// Decide whether a hovered tab should become active
+///
+/// `delay_ms` is the configured hover delay (None when hover switching is
+/// disabled) and `hovered_secs` is how long the tab has been hovered.
+fn hover_switch_due(delay_ms: Option<u64>, hovered_secs: f64) -> bool {
+    delay_ms.is_some_and(|ms| hovered_secs * 1000.0 >= ms as f64)
+}
+
+/// The main browser application state
+struct BrowserApp {
+    /// Window being rendered, which is the main window outside of
+    /// `render_other_windows`
+    window: WindowState,
+    /// Windows other than the one being rendered
+    windows: Vec<WindowState>,
+    /// Number of windows opened, used to give each a unique viewport
+    windows_opened: u64,
+    /// Window to merge the other windows into once every window has rendered
+    merge_into: Option<egui::ViewportId>,
+    /// Size and decorations new windows are opened with
+    window_viewport: egui::ViewportBuilder,
+    /// Decoded tab favicons by favicon URL, `None` if decoding failed
+    favicon_textures: HashMap<String, Option<egui::TextureHandle>>,
+    /// Settings state
+    settings: crate::settings::SettingsUI,
+    /// Sidebar state
+    sidebar: crate::sidebar::Sidebar,
+    /// Saved bookmarks
+    bookmarks: BookmarkManager,
+    /// Browsing history
+    history: HistoryStore,
+    /// Cookie jar, kept only in memory in private windows
+    cookies: CookieStore,
+    /// Download manager, if an async runtime is available to run transfers
+    downloads: Option<DownloadManager>,
+    /// Network manager providing the client downloads are fetched with
+    network: NetworkManager,
+    /// Network settings the network manager was last configured with
+    applied_network: Option<crate::settings::NetworkSettings>,
+    /// Page zoom level of each site
+    zoom_levels: ZoomLevels,
+    /// Concrete theme in use, with `Auto` resolved against the system
+    active_theme: crate::settings::Theme,
+    /// Theme providing the window's colors
+    theme: crate::theme::Theme,
+    /// Built-in themes and those loaded from the themes folder
+    themes: crate::theme::ThemeRegistry,
+    /// Font family the loaded fonts were built from
+    font_family: String,
+    /// Family text is drawn in, resolved from `font_family`
+    text_family: egui::FontFamily,
+    /// Policy deciding which URLs pages may load
+    security_policy: SecurityPolicy,
+    /// Name typed for a custom search engine
+    custom_engine_name: String,
+    /// URL template typed for a custom search engine
+    custom_engine_template: String,
+    /// Why the typed custom search engine was rejected
+    custom_engine_error: Option<String>,
+    /// Problems that kept the settings from being saved
+    settings_errors: Vec<crate::settings::SettingsError>,
+    /// File settings are exported to and imported from
+    settings_transfer_path: String,
+    /// Outcome of the last settings export or import
+    settings_transfer_status: Option<Result<String, String>>,
+    /// Action waiting for a key press to become its shortcut
+    recording_shortcut: Option<BrowserAction>,
+    /// Renderer for the page content, timing each frame
+    renderer: Renderer,
+}
+
+/// State of a single browser window; each window has its own tabs
+struct WindowState {
+    /// Viewport the window is shown in
+    viewport_id: egui::ViewportId,
+    /// Tab manager
+    tab_manager: TabManager,
+    /// URL input buffer
+    url_input: String,
+    /// Text typed into the address bar before moving through suggestions
+    typed_input: String,
+    /// Address bar autocomplete suggestions
+    suggestions: Vec<String>,
+    /// Highlighted autocomplete suggestion
+    highlighted: Option<usize>,
+    /// Tab to close (deferred)
+    tab_to_close: Option<usize>,
+    /// Hovered inactive tab and the time the hover started
+    tab_hover: Option<(usize, f64)>,
+    /// Find bar state, while the find bar is open
+    find: Option<FindState>,
+    /// Whether to focus the find bar's input on the next frame
+    focus_find: bool,
+    /// Command palette state, while the palette is open
+    command_palette: Option<CommandPalette>,
+    /// Whether to focus and select the address bar on the next frame
+    focus_address_bar: bool,
+    /// Zoom levels of a private window, kept in memory so the sites zoomed
+    /// in it are never saved
+    private_zoom: Option<ZoomLevels>,
+    /// Status bar contents, rebuilt every frame
+    status: StatusBarState,
+    /// Search text in the history panel
+    history_query: String,
+    /// History shown in the history panel for `history_query`
+    history_list: HistoryList,
+}
+
+impl WindowState {
+    /// Create a window with a home tab, private if `private`
+    fn new(viewport_id: egui::ViewportId, private: bool) -> Self {
+        let tab_manager = if private {
+            TabManager::new_private()
+        } else {
+            TabManager::new()
+        };
+        let url_input = tab_manager.active_tab().url.clone();
+        // A fresh start page is ready for typing an address
+        let focus_address_bar = url_input == "about:home";
+
+        Self {
+            viewport_id,
+            tab_manager,
+            typed_input: url_input.clone(),
+            url_input,
+            suggestions: Vec::new(),
+            highlighted: None,
+            tab_to_close: None,
+            tab_hover: None,
+            find: None,
+            focus_find: false,
+            command_palette: None,
+            focus_address_bar,
+            private_zoom: private.then(ZoomLevels::new),
+            status: StatusBarState::default(),
+            history_query: String::new(),
+            history_list: HistoryList::default(),
+        }
+    }
+}
+
+impl BrowserApp {
+    /// Create a new browser application, in a private window if `private`
+    fn new(private: bool) -> Self {
+        let window = WindowState::new(egui::ViewportId::ROOT, private);
+        let settings = crate::settings::SettingsUI::load();
+        let sidebar = crate::sidebar::Sidebar::new();
+        let bookmarks = Self::load_bookmarks();
+        let history = Self::load_history();
+        let cookies = Self::load_cookies(&window.tab_manager);
+        let network = NetworkManager::default();
+        let downloads =
+            Self::load_downloads(&window.tab_manager, &settings, network.client().clone());
+        let zoom_levels = Self::load_zoom_levels();
+        let renderer = Renderer::new(RendererConfig {
+            backend: RendererBackend::from_hardware_acceleration(
+                settings.advanced.hardware_acceleration,
+            ),
+            ..RendererConfig::default()
+        });
+        let (custom_engine_name, custom_engine_template) =
+            custom_engine_fields(&settings.general.search_engine);
+
+        let mut app = Self {
+            window,
+            windows: Vec::new(),
+            windows_opened: 0,
+            merge_into: None,
+            window_viewport: egui::ViewportBuilder::default(),
+            favicon_textures: HashMap::new(),
+            settings,
+            sidebar,
+            bookmarks,
+            history,
+            cookies,
+            downloads,
+            network,
+            applied_network: None,
+            zoom_levels,
+            active_theme: crate::settings::Theme::Dark,
+            theme: crate::theme::Theme::default(),
+            themes: crate::theme::ThemeRegistry::new(),
+            font_family: String::new(),
+            text_family: egui::FontFamily::Proportional,
+            security_policy: SecurityPolicy::default(),
+            custom_engine_name,
+            custom_engine_template,
+            custom_engine_error: None,
+            settings_errors: Vec::new(),
+            settings_transfer_path: crate::settings::SettingsUI::data_dir()
+                .join("horizon-settings-export.toml")
+                .to_string_lossy()
+                .into_owned(),
+            settings_transfer_status: None,
+            recording_shortcut: None,
+            renderer,
+        };
+        app.apply_network_settings();
+        app
+    }
+
+    /// Apply the HTTPS-only, tracking protection, and proxy settings to the
+    /// network manager where they changed since it was last configured
+    ///
+    /// If the proxy settings fail to apply the previous proxy is kept.
+    fn apply_network_settings(&mut self) {
+        let privacy = &self.settings.privacy;
+        let mut changed = false;
+        if self.network.is_https_only() != privacy.https_only {
+            self.network.set_https_only(privacy.https_only);
+            changed = true;
+        }
+        if self.network.has_blocklist() != privacy.tracking_protection {
+            self.network
+                .set_blocklist(privacy.tracking_protection.then(Blocklist::bundled));
+            changed = true;
+        }
+        if self.applied_network.as_ref() != Some(&self.settings.network) {
+            let vpn_config = self.settings.network.vpn_config();
+            match self.network.apply_vpn_config(vpn_config.as_ref()) {
+                Ok(()) => {
+                    self.applied_network = Some(self.settings.network.clone());
+                    changed = true;
+                }
+                Err(e) => tracing::warn!("Failed to apply VPN settings: {}", e),
+            }
+        }
+        if changed {
+            if let Some(downloads) = self.downloads.as_mut() {
+                downloads.set_client(self.network.client().clone());
+            }
+        }
+    }
+
+    /// Set the policy deciding which URLs pages may load
+    fn with_security_policy(mut self, security_policy: SecurityPolicy) -> Self {
+        self.security_policy = security_policy;
+        self
+    }
+
+    /// Use a theme registry instead of only the built-in themes
+    fn with_themes(mut self, themes: crate::theme::ThemeRegistry) -> Self {
+        self.themes = themes;
+        self
+    }
+
+    /// Set the size and decorations new windows are opened with
+    fn with_window_viewport(mut self, window_viewport: egui::ViewportBuilder) -> Self {
+        self.window_viewport = window_viewport;
+        self
+    }
+
+    /// Open a new window with a home tab
+    ///
+    /// Windows share the cookie jar, so a new window is private if the
+    /// current one is.
+    fn open_window(&mut self) -> &mut WindowState {
+        self.windows_opened += 1;
+        let viewport_id = egui::ViewportId::from_hash_of(("browser_window", self.windows_opened));
+        let private = self.window.tab_manager.is_private();
+        self.windows.push(WindowState::new(viewport_id, private));
+        tracing::info!("Opened a new window");
+        self.windows.last_mut().expect("window was just opened")
+    }
+
+    /// Apply an action chosen from a tab's context menu
+    fn apply_tab_menu_action(&mut self, index: usize, action: TabMenuAction) {
+        let tabs = &mut self.window.tab_manager;
+        match action {
+            TabMenuAction::Reload => {
+                if let Some(tab) = tabs.tab_mut(index) {
+                    tab.reload();
+                }
+            }
+            TabMenuAction::Duplicate => {
+                tabs.duplicate_tab(index);
+            }
+            TabMenuAction::TogglePin => {
+                tabs.toggle_pin(index);
+            }
+            TabMenuAction::ToggleMute => {
+                if let Some(tab) = tabs.tab_mut(index) {
+                    tab.toggle_mute();
+                }
+            }
+            TabMenuAction::Close => self.window.tab_to_close = Some(index),
+            TabMenuAction::CloseOthers => {
+                let closed = tabs.close_others(index);
+                tracing::info!("Closed {} other tabs", closed);
+            }
+            TabMenuAction::CloseToRight => {
+                let closed = tabs.close_to_right(index);
+                tracing::info!("Closed {} tabs to the right", closed);
+            }
+            TabMenuAction::MoveToNewWindow => {
+                self.move_tab_to_new_window(index);
+                return;
+            }
+        }
+        self.window.url_input = self.window.tab_manager.active_tab().url.clone();
+    }
+
+    /// Move a tab of the current window into a new window of its own
+    fn move_tab_to_new_window(&mut self, index: usize) {
+        let Some(tab) = self.window.tab_manager.take_tab(index) else {
+            return;
+        };
+        self.window.url_input = self.window.tab_manager.active_tab().url.clone();
+
+        let window = self.open_window();
+        window.tab_manager.adopt_tab(tab);
+        // The moved tab replaces the new window's home tab
+        window.tab_manager.close_tab(0);
+        window.url_input = window.tab_manager.active_tab().url.clone();
+        window.typed_input = window.url_input.clone();
+        window.focus_address_bar = false;
+    }
+
+    /// Move the tabs of every other window into the window with `viewport_id`
+    ///
+    /// Private and normal windows are never merged, so windows of the other
+    /// mode stay open. If the main window is merged away, the window merged
+    /// into takes its place.
+    fn merge_windows(&mut self, mut viewport_id: egui::ViewportId) {
+        let Some(private) = std::iter::once(&self.window)
+            .chain(&self.windows)
+            .find(|window| window.viewport_id == viewport_id)
+            .map(|window| window.tab_manager.is_private())
+        else {
+            return;
+        };
+
+        let (merged, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.windows)
+            .into_iter()
+            .partition(|window| {
+                window.viewport_id != viewport_id && window.tab_manager.is_private() == private
+            });
+        self.windows = kept;
+        let mut managers: Vec<TabManager> = merged
+            .into_iter()
+            .map(|window| window.tab_manager)
+            .collect();
+
+        if self.window.viewport_id != viewport_id && self.window.tab_manager.is_private() == private
+        {
+            let Some(index) = self
+                .windows
+                .iter()
+                .position(|window| window.viewport_id == viewport_id)
+            else {
+                return;
+            };
+            let mut target = self.windows.remove(index);
+            target.viewport_id = egui::ViewportId::ROOT;
+            viewport_id = egui::ViewportId::ROOT;
+            let main = std::mem::replace(&mut self.window, target);
+            managers.insert(0, main.tab_manager);
+        }
+
+        let count = managers.len();
+        let Some(target) = std::iter::once(&mut self.window)
+            .chain(&mut self.windows)
+            .find(|window| window.viewport_id == viewport_id)
+        else {
+            return;
+        };
+        let refused = target.tab_manager.merge_from(managers);
+        debug_assert!(refused.is_empty());
+        target.url_input = target.tab_manager.active_tab().url.clone();
+        tracing::info!("Merged {} windows", count);
+    }
+
+    /// Render the windows other than the main one, each in its own viewport
+    ///
+    /// A window's state is swapped into `self.window` while it renders, so
+    /// the rendering code always works on the window being drawn.
+    fn render_other_windows(&mut self, ctx: &egui::Context) {
+        let mut windows = std::mem::take(&mut self.windows);
+        windows.retain_mut(|window| {
+            std::mem::swap(&mut self.window, window);
+            let mut open = true;
+            ctx.show_viewport_immediate(
+                self.window.viewport_id,
+                self.window_viewport.clone(),
+                |ctx, _class| {
+                    self.render_window(ctx);
+                    open = !ctx.input(|i| i.viewport().close_requested());
+                },
+            );
+            std::mem::swap(&mut self.window, window);
+            open
+        });
+
+        // Keep windows opened while the others rendered
+        windows.append(&mut self.windows);
+        self.windows = windows;
+    }
+
+    /// Load bookmarks from the user data directory, falling back to memory
+    fn load_bookmarks() -> BookmarkManager {
+        let data_dir = crate::settings::SettingsUI::data_dir();
+        match UserDataManager::new(data_dir).and_then(|d| BookmarkManager::from_user_data(&d)) {
+            Ok(manager) => manager,
+            Err(e) => {
+                tracing::warn!("Failed to load bookmarks: {}. Using empty set.", e);
+                BookmarkManager::new()
+            }
+        }
+    }
+
+    /// Load browsing history from the user data directory, falling back to memory
+    fn load_history() -> HistoryStore {
+        let data_dir = crate::settings::SettingsUI::data_dir();
+        match UserDataManager::new(data_dir).and_then(|d| HistoryStore::from_user_data(&d)) {
+            Ok(store) => store,
+            Err(e) => {
+                tracing::warn!("Failed to load history: {}. Using empty history.", e);
+                HistoryStore::new()
+            }
+        }
+    }
+
+    /// Load the cookie jar from the user data directory, falling back to memory
+    fn load_cookies(tab_manager: &TabManager) -> CookieStore {
+        let data_dir = crate::settings::SettingsUI::data_dir();
+        match UserDataManager::new(data_dir).and_then(|d| tab_manager.cookie_store(&d)) {
+            Ok(cookies) => cookies,
+            Err(e) => {
+                tracing::warn!("Failed to load cookies: {}. Using empty jar.", e);
+                CookieStore::new()
+            }
+        }
+    }
+
+    /// Load the download list from the user data directory
+    ///
+    /// Returns `None` without an async runtime to run transfers on. Private
+    /// windows keep their download list only in memory.
+    fn load_downloads(
+        tab_manager: &TabManager,
+        settings: &crate::settings::SettingsUI,
+        client: HttpClient,
+    ) -> Option<DownloadManager> {
+        if tokio::runtime::Handle::try_current().is_err() {
+            tracing::debug!("No async runtime; downloads are unavailable");
+            return None;
+        }
+
+        let download_dir = settings.downloads.download_directory.clone();
+        let result = if tab_manager.is_private() {
+            Ok(DownloadManager::new(client, download_dir))
+        } else {
+            let data_dir = crate::settings::SettingsUI::data_dir();
+            UserDataManager::new(data_dir)
+                .and_then(|d| DownloadManager::from_user_data(client, download_dir, &d))
+        };
+        match result {
+            Ok(downloads) => Some(downloads),
+            Err(e) => {
+                tracing::warn!("Failed to load downloads: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Name of the theme to draw with: the custom theme if it is loaded,
+    /// otherwise the built-in theme the setting resolves to
+    fn theme_name(&self) -> &str {
+        self.settings
+            .appearance
+            .custom_theme
+            .as_deref()
+            .filter(|name| self.themes.get(name).is_some())
+            .unwrap_or(self.active_theme.name())
+    }
+
+    /// Pause, resume, or cancel a download and save the download list
+    fn apply_download_action(&mut self, id: &str, action: DownloadAction) {
+        let Some(downloads) = self.downloads.as_mut() else {
+            return;
+        };
+
+        // Pausing and cancelling wait for the transfer task to stop
+        let result = tokio::task::block_in_place(|| {
+            let runtime = tokio::runtime::Handle::current();
+            match action {
+                DownloadAction::Pause => runtime.block_on(downloads.pause(id)),
+                DownloadAction::Resume => downloads.resume(id),
+                DownloadAction::Cancel => runtime.block_on(downloads.cancel(id)),
+            }
+        });
+        if let Err(e) = result {
+            tracing::warn!("Failed to {:?} download {}: {}", action, id, e);
+            return;
+        }
+
+        if !self.window.tab_manager.is_private() {
+            if let Err(e) = downloads.save_to_storage() {
+                tracing::error!("Failed to save downloads: {}", e);
+            }
+        }
+    }
+
+    /// Remove completed downloads from the list and save it
+    fn clear_completed_downloads(&mut self) {
+        let Some(downloads) = self.downloads.as_mut() else {
+            return;
+        };
+
+        if downloads.clear_completed() > 0 && !self.window.tab_manager.is_private() {
+            if let Err(e) = downloads.save_to_storage() {
+                tracing::error!("Failed to save downloads: {}", e);
+            }
+        }
+    }
+
+    /// Get the zoom levels of the active window
+    fn zoom_levels(&self) -> &ZoomLevels {
+        match &self.window.private_zoom {
+            Some(private_zoom) => private_zoom,
+            None => &self.zoom_levels,
+        }
+    }
+
+    /// Get the zoom levels of the active window for changing
+    fn zoom_levels_mut(&mut self) -> &mut ZoomLevels {
+        match &mut self.window.private_zoom {
+            Some(private_zoom) => private_zoom,
+            None => &mut self.zoom_levels,
+        }
+    }
+
+    /// Load per-site zoom levels from the user data directory, falling back to memory
+    fn load_zoom_levels() -> ZoomLevels {
+        let data_dir = crate::settings::SettingsUI::data_dir();
+        match UserDataManager::new(data_dir).and_then(|d| ZoomLevels::from_user_data(&d)) {
+            Ok(zoom) => zoom,
+            Err(e) => {
+                tracing::warn!("Failed to load zoom levels: {}. Using defaults.", e);
+                ZoomLevels::new()
+            }
+        }
+    }
+
+    /// Get the key the active tab's zoom level is saved under
+    ///
+    /// Web pages share a level per host; other pages use their whole URL.
+    fn zoom_host(&self) -> String {
+        let url = &self.window.tab_manager.active_tab().url;
+        ParsedUrl::parse(url)
+            .ok()
+            .and_then(|parsed| parsed.host().map(str::to_string))
+            .unwrap_or_else(|| url.clone())
+    }
+
+    /// Navigate the active tab to a URL and record the visit in history
+    fn navigate_active_tab(&mut self, url: impl Into<String>) {
+        let mut url = url.into();
+        if self.settings.privacy.https_only {
+            if let Some(https) = horizon_networking::https_only::upgrade(&url) {
+                url = https;
+            }
+        }
+        self.window.tab_manager.active_tab_mut().navigate_to(&url);
+        if self.security_policy.allow_resource(&url, None) {
+            if self
+                .window
+                .tab_manager
+                .record_visit(&mut self.history, &url, "")
+            {
+                if let Err(e) = self.history.save_to_storage() {
+                    tracing::error!("Failed to save history: {}", e);
+                }
+            }
+        } else {
+            tracing::warn!("Blocked by security policy: {}", url);
+        }
+        self.window.url_input = url;
+    }
+
+    /// Run a browser action triggered by a keyboard shortcut
+    fn perform_action(&mut self, action: BrowserAction) {
+        match action {
+            BrowserAction::NewTab => {
+                self.window.tab_manager.new_tab("about:home");
+                self.window.url_input = "about:home".to_string();
+            }
+            BrowserAction::NewWindow => {
+                self.open_window();
+            }
+            BrowserAction::MergeWindows => self.merge_into = Some(self.window.viewport_id),
+            BrowserAction::CloseTab => {
+                // The last tab is never closed
+                if self.window.tab_manager.tab_count() > 1 {
+                    self.window.tab_to_close = Some(self.window.tab_manager.active_tab_index());
+                }
+            }
+            BrowserAction::NextTab => {
+                self.window.tab_manager.next_tab();
+                self.window.url_input = self.window.tab_manager.active_tab().url.clone();
+            }
+            BrowserAction::PrevTab => {
+                self.window.tab_manager.prev_tab();
+                self.window.url_input = self.window.tab_manager.active_tab().url.clone();
+            }
+            BrowserAction::Reload => self.window.tab_manager.active_tab_mut().reload(),
+            BrowserAction::Back => {
+                if self.window.tab_manager.active_tab().can_go_back() {
+                    self.window.tab_manager.active_tab_mut().go_back();
+                    self.window.url_input = self.window.tab_manager.active_tab().url.clone();
+                }
+            }
+            BrowserAction::Forward => {
+                if self.window.tab_manager.active_tab().can_go_forward() {
+                    self.window.tab_manager.active_tab_mut().go_forward();
+                    self.window.url_input = self.window.tab_manager.active_tab().url.clone();
+                }
+            }
+            BrowserAction::Home => {
+                let homepage = self.settings.general.homepage.clone();
+                self.navigate_active_tab(homepage);
+            }
+            BrowserAction::FocusAddressBar => self.window.focus_address_bar = true,
+            BrowserAction::CommandPalette => {
+                self.window.command_palette = match self.window.command_palette {
+                    Some(_) => None,
+                    None => Some(CommandPalette::new()),
+                };
+            }
+            BrowserAction::FindInPage => {
+                if self.window.find.is_some() {
+                    self.window.find = None;
+                } else {
+                    self.window.find = Some(FindState::new());
+                    self.window.focus_find = true;
+                }
+            }
+            BrowserAction::ZoomIn | BrowserAction::ZoomOut | BrowserAction::ResetZoom => {
+                let host = self.zoom_host();
+                let zoom_levels = self.zoom_levels_mut();
+                let level = match action {
+                    BrowserAction::ZoomIn => zoom_levels.zoom_in(&host),
+                    BrowserAction::ZoomOut => zoom_levels.zoom_out(&host),
+                    _ => {
+                        zoom_levels.reset(&host);
+                        DEFAULT_ZOOM
+                    }
+                };
+                tracing::debug!("Zoom for {} set to {:.0}%", host, level * 100.0);
+                if !self.window.tab_manager.is_private() {
+                    if let Err(e) = self.zoom_levels.save_to_storage() {
+                        tracing::error!("Failed to save zoom levels: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Apply an autocomplete key press to the address bar
+    fn handle_suggestion_key(&mut self, key: SuggestionKey) {
+        // With nothing highlighted, the address bar holds what the user typed
+        if self.window.highlighted.is_none() {
+            self.window.typed_input = self.window.url_input.clone();
+        }
+
+        match autocomplete::handle_key(
+            key,
+            &mut self.window.highlighted,
+            &mut self.window.typed_input,
+            &self.window.suggestions,
+        ) {
+            SuggestionOutcome::Show(text) => self.window.url_input = text,
+            SuggestionOutcome::Dismiss(text) => {
+                self.window.url_input = text;
+                self.window.suggestions.clear();
+            }
+            SuggestionOutcome::Navigate(text) => {
+                let url = process_url_input(&text, &self.settings.general.search_engine);
+                self.window.suggestions.clear();
+                self.navigate_active_tab(url);
+                tracing::info!("Navigating to: {}", self.window.url_input);
+            }
+        }
+    }
+
+    /// Render the autocomplete dropdown below the address bar
+    ///
+    /// Returns the index of a clicked suggestion and whether the pointer is
+    /// over the dropdown.
+    fn render_suggestions(&self, ctx: &egui::Context, anchor: egui::Rect) -> (Option<usize>, bool) {
+        let palette = self.theme.palette().clone();
+        let mut clicked = None;
+
+        let area = egui::Area::new(egui::Id::new("address_bar_suggestions"))
+            .order(egui::Order::Foreground)
+            .fixed_pos(anchor.left_bottom() + egui::vec2(0.0, 4.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style())
+                    .fill(palette.bg_toolbar.to_egui()) // Toolbar color
+                    .stroke(egui::Stroke::new(1.0, palette.border_subtle.to_egui()))
+                    .rounding(egui::Rounding::same(6.0))
+                    .show(ui, |ui| {
+                        ui.set_width(anchor.width());
+                        for (index, suggestion) in self.window.suggestions.iter().enumerate() {
+                            let is_highlighted = self.window.highlighted == Some(index);
+                            let label = egui::SelectableLabel::new(
+                                is_highlighted,
+                                egui::RichText::new(format!("🕐 {}", suggestion)).size(13.0),
+                            );
+                            if ui.add_sized([ui.available_width(), 24.0], label).clicked() {
+                                clicked = Some(index);
+                            }
+                        }
+                    });
+            });
+
+        (clicked, area.response.contains_pointer())
+    }
+
+    /// Get the open tabs, bookmarks, and recent history the command palette
+    /// searches besides commands
+    fn palette_items(&self) -> Vec<PaletteItem> {
+        let tabs = self
+            .window
+            .tab_manager
+            .tabs()
+            .iter()
+            .enumerate()
+            .map(|(index, tab)| PaletteItem {
+                label: tab.display_title(),
+                detail: format!("Tab · {}", tab.url),
+                target: PaletteTarget::Tab(index),
+            });
+        let bookmarks = self.bookmarks.list().iter().map(|bookmark| PaletteItem {
+            label: if bookmark.title.is_empty() {
+                bookmark.url.clone()
+            } else {
+                bookmark.title.clone()
+            },
+            detail: format!("Bookmark · {}", bookmark.url),
+            target: PaletteTarget::Url(bookmark.url.clone()),
+        });
+        let history = self
+            .history
+            .recent(50)
+            .into_iter()
+            .map(|entry| PaletteItem {
+                label: if entry.title.is_empty() {
+                    entry.url.clone()
+                } else {
+                    entry.title.clone()
+                },
+                detail: format!("History · {}", entry.url),
+                target: PaletteTarget::Url(entry.url),
+            });
+
+        tabs.chain(bookmarks).chain(history).collect()
+    }
+
+    /// Render the command palette over the top of the window
+    fn render_command_palette(&mut self, ctx: &egui::Context) {
+        let palette = self.theme.palette().clone();
+        let items = self.palette_items();
+        let Some(command_palette) = self.window.command_palette.as_mut() else {
+            return;
+        };
+        let results = command_palette.results(items);
+        command_palette.selected = command_palette
+            .selected
+            .min(results.len().saturating_sub(1));
+        let mut chosen: Option<PaletteTarget> = None;
+        let mut close = false;
+
+        let width = 480.0_f32.min(ctx.screen_rect().width() - 40.0);
+        egui::Area::new(egui::Id::new("command_palette"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style())
+                    .fill(palette.bg_toolbar.to_egui())
+                    .stroke(egui::Stroke::new(1.0, palette.border_subtle.to_egui()))
+                    .rounding(egui::Rounding::same(8.0))
+                    .show(ui, |ui| {
+                        ui.set_width(width);
+                        let response = ui.add(
+                            egui::TextEdit::singleline(&mut command_palette.query)
+                                .hint_text("Search commands, tabs, bookmarks, and history")
+                                .desired_width(f32::INFINITY),
+                        );
+                        response.request_focus();
+                        if response.changed() {
+                            command_palette.selected = 0;
+                        }
+
+                        ui.input(|i| {
+                            if i.key_pressed(egui::Key::ArrowDown) {
+                                command_palette.select_next(results.len());
+                            }
+                            if i.key_pressed(egui::Key::ArrowUp) {
+                                command_palette.select_prev(results.len());
+                            }
+                            if i.key_pressed(egui::Key::Enter) {
+                                chosen = results
+                                    .get(command_palette.selected)
+                                    .map(|item| item.target.clone());
+                            }
+                            close |= i.key_pressed(egui::Key::Escape);
+                        });
+
+                        ui.add_space(4.0);
+                        for (index, item) in results.iter().enumerate() {
+                            let label = egui::SelectableLabel::new(
+                                index == command_palette.selected,
+                                egui::RichText::new(&item.label).size(13.0),
+                            );
+                            let response = ui
+                                .add_sized([ui.available_width(), 24.0], label)
+                                .on_hover_text(&item.detail);
+                            if response.clicked() {
+                                chosen = Some(item.target.clone());
+                            }
+                        }
+                        if results.is_empty() {
+                            ui.label(
+                                egui::RichText::new("No matches")
+                                    .size(13.0)
+                                    .color(palette.text_secondary.to_egui()),
+                            );
+                        }
+                    });
+            });
+
+        if close || chosen.is_some() {
+            self.window.command_palette = None;
+        }
+        match chosen {
+            Some(PaletteTarget::Command(action)) => self.perform_action(action),
+            Some(PaletteTarget::Tab(index)) => {
+                self.window.tab_manager.switch_to_tab(index);
+                self.window.url_input = self.window.tab_manager.active_tab().url.clone();
+            }
+            Some(PaletteTarget::Url(url)) => self.navigate_active_tab(url),
+            None => {}
+        }
+    }
+
+    /// Render the bookmarks bar below the navigation bar
+    ///
+    /// Bookmarks that don't fit move into a "»" menu at the end of the bar.
+    fn render_bookmarks_bar(&mut self, ctx: &egui::Context) {
+        let palette = self.theme.palette().clone();
+        let mut navigate_to: Option<String> = None;
+        let mut hovered_url: Option<String> = None;
+
+        egui::TopBottomPanel::top("bookmarks_bar")
+            .frame(
+                egui::Frame::none()
+                    .fill(palette.bg_toolbar.to_egui()) // Toolbar color
+                    .inner_margin(egui::Margin::symmetric(12.0, 4.0)),
+            )
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let bookmarks = self.bookmarks.list();
+                    if bookmarks.is_empty() {
+                        ui.label(
+                            egui::RichText::new("Bookmarked pages appear here")
+                                .size(12.0)
+                                .color(palette.text_secondary.to_egui()),
+                        );
+                        return;
+                    }
+
+                    let spacing = ui.spacing().item_spacing.x;
+                    // Each bookmark is followed by the item spacing and the
+                    // extra space added after it
+                    let gap = spacing * 2.0;
+                    let font = egui::FontId::proportional(12.0);
+                    let titles: Vec<String> = bookmarks
+                        .iter()
+                        .map(|bookmark| truncate_tab_title(&bookmark.title))
+                        .collect();
+                    let widths: Vec<f32> = titles
+                        .iter()
+                        .map(|title| {
+                            let galley = ui.fonts(|f| {
+                                f.layout_no_wrap(title.clone(), font.clone(), egui::Color32::WHITE)
+                            });
+                            14.0 + spacing + galley.size().x
+                        })
+                        .collect();
+                    let overflow_width = ui.fonts(|f| {
+                        let button_font = egui::TextStyle::Button.resolve(ui.style());
+                        f.layout_no_wrap("»".to_string(), button_font, egui::Color32::WHITE)
+                            .size()
+                            .x
+                    }) + 2.0 * ui.spacing().button_padding.x;
+                    let fit = bookmarks_bar_fit(&widths, ui.available_width(), gap, overflow_width);
+
+                    for (bookmark, title) in bookmarks.iter().zip(&titles).take(fit) {
+                        let host = ParsedUrl::parse(&bookmark.url)
+                            .ok()
+                            .and_then(|url| url.host_without_www().map(str::to_string))
+                            .unwrap_or_else(|| bookmark.url.clone());
+                        let response = ui
+                            .horizontal(|ui| {
+                                render_letter_tile(ui, &host, 14.0);
+                                ui.label(
+                                    egui::RichText::new(title)
+                                        .size(12.0)
+                                        .color(palette.text_primary.to_egui()),
+                                );
+                            })
+                            .response
+                            .interact(egui::Sense::click())
+                            .on_hover_text(&bookmark.url)
+                            .on_hover_cursor(egui::CursorIcon::PointingHand);
+                        if response.hovered() {
+                            hovered_url = Some(bookmark.url.clone());
+                        }
+                        if response.clicked() {
+                            navigate_to = Some(bookmark.url.clone());
+                        }
+                        ui.add_space(gap - spacing);
+                    }
+
+                    if fit < bookmarks.len() {
+                        ui.menu_button("»", |ui| {
+                            for bookmark in &bookmarks[fit..] {
+                                if ui.button(truncate_tab_title(&bookmark.title)).clicked() {
+                                    navigate_to = Some(bookmark.url.clone());
+                                    ui.close_menu();
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_text("More bookmarks");
+                    }
+                });
+            });
+
+        if hovered_url.is_some() {
+            self.window.status.hovered_url = hovered_url;
+        }
+        if let Some(url) = navigate_to {
+            self.navigate_active_tab(url);
+        }
+    }
 
-// Constants for tab title display
-const MAX_TAB_TITLE_LENGTH: usize = 25;
-const TRUNCATE_AT: usize = 22;
+    /// Render the status bar with the hovered link and the page's security
+    ///
+    /// The bar is hidden while no link is hovered.
+    fn render_status_bar(&mut self, ctx: &egui::Context) {
+        self.window.status.security = classify_security(&self.window.tab_manager.active_tab().url);
+        if self.window.status.is_empty() {
+            return;
+        }
 
-/// The main browser application state
-struct BrowserApp {
-    /// Tab manager
-    tab_manager: TabManager,
-    /// URL input buffer
-    url_input: String,
-    /// Tab to close (deferred)
-    tab_to_close: Option<usize>,
-    /// Settings state
-    settings: crate::settings::SettingsUI,
-    /// Sidebar state
-    sidebar: crate::sidebar::Sidebar,
-}
+        let palette = self.theme.palette().clone();
+        let status = &self.window.status;
+        egui::TopBottomPanel::bottom("status_bar")
+            .frame(
+                egui::Frame::none()
+                    .fill(palette.bg_toolbar.to_egui()) // Toolbar color
+                    .inner_margin(egui::Margin::symmetric(8.0, 2.0)),
+            )
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    if let Some(url) = &status.hovered_url {
+                        ui.add(
+                            egui::Label::new(
+                                egui::RichText::new(url)
+                                    .size(12.0)
+                                    .color(palette.text_secondary.to_egui()),
+                            )
+                            .truncate(),
+                        );
+                    }
 
-impl BrowserApp {
-    /// Create a new browser application
-    fn new() -> Self {
-        let tab_manager = TabManager::new();
-        let url_input = tab_manager.active_tab().url.clone();
-        let settings = crate::settings::SettingsUI::load();
-        let sidebar = crate::sidebar::Sidebar::new();
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.label(
+                            egui::RichText::new(status.security.description())
+                                .size(12.0)
+                                .color(palette.text_secondary.to_egui()),
+                        );
+                        ui.label(
+                            egui::RichText::new(status.security.icon())
+                                .size(12.0)
+                                .color(status.security.color(&palette).to_egui()),
+                        );
+                    });
+                });
+            });
+    }
 
-        Self {
-            tab_manager,
-            url_input,
-            tab_to_close: None,
-            settings,
-            sidebar,
+    /// Toggle the active tab's URL in or out of the bookmarks
+    fn toggle_bookmark(&mut self) {
+        let tab = self.window.tab_manager.active_tab();
+        if !self.bookmarks.remove_by_url(&tab.url) {
+            self.bookmarks
+                .add(Bookmark::new(tab.url.clone(), tab.display_title()));
+        }
+
+        if let Err(e) = self.bookmarks.save_to_storage() {
+            tracing::error!("Failed to save bookmarks: {}", e);
+        }
+    }
+
+    /// Render the bookmarks panel, grouped by folder
+    fn render_bookmarks_panel(&mut self, ctx: &egui::Context) {
+        let palette = self.theme.palette().clone();
+        let mut action = None;
+        let mut hovered_url = None;
+        let mut close = false;
+
+        egui::SidePanel::right("bookmarks_panel")
+            .default_width(280.0)
+            .frame(
+                egui::Frame::none()
+                    .fill(palette.bg_toolbar.to_egui()) // Toolbar color
+                    .inner_margin(egui::Margin::same(12.0)),
+            )
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new("⭐ Bookmarks")
+                            .size(18.0)
+                            .strong()
+                            .color(palette.text_primary.to_egui()),
+                    );
+                    if ui.small_button("✕").on_hover_text("Close").clicked() {
+                        close = true;
+                    }
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| {
+                        action =
+                            render_bookmark_list(ui, &self.bookmarks, &palette, &mut hovered_url);
+                    });
+            });
+
+        if close {
+            self.sidebar.selected_item = None;
+        }
+
+        if hovered_url.is_some() {
+            self.window.status.hovered_url = hovered_url;
+        }
+        if let Some(action) = action {
+            self.apply_bookmark_row_action(action);
         }
     }
 
-    /// Process URL input and return a properly formatted URL
-    fn process_url_input(&self, input: &str) -> String {
-        let trimmed = input.trim();
+    /// Render the history panel, most recent first and grouped by day
+    fn render_history_panel(&mut self, ctx: &egui::Context) {
+        let palette = self.theme.palette().clone();
+        let mut action = None;
+        let mut hovered_url = None;
+        let mut clear = None;
+        let mut close = false;
+
+        egui::SidePanel::right("history_panel")
+            .default_width(300.0)
+            .frame(
+                egui::Frame::none()
+                    .fill(palette.bg_toolbar.to_egui()) // Toolbar color
+                    .inner_margin(egui::Margin::same(12.0)),
+            )
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new("🕐 History")
+                            .size(18.0)
+                            .strong()
+                            .color(palette.text_primary.to_egui()),
+                    );
+                    if ui.small_button("✕").on_hover_text("Close").clicked() {
+                        close = true;
+                    }
+                });
+                ui.add_space(8.0);
+
+                clear = render_history_controls(ui, &mut self.window.history_query, &palette);
+                ui.separator();
+
+                let days = self
+                    .window
+                    .history_list
+                    .days(&self.history, &self.window.history_query);
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .show(ui, |ui| {
+                        action = render_history_list(ui, days, &palette, &mut hovered_url);
+                    });
+            });
+
+        if close {
+            self.sidebar.selected_item = None;
+        }
 
-        // Check for special URLs
-        if trimmed.starts_with("about:") {
-            return trimmed.to_string();
+        if let Some(window) = clear {
+            self.clear_history_window(window);
         }
+        if hovered_url.is_some() {
+            self.window.status.hovered_url = hovered_url;
+        }
+        if let Some(action) = action {
+            self.apply_history_row_action(action);
+        }
+    }
 
-        // Check for explicit protocol
-        if trimmed.starts_with("http://") || trimmed.starts_with("https://") {
-            return trimmed.to_string();
+    /// Open or remove the bookmark of a bookmark list row
+    fn apply_bookmark_row_action(&mut self, action: LinkRowAction) {
+        match action {
+            LinkRowAction::Open(url) => self.navigate_active_tab(url),
+            LinkRowAction::Remove(url) => {
+                self.bookmarks.remove_by_url(&url);
+                if let Err(e) = self.bookmarks.save_to_storage() {
+                    tracing::error!("Failed to save bookmarks: {}", e);
+                }
+            }
         }
+    }
 
-        // Check if it looks like a domain/URL:
-        // - Contains at least one dot
-        // - Doesn't contain spaces
-        // - Has a valid TLD-like pattern (at least 2 chars after last dot)
-        if trimmed.contains('.') && !trimmed.contains(' ') {
-            let parts: Vec<&str> = trimmed.split('.').collect();
-            if parts.len() >= 2 {
-                let last_part = parts.last().unwrap();
-                // Check if the last part looks like a TLD (2+ characters, alphanumeric)
-                if last_part.len() >= 2 && last_part.chars().all(|c| c.is_alphanumeric()) {
-                    return format!("https://{}", trimmed);
+    /// Open or delete the entry of a history list row
+    fn apply_history_row_action(&mut self, action: LinkRowAction) {
+        match action {
+            LinkRowAction::Open(url) => self.navigate_active_tab(url),
+            LinkRowAction::Remove(url) => {
+                if self.history.remove(&url) {
+                    if let Err(e) = self.history.save_to_storage() {
+                        tracing::error!("Failed to save history: {}", e);
+                    }
                 }
             }
         }
+    }
 
-        // Treat as search query
-        self.settings.general.search_engine.search_url(trimmed)
+    /// Clear the history visited within a time window
+    fn clear_history_window(&mut self, window: ClearWindow) {
+        if self.history.clear_window(window, SystemTime::now()) > 0 {
+            if let Err(e) = self.history.save_to_storage() {
+                tracing::error!("Failed to save history: {}", e);
+            }
+        }
     }
 
     /// Render the home page content with Firefox-inspired design
     fn render_home_page(&self, ui: &mut egui::Ui) {
+        let palette = self.theme.palette().clone();
         ui.vertical_centered(|ui| {
             ui.add_space(60.0);
 
@@ -134,7 +2117,7 @@ impl BrowserApp {
                 egui::RichText::new("🌅 Horizon")
                     .size(64.0)
                     .strong()
-                    .color(egui::Color32::from_rgb(59, 130, 246)), // Firefox blue
+                    .color(palette.accent.to_egui()), // Firefox blue
             );
 
             ui.add_space(12.0);
@@ -142,7 +2125,7 @@ impl BrowserApp {
             ui.label(
                 egui::RichText::new("Modern, Privacy-Focused Web Browser")
                     .size(20.0)
-                    .color(egui::Color32::from_rgb(156, 163, 175)), // Secondary text
+                    .color(palette.text_secondary.to_egui()), // Secondary text
             );
 
             ui.add_space(40.0);
@@ -152,8 +2135,8 @@ impl BrowserApp {
                 ui.add_space((ui.available_width() - 600.0) / 2.0);
 
                 egui::Frame::none()
-                    .fill(egui::Color32::from_rgba_premultiplied(31, 41, 51, 200))
-                    .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(55, 65, 81)))
+                    .fill(palette.bg_toolbar.with_alpha(200).to_egui_premultiplied()) // Translucent toolbar color
+                    .stroke(egui::Stroke::new(1.0, palette.border_subtle.to_egui()))
                     .inner_margin(egui::Margin::symmetric(16.0, 12.0))
                     .rounding(egui::Rounding::same(6.0)) // Firefox 6px for inputs
                     .show(ui, |ui| {
@@ -217,7 +2200,7 @@ impl BrowserApp {
                 ui.label(
                     egui::RichText::new("Connect:")
                         .size(14.0)
-                        .color(egui::Color32::from_rgb(156, 163, 175)),
+                        .color(palette.text_secondary.to_egui()),
                 );
                 ui.add_space(10.0);
 
@@ -242,9 +2225,10 @@ impl BrowserApp {
 
     /// Render an app shortcut card with Firefox styling
     fn render_app_card(&self, ui: &mut egui::Ui, icon: &str, title: &str, subtitle: &str) {
+        let palette = self.theme.palette().clone();
         egui::Frame::none()
-            .fill(egui::Color32::from_rgb(31, 41, 51)) // Toolbar color
-            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(55, 65, 81)))
+            .fill(palette.bg_toolbar.to_egui()) // Toolbar color
+            .stroke(egui::Stroke::new(1.0, palette.border_subtle.to_egui()))
             .inner_margin(egui::Margin::same(20.0))
             .rounding(egui::Rounding::same(6.0)) // Firefox 6px for panels
             .show(ui, |ui| {
@@ -256,13 +2240,13 @@ impl BrowserApp {
                         egui::RichText::new(title)
                             .size(18.0)
                             .strong()
-                            .color(egui::Color32::from_rgb(249, 250, 251)), // Primary text
+                            .color(palette.text_primary.to_egui()), // Primary text
                     );
                     ui.add_space(4.0);
                     ui.label(
                         egui::RichText::new(subtitle)
                             .size(13.0)
-                            .color(egui::Color32::from_rgb(156, 163, 175)), // Secondary text
+                            .color(palette.text_secondary.to_egui()), // Secondary text
                     );
                 });
             });
@@ -270,9 +2254,10 @@ impl BrowserApp {
 
     /// Render weather widget with Firefox styling
     fn render_weather_widget(&self, ui: &mut egui::Ui) {
+        let palette = self.theme.palette().clone();
         egui::Frame::none()
-            .fill(egui::Color32::from_rgb(31, 41, 51)) // Toolbar color
-            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(55, 65, 81)))
+            .fill(palette.bg_toolbar.to_egui()) // Toolbar color
+            .stroke(egui::Stroke::new(1.0, palette.border_subtle.to_egui()))
             .inner_margin(egui::Margin::same(16.0))
             .rounding(egui::Rounding::same(6.0)) // Firefox 6px for panels
             .show(ui, |ui| {
@@ -280,7 +2265,7 @@ impl BrowserApp {
                 ui.heading(
                     egui::RichText::new("🌤 Weather")
                         .size(18.0)
-                        .color(egui::Color32::from_rgb(249, 250, 251)), // Primary text
+                        .color(palette.text_primary.to_egui()), // Primary text
                 );
                 ui.add_space(12.0);
 
@@ -292,12 +2277,12 @@ impl BrowserApp {
                             egui::RichText::new("72°F / 22°C")
                                 .size(24.0)
                                 .strong()
-                                .color(egui::Color32::from_rgb(249, 250, 251)),
+                                .color(palette.text_primary.to_egui()),
                         );
                         ui.label(
                             egui::RichText::new("Sunny")
                                 .size(14.0)
-                                .color(egui::Color32::from_rgb(156, 163, 175)), // Secondary
+                                .color(palette.text_secondary.to_egui()), // Secondary
                         );
                     });
                 });
@@ -310,13 +2295,13 @@ impl BrowserApp {
                     ui.label(
                         egui::RichText::new("💧 Humidity:")
                             .size(13.0)
-                            .color(egui::Color32::from_rgb(156, 163, 175)),
+                            .color(palette.text_secondary.to_egui()),
                     );
                     ui.add_space(8.0);
                     ui.label(
                         egui::RichText::new("45%")
                             .size(13.0)
-                            .color(egui::Color32::from_rgb(249, 250, 251)),
+                            .color(palette.text_primary.to_egui()),
                     );
                 });
             });
@@ -324,9 +2309,10 @@ impl BrowserApp {
 
     /// Render news feed widget with Firefox styling
     fn render_news_feed(&self, ui: &mut egui::Ui) {
+        let palette = self.theme.palette().clone();
         egui::Frame::none()
-            .fill(egui::Color32::from_rgb(31, 41, 51)) // Toolbar color
-            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(55, 65, 81)))
+            .fill(palette.bg_toolbar.to_egui()) // Toolbar color
+            .stroke(egui::Stroke::new(1.0, palette.border_subtle.to_egui()))
             .inner_margin(egui::Margin::same(16.0))
             .rounding(egui::Rounding::same(6.0))
             .show(ui, |ui| {
@@ -334,7 +2320,7 @@ impl BrowserApp {
                 ui.heading(
                     egui::RichText::new("📰 Latest News")
                         .size(18.0)
-                        .color(egui::Color32::from_rgb(249, 250, 251)),
+                        .color(palette.text_primary.to_egui()),
                 );
                 ui.add_space(12.0);
 
@@ -347,140 +2333,557 @@ impl BrowserApp {
                             egui::RichText::new("Horizon Browser v0.0.1 Released")
                                 .size(14.0)
                                 .strong()
-                                .color(egui::Color32::from_rgb(249, 250, 251)),
+                                .color(palette.text_primary.to_egui()),
                         );
                         ui.label(
                             egui::RichText::new("New UI design with modern features")
                                 .size(12.0)
-                                .color(egui::Color32::from_rgb(156, 163, 175)),
+                                .color(palette.text_secondary.to_egui()),
+                        );
+                    });
+                });
+
+                ui.add_space(10.0);
+                ui.separator();
+                ui.add_space(10.0);
+
+                // News item 2
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("🔒").size(24.0));
+                    ui.add_space(8.0);
+                    ui.vertical(|ui| {
+                        ui.label(
+                            egui::RichText::new("Enhanced Privacy Features")
+                                .size(14.0)
+                                .strong()
+                                .color(palette.text_primary.to_egui()),
+                        );
+                        ui.label(
+                            egui::RichText::new("Better tracking protection added")
+                                .size(12.0)
+                                .color(palette.text_secondary.to_egui()),
                         );
                     });
                 });
+            });
+    }
+
+    /// Render a blank page
+    fn render_blank_page(&self, ui: &mut egui::Ui) {
+        let palette = self.theme.palette().clone();
+        ui.vertical_centered(|ui| {
+            ui.add_space(300.0);
+            ui.label(
+                egui::RichText::new("about:blank")
+                    .size(24.0)
+                    .color(palette.text_secondary.to_egui()),
+            );
+        });
+    }
+
+    /// Render the page shown in place of a URL the security policy blocks
+    fn render_blocked_page(&self, ui: &mut egui::Ui, url: &str) {
+        let palette = self.theme.palette().clone();
+        ui.vertical_centered(|ui| {
+            ui.add_space(200.0);
+            ui.label(
+                egui::RichText::new("🚫 Page blocked")
+                    .size(24.0)
+                    .color(palette.text_primary.to_egui()),
+            );
+            ui.add_space(10.0);
+            ui.label(
+                egui::RichText::new(blocked_message(url))
+                    .size(14.0)
+                    .color(palette.text_secondary.to_egui()),
+            );
+        });
+    }
+
+    /// Render the page shown when navigating to the active tab failed
+    fn render_error_page(&mut self, ui: &mut egui::Ui, error: NavigationError) {
+        let palette = self.theme.palette().clone();
+        ui.vertical_centered(|ui| {
+            ui.add_space(200.0);
+            ui.label(
+                egui::RichText::new(format!("⚠ {}", error.title()))
+                    .size(24.0)
+                    .color(palette.text_primary.to_egui()),
+            );
+            ui.add_space(10.0);
+            ui.label(
+                egui::RichText::new(error.description())
+                    .size(14.0)
+                    .color(palette.text_secondary.to_egui()),
+            );
+            ui.add_space(20.0);
+            if ui.button("Retry").clicked() {
+                self.window.tab_manager.active_tab_mut().reload();
+            }
+        });
+    }
+
+    /// Render a generic web page
+    fn render_web_page(&self, ui: &mut egui::Ui, url: &str) {
+        let palette = self.theme.palette().clone();
+        ui.vertical(|ui| {
+            ui.add_space(50.0);
+
+            ui.horizontal(|ui| {
+                ui.add_space(50.0);
+                ui.vertical(|ui| {
+                    ui.heading(egui::RichText::new("Web Page Preview")
+                        .size(28.0)
+                        .color(palette.accent_hover().to_egui()));
+
+                    ui.add_space(20.0);
+
+                    ui.label(egui::RichText::new(format!("📄 {}", url))
+                        .size(16.0)
+                        .color(palette.text_primary.to_egui()));
+
+                    ui.add_space(30.0);
+
+                    ui.label(egui::RichText::new("This is a placeholder for web content.")
+                        .size(14.0)
+                        .color(palette.text_secondary.to_egui()));
+
+                    ui.add_space(10.0);
+
+                    ui.label("In a full implementation, this area would display");
+                    ui.label("the rendered web page using a WebView component.");
+
+                    ui.add_space(30.0);
+
+                    // Simulated content
+                    egui::Frame::group(ui.style())
+                        .fill(palette.bg_toolbar.to_egui())
+                        .stroke(egui::Stroke::new(1.0, palette.border_subtle.to_egui()))
+                        .inner_margin(egui::Margin::same(20.0))
+                        .show(ui, |ui| {
+                            ui.label(egui::RichText::new(WEB_PAGE_PLACEHOLDER_TEXT[0])
+                                .size(18.0)
+                                .strong());
+                            ui.add_space(10.0);
+                            ui.label(WEB_PAGE_PLACEHOLDER_TEXT[1]);
+                            ui.label(WEB_PAGE_PLACEHOLDER_TEXT[2]);
+                            ui.add_space(10.0);
+                            ui.label(WEB_PAGE_PLACEHOLDER_TEXT[3]);
+                            ui.label(WEB_PAGE_PLACEHOLDER_TEXT[4]);
+                            ui.label(WEB_PAGE_PLACEHOLDER_TEXT[5]);
+                        });
+                });
+            });
+        });
+    }
+
+    /// Render the active page simplified for reading
+    fn render_reader_view(&self, ui: &mut egui::Ui, article: &Article) {
+        let palette = self.theme.palette().clone();
+        let base_size = f32::from(self.theme.typography().base_size);
+        let title = article
+            .title
+            .clone()
+            .unwrap_or_else(|| self.window.tab_manager.active_tab().display_title());
+
+        ui.vertical_centered(|ui| {
+            ui.set_max_width(READER_MAX_WIDTH);
+            ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
+                ui.add_space(40.0);
+                ui.label(
+                    egui::RichText::new(title)
+                        .size(base_size * 2.0)
+                        .strong()
+                        .color(palette.text_primary.to_egui()),
+                );
+
+                if let Some(byline) = &article.byline {
+                    ui.add_space(8.0);
+                    ui.label(
+                        egui::RichText::new(byline)
+                            .size(base_size)
+                            .italics()
+                            .color(palette.text_secondary.to_egui()),
+                    );
+                }
+
+                ui.add_space(24.0);
+                for paragraph in &article.paragraphs {
+                    ui.label(
+                        egui::RichText::new(paragraph)
+                            .size(base_size * 1.2)
+                            .color(palette.text_primary.to_egui()),
+                    );
+                    ui.add_space(base_size);
+                }
+                ui.add_space(40.0);
+            });
+        });
+    }
+
+    /// Get the text shown on the active page, for find in page
+    fn page_text(&self) -> String {
+        let tab = self.window.tab_manager.active_tab();
+        if let Some(error) = tab.error {
+            return format!("{}\n{}", error.title(), error.description());
+        }
+        let url = tab.url.as_str();
+        match InternalPage::parse(url) {
+            Some(InternalPage::Blank) => String::new(),
+            Some(InternalPage::Source(target)) => self.page_source(&target),
+            Some(InternalPage::Unknown(name)) => {
+                format!("Page not found\n{}", unknown_page_message(&name))
+            }
+            Some(_) => tab.display_title(),
+            None if !self.security_policy.allow_resource(url, None) => {
+                format!("Page blocked\n{}", blocked_message(url))
+            }
+            None => {
+                let text = web_page_text(url);
+                match Article::extract(&text).filter(|_| tab.reader_mode) {
+                    Some(article) => article.text(),
+                    None => text,
+                }
+            }
+        }
+    }
+
+    /// Get the source of a web page
+    ///
+    /// Pages are not fetched yet, so this is the placeholder content shown
+    /// for the page.
+    fn page_source(&self, url: &str) -> String {
+        if !self.security_policy.allow_resource(url, None) {
+            return format!("Page blocked\n{}", blocked_message(url));
+        }
+        web_page_text(url)
+    }
+
+    /// Render the source of a page as plain text
+    fn render_page_source(&self, ui: &mut egui::Ui, url: &str) {
+        let palette = self.theme.palette().clone();
+        ui.add_space(16.0);
+        ui.horizontal(|ui| {
+            ui.add_space(16.0);
+            ui.vertical(|ui| {
+                ui.label(
+                    egui::RichText::new(format!("Source of {}", url))
+                        .size(14.0)
+                        .color(palette.text_secondary.to_egui()),
+                );
+                ui.add_space(8.0);
+                ui.label(
+                    egui::RichText::new(self.page_source(url))
+                        .monospace()
+                        .color(palette.text_primary.to_egui()),
+                );
+            });
+        });
+    }
+
+    /// Apply an action chosen from the page's context menu
+    fn apply_page_menu_action(&mut self, ctx: &egui::Context, action: PageMenuAction) {
+        match action {
+            PageMenuAction::Back => self.perform_action(BrowserAction::Back),
+            PageMenuAction::Forward => self.perform_action(BrowserAction::Forward),
+            PageMenuAction::Reload => self.perform_action(BrowserAction::Reload),
+            PageMenuAction::CopyUrl => {
+                let url = &self.window.tab_manager.active_tab().url;
+                ctx.copy_text(url_for_clipboard(url));
+            }
+            PageMenuAction::ViewSource => {
+                let url = source_url(&self.window.tab_manager.active_tab().url);
+                self.window.tab_manager.new_tab(url.clone());
+                self.window.url_input = url;
+            }
+        }
+    }
 
-                ui.add_space(10.0);
-                ui.separator();
-                ui.add_space(10.0);
+    /// Get the article on the active page, if it is a web page that looks
+    /// like one
+    fn reader_article(&self) -> Option<Article> {
+        let tab = self.window.tab_manager.active_tab();
+        let is_web = ParsedUrl::parse(&tab.url).is_ok_and(|url| url.is_web());
+        if tab.error.is_some() || !is_web {
+            return None;
+        }
+        if !self.security_policy.allow_resource(&tab.url, None) {
+            return None;
+        }
+        Article::extract(&web_page_text(&tab.url))
+    }
 
-                // News item 2
-                ui.horizontal(|ui| {
-                    ui.label(egui::RichText::new("🔒").size(24.0));
-                    ui.add_space(8.0);
-                    ui.vertical(|ui| {
+    /// Render the frame timing overlay in the corner of the page
+    fn render_frame_stats(&self, ctx: &egui::Context) {
+        let palette = self.theme.palette().clone();
+        let stats = self.renderer.stats();
+        let traffic = self.network.traffic_stats();
+        let backend = self
+            .renderer
+            .active_backend()
+            .map_or("Uninitialized", |backend| backend.name());
+        egui::Area::new(egui::Id::new("frame_stats"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -12.0))
+            .order(egui::Order::Foreground)
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::none()
+                    .fill(palette.bg_toolbar.to_egui())
+                    .stroke(egui::Stroke::new(1.0, palette.border_subtle.to_egui()))
+                    .inner_margin(egui::Margin::symmetric(8.0, 4.0))
+                    .rounding(egui::Rounding::same(4.0))
+                    .show(ui, |ui| {
                         ui.label(
-                            egui::RichText::new("Enhanced Privacy Features")
-                                .size(14.0)
-                                .strong()
-                                .color(egui::Color32::from_rgb(249, 250, 251)),
+                            egui::RichText::new(format!(
+                                "{:.0} FPS  {:.1} ms (avg {:.1} ms)  {}",
+                                stats.fps, stats.last_frame_ms, stats.avg_frame_ms, backend
+                            ))
+                            .monospace()
+                            .size(12.0)
+                            .color(palette.text_secondary.to_egui()),
                         );
                         ui.label(
-                            egui::RichText::new("Better tracking protection added")
-                                .size(12.0)
-                                .color(egui::Color32::from_rgb(156, 163, 175)),
+                            egui::RichText::new(format!(
+                                "↓ {:.1} KB/s  ↑ {:.1} KB/s",
+                                traffic.download_kbps, traffic.upload_kbps
+                            ))
+                            .monospace()
+                            .size(12.0)
+                            .color(palette.text_secondary.to_egui()),
                         );
                     });
-                });
             });
     }
 
-    /// Render a blank page
-    fn render_blank_page(&self, ui: &mut egui::Ui) {
-        ui.vertical_centered(|ui| {
-            ui.add_space(300.0);
-            ui.label(
-                egui::RichText::new("about:blank")
-                    .size(24.0)
-                    .color(egui::Color32::from_rgb(156, 163, 175)),
-            );
-        });
-    }
+    /// Render the find bar below the page
+    fn render_find_bar(&mut self, ctx: &egui::Context) {
+        let palette = self.theme.palette().clone();
+        let text = self.page_text();
+        let focus = std::mem::take(&mut self.window.focus_find);
+        let Some(find) = self.window.find.as_mut() else {
+            return;
+        };
+        let mut close = false;
 
-    /// Render a generic web page
-    fn render_web_page(&self, ui: &mut egui::Ui, url: &str) {
-        ui.vertical(|ui| {
-            ui.add_space(50.0);
+        egui::TopBottomPanel::bottom("find_bar")
+            .frame(
+                egui::Frame::none()
+                    .fill(palette.bg_toolbar.to_egui()) // Toolbar color
+                    .inner_margin(egui::Margin::symmetric(12.0, 6.0)),
+            )
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut find.query)
+                            .hint_text("Find in page")
+                            .desired_width(240.0),
+                    );
+                    if focus {
+                        response.request_focus();
+                    }
+                    find.update(&text);
 
-            ui.horizontal(|ui| {
-                ui.add_space(50.0);
-                ui.vertical(|ui| {
-                    ui.heading(egui::RichText::new("Web Page Preview")
-                        .size(28.0)
-                        .color(egui::Color32::from_rgb(88, 166, 255)));
+                    // Enter: Next match, Shift+Enter: Previous match
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        if ui.input(|i| i.modifiers.shift) {
+                            find.prev();
+                        } else {
+                            find.next();
+                        }
+                        response.request_focus();
+                    }
 
-                    ui.add_space(20.0);
+                    if ui
+                        .button("▲")
+                        .on_hover_text("Previous match (Shift+Enter)")
+                        .clicked()
+                    {
+                        find.prev();
+                    }
+                    if ui.button("▼").on_hover_text("Next match (Enter)").clicked() {
+                        find.next();
+                    }
+                    ui.checkbox(&mut find.case_sensitive, "Match case");
+                    ui.label(
+                        egui::RichText::new(find.status())
+                            .size(13.0)
+                            .color(palette.text_secondary.to_egui()),
+                    );
 
-                    ui.label(egui::RichText::new(format!("📄 {}", url))
-                        .size(16.0)
-                        .color(egui::Color32::from_rgb(249, 250, 251)));
+                    if ui.small_button("✕").on_hover_text("Close (Esc)").clicked()
+                        || ui.input(|i| i.key_pressed(egui::Key::Escape))
+                    {
+                        close = true;
+                    }
+                });
+            });
 
-                    ui.add_space(30.0);
+        if close {
+            self.window.find = None;
+        }
+    }
 
-                    ui.label(egui::RichText::new("This is a placeholder for web content.")
-                        .size(14.0)
-                        .color(egui::Color32::from_rgb(156, 163, 175)));
+    /// Render the content area based on current URL
+    fn render_content(&mut self, ui: &mut egui::Ui) {
+        let url = &self.window.tab_manager.active_tab().url.clone();
+
+        if let Some(error) = self.window.tab_manager.active_tab().error {
+            self.render_error_page(ui, error);
+        } else if let Some(page) = InternalPage::parse(url) {
+            self.render_internal_page(ui, page);
+        } else if !self.security_policy.allow_resource(url, None) {
+            self.render_blocked_page(ui, url);
+        } else if let Some(article) = self
+            .reader_article()
+            .filter(|_| self.window.tab_manager.active_tab().reader_mode)
+        {
+            self.render_reader_view(ui, &article);
+        } else {
+            self.render_web_page(ui, url);
+        }
+    }
 
-                    ui.add_space(10.0);
+    /// Render a built-in `about:` page
+    fn render_internal_page(&mut self, ui: &mut egui::Ui, page: InternalPage) {
+        match page {
+            InternalPage::Home => self.render_home_page(ui),
+            InternalPage::Settings => self.render_settings_page(ui),
+            InternalPage::Blank => self.render_blank_page(ui),
+            InternalPage::Downloads => self.render_downloads_page(ui),
+            InternalPage::History => self.render_history_page(ui),
+            InternalPage::Bookmarks => self.render_bookmarks_page(ui),
+            InternalPage::Source(target) => self.render_page_source(ui, &target),
+            InternalPage::Unknown(name) => self.render_unknown_page(ui, &name),
+        }
+    }
 
-                    ui.label("In a full implementation, this area would display");
-                    ui.label("the rendered web page using a WebView component.");
+    /// Render the downloads page, with controls for each download
+    fn render_downloads_page(&mut self, ui: &mut egui::Ui) {
+        let palette = self.theme.palette().clone();
+        let mut action = None;
+        let mut clear = false;
 
-                    ui.add_space(30.0);
+        render_page_banner(ui, &palette, "⬇ Downloads", "Files you have downloaded");
+        let list = self.downloads.as_ref().map(DownloadManager::list);
+        page_body_frame().show(ui, |ui| match list {
+            None => {
+                ui.label(
+                    egui::RichText::new("Downloads are not available.")
+                        .size(13.0)
+                        .color(palette.text_secondary.to_egui()),
+                );
+            }
+            Some(list) if list.is_empty() => {
+                ui.label(
+                    egui::RichText::new("No downloads yet.")
+                        .size(13.0)
+                        .color(palette.text_secondary.to_egui()),
+                );
+            }
+            Some(list) => {
+                let any_completed = list.iter().any(|d| d.state == DownloadState::Completed);
+                let button = egui::Button::new("Clear completed");
+                clear = ui.add_enabled(any_completed, button).clicked();
+                ui.separator();
+                action = render_download_rows(ui, &list, &palette);
+            }
+        });
 
-                    // Simulated content
-                    egui::Frame::group(ui.style())
-                        .fill(egui::Color32::from_rgb(22, 27, 34))
-                        .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(48, 54, 61)))
-                        .inner_margin(egui::Margin::same(20.0))
-                        .show(ui, |ui| {
-                            ui.label(egui::RichText::new("Simulated Web Content")
-                                .size(18.0)
-                                .strong());
-                            ui.add_space(10.0);
-                            ui.label("Lorem ipsum dolor sit amet, consectetur adipiscing elit.");
-                            ui.label("Sed do eiusmod tempor incididunt ut labore et dolore magna aliqua.");
-                            ui.add_space(10.0);
-                            ui.label("• List item 1");
-                            ui.label("• List item 2");
-                            ui.label("• List item 3");
-                        });
-                });
-            });
+        if let Some((id, action)) = action {
+            self.apply_download_action(&id, action);
+        }
+        if clear {
+            self.clear_completed_downloads();
+        }
+    }
+
+    /// Render the history page, most recent first and grouped by day
+    fn render_history_page(&mut self, ui: &mut egui::Ui) {
+        let palette = self.theme.palette().clone();
+        let mut action = None;
+        let mut hovered_url = None;
+        let mut clear = None;
+
+        render_page_banner(ui, &palette, "🕐 History", "Pages you have visited");
+        page_body_frame().show(ui, |ui| {
+            clear = render_history_controls(ui, &mut self.window.history_query, &palette);
+            ui.separator();
+            let days = self
+                .window
+                .history_list
+                .days(&self.history, &self.window.history_query);
+            action = render_history_list(ui, days, &palette, &mut hovered_url);
         });
+
+        if let Some(window) = clear {
+            self.clear_history_window(window);
+        }
+        if self.window.status.hover_in_page(hovered_url) {
+            ui.ctx().request_repaint();
+        }
+        if let Some(action) = action {
+            self.apply_history_row_action(action);
+        }
     }
 
-    /// Render the content area based on current URL
-    fn render_content(&mut self, ui: &mut egui::Ui) {
-        let url = &self.tab_manager.active_tab().url.clone();
-
-        if url == "about:settings" {
-            self.render_settings_page(ui);
-        } else if url == "about:home" {
-            self.render_home_page(ui);
-        } else if url == "about:blank" {
-            self.render_blank_page(ui);
-        } else {
-            self.render_web_page(ui, url);
+    /// Render the bookmarks page, grouped by folder
+    fn render_bookmarks_page(&mut self, ui: &mut egui::Ui) {
+        let palette = self.theme.palette().clone();
+        let mut action = None;
+        let mut hovered_url = None;
+
+        render_page_banner(ui, &palette, "⭐ Bookmarks", "Pages you have saved");
+        page_body_frame().show(ui, |ui| {
+            action = render_bookmark_list(ui, &self.bookmarks, &palette, &mut hovered_url);
+        });
+
+        if self.window.status.hover_in_page(hovered_url) {
+            ui.ctx().request_repaint();
         }
+        if let Some(action) = action {
+            self.apply_bookmark_row_action(action);
+        }
+    }
+
+    /// Render the page shown for an `about:` page that doesn't exist
+    fn render_unknown_page(&self, ui: &mut egui::Ui, name: &str) {
+        let palette = self.theme.palette().clone();
+        ui.vertical_centered(|ui| {
+            ui.add_space(200.0);
+            ui.label(
+                egui::RichText::new("⚠ Page not found")
+                    .size(24.0)
+                    .color(palette.text_primary.to_egui()),
+            );
+            ui.add_space(10.0);
+            ui.label(
+                egui::RichText::new(unknown_page_message(name))
+                    .size(14.0)
+                    .color(palette.text_secondary.to_egui()),
+            );
+        });
     }
 
     /// Render the settings page with Firefox-inspired layout
     fn render_settings_page(&mut self, ui: &mut egui::Ui) {
+        let palette = self.theme.palette().clone();
         ui.vertical(|ui| {
             // Top banner with Horizon branding (Firefox-inspired styling)
             egui::Frame::none()
-                .fill(egui::Color32::from_rgb(31, 41, 51)) // Toolbar color
+                .fill(palette.bg_toolbar.to_egui()) // Toolbar color
                 .inner_margin(egui::Margin::symmetric(20.0, 16.0))
                 .show(ui, |ui| {
                     ui.heading(
                         egui::RichText::new("🌅 Horizon Settings")
                             .size(28.0)
                             .strong()
-                            .color(egui::Color32::from_rgb(59, 130, 246)), // Firefox blue
+                            .color(palette.accent.to_egui()), // Firefox blue
                     );
                     ui.add_space(4.0);
                     ui.label(
                         egui::RichText::new("Configure your browser experience")
                             .size(14.0)
-                            .color(egui::Color32::from_rgb(156, 163, 175)), // Secondary
+                            .color(palette.text_secondary.to_egui()), // Secondary
                     );
                 });
 
@@ -493,7 +2896,7 @@ impl BrowserApp {
                     .resizable(false)
                     .frame(
                         egui::Frame::none()
-                            .fill(egui::Color32::from_rgb(31, 41, 51)) // Toolbar color
+                            .fill(palette.bg_toolbar.to_egui()) // Toolbar color
                             .inner_margin(egui::Margin::same(16.0)),
                     )
                     .show_inside(ui, |ui| {
@@ -504,6 +2907,7 @@ impl BrowserApp {
 
                         Self::render_settings_nav_item(
                             ui,
+                            &palette,
                             selected,
                             crate::settings::SettingsPanel::General,
                             "🏠",
@@ -511,6 +2915,7 @@ impl BrowserApp {
                         );
                         Self::render_settings_nav_item(
                             ui,
+                            &palette,
                             selected,
                             crate::settings::SettingsPanel::Privacy,
                             "🔒",
@@ -518,6 +2923,7 @@ impl BrowserApp {
                         );
                         Self::render_settings_nav_item(
                             ui,
+                            &palette,
                             selected,
                             crate::settings::SettingsPanel::Appearance,
                             "🎨",
@@ -525,6 +2931,7 @@ impl BrowserApp {
                         );
                         Self::render_settings_nav_item(
                             ui,
+                            &palette,
                             selected,
                             crate::settings::SettingsPanel::Network,
                             "🌐",
@@ -532,6 +2939,7 @@ impl BrowserApp {
                         );
                         Self::render_settings_nav_item(
                             ui,
+                            &palette,
                             selected,
                             crate::settings::SettingsPanel::Passwords,
                             "🔑",
@@ -539,6 +2947,7 @@ impl BrowserApp {
                         );
                         Self::render_settings_nav_item(
                             ui,
+                            &palette,
                             selected,
                             crate::settings::SettingsPanel::Extensions,
                             "🧩",
@@ -546,6 +2955,7 @@ impl BrowserApp {
                         );
                         Self::render_settings_nav_item(
                             ui,
+                            &palette,
                             selected,
                             crate::settings::SettingsPanel::Downloads,
                             "📥",
@@ -553,6 +2963,7 @@ impl BrowserApp {
                         );
                         Self::render_settings_nav_item(
                             ui,
+                            &palette,
                             selected,
                             crate::settings::SettingsPanel::Advanced,
                             "🔧",
@@ -610,15 +3021,31 @@ impl BrowserApp {
                                                     egui::RichText::new("💾 Save Settings")
                                                         .size(16.0),
                                                 )
-                                                .fill(egui::Color32::from_rgb(59, 130, 246)) // Firefox blue
+                                                .fill(palette.accent.to_egui()) // Firefox blue
                                                 .rounding(egui::Rounding::same(4.0)) // Firefox 4px
                                                 .min_size(egui::vec2(150.0, 40.0)),
                                             )
                                             .clicked()
                                         {
-                                            self.settings.save();
+                                            match self.settings.validate() {
+                                                Ok(()) => {
+                                                    self.settings.save();
+                                                    self.settings_errors.clear();
+                                                    self.apply_network_settings();
+                                                }
+                                                Err(errors) => self.settings_errors = errors,
+                                            }
                                         }
                                     });
+
+                                    // Problems that kept the settings from being saved
+                                    for error in &self.settings_errors {
+                                        ui.label(
+                                            egui::RichText::new(format!("⚠ {}", error))
+                                                .size(13.0)
+                                                .color(palette.error.to_egui()),
+                                        );
+                                    }
                                 });
                             });
                         });
@@ -630,6 +3057,7 @@ impl BrowserApp {
     /// Render a settings navigation item with Firefox styling
     fn render_settings_nav_item(
         ui: &mut egui::Ui,
+        palette: &crate::theme::ColorPalette,
         selected: &mut crate::settings::SettingsPanel,
         panel: crate::settings::SettingsPanel,
         icon: &str,
@@ -640,12 +3068,12 @@ impl BrowserApp {
         let button =
             egui::Button::new(egui::RichText::new(format!("{} {}", icon, label)).size(15.0))
                 .fill(if is_selected {
-                    egui::Color32::from_rgb(17, 24, 39) // Window background for selection
+                    palette.bg_window.to_egui() // Window background for selection
                 } else {
                     egui::Color32::TRANSPARENT
                 })
                 .stroke(if is_selected {
-                    egui::Stroke::new(2.0, egui::Color32::from_rgb(59, 130, 246)) // Firefox blue
+                    egui::Stroke::new(2.0, palette.accent.to_egui()) // Firefox blue
                 } else {
                     egui::Stroke::NONE
                 })
@@ -660,11 +3088,12 @@ impl BrowserApp {
 
     /// Render general settings panel with Firefox styling
     fn render_general_settings(&mut self, ui: &mut egui::Ui) {
+        let palette = self.theme.palette().clone();
         ui.heading(
             egui::RichText::new("General Settings")
                 .size(24.0)
                 .strong()
-                .color(egui::Color32::from_rgb(249, 250, 251)), // Primary text
+                .color(palette.text_primary.to_egui()), // Primary text
         );
         ui.add_space(8.0);
         ui.separator();
@@ -672,8 +3101,8 @@ impl BrowserApp {
 
         // Settings section
         egui::Frame::none()
-            .fill(egui::Color32::from_rgb(31, 41, 51)) // Toolbar color
-            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(55, 65, 81)))
+            .fill(palette.bg_toolbar.to_egui()) // Toolbar color
+            .stroke(egui::Stroke::new(1.0, palette.border_subtle.to_egui()))
             .inner_margin(egui::Margin::same(20.0))
             .rounding(egui::Rounding::same(6.0)) // Firefox 6px
             .show(ui, |ui| {
@@ -681,22 +3110,22 @@ impl BrowserApp {
                     egui::RichText::new("Homepage URL")
                         .size(16.0)
                         .strong()
-                        .color(egui::Color32::from_rgb(249, 250, 251)),
+                        .color(palette.text_primary.to_egui()),
                 );
                 ui.add_space(8.0);
                 ui.text_edit_singleline(&mut self.settings.general.homepage);
                 ui.label(
                     egui::RichText::new("The page that opens when you start the browser")
                         .size(12.0)
-                        .color(egui::Color32::from_rgb(156, 163, 175)), // Secondary
+                        .color(palette.text_secondary.to_egui()), // Secondary
                 );
             });
 
         ui.add_space(16.0);
 
         egui::Frame::none()
-            .fill(egui::Color32::from_rgb(31, 41, 51))
-            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(55, 65, 81)))
+            .fill(palette.bg_toolbar.to_egui())
+            .stroke(egui::Stroke::new(1.0, palette.border_subtle.to_egui()))
             .inner_margin(egui::Margin::same(20.0))
             .rounding(egui::Rounding::same(6.0))
             .show(ui, |ui| {
@@ -704,7 +3133,7 @@ impl BrowserApp {
                     egui::RichText::new("Default Search Engine")
                         .size(16.0)
                         .strong()
-                        .color(egui::Color32::from_rgb(249, 250, 251)),
+                        .color(palette.text_primary.to_egui()),
                 );
                 ui.add_space(8.0);
                 egui::ComboBox::from_label("")
@@ -713,7 +3142,7 @@ impl BrowserApp {
                         for engine in crate::settings::SearchEngine::all() {
                             ui.selectable_value(
                                 &mut self.settings.general.search_engine,
-                                *engine,
+                                engine.clone(),
                                 engine.name(),
                             );
                         }
@@ -721,15 +3150,56 @@ impl BrowserApp {
                 ui.label(
                     egui::RichText::new("Choose your preferred search engine")
                         .size(12.0)
-                        .color(egui::Color32::from_rgb(156, 163, 175)),
+                        .color(palette.text_secondary.to_egui()),
+                );
+                ui.add_space(12.0);
+                ui.label(
+                    egui::RichText::new("Custom search engine")
+                        .size(15.0)
+                        .color(palette.text_primary.to_egui()),
+                );
+                ui.add_space(4.0);
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.custom_engine_name)
+                        .hint_text("Name")
+                        .desired_width(320.0),
+                );
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.custom_engine_template)
+                        .hint_text("https://example.com/search?q={query}")
+                        .desired_width(320.0),
+                );
+                if ui.button("Use custom engine").clicked() {
+                    match crate::settings::SearchEngine::custom(
+                        &self.custom_engine_name,
+                        &self.custom_engine_template,
+                    ) {
+                        Ok(engine) => {
+                            self.settings.general.search_engine = engine;
+                            self.custom_engine_error = None;
+                        }
+                        Err(e) => self.custom_engine_error = Some(e.to_string()),
+                    }
+                }
+                if let Some(error) = &self.custom_engine_error {
+                    ui.label(
+                        egui::RichText::new(error)
+                            .size(12.0)
+                            .color(palette.error.to_egui()),
+                    );
+                }
+                ui.label(
+                    egui::RichText::new("{query} in the URL is replaced by your search terms")
+                        .size(12.0)
+                        .color(palette.text_secondary.to_egui()),
                 );
             });
 
         ui.add_space(16.0);
 
         egui::Frame::none()
-            .fill(egui::Color32::from_rgb(31, 41, 51))
-            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(55, 65, 81)))
+            .fill(palette.bg_toolbar.to_egui())
+            .stroke(egui::Stroke::new(1.0, palette.border_subtle.to_egui()))
             .inner_margin(egui::Margin::same(20.0))
             .rounding(egui::Rounding::same(6.0))
             .show(ui, |ui| {
@@ -737,23 +3207,61 @@ impl BrowserApp {
                     &mut self.settings.general.restore_tabs_on_startup,
                     egui::RichText::new("Restore tabs on startup")
                         .size(15.0)
-                        .color(egui::Color32::from_rgb(249, 250, 251)),
+                        .color(palette.text_primary.to_egui()),
                 );
                 ui.add_space(4.0);
                 ui.label(
                     egui::RichText::new("Reopen tabs from your last session")
                         .size(12.0)
-                        .color(egui::Color32::from_rgb(156, 163, 175)),
+                        .color(palette.text_secondary.to_egui()),
+                );
+                ui.add_space(12.0);
+                ui.checkbox(
+                    &mut self.settings.general.select_left_on_close,
+                    egui::RichText::new("Select the tab to the left when closing a tab")
+                        .size(15.0)
+                        .color(palette.text_primary.to_egui()),
+                );
+                ui.add_space(4.0);
+                ui.label(
+                    egui::RichText::new("Otherwise the tab to the right is selected (Ctrl+W)")
+                        .size(12.0)
+                        .color(palette.text_secondary.to_egui()),
+                );
+                ui.add_space(12.0);
+                let mut hover_switch = self.settings.general.hover_switch_ms.is_some();
+                if ui
+                    .checkbox(
+                        &mut hover_switch,
+                        egui::RichText::new("Switch to a tab when hovering over it")
+                            .size(15.0)
+                            .color(palette.text_primary.to_egui()),
+                    )
+                    .changed()
+                {
+                    self.settings.general.hover_switch_ms =
+                        hover_switch.then_some(DEFAULT_HOVER_SWITCH_MS);
+                }
+                if let Some(delay_ms) = self.settings.general.hover_switch_ms.as_mut() {
+                    ui.add_space(4.0);
+                    ui.add(egui::Slider::new(delay_ms, 100..=2000).suffix(" ms"));
+                }
+                ui.add_space(4.0);
+                ui.label(
+                    egui::RichText::new("Activate a tab after the pointer rests on it")
+                        .size(12.0)
+                        .color(palette.text_secondary.to_egui()),
                 );
             });
     }
 
     /// Render privacy settings panel
     fn render_privacy_settings(&mut self, ui: &mut egui::Ui) {
+        let palette = self.theme.palette().clone();
         ui.heading(
             egui::RichText::new("Privacy & Security")
                 .size(18.0)
-                .color(egui::Color32::from_rgb(249, 250, 251)),
+                .color(palette.text_primary.to_egui()),
         );
         ui.add_space(10.0);
 
@@ -764,7 +3272,7 @@ impl BrowserApp {
         ui.label(
             egui::RichText::new("Blocks known trackers from loading")
                 .size(12.0)
-                .color(egui::Color32::from_rgb(156, 163, 175)),
+                .color(palette.text_secondary.to_egui()),
         );
         ui.add_space(8.0);
 
@@ -772,7 +3280,7 @@ impl BrowserApp {
         ui.label(
             egui::RichText::new("Tells websites you don't want to be tracked")
                 .size(12.0)
-                .color(egui::Color32::from_rgb(156, 163, 175)),
+                .color(palette.text_secondary.to_egui()),
         );
         ui.add_space(8.0);
 
@@ -783,7 +3291,7 @@ impl BrowserApp {
         ui.label(
             egui::RichText::new("Prevents cross-site tracking")
                 .size(12.0)
-                .color(egui::Color32::from_rgb(156, 163, 175)),
+                .color(palette.text_secondary.to_egui()),
         );
         ui.add_space(8.0);
 
@@ -794,7 +3302,7 @@ impl BrowserApp {
         ui.label(
             egui::RichText::new("Only connect to secure HTTPS websites")
                 .size(12.0)
-                .color(egui::Color32::from_rgb(156, 163, 175)),
+                .color(palette.text_secondary.to_egui()),
         );
         ui.add_space(8.0);
 
@@ -805,25 +3313,48 @@ impl BrowserApp {
         ui.label(
             egui::RichText::new("Clears cookies, cache, and history when closing the browser")
                 .size(12.0)
-                .color(egui::Color32::from_rgb(156, 163, 175)),
+                .color(palette.text_secondary.to_egui()),
         );
+        ui.add_space(8.0);
+
+        let cookie_note = if self.cookies.is_persistent() {
+            format!("{} cookies stored", self.cookies.cookies().len())
+        } else {
+            "Cookies in this private window are deleted when it closes".to_string()
+        };
+        ui.horizontal(|ui| {
+            ui.label(
+                egui::RichText::new(cookie_note)
+                    .size(12.0)
+                    .color(palette.text_secondary.to_egui()),
+            );
+            if ui.button("Clear cookies").clicked() {
+                self.cookies.clear();
+                if self.cookies.is_persistent() {
+                    if let Err(e) = self.cookies.save_to_storage() {
+                        tracing::error!("Failed to save cookies: {}", e);
+                    }
+                }
+            }
+        });
     }
 
     /// Render appearance settings panel with Firefox styling
     fn render_appearance_settings(&mut self, ui: &mut egui::Ui) {
+        let palette = self.theme.palette().clone();
         ui.heading(
             egui::RichText::new("Appearance")
                 .size(24.0)
                 .strong()
-                .color(egui::Color32::from_rgb(249, 250, 251)),
+                .color(palette.text_primary.to_egui()),
         );
         ui.add_space(8.0);
         ui.separator();
         ui.add_space(20.0);
 
         egui::Frame::none()
-            .fill(egui::Color32::from_rgb(31, 41, 51))
-            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(55, 65, 81)))
+            .fill(palette.bg_toolbar.to_egui())
+            .stroke(egui::Stroke::new(1.0, palette.border_subtle.to_egui()))
             .inner_margin(egui::Margin::same(20.0))
             .rounding(egui::Rounding::same(6.0))
             .show(ui, |ui| {
@@ -831,32 +3362,55 @@ impl BrowserApp {
                     egui::RichText::new("Theme")
                         .size(16.0)
                         .strong()
-                        .color(egui::Color32::from_rgb(249, 250, 251)),
+                        .color(palette.text_primary.to_egui()),
                 );
                 ui.add_space(8.0);
+                let appearance = &mut self.settings.appearance;
+                let selected = appearance
+                    .custom_theme
+                    .clone()
+                    .unwrap_or_else(|| appearance.theme.name().to_string());
                 egui::ComboBox::from_label("")
-                    .selected_text(self.settings.appearance.theme.name())
+                    .selected_text(&selected)
                     .show_ui(ui, |ui| {
                         for theme in crate::settings::Theme::all() {
-                            ui.selectable_value(
-                                &mut self.settings.appearance.theme,
-                                *theme,
-                                theme.name(),
-                            );
+                            let is_selected =
+                                appearance.custom_theme.is_none() && appearance.theme == *theme;
+                            if ui.selectable_label(is_selected, theme.name()).clicked() {
+                                appearance.theme = *theme;
+                                appearance.custom_theme = None;
+                            }
+                        }
+                        // The built-in Dark and Light themes are listed above
+                        for name in self.themes.names() {
+                            if matches!(name, "Dark" | "Light") {
+                                continue;
+                            }
+                            let is_selected = appearance.custom_theme.as_deref() == Some(name);
+                            if ui.selectable_label(is_selected, name).clicked() {
+                                appearance.custom_theme = Some(name.to_string());
+                            }
                         }
                     });
+                let hint = if self.settings.appearance.custom_theme.is_some() {
+                    "Custom theme from the themes folder".to_string()
+                } else if self.settings.appearance.theme == crate::settings::Theme::Auto {
+                    format!("Following the system theme ({})", self.active_theme.name())
+                } else {
+                    "Switch between dark and light themes".to_string()
+                };
                 ui.label(
-                    egui::RichText::new("Switch between dark and light themes")
+                    egui::RichText::new(hint)
                         .size(12.0)
-                        .color(egui::Color32::from_rgb(156, 163, 175)),
+                        .color(palette.text_secondary.to_egui()),
                 );
             });
 
         ui.add_space(16.0);
 
         egui::Frame::none()
-            .fill(egui::Color32::from_rgb(31, 41, 51))
-            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(55, 65, 81)))
+            .fill(palette.bg_toolbar.to_egui())
+            .stroke(egui::Stroke::new(1.0, palette.border_subtle.to_egui()))
             .inner_margin(egui::Margin::same(20.0))
             .rounding(egui::Rounding::same(6.0))
             .show(ui, |ui| {
@@ -864,25 +3418,28 @@ impl BrowserApp {
                     egui::RichText::new("Font Size")
                         .size(16.0)
                         .strong()
-                        .color(egui::Color32::from_rgb(249, 250, 251)),
+                        .color(palette.text_primary.to_egui()),
                 );
                 ui.add_space(8.0);
                 ui.add(
-                    egui::Slider::new(&mut self.settings.appearance.font_size, 10..=20)
-                        .suffix(" px"),
+                    egui::Slider::new(
+                        &mut self.settings.appearance.font_size,
+                        crate::settings::MIN_FONT_SIZE..=crate::settings::MAX_FONT_SIZE,
+                    )
+                    .suffix(" px"),
                 );
                 ui.label(
                     egui::RichText::new("Adjust the size of text in the browser")
                         .size(12.0)
-                        .color(egui::Color32::from_rgb(156, 163, 175)),
+                        .color(palette.text_secondary.to_egui()),
                 );
             });
 
         ui.add_space(16.0);
 
         egui::Frame::none()
-            .fill(egui::Color32::from_rgb(31, 41, 51))
-            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(55, 65, 81)))
+            .fill(palette.bg_toolbar.to_egui())
+            .stroke(egui::Stroke::new(1.0, palette.border_subtle.to_egui()))
             .inner_margin(egui::Margin::same(20.0))
             .rounding(egui::Rounding::same(6.0))
             .show(ui, |ui| {
@@ -890,23 +3447,24 @@ impl BrowserApp {
                     &mut self.settings.appearance.show_bookmarks_bar,
                     egui::RichText::new("Show bookmarks bar")
                         .size(15.0)
-                        .color(egui::Color32::from_rgb(249, 250, 251)),
+                        .color(palette.text_primary.to_egui()),
                 );
                 ui.add_space(4.0);
                 ui.label(
                     egui::RichText::new("Display a toolbar with quick access to bookmarks")
                         .size(12.0)
-                        .color(egui::Color32::from_rgb(156, 163, 175)),
+                        .color(palette.text_secondary.to_egui()),
                 );
             });
     }
 
     /// Render downloads settings panel
     fn render_downloads_settings(&mut self, ui: &mut egui::Ui) {
+        let palette = self.theme.palette().clone();
         ui.heading(
             egui::RichText::new("Downloads")
                 .size(18.0)
-                .color(egui::Color32::from_rgb(249, 250, 251)),
+                .color(palette.text_primary.to_egui()),
         );
         ui.add_space(10.0);
 
@@ -922,10 +3480,11 @@ impl BrowserApp {
 
     /// Render advanced settings panel
     fn render_advanced_settings(&mut self, ui: &mut egui::Ui) {
+        let palette = self.theme.palette().clone();
         ui.heading(
             egui::RichText::new("Advanced")
                 .size(18.0)
-                .color(egui::Color32::from_rgb(249, 250, 251)),
+                .color(palette.text_primary.to_egui()),
         );
         ui.add_space(10.0);
 
@@ -934,9 +3493,9 @@ impl BrowserApp {
             "Enable developer tools",
         );
         ui.label(
-            egui::RichText::new("Enables debugging and inspection features")
+            egui::RichText::new("Enables debugging and inspection features, like the FPS counter")
                 .size(12.0)
-                .color(egui::Color32::from_rgb(156, 163, 175)),
+                .color(palette.text_secondary.to_egui()),
         );
         ui.add_space(8.0);
 
@@ -947,7 +3506,18 @@ impl BrowserApp {
         ui.label(
             egui::RichText::new("Improves rendering performance")
                 .size(12.0)
-                .color(egui::Color32::from_rgb(156, 163, 175)),
+                .color(palette.text_secondary.to_egui()),
+        );
+        ui.add_space(8.0);
+
+        ui.checkbox(
+            &mut self.settings.advanced.preconnect_on_startup,
+            "Preconnect to the homepage and search engine on startup",
+        );
+        ui.label(
+            egui::RichText::new("Makes the first navigation faster by resolving DNS early")
+                .size(12.0)
+                .color(palette.text_secondary.to_egui()),
         );
         ui.add_space(8.0);
 
@@ -958,16 +3528,129 @@ impl BrowserApp {
         ui.label(
             egui::RichText::new("Try new features before they're officially released")
                 .size(12.0)
-                .color(egui::Color32::from_rgb(156, 163, 175)),
+                .color(palette.text_secondary.to_egui()),
+        );
+        ui.add_space(16.0);
+
+        ui.label(
+            egui::RichText::new("Keyboard Shortcuts")
+                .size(15.0)
+                .color(palette.text_primary.to_egui()),
+        );
+        ui.add_space(4.0);
+        egui::Grid::new("keyboard_shortcuts")
+            .num_columns(3)
+            .spacing([16.0, 6.0])
+            .show(ui, |ui| {
+                for action in BrowserAction::all() {
+                    let bindings: Vec<String> = self
+                        .settings
+                        .keymap
+                        .bindings(*action)
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect();
+                    ui.label(action.name());
+                    ui.label(
+                        egui::RichText::new(if bindings.is_empty() {
+                            "None".to_string()
+                        } else {
+                            bindings.join(", ")
+                        })
+                        .monospace()
+                        .color(palette.text_secondary.to_egui()),
+                    );
+                    if self.recording_shortcut == Some(*action) {
+                        ui.label(
+                            egui::RichText::new(
+                                "Press a shortcut with Ctrl, Alt or an F key… (Esc to cancel)",
+                            )
+                            .color(palette.accent_hover().to_egui()),
+                        );
+                    } else if ui.button("Change").clicked() {
+                        self.recording_shortcut = Some(*action);
+                    }
+                    ui.end_row();
+                }
+            });
+        if ui.button("Reset Shortcuts").clicked() {
+            self.settings.keymap = crate::keymap::Keymap::default();
+            self.recording_shortcut = None;
+        }
+        ui.label(
+            egui::RichText::new("Ctrl+1 to Ctrl+9 always switch tabs by number")
+                .size(12.0)
+                .color(palette.text_secondary.to_egui()),
+        );
+        ui.add_space(16.0);
+
+        ui.label(
+            egui::RichText::new("Import & Export")
+                .size(15.0)
+                .color(palette.text_primary.to_egui()),
+        );
+        ui.add_space(4.0);
+        ui.add(
+            egui::TextEdit::singleline(&mut self.settings_transfer_path)
+                .hint_text("Settings file")
+                .desired_width(400.0),
+        );
+        ui.horizontal(|ui| {
+            let path = std::path::PathBuf::from(self.settings_transfer_path.trim());
+            if ui.button("Export Settings").clicked() {
+                self.settings_transfer_status = Some(
+                    self.settings
+                        .export(&path)
+                        .map(|()| format!("Exported settings to {}", path.display()))
+                        .map_err(|e| format!("Export failed: {}", e)),
+                );
+            }
+            if ui.button("Import Settings").clicked() {
+                self.settings_transfer_status = Some(match self.settings.import(&path) {
+                    Ok(()) => {
+                        self.settings.save();
+                        self.settings_errors.clear();
+                        (self.custom_engine_name, self.custom_engine_template) =
+                            custom_engine_fields(&self.settings.general.search_engine);
+                        self.custom_engine_error = None;
+                        self.apply_network_settings();
+                        Ok(format!("Imported settings from {}", path.display()))
+                    }
+                    Err(e) => Err(format!("Import failed: {}", e)),
+                });
+            }
+        });
+        match &self.settings_transfer_status {
+            Some(Ok(message)) => {
+                ui.label(
+                    egui::RichText::new(message)
+                        .size(12.0)
+                        .color(palette.text_secondary.to_egui()),
+                );
+            }
+            Some(Err(message)) => {
+                ui.label(
+                    egui::RichText::new(message)
+                        .size(12.0)
+                        .color(palette.error.to_egui()),
+                );
+            }
+            None => {}
+        }
+        ui.label(
+            egui::RichText::new("Move your settings between machines as a single TOML file")
+                .size(12.0)
+                .color(palette.text_secondary.to_egui()),
         );
     }
 
     /// Render network settings panel
     fn render_network_settings(&mut self, ui: &mut egui::Ui) {
+        let palette = self.theme.palette().clone();
         ui.heading(
             egui::RichText::new("Network & VPN")
                 .size(18.0)
-                .color(egui::Color32::from_rgb(249, 250, 251)),
+                .color(palette.text_primary.to_egui()),
         );
         ui.add_space(10.0);
 
@@ -990,7 +3673,7 @@ impl BrowserApp {
         ui.label(
             egui::RichText::new("Changes take effect immediately")
                 .size(12.0)
-                .color(egui::Color32::from_rgb(156, 163, 175)),
+                .color(palette.text_secondary.to_egui()),
         );
         ui.add_space(8.0);
 
@@ -1000,7 +3683,7 @@ impl BrowserApp {
             ui.label(
                 egui::RichText::new("Example: 1.1.1.1, 8.8.8.8")
                     .size(12.0)
-                    .color(egui::Color32::from_rgb(156, 163, 175)),
+                    .color(palette.text_secondary.to_egui()),
             );
             ui.add_space(8.0);
         }
@@ -1016,7 +3699,7 @@ impl BrowserApp {
         ui.label(
             egui::RichText::new("Route all browser traffic through VPN")
                 .size(12.0)
-                .color(egui::Color32::from_rgb(156, 163, 175)),
+                .color(palette.text_secondary.to_egui()),
         );
         ui.add_space(8.0);
 
@@ -1051,7 +3734,7 @@ impl BrowserApp {
                 crate::settings::VpnType::OpenVpn => {
                     ui.label(
                         egui::RichText::new("Upload .ovpn file to configure OpenVPN")
-                            .color(egui::Color32::from_rgb(88, 166, 255)),
+                            .color(palette.accent_hover().to_egui()),
                     );
                     if ui.button("📁 Select .ovpn File").clicked() {
                         // File picker would be implemented here
@@ -1080,16 +3763,17 @@ impl BrowserApp {
         ui.label(
             egui::RichText::new("Test your connection's download/upload speed and latency")
                 .size(12.0)
-                .color(egui::Color32::from_rgb(156, 163, 175)),
+                .color(palette.text_secondary.to_egui()),
         );
     }
 
     /// Render passwords settings panel
     fn render_passwords_settings(&mut self, ui: &mut egui::Ui) {
+        let palette = self.theme.palette().clone();
         ui.heading(
             egui::RichText::new("Saved Passwords")
                 .size(18.0)
-                .color(egui::Color32::from_rgb(249, 250, 251)),
+                .color(palette.text_primary.to_egui()),
         );
         ui.add_space(10.0);
 
@@ -1107,8 +3791,8 @@ impl BrowserApp {
 
         // Password list (placeholder)
         egui::Frame::none()
-            .fill(egui::Color32::from_rgb(22, 27, 34))
-            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(48, 54, 61)))
+            .fill(palette.bg_toolbar.to_egui())
+            .stroke(egui::Stroke::new(1.0, palette.border_subtle.to_egui()))
             .inner_margin(egui::Margin::same(15.0))
             .rounding(egui::Rounding::same(6.0))
             .show(ui, |ui| {
@@ -1118,13 +3802,13 @@ impl BrowserApp {
                     ui.label(
                         egui::RichText::new("No saved passwords yet")
                             .size(16.0)
-                            .color(egui::Color32::from_rgb(156, 163, 175)),
+                            .color(palette.text_secondary.to_egui()),
                     );
                     ui.add_space(5.0);
                     ui.label(
                         egui::RichText::new("Passwords will appear here when you save them")
                             .size(13.0)
-                            .color(egui::Color32::from_rgb(156, 163, 175)),
+                            .color(palette.text_secondary.to_egui()),
                     );
                 });
             });
@@ -1137,12 +3821,22 @@ impl BrowserApp {
 
         // Note: These are placeholder checkboxes for MVP. In full implementation,
         // these would be connected to settings storage and password manager state.
-        let mut save_passwords = true;
-        ui.checkbox(&mut save_passwords, "Offer to save passwords");
+        // Private windows never offer to save passwords.
+        let private = self.window.tab_manager.is_private();
+        let mut save_passwords = !private;
+        ui.add_enabled(
+            !private,
+            egui::Checkbox::new(&mut save_passwords, "Offer to save passwords"),
+        );
+        let save_note = if private {
+            "Passwords are never saved in private windows"
+        } else {
+            "Ask before saving passwords for websites (Coming soon)"
+        };
         ui.label(
-            egui::RichText::new("Ask before saving passwords for websites (Coming soon)")
+            egui::RichText::new(save_note)
                 .size(12.0)
-                .color(egui::Color32::from_rgb(156, 163, 175)),
+                .color(palette.text_secondary.to_egui()),
         );
         ui.add_space(8.0);
 
@@ -1151,7 +3845,7 @@ impl BrowserApp {
         ui.label(
             egui::RichText::new("Automatically fill in saved passwords (Coming soon)")
                 .size(12.0)
-                .color(egui::Color32::from_rgb(156, 163, 175)),
+                .color(palette.text_secondary.to_egui()),
         );
         ui.add_space(10.0);
 
@@ -1162,10 +3856,11 @@ impl BrowserApp {
 
     /// Render extensions settings panel
     fn render_extensions_settings(&mut self, ui: &mut egui::Ui) {
+        let palette = self.theme.palette().clone();
         ui.heading(
             egui::RichText::new("Extensions")
                 .size(18.0)
-                .color(egui::Color32::from_rgb(249, 250, 251)),
+                .color(palette.text_primary.to_egui()),
         );
         ui.add_space(10.0);
 
@@ -1193,8 +3888,8 @@ impl BrowserApp {
 
         // Installed extensions (placeholder)
         egui::Frame::none()
-            .fill(egui::Color32::from_rgb(22, 27, 34))
-            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(48, 54, 61)))
+            .fill(palette.bg_toolbar.to_egui())
+            .stroke(egui::Stroke::new(1.0, palette.border_subtle.to_egui()))
             .inner_margin(egui::Margin::same(15.0))
             .rounding(egui::Rounding::same(6.0))
             .show(ui, |ui| {
@@ -1204,13 +3899,13 @@ impl BrowserApp {
                     ui.label(
                         egui::RichText::new("No extensions installed")
                             .size(16.0)
-                            .color(egui::Color32::from_rgb(156, 163, 175)),
+                            .color(palette.text_secondary.to_egui()),
                     );
                     ui.add_space(5.0);
                     ui.label(
                         egui::RichText::new("Install extensions to enhance your browser")
                             .size(13.0)
-                            .color(egui::Color32::from_rgb(156, 163, 175)),
+                            .color(palette.text_secondary.to_egui()),
                     );
                 });
             });
@@ -1232,7 +3927,7 @@ impl BrowserApp {
         ui.label(
             egui::RichText::new("Enable or disable all extensions (Coming soon)")
                 .size(12.0)
-                .color(egui::Color32::from_rgb(156, 163, 175)),
+                .color(palette.text_secondary.to_egui()),
         );
         ui.add_space(8.0);
 
@@ -1241,138 +3936,189 @@ impl BrowserApp {
         ui.label(
             egui::RichText::new("Keep extensions up to date automatically (Coming soon)")
                 .size(12.0)
-                .color(egui::Color32::from_rgb(156, 163, 175)),
+                .color(palette.text_secondary.to_egui()),
         );
     }
 }
 
 impl Default for BrowserApp {
     fn default() -> Self {
-        Self::new()
+        Self::new(false)
     }
 }
 
 impl eframe::App for BrowserApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Apply Firefox-inspired dark theme
-        let mut style = (*ctx.style()).clone();
-        style.visuals = egui::Visuals::dark();
-
-        // Firefox-inspired color scheme: #111827 window, #1F2933 toolbar, #3B82F6 accent
-        style.visuals.widgets.noninteractive.bg_fill = egui::Color32::from_rgb(17, 24, 39);
-        style.visuals.widgets.inactive.bg_fill = egui::Color32::from_rgb(31, 41, 51);
-        style.visuals.widgets.hovered.bg_fill = egui::Color32::from_rgb(55, 65, 81);
-        style.visuals.widgets.active.bg_fill = egui::Color32::from_rgb(59, 130, 246); // Firefox blue
-        style.visuals.extreme_bg_color = egui::Color32::from_rgb(17, 24, 39);
-        style.visuals.window_fill = egui::Color32::from_rgb(17, 24, 39);
-        style.visuals.panel_fill = egui::Color32::from_rgb(17, 24, 39);
-
-        // Firefox-style border radius (4px for buttons/tabs, 6px for inputs)
-        style.visuals.widgets.noninteractive.rounding = egui::Rounding::same(4.0);
-        style.visuals.widgets.inactive.rounding = egui::Rounding::same(4.0);
-        style.visuals.widgets.hovered.rounding = egui::Rounding::same(4.0);
-        style.visuals.widgets.active.rounding = egui::Rounding::same(4.0);
-
-        // Firefox-inspired selection colors
-        style.visuals.selection.bg_fill = egui::Color32::from_rgba_premultiplied(59, 130, 246, 80);
-        style.visuals.selection.stroke =
-            egui::Stroke::new(1.0, egui::Color32::from_rgb(59, 130, 246));
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        // eframe measures the previous frame's update and paint
+        if let Some(seconds) = frame.info().cpu_usage {
+            self.renderer
+                .record_frame(std::time::Duration::from_secs_f32(seconds));
+        }
 
-        ctx.set_style(style);
+        // Closing the main window moves the next window into it rather than
+        // quitting while other windows are open
+        if ctx.input(|i| i.viewport().close_requested()) && !self.windows.is_empty() {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            let mut next = self.windows.remove(0);
+            next.viewport_id = egui::ViewportId::ROOT;
+            self.window = next;
+        }
 
-        // Handle keyboard shortcuts
-        ctx.input(|i| {
-            // Ctrl+T: New tab
-            if i.modifiers.command && i.key_pressed(egui::Key::T) {
-                self.tab_manager.new_tab("about:home");
-                self.url_input = "about:home".to_string();
-            }
+        self.render_window(ctx);
+        self.render_other_windows(ctx);
 
-            // Ctrl+W: Close tab
-            if i.modifiers.command && i.key_pressed(egui::Key::W) {
-                let current_index = self.tab_manager.active_tab_index();
-                if self.tab_manager.tab_count() > 1 {
-                    self.tab_to_close = Some(current_index);
-                }
-            }
+        // Other windows are only reachable once they have all rendered
+        if let Some(viewport_id) = self.merge_into.take() {
+            self.merge_windows(viewport_id);
+        }
+    }
+}
 
-            // Ctrl+R or F5: Reload
-            if (i.modifiers.command && i.key_pressed(egui::Key::R)) || i.key_pressed(egui::Key::F5)
-            {
-                self.tab_manager.active_tab_mut().reload();
+impl BrowserApp {
+    /// Render the browser UI for `self.window` into the current viewport
+    fn render_window(&mut self, ctx: &egui::Context) {
+        self.window.status.begin_frame();
+        self.active_theme = self
+            .settings
+            .appearance
+            .theme
+            .resolve(crate::settings::detect_system_theme(ctx));
+
+        if self.theme.name() != self.theme_name() {
+            if let Some(theme) = self.themes.get(self.theme_name()) {
+                self.theme = theme.clone();
             }
+        }
+        // Rebuilding fonts is expensive, so only do it when the family changes
+        if self.font_family != self.theme.font_family() {
+            let (fonts, text_family) = crate::theme::font_definitions(self.theme.font_family());
+            ctx.set_fonts(fonts);
+            self.text_family = text_family;
+            self.font_family = self.theme.font_family().to_string();
+        }
 
-            // Alt+Left: Back
-            if i.modifiers.alt
-                && i.key_pressed(egui::Key::ArrowLeft)
-                && self.tab_manager.active_tab().can_go_back()
-            {
-                self.tab_manager.active_tab_mut().go_back();
-                self.url_input = self.tab_manager.active_tab().url.clone();
-            }
+        let mut style = crate::theme::build_visuals(&self.theme);
+        style.text_styles = crate::theme::text_styles(
+            crate::theme::body_text_size(
+                self.theme.font_size(),
+                self.settings.appearance.font_size,
+            ),
+            self.text_family.clone(),
+        );
+        ctx.set_style(style);
+        let palette = self.theme.palette().clone();
 
-            // Alt+Right: Forward
-            if i.modifiers.alt
-                && i.key_pressed(egui::Key::ArrowRight)
-                && self.tab_manager.active_tab().can_go_forward()
-            {
-                self.tab_manager.active_tab_mut().go_forward();
-                self.url_input = self.tab_manager.active_tab().url.clone();
+        // Handle keyboard shortcuts
+        if let Some(action) = self.recording_shortcut {
+            // The next key press becomes the action's shortcut, Escape cancels
+            let pressed = ctx.input(|i| {
+                i.events.iter().find_map(|event| match event {
+                    egui::Event::Key {
+                        key,
+                        pressed: true,
+                        modifiers,
+                        ..
+                    } => Some(KeyBinding::from_pressed(*modifiers, *key)),
+                    _ => None,
+                })
+            });
+            // Keys without a modifier are typing, not shortcuts, so keep waiting
+            if let Some(binding) = pressed {
+                if binding.key == egui::Key::Escape {
+                    self.recording_shortcut = None;
+                } else if binding.is_shortcut() {
+                    self.settings.keymap.bind(action, binding);
+                    self.recording_shortcut = None;
+                }
             }
-
-            // Alt+Home: Go to home
-            if i.modifiers.alt && i.key_pressed(egui::Key::Home) {
-                self.tab_manager
-                    .active_tab_mut()
-                    .navigate_to(&self.settings.general.homepage);
-                self.url_input = self.settings.general.homepage.clone();
+        } else {
+            let actions = self.settings.keymap.pressed(ctx);
+            for action in actions {
+                self.perform_action(action);
             }
+        }
 
-            // Ctrl+L: Focus address bar (simulated by clearing it)
-            if i.modifiers.command && i.key_pressed(egui::Key::L) {
-                // Request focus on address bar in next frame
-                tracing::debug!("Focus address bar");
+        // Ctrl+1 to Ctrl+8: Go to that tab, Ctrl+9: Go to the last tab
+        ctx.input(|i| {
+            if i.modifiers.command {
+                let number_keys = [
+                    egui::Key::Num1,
+                    egui::Key::Num2,
+                    egui::Key::Num3,
+                    egui::Key::Num4,
+                    egui::Key::Num5,
+                    egui::Key::Num6,
+                    egui::Key::Num7,
+                    egui::Key::Num8,
+                    egui::Key::Num9,
+                ];
+                if let Some(number) = number_keys.iter().position(|key| i.key_pressed(*key)) {
+                    if self.window.tab_manager.switch_to_tab_number(number + 1) {
+                        self.window.url_input = self.window.tab_manager.active_tab().url.clone();
+                    }
+                }
             }
         });
 
         // Handle deferred tab close
-        if let Some(index) = self.tab_to_close.take() {
-            self.tab_manager.close_tab(index);
-            self.url_input = self.tab_manager.active_tab().url.clone();
+        if let Some(index) = self.window.tab_to_close.take() {
+            let direction = self.settings.general.close_direction();
+            self.window.tab_manager.close_and_select(index, direction);
+            self.window.url_input = self.window.tab_manager.active_tab().url.clone();
         }
 
         // Tab bar with Firefox-inspired styling
         let mut switch_to_tab: Option<usize> = None;
+        let mut toggle_mute_tab: Option<usize> = None;
+        let mut tab_menu_action: Option<(usize, TabMenuAction)> = None;
+        let mut hovered_tab: Option<usize> = None;
         let mut new_tab_clicked = false;
+        let private = self.window.tab_manager.is_private();
+        let tab_bar_color = if private {
+            palette.bg_toolbar.mix(PRIVATE_TINT, 0.3)
+        } else {
+            palette.bg_toolbar // Firefox toolbar color
+        };
 
         egui::TopBottomPanel::top("tab_bar")
             .frame(
                 egui::Frame::none()
-                    .fill(egui::Color32::from_rgb(31, 41, 51)) // Firefox toolbar color
+                    .fill(tab_bar_color.to_egui())
                     .inner_margin(egui::Margin::symmetric(8.0, 4.0)),
             )
             .show(ctx, |ui| {
                 ui.horizontal(|ui| {
+                    if private {
+                        ui.label(
+                            egui::RichText::new("🕶")
+                                .size(16.0)
+                                .color(PRIVATE_TINT.to_egui()),
+                        )
+                        .on_hover_text("Private window");
+                        ui.add_space(4.0);
+                    }
+
                     // Render each tab
-                    let active_index = self.tab_manager.active_tab_index();
+                    let active_index = self.window.tab_manager.active_tab_index();
+                    let tab_count = self.window.tab_manager.tab_count();
 
-                    for (index, tab) in self.tab_manager.tabs().iter().enumerate() {
+                    for (index, tab) in self.window.tab_manager.tabs().iter().enumerate() {
                         let is_active = index == active_index;
 
                         // Firefox-style tab with subtle styling
                         let (bg_color, stroke_color) = if is_active {
                             (
-                                egui::Color32::from_rgb(17, 24, 39), // Active tab matches window
-                                egui::Color32::from_rgb(59, 130, 246), // Firefox blue border
+                                palette.bg_window.to_egui(), // Active tab matches window
+                                palette.accent.to_egui(), // Firefox blue border
                             )
                         } else {
                             (
-                                egui::Color32::from_rgb(31, 41, 51), // Inactive tab matches toolbar
-                                egui::Color32::from_rgb(55, 65, 81),  // Subtle border
+                                palette.bg_toolbar.to_egui(), // Inactive tab matches toolbar
+                                palette.border_subtle.to_egui(),  // Subtle border
                             )
                         };
 
-                        egui::Frame::none()
+                        let tab_frame = egui::Frame::none()
                             .fill(bg_color)
                             .stroke(egui::Stroke::new(
                                 if is_active { 2.0 } else { 1.0 },
@@ -1382,30 +4128,42 @@ impl eframe::App for BrowserApp {
                             .rounding(egui::Rounding::same(4.0)) // Firefox 4px radius
                             .show(ui, |ui| {
                                 ui.horizontal(|ui| {
-                                    // Loading indicator
+                                    // Loading indicator, favicon, or the site's letter tile
+                                    let host = ParsedUrl::parse(&tab.url)
+                                        .ok()
+                                        .filter(ParsedUrl::is_web)
+                                        .and_then(|url| url.host_without_www().map(str::to_string));
                                     if tab.is_loading {
                                         ui.label(
                                             egui::RichText::new("⟳")
                                                 .size(10.0)
-                                                .color(egui::Color32::from_rgb(59, 130, 246)),
+                                                .color(palette.accent.to_egui()),
                                         );
+                                    } else if let Some(texture) =
+                                        favicon_texture(&mut self.favicon_textures, ui.ctx(), tab)
+                                    {
+                                        ui.add(
+                                            egui::Image::new(&texture)
+                                                .fit_to_exact_size(egui::vec2(14.0, 14.0)),
+                                        );
+                                    } else if let Some(host) = host {
+                                        render_letter_tile(ui, &host, 14.0);
                                     }
 
-                                    // Tab title
-                                    let title = tab.display_title();
-                                    let truncated_title = if title.len() > MAX_TAB_TITLE_LENGTH {
-                                        format!("{}...", &title[..TRUNCATE_AT])
+                                    // Tab title, after a pin for pinned tabs
+                                    let truncated_title = if tab.is_pinned {
+                                        format!("📌 {}", truncate_tab_title(&tab.display_title()))
                                     } else {
-                                        title
+                                        truncate_tab_title(&tab.display_title())
                                     };
 
                                     let text_color = if is_active {
-                                        egui::Color32::from_rgb(249, 250, 251) // Primary text
+                                        palette.text_primary.to_egui() // Primary text
                                     } else {
-                                        egui::Color32::from_rgb(156, 163, 175) // Secondary text
+                                        palette.text_secondary.to_egui() // Secondary text
                                     };
 
-                                    if ui
+                                    let title_response = ui
                                         .add(
                                             egui::Label::new(
                                                 egui::RichText::new(truncated_title)
@@ -1414,35 +4172,74 @@ impl eframe::App for BrowserApp {
                                             )
                                             .sense(egui::Sense::click()),
                                         )
-                                        .clicked()
-                                    {
+                                        .on_hover_ui(|ui| {
+                                            // Only built while hovered
+                                            ui.label(tab_tooltip_text(&tab.title, &tab.url));
+                                        });
+                                    title_response.context_menu(|ui| {
+                                        if let Some(action) =
+                                            render_tab_menu(ui, tab, index, tab_count)
+                                        {
+                                            tab_menu_action = Some((index, action));
+                                        }
+                                    });
+                                    if title_response.clicked() {
                                         switch_to_tab = Some(index);
                                     }
 
-                                    // Close button
+                                    // Speaker button, shown while playing audio or muted
+                                    if tab.is_audible || tab.is_muted {
+                                        let (icon, hint) = if tab.is_muted {
+                                            ("🔇", "Unmute tab")
+                                        } else {
+                                            ("🔊", "Mute tab")
+                                        };
+                                        if ui
+                                            .add(
+                                                egui::Button::new(
+                                                    egui::RichText::new(icon)
+                                                        .size(11.0)
+                                                        .color(palette.text_secondary.to_egui()),
+                                                )
+                                                .frame(false)
+                                                .small(),
+                                            )
+                                            .on_hover_text(hint)
+                                            .clicked()
+                                        {
+                                            toggle_mute_tab = Some(index);
+                                        }
+                                    }
+
+                                    // Close button, left off pinned tabs
                                     let close_color = if is_active {
-                                        egui::Color32::from_rgb(249, 250, 251)
+                                        palette.text_primary.to_egui()
                                     } else {
-                                        egui::Color32::from_rgb(156, 163, 175)
+                                        palette.text_secondary.to_egui()
                                     };
 
-                                    if ui
-                                        .add(
-                                            egui::Button::new(
-                                                egui::RichText::new("✕")
-                                                    .size(12.0)
-                                                    .color(close_color),
+                                    if !tab.is_pinned
+                                        && ui
+                                            .add(
+                                                egui::Button::new(
+                                                    egui::RichText::new("✕")
+                                                        .size(12.0)
+                                                        .color(close_color),
+                                                )
+                                                .frame(false)
+                                                .small(),
                                             )
-                                            .frame(false)
-                                            .small(),
-                                        )
-                                        .clicked()
+                                            .clicked()
                                     {
-                                        self.tab_to_close = Some(index);
+                                        self.window.tab_to_close = Some(index);
                                     }
                                 });
                             });
 
+                        if !is_active && tab_frame.response.contains_pointer() {
+                            hovered_tab = Some(index);
+                        }
+
                         ui.add_space(2.0);
                     }
 
@@ -1462,28 +4259,71 @@ impl eframe::App for BrowserApp {
                 });
             });
 
+        // Switch to a tab hovered for longer than the configured delay
+        match hovered_tab {
+            Some(index) if switch_to_tab.is_none() => {
+                let now = ctx.input(|i| i.time);
+                let started = match self.window.tab_hover {
+                    Some((hovered, started)) if hovered == index => started,
+                    _ => now,
+                };
+                self.window.tab_hover = Some((index, started));
+
+                let delay_ms = self.settings.general.hover_switch_ms;
+                if hover_switch_due(delay_ms, now - started) {
+                    switch_to_tab = Some(index);
+                    self.window.tab_hover = None;
+                } else if let Some(ms) = delay_ms {
+                    // Repaint when the delay runs out even if the pointer is still
+                    let remaining = ms as f64 / 1000.0 - (now - started);
+                    ctx.request_repaint_after(std::time::Duration::from_secs_f64(remaining));
+                }
+            }
+            _ => self.window.tab_hover = None,
+        }
+
+        let toggled = toggle_mute_tab.and_then(|index| self.window.tab_manager.tab_mut(index));
+        if let Some(tab) = toggled {
+            tab.toggle_mute();
+        }
+
+        if let Some((index, action)) = tab_menu_action {
+            self.apply_tab_menu_action(index, action);
+        }
+
         // Handle tab switching
         if let Some(index) = switch_to_tab {
-            self.tab_manager.switch_to_tab(index);
-            self.url_input = self.tab_manager.active_tab().url.clone();
+            self.window.tab_manager.switch_to_tab(index);
+            self.window.url_input = self.window.tab_manager.active_tab().url.clone();
         }
 
         // Handle new tab
         if new_tab_clicked {
-            self.tab_manager.new_tab("about:home");
-            self.url_input = "about:home".to_string();
+            self.window.tab_manager.new_tab("about:home");
+            self.window.url_input = "about:home".to_string();
         }
 
         // Left sidebar navigation with Firefox styling
         let mut sidebar_action: Option<crate::sidebar::SidebarItem> = None;
         let mut toggle_sidebar = false;
 
-        egui::SidePanel::left("sidebar")
-            .default_width(self.sidebar.effective_width())
-            .resizable(false)
+        // The collapsed rail uses its own panel so it doesn't overwrite the
+        // width egui remembers for the expanded sidebar
+        let sidebar_panel = if self.sidebar.collapsed {
+            egui::SidePanel::left("sidebar_collapsed")
+                .exact_width(crate::sidebar::COLLAPSED_WIDTH)
+                .resizable(false)
+        } else {
+            egui::SidePanel::left("sidebar")
+                .default_width(self.sidebar.width)
+                .width_range(crate::sidebar::MIN_WIDTH..=crate::sidebar::MAX_WIDTH)
+                .resizable(true)
+        };
+
+        let sidebar_response = sidebar_panel
             .frame(
                 egui::Frame::none()
-                    .fill(egui::Color32::from_rgb(17, 24, 39)) // Match window background
+                    .fill(palette.bg_window.to_egui()) // Match window background
                     .inner_margin(egui::Margin::same(8.0)),
             )
             .show(ctx, |ui| {
@@ -1519,12 +4359,12 @@ impl eframe::App for BrowserApp {
 
                         let button = egui::Button::new(button_text)
                             .fill(if is_selected {
-                                egui::Color32::from_rgb(31, 41, 51) // Toolbar color for selection
+                                palette.bg_toolbar.to_egui() // Toolbar color for selection
                             } else {
-                                egui::Color32::from_rgb(17, 24, 39) // Window background
+                                palette.bg_window.to_egui() // Window background
                             })
                             .stroke(if is_selected {
-                                egui::Stroke::new(2.0, egui::Color32::from_rgb(59, 130, 246)) // Firefox blue
+                                egui::Stroke::new(2.0, palette.accent.to_egui()) // Firefox blue
                             } else {
                                 egui::Stroke::NONE
                             })
@@ -1543,6 +4383,11 @@ impl eframe::App for BrowserApp {
                 });
             });
 
+        if !self.sidebar.collapsed {
+            self.sidebar
+                .set_width(sidebar_response.response.rect.width());
+        }
+
         // Handle sidebar actions
         if toggle_sidebar {
             self.sidebar.toggle_collapsed();
@@ -1552,14 +4397,13 @@ impl eframe::App for BrowserApp {
             self.sidebar.select_item(item);
             match item {
                 crate::sidebar::SidebarItem::Settings => {
-                    self.tab_manager
-                        .active_tab_mut()
-                        .navigate_to("about:settings");
-                    self.url_input = "about:settings".to_string();
+                    self.navigate_active_tab("about:settings");
                 }
                 crate::sidebar::SidebarItem::Search => {
-                    self.tab_manager.active_tab_mut().navigate_to("about:home");
-                    self.url_input = "about:home".to_string();
+                    self.navigate_active_tab("about:home");
+                }
+                crate::sidebar::SidebarItem::Bookmarks | crate::sidebar::SidebarItem::History => {
+                    // Shown in a side panel while selected
                 }
                 _ => {
                     tracing::info!("Sidebar item {:?} clicked (not yet implemented)", item);
@@ -1568,16 +4412,17 @@ impl eframe::App for BrowserApp {
         }
 
         // Navigation bar with Firefox-inspired styling
+        let mut download_action: Option<(String, DownloadAction)> = None;
         egui::TopBottomPanel::top("nav_bar")
             .frame(
                 egui::Frame::none()
-                    .fill(egui::Color32::from_rgb(31, 41, 51)) // Firefox toolbar color
+                    .fill(palette.bg_toolbar.to_egui()) // Firefox toolbar color
                     .inner_margin(egui::Margin::symmetric(12.0, 8.0)),
             )
             .show(ctx, |ui| {
                 ui.horizontal(|ui| {
                     // Navigation arrows
-                    let can_go_back = self.tab_manager.active_tab().can_go_back();
+                    let can_go_back = self.window.tab_manager.active_tab().can_go_back();
                     if ui
                         .add_enabled(
                             can_go_back,
@@ -1587,11 +4432,11 @@ impl eframe::App for BrowserApp {
                         .on_hover_text("Go back (Alt+Left)")
                         .clicked()
                     {
-                        self.tab_manager.active_tab_mut().go_back();
-                        self.url_input = self.tab_manager.active_tab().url.clone();
+                        self.window.tab_manager.active_tab_mut().go_back();
+                        self.window.url_input = self.window.tab_manager.active_tab().url.clone();
                     }
 
-                    let can_go_forward = self.tab_manager.active_tab().can_go_forward();
+                    let can_go_forward = self.window.tab_manager.active_tab().can_go_forward();
                     if ui
                         .add_enabled(
                             can_go_forward,
@@ -1601,12 +4446,12 @@ impl eframe::App for BrowserApp {
                         .on_hover_text("Go forward (Alt+Right)")
                         .clicked()
                     {
-                        self.tab_manager.active_tab_mut().go_forward();
-                        self.url_input = self.tab_manager.active_tab().url.clone();
+                        self.window.tab_manager.active_tab_mut().go_forward();
+                        self.window.url_input = self.window.tab_manager.active_tab().url.clone();
                     }
 
                     // Reload/Stop button
-                    let is_loading = self.tab_manager.active_tab().is_loading;
+                    let is_loading = self.window.tab_manager.active_tab().is_loading;
                     let reload_text = if is_loading { "⏹" } else { "⟳" };
                     let reload_tooltip = if is_loading {
                         "Stop loading"
@@ -1623,10 +4468,10 @@ impl eframe::App for BrowserApp {
                         .clicked()
                     {
                         if is_loading {
-                            self.tab_manager.active_tab_mut().finish_loading();
+                            self.window.tab_manager.active_tab_mut().finish_loading();
                             tracing::info!("Stopped loading");
                         } else {
-                            self.tab_manager.active_tab_mut().reload();
+                            self.window.tab_manager.active_tab_mut().reload();
                             tracing::info!("Reloading page");
                         }
                     }
@@ -1641,77 +4486,196 @@ impl eframe::App for BrowserApp {
                         .clicked()
                     {
                         let homepage = self.settings.general.homepage.clone();
-                        self.tab_manager.active_tab_mut().navigate_to(&homepage);
-                        self.url_input = homepage;
+                        self.navigate_active_tab(homepage);
                         tracing::info!("Navigating to home");
                     }
 
                     ui.add_space(8.0); // Firefox 8px spacing
 
                     // SSL/Security lock icon
-                    let current_url = &self.tab_manager.active_tab().url;
-                    let (security_icon, security_color, security_tooltip) =
-                        if current_url.starts_with("https://") {
-                            (
-                                "🔒",
-                                egui::Color32::from_rgb(34, 197, 94), // Green
-                                "Secure connection (HTTPS)",
-                            )
-                        } else if current_url.starts_with("http://") {
-                            (
-                                "⚠",
-                                egui::Color32::from_rgb(251, 191, 36), // Warning yellow
-                                "Not secure (HTTP)",
-                            )
-                        } else if current_url.starts_with("about:") {
-                            ("ℹ", egui::Color32::from_rgb(59, 130, 246), "Internal page")
-                        } else {
-                            (
-                                "🌐",
-                                egui::Color32::from_rgb(156, 163, 175),
-                                "Local or unknown",
-                            )
-                        };
-
+                    let security = classify_security(&self.window.tab_manager.active_tab().url);
                     ui.label(
-                        egui::RichText::new(security_icon)
+                        egui::RichText::new(security.icon())
                             .size(16.0)
-                            .color(security_color),
+                            .color(security.color(&palette).to_egui()),
                     )
-                    .on_hover_text(security_tooltip);
+                    .on_hover_text(security.description());
 
                     ui.add_space(6.0);
 
+                    // Keyboard selection in the suggestion dropdown, consumed before
+                    // the address bar sees the keys
+                    let address_bar_id = egui::Id::new("address_bar");
+                    let has_suggestions = !self.window.suggestions.is_empty();
+                    if has_suggestions && ui.memory(|m| m.has_focus(address_bar_id)) {
+                        let key = ui.input_mut(|i| {
+                            [
+                                (egui::Key::ArrowDown, SuggestionKey::Down),
+                                (egui::Key::ArrowUp, SuggestionKey::Up),
+                                (egui::Key::Tab, SuggestionKey::Tab),
+                                (egui::Key::Escape, SuggestionKey::Escape),
+                            ]
+                            .into_iter()
+                            .find(|(key, _)| i.consume_key(egui::Modifiers::NONE, *key))
+                            .map(|(_, suggestion_key)| suggestion_key)
+                        });
+                        if let Some(key) = key {
+                            self.handle_suggestion_key(key);
+                        }
+                    }
+
                     // Address bar with Firefox-style rounded input (6-8px radius)
                     let address_bar_response = ui.add(
-                        egui::TextEdit::singleline(&mut self.url_input)
+                        egui::TextEdit::singleline(&mut self.window.url_input)
+                            .id(address_bar_id)
                             .desired_width(ui.available_width() - 120.0)
                             .hint_text("Search or enter address...")
+                            .lock_focus(has_suggestions)
                             .frame(true),
                     );
 
+                    // Ctrl+L: Focus the address bar with its text selected
+                    if std::mem::take(&mut self.window.focus_address_bar) {
+                        address_bar_response.request_focus();
+                        let mut state = egui::TextEdit::load_state(ui.ctx(), address_bar_id)
+                            .unwrap_or_default();
+                        let range = select_all_range(&self.window.url_input);
+                        state
+                            .cursor
+                            .set_char_range(Some(egui::text::CCursorRange::two(
+                                egui::text::CCursor::new(range.start),
+                                egui::text::CCursor::new(range.end),
+                            )));
+                        state.store(ui.ctx(), address_bar_id);
+                    }
+
+                    if address_bar_response.changed() {
+                        self.window.highlighted = None;
+                        self.window.suggestions = address_suggestions(
+                            &self.history,
+                            &self.window.url_input,
+                            MAX_SUGGESTIONS,
+                        );
+                    }
+
+                    // Suggestion dropdown under the address bar
+                    let mut dropdown_hovered = false;
+                    if !self.window.suggestions.is_empty() {
+                        let (clicked, hovered) =
+                            self.render_suggestions(ui.ctx(), address_bar_response.rect);
+                        dropdown_hovered = hovered;
+                        if let Some(index) = clicked {
+                            self.window.highlighted = Some(index);
+                            self.handle_suggestion_key(SuggestionKey::Enter);
+                        }
+                    }
+
                     // Navigate on Enter key
-                    if address_bar_response.lost_focus()
-                        && ui.input(|i| i.key_pressed(egui::Key::Enter))
-                    {
-                        let url = self.process_url_input(&self.url_input);
-                        self.tab_manager.active_tab_mut().navigate_to(&url);
-                        self.url_input = url;
-                        tracing::info!("Navigating to: {}", self.url_input);
+                    if address_bar_response.lost_focus() {
+                        if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                            self.handle_suggestion_key(SuggestionKey::Enter);
+                        } else if !dropdown_hovered {
+                            // Focus moved elsewhere; close the dropdown
+                            self.window.suggestions.clear();
+                            self.window.highlighted = None;
+                        }
                     }
 
                     ui.add_space(6.0);
 
-                    // Bookmark/Star icon
+                    // Reader view toggle, for pages that look like articles
+                    if self.reader_article().is_some() {
+                        let reader_mode = self.window.tab_manager.active_tab().reader_mode;
+                        let (reader_color, reader_tooltip) = if reader_mode {
+                            (palette.accent.to_egui(), "Close reader view")
+                        } else {
+                            (palette.text_secondary.to_egui(), "Open reader view")
+                        };
+
+                        if ui
+                            .add(
+                                egui::Button::new(
+                                    egui::RichText::new("📖").size(16.0).color(reader_color),
+                                )
+                                .rounding(egui::Rounding::same(4.0)),
+                            )
+                            .on_hover_text(reader_tooltip)
+                            .clicked()
+                        {
+                            self.window
+                                .tab_manager
+                                .active_tab_mut()
+                                .toggle_reader_mode();
+                        }
+
+                        ui.add_space(4.0);
+                    }
+
+                    // Downloads button with the active count and combined progress
+                    let download_status = self.downloads.as_ref().map(|downloads| {
+                        (
+                            downloads.list(),
+                            downloads.active_count(),
+                            downloads.aggregate_progress(),
+                        )
+                    });
+                    if let Some((list, active, progress)) =
+                        download_status.filter(|(list, _, _)| !list.is_empty())
+                    {
+                        let (label, color) = if active > 0 {
+                            (format!("⬇ {}", active), palette.accent.to_egui())
+                        } else {
+                            ("⬇".to_string(), palette.text_secondary.to_egui())
+                        };
+
+                        let response = ui
+                            .menu_button(egui::RichText::new(label).size(16.0).color(color), |ui| {
+                                download_action = render_download_rows(ui, &list, &palette);
+                            })
+                            .response
+                            .on_hover_text("Downloads");
+
+                        // Combined progress along the bottom of the button
+                        if let Some(progress) = progress {
+                            let rect = response.rect;
+                            let y = rect.bottom() - 1.0;
+                            let end = rect.left() + rect.width() * progress;
+                            ui.painter().line_segment(
+                                [egui::pos2(rect.left(), y), egui::pos2(end, y)],
+                                egui::Stroke::new(2.0, palette.accent.to_egui()),
+                            );
+                            let refresh = std::time::Duration::from_millis(DOWNLOAD_REFRESH_MS);
+                            ui.ctx().request_repaint_after(refresh);
+                        }
+
+                        ui.add_space(4.0);
+                    }
+
+                    // Bookmark/Star icon (filled when the page is bookmarked)
+                    let is_bookmarked = self
+                        .bookmarks
+                        .contains(&self.window.tab_manager.active_tab().url);
+                    let (star_icon, star_color, star_tooltip) = if is_bookmarked {
+                        (
+                            "★",
+                            palette.warning.to_egui(), // Warning yellow
+                            "Remove bookmark",
+                        )
+                    } else {
+                        ("☆", palette.text_secondary.to_egui(), "Bookmark this page")
+                    };
+
                     if ui
                         .add(
-                            egui::Button::new(egui::RichText::new("⭐").size(16.0))
-                                .rounding(egui::Rounding::same(4.0)),
+                            egui::Button::new(
+                                egui::RichText::new(star_icon).size(16.0).color(star_color),
+                            )
+                            .rounding(egui::Rounding::same(4.0)),
                         )
-                        .on_hover_text("Bookmark this page")
+                        .on_hover_text(star_tooltip)
                         .clicked()
                     {
-                        tracing::info!("Bookmark clicked (not yet implemented)");
+                        self.toggle_bookmark();
                     }
 
                     ui.add_space(4.0);
@@ -1728,18 +4692,83 @@ impl eframe::App for BrowserApp {
                         tracing::info!("Profile clicked (not yet implemented)");
                     }
                 });
+
+                // Thin progress line along the bottom of the bar while loading
+                let tab = self.window.tab_manager.active_tab();
+                if tab.is_loading {
+                    let rect = ui.clip_rect();
+                    let y = rect.bottom() - 1.0;
+                    let end = rect.left() + rect.width() * tab.progress;
+                    ui.painter().line_segment(
+                        [egui::pos2(rect.left(), y), egui::pos2(end, y)],
+                        egui::Stroke::new(2.0, palette.accent.to_egui()),
+                    );
+                }
             });
 
+        if let Some((id, action)) = download_action {
+            self.apply_download_action(&id, action);
+        }
+
+        if self.settings.appearance.show_bookmarks_bar {
+            self.render_bookmarks_bar(ctx);
+        }
+
+        match self.sidebar.selected_item {
+            Some(crate::sidebar::SidebarItem::Bookmarks) => self.render_bookmarks_panel(ctx),
+            Some(crate::sidebar::SidebarItem::History) => self.render_history_panel(ctx),
+            _ => {}
+        }
+
+        self.render_status_bar(ctx);
+        self.render_find_bar(ctx);
+        self.render_command_palette(ctx);
+
+        if self.settings.advanced.enable_developer_tools {
+            self.render_frame_stats(ctx);
+        }
+
         // Central panel for content with Firefox background
+        let zoom = self.zoom_levels().get(&self.zoom_host());
+        let mut page_menu_action = None;
         egui::CentralPanel::default()
-            .frame(egui::Frame::none().fill(egui::Color32::from_rgb(17, 24, 39))) // Match window
+            .frame(egui::Frame::none().fill(palette.bg_window.to_egui())) // Match window
             .show(ctx, |ui| {
-                egui::ScrollArea::vertical()
-                    .auto_shrink([false; 2])
-                    .show(ui, |ui| {
-                        self.render_content(ui);
+                // Page content gets its own layer so the site's zoom level can
+                // scale it without scaling the browser chrome
+                let rect = ui.max_rect();
+                let physical = rect.size() * ctx.pixels_per_point();
+                self.renderer
+                    .resize(physical.x.round() as u32, physical.y.round() as u32);
+                let content = egui::Area::new(egui::Id::new("page_content"))
+                    .fixed_pos(rect.min)
+                    .constrain(false)
+                    .show(ctx, |ui| {
+                        let size = rect.size() / zoom;
+                        ui.set_clip_rect(egui::Rect::from_min_size(rect.min, size));
+                        ui.set_min_size(size);
+                        ui.set_max_size(size);
+                        egui::ScrollArea::vertical()
+                            .auto_shrink([false; 2])
+                            .show(ui, |ui| {
+                                self.render_content(ui);
+                            });
                     });
+
+                ctx.set_transform_layer(
+                    content.response.layer_id,
+                    egui::emath::TSTransform::new(rect.min.to_vec2() * (1.0 - zoom), zoom),
+                );
+
+                // Right-clicking the page, outside its own widgets
+                content.response.context_menu(|ui| {
+                    page_menu_action = render_page_menu(ui, self.window.tab_manager.active_tab());
+                });
             });
+
+        if let Some(action) = page_menu_action {
+            self.apply_page_menu_action(ctx, action);
+        }
     }
 }
 
@@ -1753,6 +4782,7 @@ mod tests {
         assert_eq!(config.title, "Horizon Browser");
         assert_eq!(config.width, 1280.0);
         assert_eq!(config.height, 720.0);
+        assert_eq!(config.themes.names(), vec!["Dark", "Light"]);
     }
 
     #[test]
@@ -1760,4 +4790,396 @@ mod tests {
         let config = WindowConfig::default();
         let _window = BrowserWindow::new(config);
     }
+
+    #[test]
+    fn test_hardware_acceleration() {
+        assert_eq!(
+            hardware_acceleration(RendererBackend::Auto),
+            eframe::HardwareAcceleration::Preferred
+        );
+        assert_eq!(
+            hardware_acceleration(RendererBackend::Hardware),
+            eframe::HardwareAcceleration::Required
+        );
+        assert_eq!(
+            hardware_acceleration(RendererBackend::Software),
+            eframe::HardwareAcceleration::Off
+        );
+    }
+
+    #[test]
+    fn test_is_software_gl_renderer() {
+        assert!(is_software_gl_renderer("llvmpipe (LLVM 15.0.7, 256 bits)"));
+        assert!(is_software_gl_renderer("Google SwiftShader"));
+        assert!(is_software_gl_renderer("GDI Generic"));
+        assert!(is_software_gl_renderer("Apple Software Renderer"));
+        assert!(!is_software_gl_renderer("NVIDIA GeForce RTX 3070"));
+        assert!(!is_software_gl_renderer("Mesa Intel(R) UHD Graphics 620"));
+    }
+
+    #[test]
+    fn test_truncate_tab_title() {
+        assert_eq!(truncate_tab_title("Short title"), "Short title");
+
+        let long = "A very long page title that overflows the tab";
+        let truncated = truncate_tab_title(long);
+        assert_eq!(truncated, "A very long page title...");
+        assert_eq!(truncated.chars().count(), TRUNCATE_AT + 3);
+    }
+
+    #[test]
+    fn test_bookmarks_bar_fit() {
+        // Everything fits without an overflow button
+        assert_eq!(bookmarks_bar_fit(&[50.0, 50.0, 50.0], 170.0, 10.0, 20.0), 3);
+        assert_eq!(bookmarks_bar_fit(&[], 100.0, 10.0, 20.0), 0);
+
+        // Overflowing keeps room for the button: 50 + 10 + 50 + 10 + 20 = 140
+        assert_eq!(bookmarks_bar_fit(&[50.0, 50.0, 50.0], 140.0, 10.0, 20.0), 2);
+        assert_eq!(bookmarks_bar_fit(&[50.0, 50.0, 50.0], 139.0, 10.0, 20.0), 1);
+
+        // A bookmark too wide for the bar goes straight into the menu
+        assert_eq!(bookmarks_bar_fit(&[200.0, 10.0], 100.0, 10.0, 20.0), 0);
+    }
+
+    #[test]
+    fn test_classify_security() {
+        let cases = [
+            ("https://example.com/", SecurityLevel::Secure),
+            ("HTTPS://EXAMPLE.COM", SecurityLevel::Secure),
+            ("http://example.com/", SecurityLevel::Insecure),
+            ("about:home", SecurityLevel::Internal),
+            ("file:///home/me/a.html", SecurityLevel::Local),
+            ("data:text/plain,hi", SecurityLevel::Unknown),
+            ("not a url", SecurityLevel::Unknown),
+        ];
+        for (url, level) in cases {
+            assert_eq!(classify_security(url), level, "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_status_bar_hides_when_empty() {
+        let mut status = StatusBarState::default();
+        assert!(status.is_empty());
+        status.security = SecurityLevel::Insecure;
+        assert!(status.is_empty());
+        status.hovered_url = Some("https://example.com/".to_string());
+        assert!(!status.is_empty());
+    }
+
+    #[test]
+    fn test_page_hover_shows_on_next_frame() {
+        let mut status = StatusBarState::default();
+        status.begin_frame();
+        let url = Some("https://example.com/".to_string());
+        assert!(status.hover_in_page(url.clone()));
+
+        status.begin_frame();
+        assert_eq!(status.hovered_url, url);
+        assert!(!status.hover_in_page(url.clone()));
+
+        status.begin_frame();
+        assert_eq!(status.hovered_url, url);
+        assert!(status.hover_in_page(None));
+        status.begin_frame();
+        assert!(status.is_empty());
+    }
+
+    #[test]
+    fn test_page_menu_follows_navigation() {
+        let enabled = |tab: &crate::tabs::Tab| {
+            page_menu_items(tab)
+                .iter()
+                .filter(|(_, _, enabled)| *enabled)
+                .map(|(action, _, _)| *action)
+                .collect::<Vec<_>>()
+        };
+
+        let mut tab = crate::tabs::Tab::new("about:home");
+        assert_eq!(
+            enabled(&tab),
+            vec![PageMenuAction::Reload, PageMenuAction::CopyUrl]
+        );
+
+        tab.navigate_to("https://example.com/");
+        assert_eq!(
+            enabled(&tab),
+            vec![
+                PageMenuAction::Back,
+                PageMenuAction::Reload,
+                PageMenuAction::CopyUrl,
+                PageMenuAction::ViewSource,
+            ]
+        );
+
+        tab.go_back();
+        assert_eq!(
+            enabled(&tab),
+            vec![
+                PageMenuAction::Forward,
+                PageMenuAction::Reload,
+                PageMenuAction::CopyUrl,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_url_for_clipboard() {
+        assert_eq!(
+            url_for_clipboard("HTTPS://Example.COM"),
+            "https://example.com/"
+        );
+        assert_eq!(
+            url_for_clipboard("https://example.com/a b?q=1"),
+            "https://example.com/a%20b?q=1"
+        );
+        assert_eq!(url_for_clipboard("about:home"), "about:home");
+        assert_eq!(url_for_clipboard("  not a url "), "not a url");
+    }
+
+    #[test]
+    fn test_source_url_round_trip() {
+        let url = "https://example.com/search?q=a&b=c";
+        let source = source_url(url);
+        assert!(source.starts_with("about:source?url=https%3A%2F%2F"));
+        assert_eq!(source_target(&source).as_deref(), Some(url));
+        assert_eq!(source_target("about:home"), None);
+    }
+
+    #[test]
+    fn test_internal_page_parse() {
+        let cases = [
+            ("about:home", InternalPage::Home),
+            ("about:settings", InternalPage::Settings),
+            ("about:blank", InternalPage::Blank),
+            ("about:downloads", InternalPage::Downloads),
+            ("about:history", InternalPage::History),
+            ("about:bookmarks", InternalPage::Bookmarks),
+            ("about:History?q=rust#top", InternalPage::History),
+        ];
+        for (url, page) in cases {
+            assert_eq!(InternalPage::parse(url), Some(page), "{}", url);
+        }
+
+        let source = source_url("https://example.com/");
+        assert_eq!(
+            InternalPage::parse(&source),
+            Some(InternalPage::Source("https://example.com/".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_unknown_internal_pages() {
+        let unknown = |name: &str| Some(InternalPage::Unknown(name.to_string()));
+        assert_eq!(InternalPage::parse("about:config"), unknown("config"));
+        assert_eq!(InternalPage::parse("about:source"), unknown("source"));
+        assert_eq!(InternalPage::parse("about:"), unknown(""));
+        assert_eq!(InternalPage::parse("https://example.com/about:home"), None);
+        assert_eq!(InternalPage::parse("example.com"), None);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MB");
+    }
+
+    #[test]
+    fn test_download_status_text() {
+        let mut download = Download {
+            id: "1".to_string(),
+            url: "https://example.com/report.pdf".to_string(),
+            filename: "report.pdf".to_string(),
+            path: std::path::PathBuf::from("report.pdf"),
+            bytes_done: 512,
+            total: Some(2048),
+            state: DownloadState::Active,
+            error: None,
+        };
+        assert_eq!(download_status_text(&download), "512 B of 2.0 KB");
+
+        download.state = DownloadState::Paused;
+        assert_eq!(download_status_text(&download), "Paused, 512 B of 2.0 KB");
+
+        download.state = DownloadState::Failed;
+        download.error = Some("connection reset".to_string());
+        assert_eq!(download_status_text(&download), "Failed: connection reset");
+    }
+
+    #[test]
+    fn test_windows_have_separate_tabs() {
+        let mut first = WindowState::new(egui::ViewportId::ROOT, false);
+        let mut second = WindowState::new(egui::ViewportId::from_hash_of("second"), false);
+
+        first.tab_manager.new_tab("https://example.com");
+        let tab = second.tab_manager.active_tab_mut();
+        tab.navigate_to("https://rust-lang.org");
+
+        assert_eq!(first.tab_manager.tab_count(), 2);
+        assert_eq!(second.tab_manager.tab_count(), 1);
+        assert_eq!(first.tab_manager.active_tab().url, "https://example.com");
+        assert_eq!(second.tab_manager.active_tab().url, "https://rust-lang.org");
+        assert_eq!(first.tab_manager.tabs()[0].url, "about:home");
+
+        // A new window starts on a home tab with the address bar focused
+        let third = WindowState::new(egui::ViewportId::from_hash_of("third"), true);
+        assert_eq!(third.tab_manager.tab_count(), 1);
+        assert_eq!(third.url_input, "about:home");
+        assert!(third.focus_address_bar);
+        assert!(third.tab_manager.is_private());
+        assert!(!first.tab_manager.is_private());
+
+        // Only private windows keep their zoom levels to themselves
+        assert!(third.private_zoom.is_some());
+        assert!(first.private_zoom.is_none());
+    }
+
+    #[test]
+    fn test_hover_switch_due() {
+        assert!(!hover_switch_due(None, 10.0));
+        assert!(!hover_switch_due(Some(500), 0.0));
+        assert!(!hover_switch_due(Some(500), 0.499));
+        assert!(hover_switch_due(Some(500), 0.5));
+        assert!(hover_switch_due(Some(500), 1.2));
+        assert!(hover_switch_due(Some(0), 0.0));
+    }
+
+    #[test]
+    fn test_process_url_input() {
+        let engine = crate::settings::SearchEngine::DuckDuckGo;
+
+        assert_eq!(
+            process_url_input("localhost:3000", &engine),
+            "http://localhost:3000/"
+        );
+        assert_eq!(
+            process_url_input(" 192.168.1.1:8080 ", &engine),
+            "http://192.168.1.1:8080/"
+        );
+        assert_eq!(process_url_input("::1", &engine), "http://[::1]/");
+        assert_eq!(
+            process_url_input("about:settings", &engine),
+            "about:settings"
+        );
+        assert_eq!(
+            process_url_input("rust borrow checker", &engine),
+            "https://duckduckgo.com/?q=rust%20borrow%20checker"
+        );
+    }
+
+    #[test]
+    fn test_process_url_input_file_paths() {
+        let engine = crate::settings::SearchEngine::DuckDuckGo;
+
+        assert_eq!(
+            process_url_input("/home/me/my page.html", &engine),
+            "file:///home/me/my%20page.html"
+        );
+        assert_eq!(
+            process_url_input(r"C:\docs\x.html", &engine),
+            "file:///C:/docs/x.html"
+        );
+        assert_eq!(
+            process_url_input("file:///home/me/page.html", &engine),
+            "file:///home/me/page.html"
+        );
+    }
+
+    #[test]
+    fn test_file_urls_respect_policy() {
+        let engine = crate::settings::SearchEngine::DuckDuckGo;
+        let url = process_url_input("/home/me/page.html", &engine);
+
+        assert!(!SecurityPolicy::default().allow_resource(&url, None));
+        assert!(blocked_message(&url).contains("local files"));
+        assert!(SecurityPolicy::permissive().allow_resource(&url, None));
+    }
+
+    #[test]
+    fn test_select_all_range() {
+        assert_eq!(select_all_range(""), 0..0);
+        assert_eq!(select_all_range("about:home"), 0..10);
+        // Counted in characters, not bytes
+        assert_eq!(select_all_range("https://例え.jp/"), 0..14);
+    }
+
+    #[test]
+    fn test_tab_tooltip_text() {
+        assert_eq!(
+            tab_tooltip_text("Example Domain", "https://example.com"),
+            "Example Domain\nhttps://example.com"
+        );
+        assert_eq!(tab_tooltip_text("New Tab", "about:home"), "about:home");
+        assert_eq!(tab_tooltip_text("", "about:blank"), "about:blank");
+    }
+
+    #[test]
+    fn test_typing_about_suggests_internal_pages() {
+        let mut history = HistoryStore::new();
+        let tabs = TabManager::new();
+        // Visiting an about: page leaves no history entry
+        assert!(!tabs.record_visit(&mut history, "about:settings", ""));
+        history.record_visit("https://about.example.com", "");
+
+        let suggestions = address_suggestions(&history, "about:", MAX_SUGGESTIONS);
+        assert_eq!(suggestions.len(), MAX_SUGGESTIONS);
+        assert!(suggestions.iter().all(|url| url.starts_with("about:")));
+        assert_eq!(
+            address_suggestions(&history, "About:Se", MAX_SUGGESTIONS),
+            vec!["about:settings"]
+        );
+        assert_eq!(
+            address_suggestions(&history, "abo", MAX_SUGGESTIONS)[0],
+            "https://about.example.com"
+        );
+        assert!(address_suggestions(&history, " ", MAX_SUGGESTIONS).is_empty());
+    }
+
+    #[test]
+    fn test_custom_engine_fields() {
+        use crate::settings::SearchEngine;
+
+        assert_eq!(
+            custom_engine_fields(&SearchEngine::DuckDuckGo),
+            (String::new(), String::new())
+        );
+        let custom = SearchEngine::Custom {
+            name: "Wiki".to_string(),
+            url_template: "https://en.wikipedia.org/w/index.php?search=%s".to_string(),
+        };
+        assert_eq!(
+            custom_engine_fields(&custom),
+            (
+                "Wiki".to_string(),
+                "https://en.wikipedia.org/w/index.php?search=%s".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_history_list_rebuilds_when_history_or_query_changes() {
+        let mut history = HistoryStore::new();
+        history.record_visit("https://example.com", "Example");
+        let mut list = HistoryList::default();
+
+        assert_eq!(list.days(&history, "").len(), 1);
+        history.record_visit("https://rust-lang.org", "Rust");
+        assert_eq!(list.days(&history, "")[0].entries.len(), 2);
+        assert_eq!(list.days(&history, "rust")[0].entries.len(), 1);
+
+        history.clear_all();
+        assert!(list.days(&history, "rust").is_empty());
+    }
+
+    #[test]
+    fn test_tab_tooltip_shows_full_truncated_title() {
+        let long = "A very long page title that overflows the tab";
+        let tooltip = tab_tooltip_text(long, "https://example.com/article");
+
+        assert_ne!(truncate_tab_title(long), long);
+        assert!(tooltip.starts_with(long));
+        assert!(tooltip.ends_with("https://example.com/article"));
+    }
 }